@@ -0,0 +1,5 @@
+// massive_game_server/protocol/src/lib.rs
+// Rules shared between the server and clients. Everything here is pure and deterministic
+// (no clocks, RNG, locks or allocation-heavy state) so a Rust/WASM client can run the
+// exact code the server runs for prediction and reconciliation.
+pub mod movement;
@@ -0,0 +1,174 @@
+// massive_game_server/protocol/src/movement.rs
+// Deterministic player movement, shared by the server and predicting clients.
+//
+// A client predicts by running `step_movement` for each unacknowledged input, and
+// reconciles by resetting to the server state for `last_processed_input_sequence` and
+// replaying the inputs after it. Both sides must feed the same walls and tick length.
+
+pub const PLAYER_RADIUS: f32 = 15.0;
+pub const PLAYER_BASE_SPEED: f32 = 150.0;
+pub const SPEED_BOOST_MULTIPLIER: f32 = 1.5;
+
+pub const WORLD_MIN_X: f32 = -800.0;
+pub const WORLD_MAX_X: f32 = 800.0;
+pub const WORLD_MIN_Y: f32 = -600.0;
+pub const WORLD_MAX_Y: f32 = 600.0;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MovementState {
+    pub x: f32,
+    pub y: f32,
+    pub velocity_x: f32,
+    pub velocity_y: f32,
+    pub rotation: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MovementInput {
+    pub move_forward: bool,
+    pub move_backward: bool,
+    pub move_left: bool,
+    pub move_right: bool,
+    pub rotation: f32,
+    pub speed_multiplier: f32, // From the player's status effects; 1.0 is base speed, 0.0 can't move
+}
+
+impl Default for MovementInput {
+    fn default() -> Self {
+        MovementInput {
+            move_forward: false,
+            move_backward: false,
+            move_left: false,
+            move_right: false,
+            rotation: 0.0,
+            speed_multiplier: 1.0,
+        }
+    }
+}
+
+/// Axis-aligned wall rectangle; (x, y) is the top-left corner.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WallRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+/// Applies one input, then advances the player by `dt` seconds.
+pub fn step_movement(state: &MovementState, input: &MovementInput, walls: &[WallRect], dt: f32) -> MovementState {
+    integrate_movement(&apply_movement_input(state, input), walls, dt)
+}
+
+/// Sets rotation and velocity from an input. Movement is relative to the input's rotation.
+pub fn apply_movement_input(state: &MovementState, input: &MovementInput) -> MovementState {
+    let mut forward_intent = 0.0_f32;
+    let mut strafe_intent = 0.0_f32;
+    if input.move_forward { forward_intent += 1.0; }
+    if input.move_backward { forward_intent -= 1.0; }
+    if input.move_left { strafe_intent -= 1.0; }
+    if input.move_right { strafe_intent += 1.0; }
+
+    let mut next = *state;
+    next.rotation = input.rotation;
+
+    if forward_intent == 0.0 && strafe_intent == 0.0 {
+        next.velocity_x = 0.0;
+        next.velocity_y = 0.0;
+        return next;
+    }
+
+    let magnitude = (forward_intent * forward_intent + strafe_intent * strafe_intent).sqrt();
+    forward_intent /= magnitude;
+    strafe_intent /= magnitude;
+
+    let speed = PLAYER_BASE_SPEED * input.speed_multiplier.max(0.0);
+    let (sin_rot, cos_rot) = input.rotation.sin_cos();
+    // Forward along the rotation, strafe perpendicular to it.
+    next.velocity_x = (cos_rot * forward_intent - sin_rot * strafe_intent) * speed;
+    next.velocity_y = (sin_rot * forward_intent + cos_rot * strafe_intent) * speed;
+    next
+}
+
+/// Advances the player by `dt` seconds at its current velocity. Each axis is resolved
+/// separately, so a player pressed against a wall slides along it instead of stopping.
+/// Blocked axes have their velocity zeroed.
+pub fn integrate_movement(state: &MovementState, walls: &[WallRect], dt: f32) -> MovementState {
+    let mut next = *state;
+    // A player already overlapping a wall (e.g. one that respawned on top of them) may move freely out of it.
+    let check_walls = !overlaps_any_wall(state.x, state.y, walls);
+
+    let desired_x = state.x + state.velocity_x * dt;
+    let clamped_x = desired_x.clamp(WORLD_MIN_X + PLAYER_RADIUS, WORLD_MAX_X - PLAYER_RADIUS);
+    if check_walls && overlaps_any_wall(clamped_x, next.y, walls) {
+        next.velocity_x = 0.0;
+    } else {
+        next.x = clamped_x;
+        if clamped_x != desired_x {
+            next.velocity_x = 0.0;
+        }
+    }
+
+    let desired_y = state.y + state.velocity_y * dt;
+    let clamped_y = desired_y.clamp(WORLD_MIN_Y + PLAYER_RADIUS, WORLD_MAX_Y - PLAYER_RADIUS);
+    if check_walls && overlaps_any_wall(next.x, clamped_y, walls) {
+        next.velocity_y = 0.0;
+    } else {
+        next.y = clamped_y;
+        if clamped_y != desired_y {
+            next.velocity_y = 0.0;
+        }
+    }
+
+    next
+}
+
+pub fn overlaps_wall(x: f32, y: f32, wall: &WallRect) -> bool {
+    let closest_x = x.clamp(wall.x, wall.x + wall.width);
+    let closest_y = y.clamp(wall.y, wall.y + wall.height);
+    (x - closest_x).powi(2) + (y - closest_y).powi(2) < PLAYER_RADIUS * PLAYER_RADIUS
+}
+
+pub fn overlaps_any_wall(x: f32, y: f32, walls: &[WallRect]) -> bool {
+    walls.iter().any(|wall| overlaps_wall(x, y, wall))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f32 = 1.0 / 60.0;
+
+    fn forward(rotation: f32) -> MovementInput {
+        MovementInput { move_forward: true, rotation, ..Default::default() }
+    }
+
+    #[test]
+    fn test_step_is_deterministic() {
+        let start = MovementState::default();
+        let input = MovementInput { move_forward: true, move_right: true, rotation: 0.7, ..Default::default() };
+        let a = (0..120).fold(start, |s, _| step_movement(&s, &input, &[], DT));
+        let b = (0..120).fold(start, |s, _| step_movement(&s, &input, &[], DT));
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_slides_along_wall() {
+        // Wall directly to the right; moving diagonally right-down should keep the downward component.
+        let wall = WallRect { x: 16.0, y: -100.0, width: 20.0, height: 200.0 };
+        let start = MovementState::default();
+        let input = forward(std::f32::consts::FRAC_PI_4);
+        let next = (0..10).fold(start, |s, _| step_movement(&s, &input, &[wall], DT));
+        assert!(next.x < 1.0 + f32::EPSILON);
+        assert!(next.y > 10.0);
+        assert!(!overlaps_wall(next.x, next.y, &wall));
+    }
+
+    #[test]
+    fn test_clamped_to_world_bounds() {
+        let start = MovementState { x: WORLD_MAX_X - PLAYER_RADIUS - 1.0, ..Default::default() };
+        let next = step_movement(&start, &forward(0.0), &[], 1.0);
+        assert_eq!(next.x, WORLD_MAX_X - PLAYER_RADIUS);
+        assert_eq!(next.velocity_x, 0.0);
+    }
+}
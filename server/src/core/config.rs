@@ -1,6 +1,12 @@
 // massive_game_server/server/src/core/config.rs
 // Basic configuration structure
 // Removed unused: use std::sync::Arc;
+use crate::flatbuffers_generated::game_protocol as fb;
+use crate::systems::combat::abilities::PlayerClass;
+use crate::systems::combat::weapons::{default_spawn_loadout, LoadoutEntry};
+use crate::systems::objectives::rules::MatchRules;
+use crate::world::boundary::{default_safe_zone_phases, SafeZonePhase};
+use subtle::ConstantTimeEq;
 
 #[derive(Debug, Clone)]
 pub struct ThreadPoolConfig {
@@ -32,6 +38,22 @@ pub struct ServerConfig {
     pub world_partition_grid_dim: usize, 
     pub thread_pools: ThreadPoolConfig,
     pub max_players_per_match: usize, // <<< ADD THIS LINE
+    pub reconnect_grace_period_secs: f32,
+    pub client_bytes_per_tick_budget: usize,
+    pub caster_key: Option<String>, // Required as ws?caster=<key>; no caster feed is recorded without one
+    pub admin_key: Option<String>,  // Required by the /admin endpoints; they are disabled without one
+    pub open_spectating: bool,      // Anyone may spectate live; otherwise spectating takes the caster key
+    pub caster_delay_secs: f32,
+    pub caster_buffer_max_bytes: usize,
+    pub chat_word_filter: Vec<String>, // Words masked out of player chat
+    pub game_mode: fb::GameModeType,
+    pub battle_royale_teams: bool, // Last team standing instead of last player
+    pub safe_zone_phases: Vec<SafeZonePhase>,
+    pub rounds_best_of: u8, // Round-based modes end once a team has won a majority of this many
+    pub match_rules: Option<MatchRules>, // Replaces the game mode's default rules
+    pub spawn_loadout: Vec<LoadoutEntry>, // Weapons everyone spawns with, the first one held
+    pub default_class: PlayerClass, // Class players join as until they pick one
+    pub projectile_ricochet: bool,  // Glancing pistol and rifle shots bounce off walls
 }
 
 impl Default for ServerConfig {
@@ -43,10 +65,45 @@ impl Default for ServerConfig {
             world_partition_grid_dim: super::constants::PARTITION_GRID_SIZE,
             thread_pools: ThreadPoolConfig::default(),
             max_players_per_match: 400, // <<< ADD THIS LINE (or your desired default)
+            reconnect_grace_period_secs: super::constants::RECONNECT_GRACE_PERIOD_SECS,
+            client_bytes_per_tick_budget: super::constants::CLIENT_BYTES_PER_TICK_BUDGET,
+            caster_key: None,
+            admin_key: None,
+            open_spectating: false,
+            caster_delay_secs: super::constants::CASTER_DEFAULT_DELAY_SECS,
+            caster_buffer_max_bytes: super::constants::CASTER_BUFFER_MAX_BYTES,
+            chat_word_filter: Vec::new(),
+            game_mode: fb::GameModeType::CaptureTheFlag,
+            battle_royale_teams: false,
+            safe_zone_phases: default_safe_zone_phases(),
+            rounds_best_of: super::constants::DEFAULT_ROUNDS_BEST_OF,
+            match_rules: None,
+            spawn_loadout: default_spawn_loadout(),
+            default_class: PlayerClass::default(),
+            projectile_ricochet: false,
         }
     }
 }
 
+impl ServerConfig {
+    pub fn is_admin_key(&self, key: Option<&str>) -> bool {
+        secret_matches(self.admin_key.as_deref(), key)
+    }
+
+    pub fn is_caster_key(&self, key: Option<&str>) -> bool {
+        secret_matches(self.caster_key.as_deref(), key)
+    }
+}
+
+/// Compares in constant time so a wrong guess doesn't reveal how much of the key it got right.
+/// Nothing matches a key that isn't configured.
+fn secret_matches(configured: Option<&str>, given: Option<&str>) -> bool {
+    match (configured, given) {
+        (Some(configured), Some(given)) => configured.as_bytes().ct_eq(given.as_bytes()).into(),
+        _ => false,
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct CoreAllocation {
     pub physics_cores_indices: Vec<usize>,    
@@ -83,3 +140,18 @@ impl CoreAllocation {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keys_match_only_when_configured() {
+        let config = ServerConfig { admin_key: Some("hunter2".to_string()), ..ServerConfig::default() };
+        assert!(config.is_admin_key(Some("hunter2")));
+        assert!(!config.is_admin_key(Some("hunter")));
+        assert!(!config.is_admin_key(None));
+        // No caster key configured: even an empty key is refused
+        assert!(!config.is_caster_key(Some("")));
+    }
+}
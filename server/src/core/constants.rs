@@ -1,15 +1,16 @@
 // massive_game_server/server/src/core/constants.rs
 use std::time::Duration;
+use massive_game_protocol::movement as movement_rules;
 
 pub const SERVER_TICK_RATE: u64 = 60;
 pub const TICK_DURATION_MS: u64 = 1000 / SERVER_TICK_RATE;
 pub const TICK_DURATION: Duration = Duration::from_millis(TICK_DURATION_MS);
 
 // World constants
-pub const WORLD_MIN_X: f32 = -800.0; // Example, adjust as needed
-pub const WORLD_MAX_X: f32 = 800.0;  // Example
-pub const WORLD_MIN_Y: f32 = -600.0; // Example
-pub const WORLD_MAX_Y: f32 = 600.0;  // Example
+pub const WORLD_MIN_X: f32 = movement_rules::WORLD_MIN_X; // Shared with clients via massive_game_protocol
+pub const WORLD_MAX_X: f32 = movement_rules::WORLD_MAX_X;
+pub const WORLD_MIN_Y: f32 = movement_rules::WORLD_MIN_Y;
+pub const WORLD_MAX_Y: f32 = movement_rules::WORLD_MAX_Y;
 pub const PARTITION_GRID_SIZE: usize = 8; 
 pub const PARTITION_SIZE_X: f32 = (WORLD_MAX_X - WORLD_MIN_X) / PARTITION_GRID_SIZE as f32;
 pub const PARTITION_SIZE_Y: f32 = (WORLD_MAX_Y - WORLD_MIN_Y) / PARTITION_GRID_SIZE as f32;
@@ -20,10 +21,18 @@ pub const SPATIAL_INDEX_CELL_SIZE: f32 = 400.0;
 
 // Player constants
 pub const PLAYER_SHARDS_COUNT: usize = 32; // Default, overridden by dev config
-pub const PLAYER_RADIUS: f32 = 15.0; // Player hitbox radius
-pub const PLAYER_BASE_SPEED: f32 = 150.0; // Base movement speed for players
+pub const PLAYER_RADIUS: f32 = movement_rules::PLAYER_RADIUS; // Player hitbox radius
+pub const PLAYER_BASE_SPEED: f32 = movement_rules::PLAYER_BASE_SPEED; // Base movement speed for players
 pub const MIN_PLAYERS_TO_START: usize = 1; // Reduced to 1 so single player can start with bots
 
+// Match constants
+pub const DEFAULT_MATCH_DURATION_SECS: f32 = 300.0;
+pub const MATCH_RESET_DELAY_SECS: f32 = 10.0;  // Results are shown this long before the next match
+pub const MATCH_START_COUNTDOWN_SECS: f32 = 5.0; // Countdown once enough players are in
+pub const SUDDEN_DEATH_MAX_SECS: f32 = 300.0;   // A match still tied after this much sudden death ends tied
+pub const CTF_CAPTURES_TO_WIN: i32 = 3;
+pub const FLAG_AUTO_RETURN_SECS: f32 = 30.0;   // A dropped flag returns to base after this long
+
 // Projectile constants
 // (Add if needed, e.g., default projectile speed, lifetime)
 
@@ -32,7 +41,7 @@ pub const PICKUP_COLLECTION_RADIUS: f32 = 25.0;
 pub const PICKUP_DEFAULT_RESPAWN_TIME_SECS: f32 = 10.0;
 
 // Anti-cheat constants (example values)
-pub const MAX_PLAYER_SPEED_MULTIPLIER: f32 = 1.5; // For speed boosts
+pub const MAX_PLAYER_SPEED_MULTIPLIER: f32 = movement_rules::SPEED_BOOST_MULTIPLIER; // For speed boosts
 pub const MAX_POSITION_DELTA_SLACK: f32 = 10.0; // Max allowed movement per tick if not moving via velocity
 pub const MIN_SHOT_INTERVAL_SECONDS: f32 = 0.05; // Minimum interval between shots
 pub const POSITION_VALIDATION_VIOLATION_THRESHOLD: u32 = 5;
@@ -44,9 +53,36 @@ pub const SHOTGUN_SPREAD_ANGLE_RAD: f32 = 0.4;
 // Other game constants
 pub const DEFAULT_RESPAWN_DURATION_SECS: f32 = 5.0;
 pub const MAX_INPUT_QUEUE_SIZE_PER_PLAYER: usize = 32;
+pub const INPUT_BUFFER_MIN_DEPTH: usize = 1;        // Inputs buffered before playback starts
+pub const INPUT_BUFFER_MAX_DEPTH: usize = 6;        // ~100ms of added input delay at worst
+pub const INPUT_BUFFER_SLACK: usize = 2;            // Extra inputs tolerated before catching up
+pub const INPUT_BUFFER_SHRINK_TICKS: u32 = 300;     // Clean ticks before the buffer shrinks by one
 
 pub const SAFE_SPAWN_RADIUS_FROM_ENEMY: f32 = 300.0; // Example value, adjust as needed
 
+// Session constants
+pub const RECONNECT_GRACE_PERIOD_SECS: f32 = 20.0; // How long a disconnected player's state is kept for resumption
+
+// Client message limits (per peer)
+pub const MAX_CLIENT_MESSAGE_BYTES: usize = 1024;   // Larger data channel messages are rejected unparsed
+pub const INPUT_RATE_PER_SEC: f32 = 120.0;          // Sustained PlayerInput rate (2x the tick rate)
+pub const INPUT_BURST: f32 = 30.0;
+pub const CHAT_RATE_PER_SEC: f32 = 1.0;
+pub const CHAT_BURST: f32 = 5.0;
+pub const PONG_RATE_PER_SEC: f32 = 4.0;
+pub const PONG_BURST: f32 = 4.0;
+pub const SPECTATOR_CONTROL_RATE_PER_SEC: f32 = 30.0; // Camera moves; also carries the spectator's snapshot acks
+pub const SPECTATOR_CONTROL_BURST: f32 = 10.0;
+pub const MAX_CHAT_MESSAGE_CHARS: usize = 100;
+pub const CHAT_HISTORY_SIZE: usize = 50;            // Chat messages kept for delivery to clients
+pub const MAX_BOT_COUNT_COMMAND: u64 = 200;         // Upper bound for the /bots moderator command
+pub const MAX_INPUT_SEQUENCE_JUMP: u32 = 10_000;    // Inputs further ahead of the last processed one are bogus
+pub const MAX_WEAPON_SLOT: u8 = 7;
+pub const MAX_ABILITY_SLOT: u8 = 4;
+pub const VIOLATIONS_BEFORE_WARN: u32 = 10;         // Dropped messages before the client is warned
+pub const VIOLATIONS_BEFORE_DISCONNECT: u32 = 50;   // Dropped messages before the client is kicked
+pub const VIOLATION_DECAY_SECS: f32 = 10.0;         // A clean period this long resets the violation count
+
 
 
 // Performance 
@@ -64,7 +100,108 @@ pub const PISTOL_PROJECTILE_SPEED: f32 = 450.0;
 pub const SHOTGUN_PROJECTILE_SPEED: f32 = 400.0;
 pub const RIFLE_PROJECTILE_SPEED: f32 = 600.0;
 pub const SNIPER_PROJECTILE_SPEED: f32 = 800.0;
+pub const ROCKET_PROJECTILE_SPEED: f32 = 350.0;
+pub const GRENADE_PROJECTILE_SPEED: f32 = 300.0;
+pub const EXPLOSIVE_WALL_STANDOFF: f32 = 2.0; // How far short of a wall explosives stop
 
 
 pub const AOI_RADIUS: f32 = 600.0; 
 pub const AOI_UPDATE_INTERVAL_SECS: f32 = 0.1;
+
+// Snapshot deltas
+pub const SNAPSHOT_BASELINE_RING_SIZE: usize = 64; // Per-client baselines kept for acks (~1s at 60 Hz)
+
+// Bandwidth budget and entity priority
+pub const CLIENT_BYTES_PER_TICK_BUDGET: usize = 6 * 1024;  // Stays well under the SCTP message size
+pub const MAX_TICKS_BETWEEN_ENTITY_UPDATES: u64 = 20;       // Minimum rate for starved entities (3 Hz at 60 Hz)
+pub const MANDATORY_OVERDRAFT_PERCENT: usize = 50;          // How far overdue entities may push past the budget
+pub const RECENT_ATTACKER_WINDOW_TICKS: u64 = 180;          // Attackers stay relevant for 3s after a hit
+pub const ESTIMATED_PLAYER_STATE_BYTES: usize = 128;       // Plus id and username lengths
+pub const ESTIMATED_WEAPON_SLOT_BYTES: usize = 28;         // Per owned weapon in the inventory
+pub const ESTIMATED_STATUS_EFFECT_BYTES: usize = 28;       // Per active status effect
+pub const ESTIMATED_PLAYER_CLASS_BYTES: usize = 4;         // Class field and its vtable entry
+pub const ESTIMATED_PROJECTILE_BYTES: usize = 72;          // Plus owner id length
+pub const ESTIMATED_PICKUP_BYTES: usize = 48;              // Plus id length
+pub const ESTIMATED_WALL_BYTES: usize = 56;                // Plus id length
+pub const PRIORITY_DISTANCE_WEIGHT: f32 = 4.0;
+pub const PRIORITY_ATTACKER_BONUS: f32 = 6.0;
+pub const PRIORITY_FLAG_CARRIER_BONUS: f32 = 3.0;
+pub const PRIORITY_TEAMMATE_BONUS: f32 = 1.0;
+pub const PRIORITY_VELOCITY_WEIGHT: f32 = 1.0;
+pub const PRIORITY_STALENESS_WEIGHT: f32 = 0.5;
+
+// Lag compensation
+pub const MAX_LAG_COMPENSATION_MS: u32 = 250;   // Shots are never resolved further back than this
+pub const HITBOX_HISTORY_TICKS: usize = 32;     // Covers the max rewind at 60 Hz with margin
+
+// Spectators
+pub const SPECTATOR_OVERVIEW_INTERVAL_TICKS: u64 = 6;  // Overview streams refresh at 10 Hz
+
+// Caster feed
+pub const CASTER_MIN_DELAY_SECS: f32 = 30.0;
+pub const CASTER_MAX_DELAY_SECS: f32 = 120.0;
+pub const CASTER_DEFAULT_DELAY_SECS: f32 = 60.0;
+pub const CASTER_FRAME_INTERVAL_TICKS: u64 = 3;                // Caster frames are recorded at 20 Hz
+pub const CASTER_BUFFER_MAX_BYTES: usize = 128 * 1024 * 1024;  // ~2 minutes of a full 400-player match
+pub const CASTER_FRAME_CHUNK_BYTES: usize = 16 * 1024;         // Per message, well under the SCTP message size
+pub const CASTER_CHUNK_ENVELOPE_BYTES: usize = 256;            // Message table and vector headers of a chunk
+
+// Kill cam
+pub const KILL_CAM_HISTORY_MS: u64 = 5000;          // Trajectory length sent to a victim
+pub const KILL_CAM_SAMPLE_INTERVAL_TICKS: u64 = 3;  // Positions are sampled at 20 Hz
+
+// Death recap
+pub const MAX_DAMAGE_LOG_ENTRIES: usize = 64;  // Hits remembered per life
+
+// Capture zones (Domination)
+pub const ZONE_DEFAULT_RADIUS: f32 = 100.0;
+pub const ZONE_CAPTURE_SECS: f32 = 10.0;           // One player alone takes this long to capture a zone
+pub const ZONE_MAX_CAPTURE_MULTIPLIER: f32 = 3.0;  // Extra players speed capture up to this many times
+pub const ZONE_DECAY_PER_SEC: f32 = 0.05;          // Progress lost per second while nobody is inside
+pub const ZONE_SCORE_INTERVAL_SECS: f32 = 1.0;     // Each held zone gives its owner a point this often
+pub const DOMINATION_SCORE_TO_WIN: i32 = 250;
+
+// Battle royale
+pub const SAFE_ZONE_DAMAGE_INTERVAL_SECS: f32 = 1.0;  // Players outside the zone are hurt this often
+pub const BATTLE_ROYALE_OVERTIME_SECS: f32 = 60.0;    // Time after the last circle closes before the match is called
+
+// Infection
+pub const INFECTION_INITIAL_FRACTION: f32 = 0.15;    // Share of players infected at the start, at least one
+pub const INFECTION_MATCH_DURATION_SECS: f32 = 240.0; // Survivors win if any are left when this runs out
+pub const INFECTED_SPEED_MULTIPLIER: f32 = 1.3;        // Infected move speed; keep within MAX_PLAYER_SPEED_MULTIPLIER
+
+// Round-based elimination
+pub const DEFAULT_ROUNDS_BEST_OF: u8 = 7;
+pub const ROUND_WARMUP_SECS: f32 = 30.0;  // Free respawns before the first round
+pub const ROUND_FREEZE_SECS: f32 = 5.0;   // Everyone at their spawn, input locked
+pub const ROUND_LIVE_SECS: f32 = 120.0;   // After this the team with more players alive takes the round
+pub const ROUND_END_SECS: f32 = 5.0;      // Results shown before the next round's freeze
+
+// Scoring
+pub const ASSIST_WINDOW_MS: u64 = 5000;        // Damage this recent before a kill earns an assist
+pub const MULTI_KILL_WINDOW_MS: u64 = 4000;    // Kills this close together chain into a multi-kill
+pub const KILL_STREAK_MILESTONE: u32 = 5;      // Streak bonus every this many kills without dying
+pub const FLAG_DEFENSE_RADIUS: f32 = 400.0;    // Kills this close to your own flag count as defenses
+pub const SHARPSHOOTER_MIN_SHOTS: u32 = 20;    // Shots needed to qualify for the accuracy award
+pub const MATCH_SUMMARY_TOP_PLAYERS: usize = 32; // Scoreboard rows every client gets; keeps the summary to one message
+
+// Weapon inventory
+pub const MAX_RESERVE_MAGAZINES: i32 = 4;          // Reserve ammo is capped at this many magazines per weapon
+pub const AMMO_PICKUP_MAGAZINES: i32 = 1;          // An ammo pickup adds this many magazines to each owned gun
+pub const WEAPON_CRATE_RESERVE_MAGAZINES: i32 = 2; // Reserve that comes with a weapon from a crate
+pub const DEFAULT_SPAWN_RESERVE_MAGAZINES: i32 = 2; // Pistol reserve in the default spawn loadout
+
+// Explosives
+pub const EXPLOSION_SELF_DAMAGE_FRACTION: f32 = 0.5; // Share of its blast damage an explosive deals its owner
+
+// Abilities
+pub const ABILITY_LOCKOUT_SECS: f32 = 0.5;   // After using any ability, none can be used for this long
+pub const DASH_DISTANCE: f32 = 150.0;
+pub const COVER_DISTANCE: f32 = 40.0;        // From the player's center to the middle of deployed cover
+pub const COVER_LENGTH: f32 = 90.0;
+pub const COVER_THICKNESS: f32 = 16.0;
+pub const COVER_HEALTH: i32 = 150;
+pub const COVER_LIFETIME_SECS: f32 = 12.0;   // Deployed cover disappears after this if not destroyed first
+pub const AREA_HEAL_RADIUS: f32 = 160.0;
+pub const AREA_HEAL_AMOUNT: i32 = 40;
+pub const SCAN_RADIUS: f32 = 900.0;          // Enemies this close are revealed to the scanner's team
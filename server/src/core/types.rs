@@ -1,10 +1,18 @@
 // massive_game_server/server/src/core/types.rs
-use std::collections::{VecDeque, HashSet}; 
+use std::collections::HashSet; 
 use std::sync::Arc;
 use std::time::{Instant}; // Removed unused Duration
 use uuid::Uuid;
 use dashmap::DashMap; 
 use std::time::Duration;
+use crate::network::input_buffer::InputJitterBuffer;
+use crate::systems::combat::abilities::{Abilities, AbilityKind, PlayerClass};
+use crate::systems::combat::damage::{DamageCause, DamageLog, DamageTaken};
+use crate::systems::combat::effects::{EffectKind, StatusEffects};
+use crate::systems::combat::weapons::{LoadoutEntry, WeaponInventory};
+use crate::systems::objectives::scoring::PlayerMatchStats;
+use crate::systems::physics::ballistics::ballistic_spec;
+use crate::systems::physics::movement::MovementInput;
 
 
 pub type PlayerID = Arc<String>;
@@ -18,6 +26,8 @@ pub enum ServerWeaponType {
     Rifle,
     Sniper,
     Melee,
+    RocketLauncher,  // Explodes on impact
+    GrenadeLauncher, // Explodes when its fuse runs out
 }
 
 impl Default for ServerWeaponType {
@@ -71,6 +81,7 @@ pub const FIELD_SCORE_STATS: u16     = 1 << 3;
 pub const FIELD_POWERUPS: u16        = 1 << 4;
 pub const FIELD_SHIELD: u16          = 1 << 5;
 pub const FIELD_FLAG: u16            = 1 << 6;
+pub const FIELD_ABILITIES: u16       = 1 << 7;
 
 // --- Game Entities (Basic Definitions) ---
 #[derive(Clone, Debug, PartialEq)]
@@ -86,7 +97,8 @@ pub struct PlayerState {
     pub max_health: i32,
     pub alive: bool,
     pub last_processed_input_sequence: u32,
-    pub input_queue: VecDeque<PlayerInputData>,
+    pub input_buffer: InputJitterBuffer,
+    pub pending_movement: Option<MovementInput>, // This tick's movement input, stepped by the physics update
     pub score: i32,
     pub kills: i32,
     pub deaths: i32,
@@ -94,20 +106,26 @@ pub struct PlayerState {
     pub last_update_timestamp: Option<Instant>,
 
     pub weapon: ServerWeaponType,
-    pub ammo: i32,
+    pub ammo: i32,                  // Rounds loaded in the held weapon
+    pub inventory: WeaponInventory, // Owned weapons with their reserve ammo
     pub respawn_timer: Option<f32>,
     pub reload_progress: Option<f32>,
     pub last_shot_time: Option<Instant>,
 
-    pub speed_boost_remaining: f32,
-    pub damage_boost_remaining: f32,
-    pub shield_current: i32,
-    pub shield_max: i32,
+    pub effects: StatusEffects, // Power-ups and other buffs and debuffs
+    pub class: PlayerClass,     // Takes effect at the next spawn
+    pub abilities: Abilities,
     pub is_carrying_flag_team_id: u8,
+    pub ping_ms: u16,
 
     pub last_valid_position: (f32, f32),
     pub violation_count: u32,
 
+    pub muted_players: HashSet<PlayerID>, // Chat from these players is not delivered to this one
+    pub is_moderator: bool,              // Logged in with /login; may use moderator chat commands
+    pub damage_log: DamageLog,           // Hits taken this life, for the death recap
+    pub match_stats: PlayerMatchStats,   // Everything but score, kills and deaths; reset with them
+
     pub changed_fields: u16,
 }
 
@@ -129,7 +147,8 @@ impl PlayerState {
             max_health: 100,
             alive: true,
             last_processed_input_sequence: 0,
-            input_queue: VecDeque::with_capacity(crate::core::constants::MAX_INPUT_QUEUE_SIZE_PER_PLAYER),
+            input_buffer: InputJitterBuffer::new(),
+            pending_movement: None,
             score: 0,
             kills: 0,
             deaths: 0,
@@ -137,25 +156,27 @@ impl PlayerState {
             last_update_timestamp: Some(Instant::now()),
             weapon: default_weapon,
             ammo: default_ammo,
+            inventory: WeaponInventory::default(),
             respawn_timer: None,
             reload_progress: None,
             last_shot_time: None,
-            speed_boost_remaining: 0.0,
-            damage_boost_remaining: 0.0,
-            shield_current: 0,
-            shield_max: 0,
+            effects: StatusEffects::default(),
+            class: PlayerClass::default(),
+            abilities: Abilities::for_class(PlayerClass::default()),
             is_carrying_flag_team_id: 0,
+            ping_ms: 0,
             last_valid_position: (initial_x, initial_y),
             violation_count: 0,
+            muted_players: HashSet::new(),
+            is_moderator: false,
+            damage_log: DamageLog::default(),
+            match_stats: PlayerMatchStats::default(),
             changed_fields: 0xFFFF, 
         }
     }
 
     pub fn queue_input(&mut self, input: PlayerInputData) {
-        if self.input_queue.len() >= crate::core::constants::MAX_INPUT_QUEUE_SIZE_PER_PLAYER {
-            self.input_queue.pop_front();
-        }
-        self.input_queue.push_back(input);
+        self.input_buffer.push(input);
     }
 
     pub fn mark_field_changed(&mut self, field_flag: u16) {
@@ -171,6 +192,7 @@ impl PlayerState {
             ServerWeaponType::Pistol => 7, ServerWeaponType::Shotgun => 5,
             ServerWeaponType::Rifle => 30, ServerWeaponType::Sniper => 5,
             ServerWeaponType::Melee => 0, 
+            ServerWeaponType::RocketLauncher => 1, ServerWeaponType::GrenadeLauncher => 3,
         }
     }
 
@@ -179,6 +201,7 @@ impl PlayerState {
             ServerWeaponType::Pistol => 0.6, ServerWeaponType::Shotgun => 0.8,
             ServerWeaponType::Rifle => 0.1, ServerWeaponType::Sniper => 1.2,
             ServerWeaponType::Melee => 0.5,
+            ServerWeaponType::RocketLauncher => 1.0, ServerWeaponType::GrenadeLauncher => 0.8,
         }
     }
 
@@ -187,17 +210,19 @@ impl PlayerState {
             ServerWeaponType::Pistol => 1.5, ServerWeaponType::Shotgun => 2.5,
             ServerWeaponType::Rifle => 2.0, ServerWeaponType::Sniper => 3.0,
             ServerWeaponType::Melee => 0.0, 
+            ServerWeaponType::RocketLauncher => 2.5, ServerWeaponType::GrenadeLauncher => 3.0,
         }
     }
 
-    pub fn get_weapon_damage(weapon_type: ServerWeaponType, damage_boost_active: bool) -> i32 {
+    pub fn get_weapon_damage(weapon_type: ServerWeaponType, damage_multiplier: f32) -> i32 {
         let base_damage = match weapon_type {
             ServerWeaponType::Pistol => 8, ServerWeaponType::Shotgun => 7, 
             ServerWeaponType::Rifle => 10, ServerWeaponType::Sniper => 50,
             ServerWeaponType::Melee => 30,
+            // Blast damage at the center
+            ServerWeaponType::RocketLauncher => 80, ServerWeaponType::GrenadeLauncher => 60,
         };
-        let multiplier = if damage_boost_active { 1.5 } else { 1.0 };
-        (base_damage as f32 * multiplier) as i32
+        (base_damage as f32 * damage_multiplier) as i32
     }
 
     pub fn can_shoot(&self, current_time: Instant) -> bool {
@@ -214,6 +239,7 @@ impl PlayerState {
 
     pub fn start_reload(&mut self, _current_time: Instant) { 
         if self.reload_progress.is_some() || !self.alive || self.ammo == Self::get_max_ammo_for_weapon(self.weapon) { return; }
+        if self.reserve_ammo() <= 0 { return; }
         let reload_duration = Self::get_weapon_reload_time_seconds(self.weapon);
         if reload_duration > 0.0 {
             self.reload_progress = Some(0.0); 
@@ -227,7 +253,8 @@ impl PlayerState {
             if reload_duration > 0.0 {
                 *progress += delta_time / reload_duration; 
                 if *progress >= 1.0 {
-                    self.ammo = Self::get_max_ammo_for_weapon(self.weapon);
+                    let needed = Self::get_max_ammo_for_weapon(self.weapon) - self.ammo;
+                    self.ammo += self.inventory.take_reserve(self.weapon, needed);
                     self.reload_progress = None;
                     self.mark_field_changed(FIELD_WEAPON_AMMO);
                 } else {
@@ -241,13 +268,12 @@ impl PlayerState {
 
     pub fn apply_damage(&mut self, damage: i32) -> bool { 
         if !self.alive { return false; }
+        let damage = (damage as f32 * self.effects.modifiers().damage_taken).round() as i32;
         let mut remaining_damage = damage;
 
-        if self.shield_current > 0 {
-            let shield_damage = remaining_damage.min(self.shield_current);
-            self.shield_current -= shield_damage;
-            remaining_damage -= shield_damage;
-            self.mark_field_changed(FIELD_SHIELD);
+        if self.effects.absorb_amount() > 0 {
+            remaining_damage = self.effects.absorb(remaining_damage);
+            self.mark_field_changed(FIELD_SHIELD | FIELD_POWERUPS);
         }
 
         if remaining_damage > 0 {
@@ -265,9 +291,27 @@ impl PlayerState {
         false 
     }
 
+    /// Applies a hit and records it in the damage log. Returns true if the player died.
+    pub fn take_hit(&mut self, damage: i32, attacker_id: Option<PlayerID>, cause: DamageCause, distance: f32, timestamp_ms: u64) -> bool {
+        if !self.alive { return false; }
+        let (shield_before, health_before) = (self.effects.absorb_amount(), self.health);
+        let died = self.apply_damage(damage);
+        self.damage_log.record(DamageTaken {
+            attacker_id,
+            cause,
+            shield_damage: shield_before - self.effects.absorb_amount(),
+            health_damage: health_before - self.health,
+            is_melee: cause == DamageCause::Weapon(ServerWeaponType::Melee),
+            distance,
+            timestamp_ms,
+        });
+        died
+    }
+
     fn die(&mut self) {
         self.alive = false; 
         self.deaths += 1; 
+        self.match_stats.end_streak();
         self.respawn_timer = Some(crate::core::constants::DEFAULT_RESPAWN_DURATION_SECS);
         self.velocity_x = 0.0; // Added for consistency
         self.velocity_y = 0.0; // Added for consistency
@@ -276,24 +320,134 @@ impl PlayerState {
         self.mark_field_changed(FIELD_HEALTH_ALIVE | FIELD_SCORE_STATS | FIELD_POSITION_ROTATION); // FIELD_FLAG will be marked by caller if changed
     }
 
-    pub fn respawn(&mut self, new_x: f32, new_y: f32) {
+    /// Takes a player who joined mid-round out of play without counting a death. They come back
+    /// on the next respawn the mode allows.
+    pub fn sit_out(&mut self) {
+        self.alive = false;
+        self.health = 0;
+        self.respawn_timer = Some(0.0);
+        self.velocity_x = 0.0;
+        self.velocity_y = 0.0;
+        self.mark_field_changed(FIELD_HEALTH_ALIVE | FIELD_POSITION_ROTATION);
+    }
+
+    /// Clears score, kills, deaths and the rest of the match stats for a new match.
+    pub fn reset_match_stats(&mut self) {
+        self.score = 0;
+        self.kills = 0;
+        self.deaths = 0;
+        self.match_stats = PlayerMatchStats::default();
+        self.mark_field_changed(FIELD_SCORE_STATS);
+    }
+
+    /// Reserve ammo for the held weapon.
+    pub fn reserve_ammo(&self) -> i32 {
+        self.inventory.reserve(self.weapon)
+    }
+
+    /// Switches to an owned weapon, putting the current one away with whatever it had loaded.
+    /// Returns false if the weapon isn't owned or is already held.
+    pub fn equip_weapon(&mut self, weapon: ServerWeaponType) -> bool {
+        if weapon == self.weapon || !self.inventory.owns(weapon) { return false; }
+        self.inventory.stash_magazine(self.weapon, self.ammo);
+        self.weapon = weapon;
+        self.ammo = self.inventory.get(weapon).map_or(0, |slot| slot.magazine);
+        self.reload_progress = None;
+        self.mark_field_changed(FIELD_WEAPON_AMMO);
+        true
+    }
+
+    /// Gives a weapon, or more reserve for one already owned. Returns false if nothing changed.
+    pub fn grant_weapon(&mut self, weapon: ServerWeaponType, reserve: i32) -> bool {
+        let had_reserve = self.inventory.reserve(weapon);
+        let changed = self.inventory.grant(weapon, reserve) || self.inventory.reserve(weapon) != had_reserve;
+        if changed { self.mark_field_changed(FIELD_WEAPON_AMMO); }
+        changed
+    }
+
+    /// Adds `magazines` worth of reserve to every owned gun. Returns false if all were full.
+    pub fn add_reserve_magazines(&mut self, magazines: i32) -> bool {
+        let weapons: Vec<ServerWeaponType> = self.inventory.slots().iter().map(|slot| slot.weapon).collect();
+        let added: i32 = weapons.into_iter()
+            .map(|weapon| self.inventory.add_reserve(weapon, Self::get_max_ammo_for_weapon(weapon) * magazines))
+            .sum();
+        if added > 0 { self.mark_field_changed(FIELD_WEAPON_AMMO); }
+        added > 0
+    }
+
+    /// Replaces the inventory with a fresh loadout, holding its first weapon (melee if empty).
+    pub fn reset_loadout(&mut self, loadout: &[LoadoutEntry]) {
+        self.inventory = WeaponInventory::from_loadout(loadout);
+        self.weapon = loadout.first().map_or(ServerWeaponType::Melee, |entry| entry.weapon);
+        self.ammo = Self::get_max_ammo_for_weapon(self.weapon);
+        self.reload_progress = None;
+        self.mark_field_changed(FIELD_WEAPON_AMMO);
+    }
+
+    pub fn respawn(&mut self, new_x: f32, new_y: f32, loadout: &[LoadoutEntry]) {
         self.alive = true;
         self.health = self.max_health;
         self.respawn_timer = None;
         self.x = new_x; self.y = new_y;
         self.last_valid_position = (new_x, new_y);
         self.velocity_x = 0.0; self.velocity_y = 0.0;
-        self.weapon = ServerWeaponType::Pistol;
-        self.ammo = Self::get_max_ammo_for_weapon(self.weapon);
-        self.reload_progress = None;
-        self.shield_current = 0; 
+        self.reset_loadout(loadout);
+        self.effects.clear();
+        self.reset_abilities();
         self.is_carrying_flag_team_id = 0; // Reset flag carrying state on respawn
-        self.mark_field_changed(FIELD_HEALTH_ALIVE | FIELD_POSITION_ROTATION | FIELD_WEAPON_AMMO | FIELD_SHIELD | FIELD_FLAG);
+        self.damage_log.clear();
+        self.mark_field_changed(FIELD_HEALTH_ALIVE | FIELD_POSITION_ROTATION | FIELD_WEAPON_AMMO | FIELD_SHIELD | FIELD_POWERUPS | FIELD_FLAG);
+    }
+
+    /// Gives the player their class's abilities, fully charged.
+    pub fn reset_abilities(&mut self) {
+        self.abilities = Abilities::for_class(self.class);
+        self.mark_field_changed(FIELD_ABILITIES);
+    }
+
+    /// Restores health up to the maximum. Returns how much was restored.
+    pub fn heal(&mut self, amount: i32) -> i32 {
+        if !self.alive {
+            return 0;
+        }
+        let healed = amount.clamp(0, (self.max_health - self.health).max(0));
+        if healed > 0 {
+            self.health += healed;
+            self.mark_field_changed(FIELD_HEALTH_ALIVE);
+        }
+        healed
+    }
+
+    /// Applies a status effect. Returns false if it was already active and reapplying did nothing.
+    pub fn apply_effect(&mut self, kind: EffectKind, source: Option<PlayerID>) -> bool {
+        let applied = self.effects.apply(kind, source);
+        if applied { self.mark_field_changed(FIELD_POWERUPS | FIELD_SHIELD); }
+        applied
+    }
+
+    /// Advances status effects and applies their healing. Returns damage over time still to be
+    /// dealt, with who caused it, so the caller can handle a death like any other.
+    /// Returns the damage over time dealt this tick, with who applied it and by which effect.
+    pub fn update_effects(&mut self, delta_time: f32) -> Vec<(i32, Option<PlayerID>, EffectKind)> {
+        if self.effects.active().is_empty() {
+            return Vec::new();
+        }
+        self.mark_field_changed(FIELD_POWERUPS);
+        let mut damage = Vec::new();
+        for sourced in self.effects.update(delta_time) {
+            if sourced.tick.heal > 0 && self.alive && self.health < self.max_health {
+                self.health = (self.health + sourced.tick.heal).min(self.max_health);
+                self.mark_field_changed(FIELD_HEALTH_ALIVE);
+            }
+            if sourced.tick.damage > 0 {
+                damage.push((sourced.tick.damage, sourced.source, sourced.kind));
+            }
+        }
+        damage
     }
 
     pub fn update_timers(&mut self, delta_time: f32) {
         let mut changed_health_alive = false;
-        let mut changed_powerups = false;
 
         if !self.alive {
             if let Some(timer) = &mut self.respawn_timer {
@@ -305,23 +459,18 @@ impl PlayerState {
             }
         }
 
-        if self.speed_boost_remaining > 0.0 {
-            self.speed_boost_remaining = (self.speed_boost_remaining - delta_time).max(0.0);
-            changed_powerups = true;
-        }
-        if self.damage_boost_remaining > 0.0 {
-            self.damage_boost_remaining = (self.damage_boost_remaining - delta_time).max(0.0);
-            changed_powerups = true;
-        }
-
         if changed_health_alive { self.mark_field_changed(FIELD_HEALTH_ALIVE); }
-        if changed_powerups { self.mark_field_changed(FIELD_POWERUPS); }
 
         let old_reload_progress = self.reload_progress;
         self.update_reload_progress(delta_time);
         if self.reload_progress != old_reload_progress { 
              self.mark_field_changed(FIELD_WEAPON_AMMO);
         }
+
+        // Clients count cooldowns down themselves; they only need to hear when a charge is back
+        if self.abilities.update(delta_time) {
+            self.mark_field_changed(FIELD_ABILITIES);
+        }
     }
 }
 
@@ -333,11 +482,17 @@ pub struct Projectile {
     pub weapon_type: ServerWeaponType,
     pub x: f32,
     pub y: f32,
+    pub origin_x: f32, // Where it was fired from, for kill cams
+    pub origin_y: f32,
     pub velocity_x: f32,
     pub velocity_y: f32,
     pub damage: i32,
     pub creation_time: Instant,
     pub max_lifetime_secs: f32,
+    pub rewind_ms: u32, // Lag compensation: targets are tested at their positions this long ago
+    pub penetrations_left: u8,
+    pub ricochets_left: u8,
+    pub inside_wall: Option<EntityId>, // Wall it last went into or glanced off, which it doesn't hit again
 }
 
 impl Projectile {
@@ -359,11 +514,13 @@ impl Projectile {
             ServerWeaponType::Rifle => (550.0, 2.5),
             ServerWeaponType::Sniper => (700.0, 4.0),
             ServerWeaponType::Melee => (0.0, 0.0), 
+            ServerWeaponType::RocketLauncher => (crate::core::constants::ROCKET_PROJECTILE_SPEED, 3.0),
+            ServerWeaponType::GrenadeLauncher => (crate::core::constants::GRENADE_PROJECTILE_SPEED, 1.5), // Lifetime is the fuse
         };
         
         // Use PlayerState::get_weapon_damage for consistent damage calculation
-        let has_damage_boost = damage_multiplier > 1.0;
-        let damage = PlayerState::get_weapon_damage(weapon_type, has_damage_boost);
+        let damage = PlayerState::get_weapon_damage(weapon_type, damage_multiplier);
+        let ballistics = ballistic_spec(weapon_type);
 
         Projectile {
             id,
@@ -371,11 +528,17 @@ impl Projectile {
             weapon_type,
             x: start_x,
             y: start_y,
+            origin_x: start_x,
+            origin_y: start_y,
             velocity_x: direction_x * speed,
             velocity_y: direction_y * speed,
             damage,
             creation_time: Instant::now(),
             max_lifetime_secs: lifetime,
+            rewind_ms: 0,
+            penetrations_left: ballistics.penetrations,
+            ricochets_left: ballistics.ricochets,
+            inside_wall: None,
         }
     }
     pub fn should_remove(&self) -> bool {
@@ -401,6 +564,10 @@ pub enum GameEvent {
     FlagDropped { player_id: PlayerID, flag_team_id: u8, position: Vec2 },
     FlagReturned { player_id: PlayerID, flag_team_id: u8, position: Vec2 },
     FlagCaptured { capturer_id: PlayerID, captured_flag_team_id: u8, capturing_team_id: u8, position: Vec2 },
+    ZoneCaptured { zone_id: u8, capturing_team_id: u8, position: Vec2 },
+    Explosion { owner_id: PlayerID, weapon: ServerWeaponType, position: Vec2, radius: f32 },
+    AbilityUsed { player_id: PlayerID, ability: AbilityKind, position: Vec2 },
+    PlayerInfected { player_id: PlayerID, infected_by: PlayerID, position: Vec2 },
 }
 
 
@@ -541,4 +708,8 @@ impl RTCDataChannel {
             .map(|_bytes_sent| ()) 
             .map_err(|e| e.to_string())
     }
+
+    pub async fn close(&self) -> Result<(), String> {
+        self.inner.close().await.map_err(|e| e.to_string())
+    }
 }
@@ -27,6 +27,11 @@ impl PlayerIdPool {
         new_arc_id
     }
 
+    /// Looks up an id without allocating one.
+    pub fn get(&self, id_str: &str) -> Option<PlayerID> {
+        self.allocated_ids.get(id_str).map(|entry| entry.value().clone())
+    }
+
     pub fn remove(&self, id_str: &str) -> Option<PlayerID> {
         self.allocated_ids.remove(id_str).map(|(_key, arc_id)| arc_id)
     }
@@ -15,7 +15,7 @@ pub mod entities;
 pub mod world;
 pub mod server;
 pub mod network; // Assuming signaling.rs is in here
-// pub mod state_sync;
+pub mod state_sync;
 pub mod operational;
 pub mod systems;
 // pub mod memory;
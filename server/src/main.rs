@@ -2,17 +2,26 @@
 use massive_game_server_core::server::instance::MassiveGameServer;
 use massive_game_server_core::concurrent::thread_pools::ThreadPoolSystem;
 use massive_game_server_core::core::config::ServerConfig;
+use massive_game_server_core::core::constants::DEFAULT_ROUNDS_BEST_OF;
 use massive_game_server_core::network::signaling::{
     handle_signaling_connection, ChatMessagesQueue, ClientStatesMap,
     DataChannelsMap, PlayerManagerRef, SignalingPeers, WorldPartitionManagerRef, ServerInstanceRef, // Added ServerInstanceRef
 };
 use massive_game_server_core::core::types::PlayerAoI;
+use massive_game_server_core::network::spectator::{SpectatorMode, SpectatorView};
+use massive_game_server_core::network::caster::CasterSeat;
+use massive_game_server_core::systems::objectives::{create_game_mode, parse_game_mode};
+use massive_game_server_core::systems::objectives::rules::{parse_match_rules, unsupported_overrides};
+use massive_game_server_core::systems::combat::abilities::PlayerClass;
+use massive_game_server_core::systems::combat::weapons::{default_spawn_loadout, parse_spawn_loadout};
+use massive_game_server_core::world::boundary::{default_safe_zone_phases, parse_safe_zone_phases};
+use massive_game_server_core::flatbuffers_generated::game_protocol as fb;
 use dashmap::DashMap;
 
 use std::collections::{VecDeque};
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::{error, info, Level};
+use tracing::{error, info, warn, Level};
 use tracing_subscriber::{EnvFilter, fmt};
 use warp::Filter;
 use uuid::Uuid;
@@ -23,6 +32,9 @@ use std::collections::HashMap;
 
 
 
+// Admin requests carry the key in this header; query strings end up in access logs
+const ADMIN_KEY_HEADER: &str = "x-admin-key";
+
 fn init_logging() -> anyhow::Result<()> {
     let subscriber = fmt::Subscriber::builder()
         .with_max_level(Level::INFO) // Adjusted default to INFO
@@ -83,7 +95,64 @@ async fn main() -> anyhow::Result<()> {
 
     info!("Massive Game Server starting up...");
 
-    let config = Arc::new(ServerConfig::default());
+    let mut config = ServerConfig {
+        caster_key: std::env::var("CASTER_KEY").ok().filter(|key| !key.is_empty()),
+        admin_key: std::env::var("ADMIN_KEY").ok().filter(|key| !key.is_empty()),
+        open_spectating: std::env::var("OPEN_SPECTATING").is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true")),
+        chat_word_filter: std::env::var("CHAT_WORD_FILTER")
+            .map(|words| words.split(',').map(str::trim).filter(|word| !word.is_empty()).map(String::from).collect())
+            .unwrap_or_default(),
+        game_mode: match std::env::var("GAME_MODE") {
+            Ok(name) => parse_game_mode(&name).unwrap_or_else(|| {
+                warn!("Unknown GAME_MODE '{}', falling back to capture the flag.", name);
+                fb::GameModeType::CaptureTheFlag
+            }),
+            Err(_) => fb::GameModeType::CaptureTheFlag,
+        },
+        battle_royale_teams: std::env::var("BATTLE_ROYALE_TEAMS").is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true")),
+        safe_zone_phases: match std::env::var("SAFE_ZONE_PHASES") {
+            Ok(spec) => parse_safe_zone_phases(&spec).unwrap_or_else(|| {
+                warn!("Invalid SAFE_ZONE_PHASES '{}', using the default phases.", spec);
+                default_safe_zone_phases()
+            }),
+            Err(_) => default_safe_zone_phases(),
+        },
+        rounds_best_of: std::env::var("ROUNDS_BEST_OF").ok()
+            .and_then(|n| n.parse::<u8>().ok())
+            .filter(|n| *n > 0)
+            .unwrap_or(DEFAULT_ROUNDS_BEST_OF),
+        spawn_loadout: match std::env::var("SPAWN_LOADOUT") {
+            Ok(spec) => parse_spawn_loadout(&spec).unwrap_or_else(|| {
+                warn!("Invalid SPAWN_LOADOUT '{}', using the default loadout.", spec);
+                default_spawn_loadout()
+            }),
+            Err(_) => default_spawn_loadout(),
+        },
+        projectile_ricochet: std::env::var("PROJECTILE_RICOCHET").is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true")),
+        default_class: match std::env::var("DEFAULT_CLASS") {
+            Ok(name) => PlayerClass::parse(&name).unwrap_or_else(|| {
+                warn!("Unknown DEFAULT_CLASS '{}', falling back to {}.", name, PlayerClass::default().name());
+                PlayerClass::default()
+            }),
+            Err(_) => PlayerClass::default(),
+        },
+        ..ServerConfig::default()
+    };
+    // Overrides apply on top of the selected mode's own rules
+    if let Ok(spec) = std::env::var("MATCH_RULES") {
+        let mode = create_game_mode(&config);
+        let mode_rules = mode.default_rules();
+        config.match_rules = parse_match_rules(&spec, &mode_rules);
+        match config.match_rules.as_ref().map(|rules| unsupported_overrides(rules, &mode_rules, mode.supported_rules())) {
+            None => warn!("Invalid MATCH_RULES '{}', using the game mode's rules.", spec),
+            Some(unsupported) if !unsupported.is_empty() => {
+                warn!("MATCH_RULES '{}' sets {} which {:?} doesn't use, using the game mode's rules.", spec, unsupported.join(", "), mode.mode_type());
+                config.match_rules = None;
+            }
+            Some(_) => {}
+        }
+    }
+    let config = Arc::new(config);
     info!("Server configuration loaded. Tick rate: {}", config.tick_rate);
 
     let thread_pool_system = match ThreadPoolSystem::new(config.clone()) {
@@ -128,6 +197,7 @@ async fn main() -> anyhow::Result<()> {
 
     let signaling_route = warp::path("ws")
         .and(warp::ws())
+        .and(warp::query::<HashMap<String, String>>())
         .and(warp::any().map(move || signaling_peers_for_ws.clone()))
         .and(warp::any().map(move || player_manager_for_ws.clone()))
         .and(warp::any().map(move || world_partition_manager_for_ws.clone()))
@@ -139,6 +209,7 @@ async fn main() -> anyhow::Result<()> {
         .and(warp::any().map(move || server_instance_for_ws.clone())) // Pass server instance Arc
         .map(
             |ws: warp::ws::Ws,
+             query_params: HashMap<String, String>,
              s_peers: SignalingPeers,
              p_manager: PlayerManagerRef,
              w_p_manager: WorldPartitionManagerRef,
@@ -148,8 +219,30 @@ async fn main() -> anyhow::Result<()> {
              conf: Arc<ServerConfig>,
              p_aois: Arc<DashMap<String, PlayerAoI>>,
              server_inst: ServerInstanceRef| { // Accept server instance Arc
-                let peer_id = Uuid::new_v4().to_string();
-                ws.on_upgrade(move |socket| {
+                // A valid resume token re-binds this connection to the player it was issued for.
+                let resumed_peer_id = query_params.get("resume")
+                    .and_then(|token| server_inst.session_manager.resume(token));
+                // ws?spectate=<follow|free|overview>[&target=<player id>][&key=<caster key>] joins without a player.
+                let spectate_authorized = conf.open_spectating || conf.is_caster_key(query_params.get("key").map(String::as_str));
+                if query_params.contains_key("spectate") && resumed_peer_id.is_none() && !spectate_authorized {
+                    return Box::new(warp::reply::with_status("spectating needs the caster key", warp::http::StatusCode::FORBIDDEN)) as Box<dyn warp::Reply>;
+                }
+                let spectator_view = query_params.get("spectate")
+                    .filter(|_| resumed_peer_id.is_none())
+                    .and_then(|mode| SpectatorMode::from_query(mode))
+                    .map(|mode| {
+                        let target = query_params.get("target").and_then(|id| server_inst.player_manager.id_pool.get(id));
+                        SpectatorView::new(mode, target)
+                    });
+                // ws?caster=<key> joins the delayed caster feed.
+                let is_caster = resumed_peer_id.is_none() && conf.is_caster_key(query_params.get("caster").map(String::as_str));
+                let peer_id = resumed_peer_id.unwrap_or_else(|| Uuid::new_v4().to_string());
+                Box::new(ws.on_upgrade(move |socket| {
+                    if is_caster {
+                        server_inst.caster_seats.insert(peer_id.clone(), CasterSeat::default());
+                    } else if let Some(view) = spectator_view {
+                        server_inst.spectators.insert(peer_id.clone(), view);
+                    }
                     handle_signaling_connection(
                         socket,
                         peer_id,
@@ -163,10 +256,62 @@ async fn main() -> anyhow::Result<()> {
                         p_aois,
                         server_inst, // Pass server instance to handler
                     )
-                })
+                }))
             },
         );
 
+    // POST /admin/caster_delay?secs=<30-120>, with the admin key in the X-Admin-Key header
+    let server_instance_for_admin = game_server_instance.clone();
+    let caster_delay_route = warp::path!("admin" / "caster_delay")
+        .and(warp::post())
+        .and(warp::header::optional::<String>(ADMIN_KEY_HEADER))
+        .and(warp::query::<HashMap<String, String>>())
+        .map(move |admin_key: Option<String>, query_params: HashMap<String, String>| {
+            if !server_instance_for_admin.config.is_admin_key(admin_key.as_deref()) {
+                return warp::reply::with_status("forbidden".to_string(), warp::http::StatusCode::FORBIDDEN);
+            }
+            match query_params.get("secs").and_then(|secs| secs.parse::<f32>().ok()) {
+                Some(secs) => {
+                    let applied = server_instance_for_admin.set_caster_delay_secs(secs);
+                    warp::reply::with_status(format!("caster delay set to {}s", applied), warp::http::StatusCode::OK)
+                }
+                None => warp::reply::with_status("missing or invalid secs".to_string(), warp::http::StatusCode::BAD_REQUEST),
+            }
+        });
+
+    // POST /admin/match_rules?rules=<MATCH_RULES syntax>, with the admin key in the X-Admin-Key
+    // header; applied from the next match
+    let server_instance_for_rules = game_server_instance.clone();
+    let match_rules_route = warp::path!("admin" / "match_rules")
+        .and(warp::post())
+        .and(warp::header::optional::<String>(ADMIN_KEY_HEADER))
+        .and(warp::query::<HashMap<String, String>>())
+        .map(move |admin_key: Option<String>, query_params: HashMap<String, String>| {
+            if !server_instance_for_rules.config.is_admin_key(admin_key.as_deref()) {
+                return warp::reply::with_status("forbidden".to_string(), warp::http::StatusCode::FORBIDDEN);
+            }
+            let game_mode = &server_instance_for_rules.game_mode;
+            let base = game_mode.default_rules();
+            let parsed = query_params.get("rules").and_then(|spec| parse_match_rules(spec, &base)).map(|rules| {
+                let unsupported = unsupported_overrides(&rules, &base, game_mode.supported_rules());
+                (rules, unsupported)
+            });
+            match parsed {
+                Some((_, unsupported)) if !unsupported.is_empty() => {
+                    warp::reply::with_status(
+                        format!("{:?} doesn't use {}", game_mode.mode_type(), unsupported.join(", ")),
+                        warp::http::StatusCode::BAD_REQUEST,
+                    )
+                }
+                Some((rules, _)) => {
+                    let reply = format!("match rules for the next match: {:?}", rules);
+                    server_instance_for_rules.set_next_match_rules(rules);
+                    warp::reply::with_status(reply, warp::http::StatusCode::OK)
+                }
+                None => warp::reply::with_status("missing or invalid rules".to_string(), warp::http::StatusCode::BAD_REQUEST),
+            }
+        });
+
     let static_files_route = warp::fs::dir("static_client")
         .map(|reply: warp::filters::fs::File| {
             if reply.path().extension().map_or(false, |ext| ext == "html") {
@@ -177,8 +322,10 @@ async fn main() -> anyhow::Result<()> {
         });
 
     let routes = signaling_route
+        .or(caster_delay_route)
+        .or(match_rules_route)
         .or(static_files_route)
-        .with(warp::cors().allow_any_origin().allow_methods(vec!["GET", "POST", "OPTIONS"]).allow_headers(vec!["Content-Type", "User-Agent", "Sec-WebSocket-Key", "Sec-WebSocket-Version", "Sec-WebSocket-Extensions", "Upgrade", "Connection"]));
+        .with(warp::cors().allow_any_origin().allow_methods(vec!["GET", "POST", "OPTIONS"]).allow_headers(vec!["Content-Type", "X-Admin-Key", "User-Agent", "Sec-WebSocket-Key", "Sec-WebSocket-Version", "Sec-WebSocket-Extensions", "Upgrade", "Connection"]));
 
     let game_server_for_loop = Arc::clone(&game_server_instance); // Use the renamed variable
     tokio::spawn(async move {
@@ -0,0 +1,162 @@
+// massive_game_server/server/src/network/caster.rs
+// Delayed caster feed for streamed matches.
+//
+// Caster connections (ws?caster=<key>) are spectators that only ever see the match as it
+// was `delay` seconds ago, so stream viewers can't relay live positions. Every few ticks the
+// server encodes a full-world snapshot (which covers any camera the caster may use) and keeps
+// it here until it is old enough to replay. Encoded frames are kept rather than world state
+// because they are far smaller and already in wire format. A frame is split into chunks that
+// each fit in one data channel message; the first is the full snapshot, the rest add entities.
+//
+// The buffer is capped in bytes. When full, the oldest frames are dropped: the caster stream
+// stutters, but the delay is never shortened.
+use crate::core::constants::{CASTER_MAX_DELAY_SECS, CASTER_MIN_DELAY_SECS};
+use crate::core::types::GameEvent;
+use bytes::Bytes;
+use dashmap::DashMap;
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+pub type CasterSeatsMap = Arc<DashMap<String, CasterSeat>>;
+
+#[derive(Debug, Clone, Default)]
+pub struct CasterSeat {
+    pub walls_sent: bool,
+    pub last_sent_tick: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CasterFrame {
+    pub tick: u64,
+    pub recorded_at_ms: u64,
+    pub chunks: Vec<Bytes>,
+}
+
+impl CasterFrame {
+    pub fn byte_len(&self) -> usize {
+        self.chunks.iter().map(Bytes::len).sum()
+    }
+}
+
+#[derive(Debug)]
+pub struct CasterFeed {
+    frames: VecDeque<CasterFrame>,
+    buffered_bytes: usize,
+    max_bytes: usize,
+    delay_ms: u64,
+    dropped_frames: u64,
+    pending_events: Vec<GameEvent>, // Events since the last recorded frame
+}
+
+impl CasterFeed {
+    pub fn new(delay_secs: f32, max_bytes: usize) -> Self {
+        let mut feed = CasterFeed {
+            frames: VecDeque::new(),
+            buffered_bytes: 0,
+            max_bytes,
+            delay_ms: 0,
+            dropped_frames: 0,
+            pending_events: Vec::new(),
+        };
+        feed.set_delay_secs(delay_secs);
+        feed
+    }
+
+    /// Sets the replay delay, clamped to the allowed range. Returns the delay applied.
+    pub fn set_delay_secs(&mut self, delay_secs: f32) -> f32 {
+        let clamped = if delay_secs.is_finite() {
+            delay_secs.clamp(CASTER_MIN_DELAY_SECS, CASTER_MAX_DELAY_SECS)
+        } else {
+            CASTER_MAX_DELAY_SECS
+        };
+        self.delay_ms = (clamped * 1000.0) as u64;
+        clamped
+    }
+
+    pub fn delay_secs(&self) -> f32 {
+        self.delay_ms as f32 / 1000.0
+    }
+
+    pub fn buffered_bytes(&self) -> usize {
+        self.buffered_bytes
+    }
+
+    pub fn dropped_frames(&self) -> u64 {
+        self.dropped_frames
+    }
+
+    /// Holds a tick's events until the next frame is recorded.
+    pub fn queue_events(&mut self, events: &[GameEvent]) {
+        self.pending_events.extend_from_slice(events);
+    }
+
+    pub fn take_pending_events(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.pending_events)
+    }
+
+    /// Buffers a frame, dropping the oldest ones if the byte cap is exceeded.
+    pub fn record(&mut self, frame: CasterFrame) {
+        self.buffered_bytes += frame.byte_len();
+        self.frames.push_back(frame);
+        while self.buffered_bytes > self.max_bytes {
+            let Some(dropped) = self.frames.pop_front() else { break };
+            self.buffered_bytes -= dropped.byte_len();
+            self.dropped_frames += 1;
+        }
+    }
+
+    /// Frames old enough to replay that come after `last_sent_tick`, oldest first.
+    /// A caster that hasn't been sent anything starts at the newest due frame.
+    pub fn due_frames(&self, now_ms: u64, last_sent_tick: Option<u64>) -> Vec<CasterFrame> {
+        let due = self.frames.iter().take_while(|frame| frame.recorded_at_ms + self.delay_ms <= now_ms);
+        match last_sent_tick {
+            Some(last_tick) => due.filter(|frame| frame.tick > last_tick).cloned().collect(),
+            None => due.last().cloned().into_iter().collect(),
+        }
+    }
+
+    /// Drops due frames older than the newest due one; every seat has been sent them by now.
+    pub fn prune(&mut self, now_ms: u64) {
+        while self.frames.len() > 1 && self.frames[1].recorded_at_ms + self.delay_ms <= now_ms {
+            if let Some(old) = self.frames.pop_front() {
+                self.buffered_bytes -= old.byte_len();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(tick: u64, recorded_at_ms: u64, len: usize) -> CasterFrame {
+        CasterFrame { tick, recorded_at_ms, chunks: vec![Bytes::from(vec![0u8; len])] }
+    }
+
+    #[test]
+    fn test_frames_replay_only_after_delay() {
+        let mut feed = CasterFeed::new(30.0, 1024);
+        feed.record(frame(1, 1_000, 10));
+        feed.record(frame(2, 2_000, 10));
+
+        assert!(feed.due_frames(30_999, None).is_empty());
+        let due: Vec<u64> = feed.due_frames(32_000, Some(0)).iter().map(|f| f.tick).collect();
+        assert_eq!(due, vec![1, 2]);
+        assert_eq!(feed.due_frames(32_000, None).len(), 1);
+
+        feed.prune(32_000);
+        assert_eq!(feed.buffered_bytes(), 10);
+    }
+
+    #[test]
+    fn test_byte_cap_drops_oldest_and_delay_is_clamped() {
+        let mut feed = CasterFeed::new(5.0, 25);
+        assert_eq!(feed.delay_secs(), CASTER_MIN_DELAY_SECS);
+        for tick in 1..=3 {
+            feed.record(frame(tick, tick * 1000, 10));
+        }
+        assert_eq!(feed.buffered_bytes(), 20);
+        assert_eq!(feed.dropped_frames(), 1);
+        assert_eq!(feed.set_delay_secs(600.0), CASTER_MAX_DELAY_SECS);
+    }
+}
@@ -0,0 +1,411 @@
+// massive_game_server/server/src/network/chat.rs
+// Chat scopes, slash commands and moderation.
+//
+// Player text is sanitized by the rate limiter first, then either spoken (all/team/whisper,
+// through the word filter) or run as a command. Commands answer with System messages that
+// only the issuing player sees. Moderators log in with `/login <admin key>`.
+use crate::core::constants::*;
+use crate::core::types::{PlayerID, Vec2};
+use crate::entities::player::ImprovedPlayerManager;
+use crate::flatbuffers_generated::game_protocol as fb;
+use crate::network::signaling::ChatMessage;
+use crate::operational::monitoring::metrics as server_metrics;
+use crate::server::instance::MassiveGameServer;
+use crate::systems::combat::abilities::PlayerClass;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::{info, warn};
+
+static NEXT_CHAT_MESSAGE_SEQ: AtomicU64 = AtomicU64::new(1);
+
+pub const SYSTEM_SENDER_ID: &str = "server";
+pub const SYSTEM_SENDER_NAME: &str = "Server";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChatScope {
+    All,
+    Team(u8),
+    Whisper { to: PlayerID },
+    System { to: Option<PlayerID> }, // None: everyone
+}
+
+impl ChatScope {
+    pub fn to_fb(&self) -> fb::ChatScope {
+        match self {
+            ChatScope::All => fb::ChatScope::All,
+            ChatScope::Team(_) => fb::ChatScope::Team,
+            ChatScope::Whisper { .. } => fb::ChatScope::Whisper,
+            ChatScope::System { .. } => fb::ChatScope::System,
+        }
+    }
+}
+
+impl ChatMessage {
+    /// Whether `recipient` (on `team_id`, muting `muted`) should receive this message.
+    /// Spectators pass team 0 and see only all-chat and broadcast system messages.
+    pub fn is_visible_to(&self, recipient: &PlayerID, team_id: u8, muted: &HashSet<PlayerID>) -> bool {
+        match &self.scope {
+            ChatScope::System { to } => to.as_ref().is_none_or(|to| to == recipient),
+            _ if muted.contains(&self.player_id) => false,
+            ChatScope::All => true,
+            ChatScope::Team(team) => *team != 0 && *team == team_id,
+            ChatScope::Whisper { to } => to == recipient || &self.player_id == recipient,
+        }
+    }
+}
+
+/// Who a chat batch is being delivered to.
+pub struct ChatRecipient {
+    id: PlayerID,
+    team_id: u8,
+    muted: HashSet<PlayerID>,
+}
+
+impl ChatRecipient {
+    /// Spectators have no PlayerState, so they get team 0 and no mutes.
+    pub fn lookup(player_manager: &ImprovedPlayerManager, peer_id_str: &str) -> Self {
+        let id = player_manager.id_pool.get_or_create(peer_id_str);
+        let (team_id, muted) = player_manager.get_player_state(&id)
+            .map(|p| (p.team_id, p.muted_players.clone()))
+            .unwrap_or_default();
+        ChatRecipient { id, team_id, muted }
+    }
+
+    pub fn can_see(&self, message: &ChatMessage) -> bool {
+        message.is_visible_to(&self.id, self.team_id, &self.muted)
+    }
+}
+
+pub fn next_chat_seq() -> u64 {
+    NEXT_CHAT_MESSAGE_SEQ.fetch_add(1, Ordering::Relaxed)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChatInput<'a> {
+    Say { team_only: bool, text: &'a str },
+    Whisper { target: &'a str, text: &'a str },
+    Mute(&'a str),
+    Unmute(&'a str),
+    Report { target: &'a str, reason: &'a str },
+    Login(&'a str),
+    Kick(&'a str),
+    Bots(u64),
+    Class(PlayerClass),
+    Usage(&'static str), // Malformed or unknown command; the reply to send
+}
+
+/// Splits sanitized chat text into plain speech or a slash command.
+pub fn parse_chat_input(text: &str, team_only: bool) -> ChatInput<'_> {
+    let Some(command_line) = text.strip_prefix('/') else {
+        return ChatInput::Say { team_only, text };
+    };
+    let (command, args) = split_word(command_line);
+    match command.to_ascii_lowercase().as_str() {
+        "team" | "t" if !args.is_empty() => ChatInput::Say { team_only: true, text: args },
+        "all" | "a" if !args.is_empty() => ChatInput::Say { team_only: false, text: args },
+        "w" | "whisper" => match split_word(args) {
+            (target, text) if !target.is_empty() && !text.is_empty() => ChatInput::Whisper { target, text },
+            _ => ChatInput::Usage("Usage: /w <player> <message>"),
+        },
+        "mute" if !args.is_empty() => ChatInput::Mute(split_word(args).0),
+        "unmute" if !args.is_empty() => ChatInput::Unmute(split_word(args).0),
+        "report" if !args.is_empty() => {
+            let (target, reason) = split_word(args);
+            ChatInput::Report { target, reason }
+        }
+        "login" if !args.is_empty() => ChatInput::Login(args),
+        "kick" if !args.is_empty() => ChatInput::Kick(split_word(args).0),
+        "bots" => match args.parse::<u64>() {
+            Ok(count) if count <= MAX_BOT_COUNT_COMMAND => ChatInput::Bots(count),
+            _ => ChatInput::Usage("Usage: /bots <count>"),
+        },
+        "class" => match PlayerClass::parse(args) {
+            Some(class) => ChatInput::Class(class),
+            None => ChatInput::Usage("Usage: /class <assault|engineer|medic|recon>"),
+        },
+        "team" | "t" | "all" | "a" => ChatInput::Usage("Usage: /team <message> or /all <message>"),
+        "mute" | "unmute" | "kick" => ChatInput::Usage("Usage: /mute, /unmute or /kick <player>"),
+        "report" => ChatInput::Usage("Usage: /report <player> [reason]"),
+        "login" => ChatInput::Usage("Usage: /login <key>"),
+        _ => ChatInput::Usage("Commands: /team, /all, /w, /mute, /unmute, /report, /class"),
+    }
+}
+
+fn split_word(text: &str) -> (&str, &str) {
+    let text = text.trim_start();
+    match text.split_once(char::is_whitespace) {
+        Some((word, rest)) => (word, rest.trim_start()),
+        None => (text, ""),
+    }
+}
+
+/// Masks configured words (case-insensitive, whole words only) with asterisks.
+#[derive(Debug, Clone, Default)]
+pub struct WordFilter {
+    words: Vec<Vec<char>>,
+}
+
+impl WordFilter {
+    pub fn new<S: AsRef<str>>(words: impl IntoIterator<Item = S>) -> Self {
+        WordFilter {
+            words: words.into_iter()
+                .map(|word| word.as_ref().trim().chars().map(fold_case).collect::<Vec<char>>())
+                .filter(|word| !word.is_empty())
+                .collect(),
+        }
+    }
+
+    pub fn apply(&self, text: &str) -> String {
+        let mut chars: Vec<char> = text.chars().collect();
+        let folded: Vec<char> = chars.iter().copied().map(fold_case).collect();
+        let is_boundary = |idx: usize| folded.get(idx).is_none_or(|c| !c.is_alphanumeric());
+
+        for word in &self.words {
+            let mut start = 0;
+            while start + word.len() <= folded.len() {
+                let end = start + word.len();
+                let at_word_start = start == 0 || is_boundary(start - 1);
+                if at_word_start && folded[start..end] == word[..] && is_boundary(end) {
+                    chars[start..end].fill('*');
+                    start = end;
+                } else {
+                    start += 1;
+                }
+            }
+        }
+        chars.into_iter().collect()
+    }
+}
+
+fn fold_case(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+impl MassiveGameServer {
+    /// Speaks or runs one line of sanitized chat from `peer_id_str`.
+    pub async fn handle_chat_message(&self, peer_id_str: &str, text: &str, team_only: bool, timestamp: u64) {
+        let sender_id = self.player_manager.id_pool.get_or_create(peer_id_str);
+        let Some((username, team_id, is_moderator)) = self.player_manager.get_player_state(&sender_id)
+            .map(|p| (p.username.clone(), p.team_id, p.is_moderator))
+        else {
+            warn!("[{}]: Player state not found for chat message.", peer_id_str);
+            return;
+        };
+
+        let (scope, spoken) = match parse_chat_input(text, team_only) {
+            // Team 0 is nobody's team (free for all), so team chat would reach no one
+            ChatInput::Say { team_only: true, .. } if team_id == 0 => {
+                return self.send_system_chat(Some(&sender_id), "Team chat isn't available in this mode; send it to everyone instead.").await;
+            }
+            ChatInput::Say { team_only: true, text } => (ChatScope::Team(team_id), text),
+            ChatInput::Say { team_only: false, text } => (ChatScope::All, text),
+            ChatInput::Whisper { target, text } => match self.find_player_by_name(target) {
+                Some(to) => (ChatScope::Whisper { to }, text),
+                None => return self.send_system_chat(Some(&sender_id), &format!("No player named '{}'.", target)).await,
+            },
+            ChatInput::Mute(target) => {
+                let reply = self.set_muted(&sender_id, target, true);
+                return self.send_system_chat(Some(&sender_id), &reply).await;
+            }
+            ChatInput::Unmute(target) => {
+                let reply = self.set_muted(&sender_id, target, false);
+                return self.send_system_chat(Some(&sender_id), &reply).await;
+            }
+            ChatInput::Report { target, reason } => {
+                let reply = self.report_player(&sender_id, &username, target, reason).await;
+                return self.send_system_chat(Some(&sender_id), &reply).await;
+            }
+            ChatInput::Login(key) => {
+                let reply = self.login_moderator(&sender_id, key);
+                return self.send_system_chat(Some(&sender_id), reply).await;
+            }
+            ChatInput::Kick(_) | ChatInput::Bots(_) if !is_moderator => {
+                return self.send_system_chat(Some(&sender_id), "That command is for moderators.").await;
+            }
+            ChatInput::Kick(target) => {
+                let reply = self.kick_player(&username, target).await;
+                return self.send_system_chat(Some(&sender_id), &reply).await;
+            }
+            ChatInput::Bots(count) => {
+                self.target_bot_count.store(count, Ordering::Relaxed);
+                info!(target: "moderation", "{} set the bot target to {}.", username, count);
+                return self.send_system_chat(None, &format!("{} set the bot count to {}.", username, count)).await;
+            }
+            ChatInput::Class(class) => {
+                if let Some(mut player_state) = self.player_manager.get_player_state_mut(&sender_id) {
+                    player_state.class = class;
+                }
+                let reply = format!("You'll spawn as {} next time.", class.name());
+                return self.send_system_chat(Some(&sender_id), &reply).await;
+            }
+            ChatInput::Usage(reply) => return self.send_system_chat(Some(&sender_id), reply).await,
+        };
+
+        let message = self.word_filter.apply(spoken);
+        info!("[CHAT] {} ({}) [{:?}]: {}", username, *sender_id, scope.to_fb(), message);
+        self.queue_chat(ChatMessage {
+            seq: next_chat_seq(),
+            player_id: sender_id,
+            username,
+            message,
+            timestamp,
+            scope,
+        }).await;
+    }
+
+    /// Queues a server message for everyone (`to` = None) or a single player.
+    pub async fn send_system_chat(&self, to: Option<&PlayerID>, text: &str) {
+        self.queue_chat(ChatMessage {
+            seq: next_chat_seq(),
+            player_id: self.player_manager.id_pool.get_or_create(SYSTEM_SENDER_ID),
+            username: SYSTEM_SENDER_NAME.to_string(),
+            message: text.to_string(),
+            timestamp: self.get_server_timestamp(),
+            scope: ChatScope::System { to: to.cloned() },
+        }).await;
+    }
+
+    async fn queue_chat(&self, chat_entry: ChatMessage) {
+        let mut chat_q_guard = self.chat_messages_queue.write().await;
+        chat_q_guard.push_back(chat_entry);
+        while chat_q_guard.len() > CHAT_HISTORY_SIZE {
+            chat_q_guard.pop_front();
+        }
+    }
+
+    /// Matches a username (case-insensitive) or a player id.
+    pub fn find_player_by_name(&self, name: &str) -> Option<PlayerID> {
+        let mut found = None;
+        self.player_manager.for_each_player(|player_id, player_state| {
+            if found.is_none() && (player_state.username.eq_ignore_ascii_case(name) || player_id.as_str() == name) {
+                found = Some(player_id.clone());
+            }
+        });
+        found
+    }
+
+    fn set_muted(&self, player_id: &PlayerID, target: &str, muted: bool) -> String {
+        let Some(target_id) = self.find_player_by_name(target) else {
+            return format!("No player named '{}'.", target);
+        };
+        if &target_id == player_id {
+            return "You can't mute yourself.".to_string();
+        }
+        let Some(mut player_state) = self.player_manager.get_player_state_mut(player_id) else {
+            return "You are not in the match.".to_string();
+        };
+        if muted {
+            player_state.muted_players.insert(target_id);
+            format!("Muted {}.", target)
+        } else {
+            player_state.muted_players.remove(&target_id);
+            format!("Unmuted {}.", target)
+        }
+    }
+
+    async fn report_player(&self, reporter_id: &PlayerID, reporter_name: &str, target: &str, reason: &str) -> String {
+        let Some(target_id) = self.find_player_by_name(target) else {
+            return format!("No player named '{}'.", target);
+        };
+        warn!(target: "moderation", "Report from {} ({}) against {} ({}): {}", reporter_name, reporter_id, target, target_id, reason);
+        server_metrics::record_chat_report();
+
+        let mut moderators = Vec::new();
+        self.player_manager.for_each_player(|player_id, player_state| {
+            if player_state.is_moderator {
+                moderators.push(player_id.clone());
+            }
+        });
+        let notice = format!("Report from {} against {}: {}", reporter_name, target, reason);
+        for moderator_id in &moderators {
+            self.send_system_chat(Some(moderator_id), &notice).await;
+        }
+        format!("Thanks, your report against {} was sent to the moderators.", target)
+    }
+
+    fn login_moderator(&self, player_id: &PlayerID, key: &str) -> &'static str {
+        if !self.config.is_admin_key(Some(key)) {
+            warn!(target: "moderation", "Failed moderator login from {}.", player_id);
+            return "Login failed.";
+        }
+        match self.player_manager.get_player_state_mut(player_id) {
+            Some(mut player_state) => {
+                player_state.is_moderator = true;
+                info!(target: "moderation", "{} logged in as a moderator.", player_id);
+                "Logged in as a moderator."
+            }
+            None => "You are not in the match.",
+        }
+    }
+
+    /// Disconnects a player without letting them resume their session.
+    async fn kick_player(&self, moderator_name: &str, target: &str) -> String {
+        let Some(target_id) = self.find_player_by_name(target) else {
+            return format!("No player named '{}'.", target);
+        };
+        if self.bot_players.contains_key(&target_id) {
+            return "Use /bots to change the number of bots.".to_string();
+        }
+        info!(target: "moderation", "{} kicked {} ({}).", moderator_name, target, target_id);
+        self.session_manager.forget(target_id.as_str());
+        if let Some((_, data_channel)) = self.data_channels_map.remove(target_id.as_str()) {
+            if let Err(e) = data_channel.close().await {
+                warn!("[{}]: Error closing data channel after kick: {}", target_id, e);
+            }
+        }
+        let last_position = self.player_manager.get_player_state(&target_id).map(|p| Vec2::new(p.x, p.y));
+        if let Some(position) = last_position {
+            self.notify_player_leave(&target_id, position);
+        }
+        self.player_manager.remove_player(target_id.as_str());
+        self.send_system_chat(None, &format!("{} was kicked by a moderator.", target)).await;
+        format!("Kicked {}.", target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(parse_chat_input("hello", false), ChatInput::Say { team_only: false, text: "hello" });
+        assert_eq!(parse_chat_input("/t push mid", false), ChatInput::Say { team_only: true, text: "push mid" });
+        assert_eq!(parse_chat_input("/w Bob  gg wp", false), ChatInput::Whisper { target: "Bob", text: "gg wp" });
+        assert_eq!(parse_chat_input("/report Bob aimbot", false), ChatInput::Report { target: "Bob", reason: "aimbot" });
+        assert_eq!(parse_chat_input("/bots 12", false), ChatInput::Bots(12));
+        assert_eq!(parse_chat_input("/class medic", false), ChatInput::Class(PlayerClass::Medic));
+        assert!(matches!(parse_chat_input("/class pilot", false), ChatInput::Usage(_)));
+        assert!(matches!(parse_chat_input("/w Bob", false), ChatInput::Usage(_)));
+        assert!(matches!(parse_chat_input("/dance", false), ChatInput::Usage(_)));
+    }
+
+    #[test]
+    fn test_word_filter_masks_whole_words_only() {
+        let filter = WordFilter::new(["darn"]);
+        assert_eq!(filter.apply("Darn it, darnation"), "**** it, darnation");
+    }
+
+    #[test]
+    fn test_visibility_by_scope_and_mutes() {
+        let alice: PlayerID = Arc::new("alice".to_string());
+        let bob: PlayerID = Arc::new("bob".to_string());
+        let carol: PlayerID = Arc::new("carol".to_string());
+        let message = |scope| ChatMessage {
+            seq: 1, player_id: alice.clone(), username: "Alice".to_string(),
+            message: "hi".to_string(), timestamp: 0, scope,
+        };
+        let no_mutes = HashSet::new();
+
+        assert!(message(ChatScope::Team(1)).is_visible_to(&bob, 1, &no_mutes));
+        assert!(!message(ChatScope::Team(1)).is_visible_to(&carol, 2, &no_mutes));
+        let whisper = message(ChatScope::Whisper { to: bob.clone() });
+        assert!(whisper.is_visible_to(&bob, 2, &no_mutes) && whisper.is_visible_to(&alice, 1, &no_mutes));
+        assert!(!whisper.is_visible_to(&carol, 1, &no_mutes));
+
+        let bob_mutes_alice: HashSet<PlayerID> = [alice.clone()].into_iter().collect();
+        assert!(!message(ChatScope::All).is_visible_to(&bob, 1, &bob_mutes_alice));
+        assert!(message(ChatScope::System { to: None }).is_visible_to(&bob, 1, &bob_mutes_alice));
+    }
+}
@@ -0,0 +1,222 @@
+// massive_game_server/server/src/network/input_buffer.rs
+// Per-player input jitter buffer.
+//
+// Clients send one PlayerInput per tick. Network jitter bunches them up, so instead of
+// applying everything that arrived since the last tick, the server plays back one input
+// per tick in sequence order from a small buffer. When the buffer runs dry the last input
+// is repeated (held buttons only) and the buffer grows; after a long clean stretch it
+// shrinks again. When it backs up, the oldest inputs are folded into the next one.
+use crate::core::constants::*;
+use crate::core::types::PlayerInputData;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone)]
+pub struct BufferedInput {
+    pub input: PlayerInputData,
+    pub repeated: bool, // Stand-in for a missing input; carries the last consumed sequence
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputJitterBuffer {
+    pending: BTreeMap<u32, PlayerInputData>,
+    last_consumed_sequence: Option<u32>,
+    last_input: Option<PlayerInputData>,
+    target_depth: usize,
+    primed: bool,
+    ticks_since_underrun: u32,
+    underruns: u64,
+}
+
+impl InputJitterBuffer {
+    pub fn new() -> Self {
+        InputJitterBuffer {
+            pending: BTreeMap::new(),
+            last_consumed_sequence: None,
+            last_input: None,
+            target_depth: INPUT_BUFFER_MIN_DEPTH,
+            primed: false,
+            ticks_since_underrun: 0,
+            underruns: 0,
+        }
+    }
+
+    /// Buffers an input. Duplicates and inputs older than the last consumed one are dropped.
+    pub fn push(&mut self, input: PlayerInputData) {
+        if self.last_consumed_sequence.is_some_and(|last| input.sequence <= last) {
+            return;
+        }
+        self.pending.insert(input.sequence, input);
+        if self.pending.len() > MAX_INPUT_QUEUE_SIZE_PER_PLAYER {
+            self.fold_oldest();
+        }
+    }
+
+    /// The input to apply this tick, if the player has sent anything yet.
+    pub fn next_input(&mut self) -> Option<BufferedInput> {
+        if !self.primed {
+            if self.pending.len() < self.target_depth {
+                return self.repeat_last();
+            }
+            self.primed = true;
+        }
+
+        // Backed up (e.g. a burst after a lag spike): catch up without applying extra moves.
+        while self.pending.len() > self.target_depth + INPUT_BUFFER_SLACK {
+            self.fold_oldest();
+        }
+
+        let Some((&sequence, _)) = self.pending.first_key_value() else {
+            self.underruns += 1;
+            self.ticks_since_underrun = 0;
+            self.target_depth = (self.target_depth + 1).min(INPUT_BUFFER_MAX_DEPTH);
+            self.primed = false;
+            return self.repeat_last();
+        };
+
+        // The next input in sequence is missing; give it a chance to arrive while we still have headroom.
+        let expected = self.last_consumed_sequence.map(|last| last.wrapping_add(1));
+        if expected.is_some_and(|expected| sequence != expected) && self.pending.len() < self.target_depth {
+            return self.repeat_last();
+        }
+
+        let (_, input) = self.pending.pop_first()?;
+        self.last_consumed_sequence = Some(sequence);
+        self.last_input = Some(input.clone());
+
+        self.ticks_since_underrun += 1;
+        if self.ticks_since_underrun >= INPUT_BUFFER_SHRINK_TICKS && self.target_depth > INPUT_BUFFER_MIN_DEPTH {
+            self.target_depth -= 1;
+            self.ticks_since_underrun = 0;
+        }
+        Some(BufferedInput { input, repeated: false })
+    }
+
+    /// Takes every pending input in sequence order, bypassing the jitter logic.
+    /// For locally generated inputs (bots), which never arrive late.
+    pub fn drain(&mut self) -> Vec<PlayerInputData> {
+        let drained: Vec<PlayerInputData> = std::mem::take(&mut self.pending).into_values().collect();
+        if let Some(last) = drained.last() {
+            self.last_consumed_sequence = Some(last.sequence);
+            self.last_input = Some(last.clone());
+        }
+        drained
+    }
+
+    pub fn depth(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn target_depth(&self) -> usize {
+        self.target_depth
+    }
+
+    pub fn underruns(&self) -> u64 {
+        self.underruns
+    }
+
+    /// Forgets everything, e.g. when a resumed session restarts its sequence numbers.
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    fn repeat_last(&self) -> Option<BufferedInput> {
+        let mut input = self.last_input.clone()?;
+        // Only held controls repeat; one-shot actions must not fire twice.
+        input.reload = false;
+        input.melee_attack = false;
+        input.change_weapon_slot = 0;
+        input.use_ability_slot = 0;
+        Some(BufferedInput { input, repeated: true })
+    }
+
+    /// Drops the oldest pending input, carrying its one-shot actions over to the next one.
+    fn fold_oldest(&mut self) {
+        let Some((sequence, dropped)) = self.pending.pop_first() else { return };
+        self.last_consumed_sequence = Some(sequence);
+        if let Some(mut next) = self.pending.first_entry() {
+            let next = next.get_mut();
+            next.reload |= dropped.reload;
+            next.melee_attack |= dropped.melee_attack;
+            next.shooting |= dropped.shooting;
+            if next.change_weapon_slot == 0 {
+                next.change_weapon_slot = dropped.change_weapon_slot;
+            }
+            if next.use_ability_slot == 0 {
+                next.use_ability_slot = dropped.use_ability_slot;
+            }
+        }
+    }
+}
+
+impl Default for InputJitterBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(sequence: u32) -> PlayerInputData {
+        PlayerInputData {
+            timestamp: 0,
+            sequence,
+            move_forward: true,
+            move_backward: false,
+            move_left: false,
+            move_right: false,
+            shooting: false,
+            reload: false,
+            rotation: 0.0,
+            melee_attack: false,
+            change_weapon_slot: 0,
+            use_ability_slot: 0,
+        }
+    }
+
+    #[test]
+    fn test_one_input_per_tick_in_sequence_order() {
+        let mut buffer = InputJitterBuffer::new();
+        for seq in [3, 1, 2, 2] {
+            buffer.push(input(seq));
+        }
+        let consumed: Vec<_> = (0..3).filter_map(|_| buffer.next_input()).map(|b| b.input.sequence).collect();
+        assert_eq!(consumed, vec![1, 2, 3]);
+
+        // Late duplicate is ignored
+        buffer.push(input(2));
+        assert_eq!(buffer.depth(), 0);
+    }
+
+    #[test]
+    fn test_underrun_repeats_held_controls_and_grows_buffer() {
+        let mut buffer = InputJitterBuffer::new();
+        let mut reloading = input(1);
+        reloading.reload = true;
+        buffer.push(reloading);
+        assert!(!buffer.next_input().unwrap().repeated);
+
+        let repeated = buffer.next_input().unwrap();
+        assert!(repeated.repeated);
+        assert!(repeated.input.move_forward);
+        assert!(!repeated.input.reload);
+        assert_eq!(buffer.target_depth(), INPUT_BUFFER_MIN_DEPTH + 1);
+        assert_eq!(buffer.underruns(), 1);
+    }
+
+    #[test]
+    fn test_burst_is_folded_instead_of_replayed() {
+        let mut buffer = InputJitterBuffer::new();
+        let mut weapon_switch = input(1);
+        weapon_switch.change_weapon_slot = 3;
+        buffer.push(weapon_switch);
+        for seq in 2..=10 {
+            buffer.push(input(seq));
+        }
+
+        let first = buffer.next_input().unwrap();
+        assert_eq!(buffer.depth(), INPUT_BUFFER_MIN_DEPTH + INPUT_BUFFER_SLACK - 1);
+        assert_eq!(first.input.change_weapon_slot, 3);
+    }
+}
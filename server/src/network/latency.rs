@@ -0,0 +1,159 @@
+// massive_game_server/server/src/network/latency.rs
+// Per-client RTT, jitter and clock-offset estimation from Ping/Pong round trips.
+use dashmap::DashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+pub const PING_INTERVAL: Duration = Duration::from_millis(1000);
+// Pings that have not been answered within this window are forgotten.
+const MAX_OUTSTANDING_PINGS: usize = 8;
+// Samples above this are treated as stalls, not latency.
+const MAX_RTT_SAMPLE_MS: f32 = 5000.0;
+// Inputs arrive every tick, so each one moves the estimate far less than a pong does.
+const INPUT_SAMPLE_GAIN: f32 = 1.0 / 64.0;
+
+pub type ClientLatenciesMap = Arc<DashMap<String, LatencyTracker>>;
+
+#[derive(Clone, Debug)]
+pub struct LatencyTracker {
+    pub smoothed_rtt_ms: f32,
+    pub jitter_ms: f32,
+    pub clock_offset_ms: i64,
+    pub samples: u32,
+    next_ping_sequence: u32,
+    last_ping_sent: Option<Instant>,
+    outstanding_pings: Vec<(u32, u64)>, // (sequence, server_timestamp_ms)
+}
+
+impl LatencyTracker {
+    pub fn new() -> Self {
+        LatencyTracker {
+            smoothed_rtt_ms: 0.0,
+            jitter_ms: 0.0,
+            clock_offset_ms: 0,
+            samples: 0,
+            next_ping_sequence: 1,
+            last_ping_sent: None,
+            outstanding_pings: Vec::with_capacity(MAX_OUTSTANDING_PINGS),
+        }
+    }
+
+    pub fn ping_due(&self) -> bool {
+        self.last_ping_sent.is_none_or(|sent| sent.elapsed() >= PING_INTERVAL)
+    }
+
+    /// Records an outgoing ping and returns its sequence number.
+    pub fn register_ping(&mut self, server_timestamp_ms: u64) -> u32 {
+        let sequence = self.next_ping_sequence;
+        self.next_ping_sequence = self.next_ping_sequence.wrapping_add(1);
+        self.last_ping_sent = Some(Instant::now());
+        if self.outstanding_pings.len() >= MAX_OUTSTANDING_PINGS {
+            self.outstanding_pings.remove(0);
+        }
+        self.outstanding_pings.push((sequence, server_timestamp_ms));
+        sequence
+    }
+
+    /// Folds a pong into the estimates. Returns the RTT sample, or None if the pong
+    /// does not match an outstanding ping (late, duplicated or forged).
+    pub fn on_pong(&mut self, sequence: u32, echoed_server_timestamp_ms: u64, client_timestamp_ms: u64, now_ms: u64) -> Option<f32> {
+        let idx = self.outstanding_pings.iter().position(|&(seq, ts)| seq == sequence && ts == echoed_server_timestamp_ms)?;
+        self.outstanding_pings.remove(idx);
+
+        let sample_ms = now_ms.saturating_sub(echoed_server_timestamp_ms) as f32;
+        if sample_ms > MAX_RTT_SAMPLE_MS {
+            return None;
+        }
+
+        // RFC 6298 style smoothing: SRTT with gain 1/8, RTTVAR with gain 1/4.
+        if self.samples == 0 {
+            self.smoothed_rtt_ms = sample_ms;
+            self.jitter_ms = sample_ms / 2.0;
+        } else {
+            self.jitter_ms = 0.75 * self.jitter_ms + 0.25 * (self.smoothed_rtt_ms - sample_ms).abs();
+            self.smoothed_rtt_ms = 0.875 * self.smoothed_rtt_ms + 0.125 * sample_ms;
+        }
+        self.samples = self.samples.saturating_add(1);
+
+        // The client stamped its reply roughly half a round trip after we sent the ping.
+        let server_time_at_reply = echoed_server_timestamp_ms as i64 + (sample_ms / 2.0) as i64;
+        let offset_sample = server_time_at_reply - client_timestamp_ms as i64;
+        self.clock_offset_ms = if self.samples == 1 {
+            offset_sample
+        } else {
+            self.clock_offset_ms + (offset_sample - self.clock_offset_ms) / 8
+        };
+
+        Some(sample_ms)
+    }
+
+    /// Folds the client timestamp on an input into the estimates. Once the clock offset is known,
+    /// an input's one-way delay is about half a round trip, so inputs give samples between pings.
+    /// Returns the RTT sample, or None before the first pong or for an implausible timestamp.
+    pub fn on_input_timestamp(&mut self, client_timestamp_ms: u64, now_ms: u64) -> Option<f32> {
+        if self.samples == 0 || client_timestamp_ms == 0 {
+            return None;
+        }
+        let one_way_ms = now_ms as i64 - (client_timestamp_ms as i64 + self.clock_offset_ms);
+        let sample_ms = (2 * one_way_ms).max(0) as f32;
+        if sample_ms > MAX_RTT_SAMPLE_MS {
+            return None;
+        }
+        self.jitter_ms += INPUT_SAMPLE_GAIN * ((self.smoothed_rtt_ms - sample_ms).abs() - self.jitter_ms);
+        self.smoothed_rtt_ms += INPUT_SAMPLE_GAIN * (sample_ms - self.smoothed_rtt_ms);
+        Some(sample_ms)
+    }
+
+    pub fn ping_ms(&self) -> u16 {
+        self.smoothed_rtt_ms.round().clamp(0.0, u16::MAX as f32) as u16
+    }
+}
+
+impl Default for LatencyTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pong_sets_rtt_and_offset() {
+        let mut tracker = LatencyTracker::new();
+        let sequence = tracker.register_ping(10_000);
+        // The client's clock runs 500ms behind ours and it replied 40ms after we sent
+        assert_eq!(tracker.on_pong(sequence, 10_000, 9_540, 10_080), Some(80.0));
+        assert_eq!(tracker.ping_ms(), 80);
+        assert_eq!(tracker.clock_offset_ms, 500);
+
+        let sequence = tracker.register_ping(11_000);
+        tracker.on_pong(sequence, 11_000, 10_560, 11_120);
+        assert_eq!(tracker.ping_ms(), 85); // 7/8 of 80 plus 1/8 of 120
+    }
+
+    #[test]
+    fn test_unmatched_pongs_are_ignored() {
+        let mut tracker = LatencyTracker::new();
+        let sequence = tracker.register_ping(10_000);
+        assert_eq!(tracker.on_pong(sequence + 1, 10_000, 0, 10_050), None);
+        assert_eq!(tracker.on_pong(sequence, 9_999, 0, 10_050), None); // Forged timestamp
+        assert_eq!(tracker.on_pong(sequence, 10_000, 0, 10_000 + MAX_RTT_SAMPLE_MS as u64 + 1), None);
+        assert_eq!(tracker.on_pong(sequence, 10_000, 0, 10_050), None); // Already answered
+        assert_eq!(tracker.samples, 0);
+    }
+
+    #[test]
+    fn test_input_timestamps_refine_rtt() {
+        let mut tracker = LatencyTracker::new();
+        assert_eq!(tracker.on_input_timestamp(9_000, 10_000), None); // No clock offset yet
+
+        let sequence = tracker.register_ping(10_000);
+        tracker.on_pong(sequence, 10_000, 9_540, 10_080);
+        // Stamped at our 10_500 (client 10_000) and received 100ms later: a 200ms round trip
+        assert_eq!(tracker.on_input_timestamp(10_000, 10_600), Some(200.0));
+        assert!((tracker.smoothed_rtt_ms - (80.0 + 120.0 / 64.0)).abs() < 1e-3);
+        assert_eq!(tracker.on_input_timestamp(0, 10_600), None);
+    }
+}
@@ -1,2 +1,9 @@
 // massive_game_server/server/src/network/mod.rs
 pub mod signaling;
+pub mod session;
+pub mod latency;
+pub mod rate_limit;
+pub mod input_buffer;
+pub mod spectator;
+pub mod caster;
+pub mod chat;
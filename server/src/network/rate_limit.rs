@@ -0,0 +1,257 @@
+// massive_game_server/server/src/network/rate_limit.rs
+// Per-peer admission control for data channel messages: size limit, message-type
+// whitelist, token buckets per message type, field validation and escalating penalties.
+use crate::core::constants::*;
+use crate::flatbuffers_generated::game_protocol as fb;
+use crate::network::spectator::SpectatorMode;
+use std::time::Instant;
+
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    capacity: f32,
+    tokens: f32,
+    refill_per_sec: f32,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(refill_per_sec: f32, capacity: f32) -> Self {
+        TokenBucket {
+            capacity,
+            tokens: capacity,
+            refill_per_sec,
+            last_refill: Instant::now(),
+        }
+    }
+
+    pub fn try_take(&mut self) -> bool {
+        self.try_take_at(Instant::now())
+    }
+
+    pub fn try_take_at(&mut self, now: Instant) -> bool {
+        let elapsed = now.duration_since(self.last_refill).as_secs_f32();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RejectReason {
+    Oversized,
+    Malformed,
+    NotAllowed,
+    RateLimited,
+    InvalidInput,
+    InvalidChat,
+}
+
+impl RejectReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RejectReason::Oversized => "oversized",
+            RejectReason::Malformed => "malformed",
+            RejectReason::NotAllowed => "not_allowed",
+            RejectReason::RateLimited => "rate_limited",
+            RejectReason::InvalidInput => "invalid_input",
+            RejectReason::InvalidChat => "invalid_chat",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Penalty {
+    Drop,
+    Warn,
+    Disconnect,
+}
+
+impl Penalty {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Penalty::Drop => "drop",
+            Penalty::Warn => "warn",
+            Penalty::Disconnect => "disconnect",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PeerRateLimiter {
+    input_bucket: TokenBucket,
+    chat_bucket: TokenBucket,
+    pong_bucket: TokenBucket,
+    spectator_control_bucket: TokenBucket,
+    violations: u32,
+    last_violation: Option<Instant>,
+}
+
+impl PeerRateLimiter {
+    pub fn new() -> Self {
+        PeerRateLimiter {
+            input_bucket: TokenBucket::new(INPUT_RATE_PER_SEC, INPUT_BURST),
+            chat_bucket: TokenBucket::new(CHAT_RATE_PER_SEC, CHAT_BURST),
+            pong_bucket: TokenBucket::new(PONG_RATE_PER_SEC, PONG_BURST),
+            spectator_control_bucket: TokenBucket::new(SPECTATOR_CONTROL_RATE_PER_SEC, SPECTATOR_CONTROL_BURST),
+            violations: 0,
+            last_violation: None,
+        }
+    }
+
+    /// Checks the message-type whitelist, payload type and rate before the payload is looked at.
+    pub fn admit(&mut self, msg_type: fb::MessageType, payload_type: fb::MessagePayload) -> Result<(), RejectReason> {
+        self.admit_at(msg_type, payload_type, Instant::now())
+    }
+
+    pub fn admit_at(&mut self, msg_type: fb::MessageType, payload_type: fb::MessagePayload, now: Instant) -> Result<(), RejectReason> {
+        let (expected_payload, bucket) = match msg_type {
+            fb::MessageType::Input => (fb::MessagePayload::PlayerInput, &mut self.input_bucket),
+            fb::MessageType::Chat => (fb::MessagePayload::ChatMessage, &mut self.chat_bucket),
+            fb::MessageType::Pong => (fb::MessagePayload::Pong, &mut self.pong_bucket),
+            fb::MessageType::SpectatorControl => (fb::MessagePayload::SpectatorControl, &mut self.spectator_control_bucket),
+            _ => return Err(RejectReason::NotAllowed),
+        };
+        if payload_type != expected_payload {
+            return Err(RejectReason::Malformed);
+        }
+        if !bucket.try_take_at(now) {
+            return Err(RejectReason::RateLimited);
+        }
+        Ok(())
+    }
+
+    /// Counts a rejected message and returns the penalty it earns.
+    pub fn record_violation(&mut self) -> Penalty {
+        self.record_violation_at(Instant::now())
+    }
+
+    pub fn record_violation_at(&mut self, now: Instant) -> Penalty {
+        if self.last_violation.is_some_and(|t| now.duration_since(t).as_secs_f32() > VIOLATION_DECAY_SECS) {
+            self.violations = 0;
+        }
+        self.last_violation = Some(now);
+        self.violations += 1;
+
+        if self.violations >= VIOLATIONS_BEFORE_DISCONNECT {
+            Penalty::Disconnect
+        } else if self.violations.is_multiple_of(VIOLATIONS_BEFORE_WARN) {
+            Penalty::Warn
+        } else {
+            Penalty::Drop
+        }
+    }
+}
+
+impl Default for PeerRateLimiter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn check_message_size(len: usize) -> Result<(), RejectReason> {
+    if len > MAX_CLIENT_MESSAGE_BYTES {
+        Err(RejectReason::Oversized)
+    } else {
+        Ok(())
+    }
+}
+
+pub fn validate_player_input(input: &fb::PlayerInput, last_processed_sequence: u32) -> Result<(), RejectReason> {
+    if !input.rotation().is_finite() {
+        return Err(RejectReason::InvalidInput);
+    }
+    // A fresh or resumed connection has no baseline yet, so any starting sequence is fine.
+    if last_processed_sequence != 0 && input.sequence().saturating_sub(last_processed_sequence) > MAX_INPUT_SEQUENCE_JUMP {
+        return Err(RejectReason::InvalidInput);
+    }
+    let weapon_slot = input.change_weapon_slot();
+    if weapon_slot < 0 || weapon_slot as u8 > MAX_WEAPON_SLOT {
+        return Err(RejectReason::InvalidInput);
+    }
+    let ability_slot = input.use_ability_slot();
+    if ability_slot < 0 || ability_slot as u8 > MAX_ABILITY_SLOT {
+        return Err(RejectReason::InvalidInput);
+    }
+    Ok(())
+}
+
+pub fn validate_spectator_control(control: &fb::SpectatorControl) -> Result<(), RejectReason> {
+    if SpectatorMode::from_fb(control.mode()).is_none() {
+        return Err(RejectReason::InvalidInput);
+    }
+    if !control.camera_x().is_finite() || !control.camera_y().is_finite() {
+        return Err(RejectReason::InvalidInput);
+    }
+    Ok(())
+}
+
+/// Returns the chat text with control characters removed and length capped,
+/// or an error if nothing printable is left.
+pub fn sanitize_chat_message(text: &str) -> Result<String, RejectReason> {
+    let cleaned: String = text.chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_CHAT_MESSAGE_CHARS)
+        .collect();
+    let trimmed = cleaned.trim();
+    if trimmed.is_empty() {
+        return Err(RejectReason::InvalidChat);
+    }
+    Ok(trimmed.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_bucket_refills_up_to_capacity() {
+        let mut bucket = TokenBucket::new(2.0, 3.0);
+        let start = Instant::now();
+        assert!((0..3).all(|_| bucket.try_take_at(start)));
+        assert!(!bucket.try_take_at(start));
+        assert!(bucket.try_take_at(start + Duration::from_millis(500)));
+        assert!(!bucket.try_take_at(start + Duration::from_millis(500)));
+
+        // A long quiet spell refills only to capacity
+        let later = start + Duration::from_secs(60);
+        assert!((0..3).all(|_| bucket.try_take_at(later)));
+        assert!(!bucket.try_take_at(later));
+    }
+
+    #[test]
+    fn test_limits_are_per_message_type() {
+        let mut limiter = PeerRateLimiter::new();
+        let now = Instant::now();
+        for _ in 0..CHAT_BURST as usize {
+            assert_eq!(limiter.admit_at(fb::MessageType::Chat, fb::MessagePayload::ChatMessage, now), Ok(()));
+        }
+        assert_eq!(limiter.admit_at(fb::MessageType::Chat, fb::MessagePayload::ChatMessage, now), Err(RejectReason::RateLimited));
+        assert_eq!(limiter.admit_at(fb::MessageType::Input, fb::MessagePayload::PlayerInput, now), Ok(()));
+
+        assert_eq!(limiter.admit_at(fb::MessageType::DeltaState, fb::MessagePayload::DeltaStateMessage, now), Err(RejectReason::NotAllowed));
+        assert_eq!(limiter.admit_at(fb::MessageType::Input, fb::MessagePayload::ChatMessage, now), Err(RejectReason::Malformed));
+        assert_eq!(check_message_size(MAX_CLIENT_MESSAGE_BYTES + 1), Err(RejectReason::Oversized));
+    }
+
+    #[test]
+    fn test_violations_escalate_and_decay() {
+        let mut limiter = PeerRateLimiter::new();
+        let start = Instant::now();
+        let penalties: Vec<Penalty> = (0..VIOLATIONS_BEFORE_DISCONNECT).map(|_| limiter.record_violation_at(start)).collect();
+        assert_eq!(penalties[0], Penalty::Drop);
+        assert_eq!(penalties[VIOLATIONS_BEFORE_WARN as usize - 1], Penalty::Warn);
+        assert_eq!(penalties[VIOLATIONS_BEFORE_WARN as usize], Penalty::Drop);
+        assert_eq!(*penalties.last().unwrap(), Penalty::Disconnect);
+
+        // A clean spell starts the count over
+        let later = start + Duration::from_secs_f32(VIOLATION_DECAY_SECS + 1.0);
+        assert_eq!(limiter.record_violation_at(later), Penalty::Drop);
+    }
+}
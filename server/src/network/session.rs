@@ -0,0 +1,142 @@
+// massive_game_server/server/src/network/session.rs
+// Resume tokens and the disconnect grace period.
+//
+// A player keeps their PlayerState for `reconnect_grace_period_secs` after their
+// connection drops. A new signaling connection that presents the token handed out
+// in the WelcomeMessage is re-bound to the same player id.
+use dashmap::DashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{debug, info};
+use uuid::Uuid;
+
+pub type SessionManagerRef = Arc<SessionManager>;
+
+pub struct SessionManager {
+    // resume token -> player id
+    tokens: DashMap<String, String>,
+    // player id -> current resume token
+    player_tokens: DashMap<String, String>,
+    // player id -> time the connection was lost
+    disconnected: DashMap<String, Instant>,
+}
+
+impl SessionManager {
+    pub fn new() -> Self {
+        SessionManager {
+            tokens: DashMap::new(),
+            player_tokens: DashMap::new(),
+            disconnected: DashMap::new(),
+        }
+    }
+
+    /// Issues a fresh resume token for `player_id`, invalidating any previous one.
+    pub fn issue_token(&self, player_id: &str) -> String {
+        let token = Uuid::new_v4().simple().to_string();
+        if let Some(old_token) = self.player_tokens.insert(player_id.to_string(), token.clone()) {
+            self.tokens.remove(&old_token);
+        }
+        self.tokens.insert(token.clone(), player_id.to_string());
+        token
+    }
+
+    /// Resolves a resume token to the player id it was issued for and clears
+    /// the player's disconnected mark. The token stays valid until a new one is issued.
+    pub fn resume(&self, token: &str) -> Option<String> {
+        let player_id = self.tokens.get(token).map(|entry| entry.value().clone())?;
+        if let Some((_, disconnected_at)) = self.disconnected.remove(&player_id) {
+            info!("[{}]: Session resumed after {:.1}s.", player_id, disconnected_at.elapsed().as_secs_f32());
+        } else {
+            info!("[{}]: Session taken over by a new connection.", player_id);
+        }
+        Some(player_id)
+    }
+
+    pub fn has_session(&self, player_id: &str) -> bool {
+        self.player_tokens.contains_key(player_id)
+    }
+
+    pub fn mark_disconnected(&self, player_id: &str) {
+        self.disconnected.insert(player_id.to_string(), Instant::now());
+        debug!("[{}]: Session marked as disconnected.", player_id);
+    }
+
+    pub fn is_disconnected(&self, player_id: &str) -> bool {
+        self.disconnected.contains_key(player_id)
+    }
+
+    pub fn disconnected_count(&self) -> usize {
+        self.disconnected.len()
+    }
+
+    /// Removes and returns every session whose grace period has run out.
+    pub fn take_expired(&self, grace_period: Duration) -> Vec<String> {
+        let expired: Vec<String> = self.disconnected.iter()
+            .filter(|entry| entry.value().elapsed() >= grace_period)
+            .map(|entry| entry.key().clone())
+            .collect();
+
+        for player_id in &expired {
+            self.disconnected.remove(player_id);
+            self.forget(player_id);
+        }
+        expired
+    }
+
+    /// Drops all session data for `player_id`.
+    pub fn forget(&self, player_id: &str) {
+        if let Some((_, token)) = self.player_tokens.remove(player_id) {
+            self.tokens.remove(&token);
+        }
+        self.disconnected.remove(player_id);
+    }
+}
+
+impl Default for SessionManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resume_with_current_token_only() {
+        let sessions = SessionManager::new();
+        let old_token = sessions.issue_token("p1");
+        let token = sessions.issue_token("p1");
+        assert_eq!(sessions.resume(&old_token), None);
+        assert_eq!(sessions.resume(&token).as_deref(), Some("p1"));
+        assert_eq!(sessions.resume("forged"), None);
+        assert!(sessions.has_session("p1"));
+    }
+
+    #[test]
+    fn test_resume_clears_disconnect() {
+        let sessions = SessionManager::new();
+        let token = sessions.issue_token("p1");
+        sessions.mark_disconnected("p1");
+        assert!(sessions.is_disconnected("p1"));
+        assert_eq!(sessions.resume(&token).as_deref(), Some("p1"));
+        assert!(!sessions.is_disconnected("p1"));
+        assert_eq!(sessions.disconnected_count(), 0);
+    }
+
+    #[test]
+    fn test_expired_sessions_are_forgotten() {
+        let sessions = SessionManager::new();
+        let token = sessions.issue_token("p1");
+        sessions.issue_token("p2");
+        sessions.mark_disconnected("p1");
+        sessions.mark_disconnected("p2");
+        assert!(sessions.take_expired(Duration::from_secs(3600)).is_empty());
+
+        let mut expired = sessions.take_expired(Duration::ZERO);
+        expired.sort();
+        assert_eq!(expired, vec!["p1".to_string(), "p2".to_string()]);
+        assert_eq!(sessions.resume(&token), None);
+        assert!(!sessions.has_session("p1"));
+    }
+}
@@ -13,6 +13,16 @@ use crate::entities::player::ImprovedPlayerManager;
 use crate::flatbuffers_generated::game_protocol as fb;
 use crate::world::partition::WorldPartitionManager;
 use crate::server::instance::MassiveGameServer; // Added for server access for initial spawn
+use crate::network::session::SessionManagerRef;
+use crate::network::rate_limit::{
+    check_message_size, sanitize_chat_message, validate_player_input, validate_spectator_control, Penalty, PeerRateLimiter,
+    RejectReason,
+};
+use crate::network::spectator::{SpectatorMode, SpectatorsMap};
+use crate::network::caster::CasterSeatsMap;
+use crate::systems::combat::abilities::{forget_reveals, RevealedPlayersMap};
+use crate::network::chat::{ChatScope, SYSTEM_SENDER_ID, SYSTEM_SENDER_NAME};
+use crate::operational::monitoring::metrics as server_metrics;
 use parking_lot::RwLock as ParkingLotRwLock;
 
 use bytes::Bytes;
@@ -43,8 +53,8 @@ use uuid::Uuid;
 // Removed: use rand::Rng; // Not directly used here after spawn logic change
 
 // Type Aliases
-pub type SignalingPeers =
-    Arc<std::sync::Mutex<HashMap<String, mpsc::UnboundedSender<Result<Message, warp::Error>>>>>;
+pub type SignalingSender = mpsc::UnboundedSender<Result<Message, warp::Error>>;
+pub type SignalingPeers = Arc<std::sync::Mutex<HashMap<String, SignalingSender>>>;
 pub type PlayerManagerRef = Arc<ImprovedPlayerManager>;
 pub type DataChannelsMap = Arc<DashMap<String, Arc<CoreRTCDataChannel>>>;
 pub type WorldPartitionManagerRef = Arc<WorldPartitionManager>;
@@ -57,10 +67,9 @@ pub struct ChatMessage {
     pub username: String,
     pub message: String,
     pub timestamp: u64,
+    pub scope: ChatScope,
 }
 pub type ChatMessagesQueue = Arc<RwLock<VecDeque<ChatMessage>>>;
-static NEXT_CHAT_MESSAGE_SEQ: std::sync::atomic::AtomicU64 =
-    std::sync::atomic::AtomicU64::new(1);
 
 #[derive(Clone, Debug)]
 pub struct ClientState {
@@ -136,6 +145,8 @@ fn map_server_weapon_to_fb(server_weapon: ServerWeaponType) -> fb::WeaponType {
         ServerWeaponType::Rifle => fb::WeaponType::Rifle,
         ServerWeaponType::Sniper => fb::WeaponType::Sniper,
         ServerWeaponType::Melee => fb::WeaponType::Melee,
+        ServerWeaponType::RocketLauncher => fb::WeaponType::RocketLauncher,
+        ServerWeaponType::GrenadeLauncher => fb::WeaponType::GrenadeLauncher,
     }
 }
 
@@ -147,7 +158,7 @@ pub async fn handle_signaling_connection(
     _world_partition_manager: WorldPartitionManagerRef, // Marked as unused if not directly used in this function
     data_channels_map: DataChannelsMap,
     client_states_map: ClientStatesMap,
-    chat_messages_queue: ChatMessagesQueue,
+    _chat_messages_queue: ChatMessagesQueue, // Chat is queued through the server instance
     config: Arc<ServerConfig>,
     player_aois: PlayerAoIs,
     server_instance: ServerInstanceRef, // Added server instance for initial spawn
@@ -157,10 +168,15 @@ pub async fn handle_signaling_connection(
     let (mut ws_tx, mut ws_rx) = ws.split();
     let (client_signaling_tx, mut client_signaling_rx) = mpsc::unbounded_channel();
 
-    signaling_peers
+    let session_manager = server_instance.session_manager.clone();
+    if signaling_peers
         .lock()
         .unwrap()
-        .insert(peer_id_str.clone(), client_signaling_tx.clone());
+        .insert(peer_id_str.clone(), client_signaling_tx.clone())
+        .is_some()
+    {
+        info!("[{}]: Replacing an existing signaling connection for this player.", peer_id_str);
+    }
 
     let peer_id_fwd = peer_id_str.clone();
     tokio::spawn(async move {
@@ -184,7 +200,7 @@ pub async fn handle_signaling_connection(
     let mut m = MediaEngine::default();
     if let Err(e) = m.register_default_codecs() {
         error!("[{}]: Failed to register default codecs: {}", peer_id_str, e);
-        cleanup_connection(&peer_id_str, &signaling_peers, &player_manager, &data_channels_map, &client_states_map, &player_aois);
+        cleanup_connection(&peer_id_str, &client_signaling_tx, &signaling_peers, &player_manager, &session_manager, &data_channels_map, &client_states_map, &player_aois, &server_instance.spectators, &server_instance.caster_seats, &server_instance.revealed_players);
         return;
     }
 
@@ -201,7 +217,7 @@ pub async fn handle_signaling_connection(
         Ok(pc) => Arc::new(pc),
         Err(e) => {
             error!("[{}]: Failed to create PeerConnection: {}", peer_id_str, e);
-            cleanup_connection(&peer_id_str, &signaling_peers, &player_manager, &data_channels_map, &client_states_map, &player_aois);
+            cleanup_connection(&peer_id_str, &client_signaling_tx, &signaling_peers, &player_manager, &session_manager, &data_channels_map, &client_states_map, &player_aois, &server_instance.spectators, &server_instance.caster_seats, &server_instance.revealed_players);
             return;
         }
     };
@@ -250,6 +266,11 @@ pub async fn handle_signaling_connection(
     let dc_map_clone_sc = data_channels_map.clone();
     let cs_map_clone_sc = client_states_map.clone();
     let pa_map_clone_sc = player_aois.clone();
+    let sm_clone_sc = session_manager.clone();
+    let spectators_clone_sc = server_instance.spectators.clone();
+    let caster_seats_clone_sc = server_instance.caster_seats.clone();
+    let revealed_players_clone_sc = server_instance.revealed_players.clone();
+    let tx_clone_sc = client_signaling_tx.clone();
 
 
     pc_for_state_change.on_peer_connection_state_change(Box::new(move |s: RTCPeerConnectionState| {
@@ -262,7 +283,7 @@ pub async fn handle_signaling_connection(
                 | RTCPeerConnectionState::Disconnected
         ) {
             info!("[{}]: Peer disconnected/closed. Initiating cleanup.", current_peer_id);
-            cleanup_connection(&current_peer_id, &sp_clone_sc, &pm_clone_sc, &dc_map_clone_sc, &cs_map_clone_sc, &pa_map_clone_sc);
+            cleanup_connection(&current_peer_id, &tx_clone_sc, &sp_clone_sc, &pm_clone_sc, &sm_clone_sc, &dc_map_clone_sc, &cs_map_clone_sc, &pa_map_clone_sc, &spectators_clone_sc, &caster_seats_clone_sc, &revealed_players_clone_sc);
         }
         Box::pin(async {})
     }));
@@ -272,9 +293,12 @@ pub async fn handle_signaling_connection(
     let player_manager_for_dc_event = player_manager.clone();
     let data_channels_map_for_dc_event = data_channels_map.clone();
     let client_states_map_for_dc_event = client_states_map.clone();
-    let chat_messages_queue_for_dc_event = chat_messages_queue.clone();
     let config_for_dc_event = config.clone();
     let server_instance_for_dc_event = server_instance.clone(); // Clone server instance for DC event
+    let session_manager_for_dc_event = session_manager.clone();
+    // One limiter per connection, shared by all of its data channels.
+    let rate_limiter_for_dc_event = Arc::new(parking_lot::Mutex::new(PeerRateLimiter::new()));
+    let pc_weak_for_dc_event = Arc::downgrade(&peer_connection);
 
 
     pc_for_datachannel_event.on_data_channel(Box::new(move |dc: Arc<RTCDataChannel>| {
@@ -291,6 +315,7 @@ pub async fn handle_signaling_connection(
         let config_on_open = config_for_dc_event.clone();
         let dc_label_for_on_open = dc_label_owned.clone();
         let server_instance_on_open = server_instance_for_dc_event.clone(); // Clone server instance for on_open
+        let session_manager_on_open = session_manager_for_dc_event.clone();
 
 
         dc_on_open_arc.on_open(Box::new(move || {
@@ -315,54 +340,39 @@ pub async fn handle_signaling_connection(
             client_states_map_on_open.write().insert(current_peer_id_on_open_cb.clone(), initial_client_state);
             info!("[{}]: Added client state. Client states map size: {}", current_peer_id_on_open_cb, client_states_map_on_open.read().len());
 
-            let username = format!("Player_{}", &current_peer_id_on_open_cb[..4.min(current_peer_id_on_open_cb.len())]);
-            
-
-            
-            // Fix 2.2: Use RespawnManager for initial spawn
-            let player_id_arc_for_spawn = player_manager_on_open.id_pool.get_or_create(&current_peer_id_on_open_cb);
-            let team_to_assign = player_manager_on_open.assign_team_to_new_player();
-            let initial_spawn_pos = server_instance_on_open.respawn_manager.get_respawn_position(
-                &server_instance_on_open, // Pass the server instance
-                &player_id_arc_for_spawn,
-                Some(team_to_assign),
-                &[] // No specific enemy positions for initial spawn balancing here
-            );
-
-            info!("[{}] Player spawned at ({}, {})", current_peer_id_on_open_cb, initial_spawn_pos.x, initial_spawn_pos.y);
-
-
-            let _player_id_arc = player_manager_on_open.add_player(
-                current_peer_id_on_open_cb.clone(),
-                username.clone(),
-                initial_spawn_pos.x, // Use determined spawn position
-                initial_spawn_pos.y  // Use determined spawn position
-            ).unwrap_or_else(|| {
-                warn!("[{}]: add_player returned None, attempting to get existing PlayerID Arc.", current_peer_id_on_open_cb);
-                player_manager_on_open.id_pool.get_or_create(&current_peer_id_on_open_cb)
-            });
-
-            let new_player_id_arc_for_team = player_manager_on_open.id_pool.get_or_create(&current_peer_id_on_open_cb);
-            // let team_to_assign = player_manager_on_open.assign_team_to_new_player(); // Moved up
-
-            if let Some(mut p_state_entry) = player_manager_on_open.get_player_state_mut(&new_player_id_arc_for_team) {
-                let p_state: &mut PlayerState = &mut *p_state_entry;
-                p_state.team_id = team_to_assign;
-                p_state.mark_field_changed(FIELD_SCORE_STATS | FIELD_FLAG);
-                info!("[{}] assigned to team {}. Player state marked as changed.", current_peer_id_on_open_cb, team_to_assign);
+            let is_spectator = server_instance_on_open.spectators.contains_key(&current_peer_id_on_open_cb)
+                || server_instance_on_open.caster_seats.contains_key(&current_peer_id_on_open_cb);
+            let player_id_arc_for_resume = player_manager_on_open.id_pool.get_or_create(&current_peer_id_on_open_cb);
+            let resumed_player = player_manager_on_open.get_player_state_mut(&player_id_arc_for_resume).map(|mut p_state_entry| {
+                // The client restarts its input sequence numbering on a new connection.
+                p_state_entry.input_buffer.clear();
+                p_state_entry.last_processed_input_sequence = 0;
+                p_state_entry.mark_field_changed(FIELD_POSITION_ROTATION | FIELD_HEALTH_ALIVE | FIELD_WEAPON_AMMO | FIELD_SCORE_STATS | FIELD_FLAG);
+            }).is_some();
+
+            if resumed_player {
+                info!("[{}] Resumed existing player. Fresh initial state will be sent by game loop.", current_peer_id_on_open_cb);
+            } else if is_spectator {
+                info!("[{}] Joined as a spectator; no player spawned.", current_peer_id_on_open_cb);
+            } else {
+                let username = format!("Player_{}", &current_peer_id_on_open_cb[..4.min(current_peer_id_on_open_cb.len())]);
+                match server_instance_on_open.spawn_new_player(current_peer_id_on_open_cb.clone(), username, config_on_open.default_class) {
+                    Some((_, team_id, spawn_pos)) => info!("[{}] Player spawned on team {} at ({}, {})", current_peer_id_on_open_cb, team_id, spawn_pos.x, spawn_pos.y),
+                    None => warn!("[{}]: add_player returned None; no player spawned.", current_peer_id_on_open_cb),
+                }
             }
 
-            if let Some(player_state) = player_manager_on_open.get_player_state(&new_player_id_arc_for_team) {
+            if let Some(player_state) = player_manager_on_open.get_player_state(&player_id_arc_for_resume) {
                 // Update spatial index with player's position
                 server_instance_on_open.spatial_index.update_player_position(
-                    new_player_id_arc_for_team.clone(), 
+                    player_id_arc_for_resume.clone(), 
                     player_state.x, 
                     player_state.y
                 );
                 
                 // Update player's AoI
                 server_instance_on_open.update_player_aoi(
-                    &new_player_id_arc_for_team, 
+                    &player_id_arc_for_resume, 
                     player_state.x, 
                     player_state.y
                 );
@@ -372,15 +382,20 @@ pub async fn handle_signaling_connection(
             }
 
             let config_for_welcome = config_on_open.clone();
+            // Spectators hold no state worth resuming, so they never get a token.
+            let resume_token = (!is_spectator).then(|| session_manager_on_open.issue_token(&current_peer_id_on_open_cb));
 
             Box::pin(async move {
                 let mut builder_welcome = flatbuffers::FlatBufferBuilder::with_capacity(256);
                 let player_id_fb_welcome = builder_welcome.create_string(&current_peer_id_on_open_cb);
                 let welcome_text_fb = builder_welcome.create_string("Welcome to MassiveGameServer!");
+                let resume_token_fb = resume_token.as_deref().map(|token| builder_welcome.create_string(token));
                 let welcome_msg_args = fb::WelcomeMessageArgs {
                     player_id: Some(player_id_fb_welcome),
                     message: Some(welcome_text_fb),
                     server_tick_rate: config_for_welcome.tick_rate as u16,
+                    resume_token: resume_token_fb,
+                    is_spectator,
                 };
                 let welcome_msg = fb::WelcomeMessage::create(&mut builder_welcome, &welcome_msg_args);
                 let game_msg_welcome_args = fb::GameMessageArgs {
@@ -403,76 +418,116 @@ pub async fn handle_signaling_connection(
 
 
         let dc_on_message_arc = Arc::clone(&dc);
+        let dc_for_penalty = Arc::clone(&dc);
         let peer_id_on_message = current_peer_id_on_dc.clone();
         let player_manager_on_message = player_manager_for_dc_event.clone();
-        let chat_q_on_message = chat_messages_queue_for_dc_event.clone();
+        let server_instance_on_message = server_instance_for_dc_event.clone();
+        let rate_limiter_on_message = rate_limiter_for_dc_event.clone();
+        let pc_weak_on_message = pc_weak_for_dc_event.clone();
 
         dc_on_message_arc.on_message(Box::new(move |msg: DataChannelMessage| {
             let pid_msg_inner_str = peer_id_on_message.clone();
             let players_map_on_msg = player_manager_on_message.clone();
-            let chat_q_on_msg = chat_q_on_message.clone();
+            let server_on_msg = server_instance_on_message.clone();
+            let rate_limiter_on_msg = rate_limiter_on_message.clone();
+            let pc_weak_on_msg = pc_weak_on_message.clone();
+            let dc_on_msg = Arc::clone(&dc_for_penalty);
 
             Box::pin(async move {
-                if let Ok(game_msg_root) = fb::root_as_game_message(&msg.data) {
-                    match game_msg_root.msg_type() {
-                        fb::MessageType::Input => {
-                            if game_msg_root.actual_message_type() == fb::MessagePayload::PlayerInput {
-                                if let Some(input_fb) = game_msg_root.actual_message_as_player_input() {
-                                    let p_input_data = PlayerInputData {
-                                        timestamp: input_fb.timestamp(),
-                                        sequence: input_fb.sequence(),
-                                        move_forward: input_fb.move_forward(),
-                                        move_backward: input_fb.move_backward(),
-                                        move_left: input_fb.move_left(),
-                                        move_right: input_fb.move_right(),
-                                        shooting: input_fb.shooting(),
-                                        reload: input_fb.reload(),
-                                        rotation: input_fb.rotation(),
-                                        melee_attack: input_fb.melee_attack(),
-                                        change_weapon_slot: input_fb.change_weapon_slot() as u8,
-                                        use_ability_slot: input_fb.use_ability_slot() as u8,
-                                    };
+                let admitted = check_message_size(msg.data.len()).and_then(|_| {
+                    let game_msg_root = fb::root_as_game_message(&msg.data).map_err(|_| RejectReason::Malformed)?;
+                    rate_limiter_on_msg.lock().admit(game_msg_root.msg_type(), game_msg_root.actual_message_type())?;
+                    Ok(game_msg_root)
+                });
+                let game_msg_root = match admitted {
+                    Ok(root) => root,
+                    Err(reason) => {
+                        penalize_peer(&pid_msg_inner_str, reason, &rate_limiter_on_msg, &dc_on_msg, &pc_weak_on_msg, &server_on_msg).await;
+                        return;
+                    }
+                };
 
-                                    let player_id_arc: PlayerID = players_map_on_msg.id_pool.get_or_create(&pid_msg_inner_str);
-                                    if let Some(mut player_entry) = players_map_on_msg.get_player_state_mut(&player_id_arc) {
+                match game_msg_root.msg_type() {
+                    fb::MessageType::Input => {
+                        if let Some(input_fb) = game_msg_root.actual_message_as_player_input() {
+                            let player_id_arc: PlayerID = players_map_on_msg.id_pool.get_or_create(&pid_msg_inner_str);
+                            let validation = match players_map_on_msg.get_player_state_mut(&player_id_arc) {
+                                Some(mut player_entry) => {
+                                    let result = validate_player_input(&input_fb, player_entry.last_processed_input_sequence);
+                                    if result.is_ok() {
+                                        let p_input_data = PlayerInputData {
+                                            timestamp: input_fb.timestamp(),
+                                            sequence: input_fb.sequence(),
+                                            move_forward: input_fb.move_forward(),
+                                            move_backward: input_fb.move_backward(),
+                                            move_left: input_fb.move_left(),
+                                            move_right: input_fb.move_right(),
+                                            shooting: input_fb.shooting(),
+                                            reload: input_fb.reload(),
+                                            rotation: input_fb.rotation(),
+                                            melee_attack: input_fb.melee_attack(),
+                                            change_weapon_slot: input_fb.change_weapon_slot() as u8,
+                                            use_ability_slot: input_fb.use_ability_slot() as u8,
+                                        };
                                         debug!("[{}]: Received player input (seq: {})", pid_msg_inner_str, p_input_data.sequence);
+                                        server_on_msg.record_input_timestamp(&pid_msg_inner_str, p_input_data.timestamp);
                                         player_entry.queue_input(p_input_data);
-                                    } else {
-                                         warn!("[{}]: Player state not found for input processing.", pid_msg_inner_str);
                                     }
+                                    result
+                                }
+                                None => {
+                                    warn!("[{}]: Player state not found for input processing.", pid_msg_inner_str);
+                                    Ok(())
                                 }
+                            };
+                            if let Err(reason) = validation {
+                                penalize_peer(&pid_msg_inner_str, reason, &rate_limiter_on_msg, &dc_on_msg, &pc_weak_on_msg, &server_on_msg).await;
+                                return;
+                            }
+                            if input_fb.last_applied_server_tick() != 0 {
+                                server_on_msg.acknowledge_snapshot(&pid_msg_inner_str, input_fb.last_applied_server_tick());
                             }
                         }
-                        fb::MessageType::Chat => {
-                            if game_msg_root.actual_message_type() == fb::MessagePayload::ChatMessage {
-                                if let Some(chat_fb) = game_msg_root.actual_message_as_chat_message() {
-                                    if let (Some(message_text_fb), Some(username_text_fb)) = (chat_fb.message(), chat_fb.username()) {
-                                        let player_id_from_connection = pid_msg_inner_str.clone();
-                                        let player_id_arc_for_chat = players_map_on_msg.id_pool.get_or_create(&player_id_from_connection);
-
-                                        let trimmed_msg: String = message_text_fb.chars().take(100).collect();
-                                        let current_seq = NEXT_CHAT_MESSAGE_SEQ.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-                                        let chat_entry = ChatMessage {
-                                            seq: current_seq,
-                                            player_id: player_id_arc_for_chat,
-                                            username: username_text_fb.to_string(),
-                                            message: trimmed_msg,
-                                            timestamp: chat_fb.timestamp(),
-                                        };
-                                        info!("[CHAT] {} ({}): {}", chat_entry.username, *chat_entry.player_id, chat_entry.message);
-                                        let mut chat_q_guard = chat_q_on_msg.write().await;
-                                        chat_q_guard.push_back(chat_entry);
-                                        if chat_q_guard.len() > 50 {
-                                            chat_q_guard.pop_front();
-                                        }
-                                    }
+                    }
+                    fb::MessageType::Chat => {
+                        if let Some(chat_fb) = game_msg_root.actual_message_as_chat_message() {
+                            let sanitized = chat_fb.message().ok_or(RejectReason::InvalidChat).and_then(sanitize_chat_message);
+                            let trimmed_msg = match sanitized {
+                                Ok(text) => text,
+                                Err(reason) => {
+                                    penalize_peer(&pid_msg_inner_str, reason, &rate_limiter_on_msg, &dc_on_msg, &pc_weak_on_msg, &server_on_msg).await;
+                                    return;
                                 }
+                            };
+
+                            let team_only = chat_fb.scope() == fb::ChatScope::Team;
+                            server_on_msg.handle_chat_message(&pid_msg_inner_str, &trimmed_msg, team_only, chat_fb.timestamp()).await;
+                        }
+                    }
+                    fb::MessageType::Pong => {
+                        if let Some(pong_fb) = game_msg_root.actual_message_as_pong() {
+                            server_on_msg.handle_pong(&pid_msg_inner_str, pong_fb.sequence(), pong_fb.server_timestamp(), pong_fb.client_timestamp());
+                        }
+                    }
+                    fb::MessageType::SpectatorControl => {
+                        if let Some(control_fb) = game_msg_root.actual_message_as_spectator_control() {
+                            let applied = validate_spectator_control(&control_fb).and_then(|_| {
+                                let mut view = server_on_msg.spectators.get_mut(&pid_msg_inner_str).ok_or(RejectReason::NotAllowed)?;
+                                let mode = SpectatorMode::from_fb(control_fb.mode()).ok_or(RejectReason::InvalidInput)?;
+                                let target = control_fb.target_player_id().and_then(|id| players_map_on_msg.id_pool.get(id));
+                                view.apply_control(mode, target, control_fb.camera_x(), control_fb.camera_y());
+                                Ok(())
+                            });
+                            if let Err(reason) = applied {
+                                penalize_peer(&pid_msg_inner_str, reason, &rate_limiter_on_msg, &dc_on_msg, &pc_weak_on_msg, &server_on_msg).await;
+                                return;
+                            }
+                            if control_fb.last_applied_server_tick() != 0 {
+                                server_on_msg.acknowledge_snapshot(&pid_msg_inner_str, control_fb.last_applied_server_tick());
                             }
                         }
-                        _ => warn!("[{}]: Received unhandled FB message type: {:?}", pid_msg_inner_str, game_msg_root.msg_type()),
                     }
-                } else {
-                    error!("[{}]: Failed to parse FlatBuffer message from client.", pid_msg_inner_str);
+                    _ => warn!("[{}]: Received unhandled FB message type: {:?}", pid_msg_inner_str, game_msg_root.msg_type()),
                 }
             })
         }));
@@ -563,31 +618,120 @@ pub async fn handle_signaling_connection(
     }
 
     info!("[{}]: WebSocket connection handler for signaling ending.", peer_id_str);
-    cleanup_connection(&peer_id_str, &signaling_peers, &player_manager, &data_channels_map, &client_states_map, &player_aois);
+    cleanup_connection(&peer_id_str, &client_signaling_tx, &signaling_peers, &player_manager, &session_manager, &data_channels_map, &client_states_map, &player_aois, &server_instance.spectators, &server_instance.caster_seats, &server_instance.revealed_players);
     if let Err(e) = peer_connection.close().await {
         error!("[{}]: Error closing PeerConnection: {}", peer_id_str, e);
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn cleanup_connection(
     peer_id_str: &str,
+    connection_tx: &SignalingSender,
     signaling_peers: &SignalingPeers,
     player_manager: &PlayerManagerRef, // This is Arc<ImprovedPlayerManager>
+    session_manager: &SessionManagerRef,
     data_channels_map: &DataChannelsMap,
     client_states_map: &ClientStatesMap,
     player_aois: &PlayerAoIs,
+    spectators: &SpectatorsMap,
+    caster_seats: &CasterSeatsMap,
+    revealed_players: &RevealedPlayersMap,
 ) {
     info!("[{}]: Cleaning up resources.", peer_id_str);
-    // Check if peer_id was already removed from signaling_peers to prevent double cleanup issues.
-    if signaling_peers.lock().unwrap().remove(peer_id_str).is_some() {
-        // Only proceed with other removals if this was the first successful removal from signaling_peers
-        player_manager.remove_player(peer_id_str); // This is where the warn originates
+    // Only the connection that currently owns the peer id may clean up. A resumed session
+    // re-uses the id, so a late cleanup from the old connection must not tear down the new one.
+    let owns_peer_id = {
+        let mut peers_guard = signaling_peers.lock().unwrap();
+        if peers_guard.get(peer_id_str).is_some_and(|tx| tx.same_channel(connection_tx)) {
+            peers_guard.remove(peer_id_str);
+            true
+        } else {
+            false
+        }
+    };
+
+    if owns_peer_id {
+        if spectators.remove(peer_id_str).is_some() || caster_seats.remove(peer_id_str).is_some() {
+            player_manager.id_pool.remove(peer_id_str);
+            info!("[{}]: Spectator left.", peer_id_str);
+        } else if session_manager.has_session(peer_id_str) {
+            // Keep the player in the world, frozen, until the grace period expires.
+            let player_id_arc = player_manager.id_pool.get_or_create(peer_id_str);
+            if let Some(mut player_entry) = player_manager.get_player_state_mut(&player_id_arc) {
+                player_entry.input_buffer.clear();
+                player_entry.velocity_x = 0.0;
+                player_entry.velocity_y = 0.0;
+                player_entry.mark_field_changed(FIELD_POSITION_ROTATION);
+            }
+            session_manager.mark_disconnected(peer_id_str);
+            info!("[{}]: Player kept for reconnect grace period.", peer_id_str);
+        } else {
+            if let Some(player_id) = player_manager.id_pool.get(peer_id_str) {
+                forget_reveals(revealed_players, &player_id);
+            }
+            player_manager.remove_player(peer_id_str); // This is where the warn originates
+        }
         data_channels_map.remove(peer_id_str);
         client_states_map.write().remove(peer_id_str); // Assuming client_states_map is Arc<ParkingLotRwLock<HashMap<...>>>
         player_aois.remove(peer_id_str);
         info!("[{}]: Player AoI data removed.", peer_id_str);
     } else {
-        debug!("[{}]: Resources already cleaned up or peer not in signaling_peers.", peer_id_str);
+        debug!("[{}]: Resources already cleaned up or peer id owned by a newer connection.", peer_id_str);
+    }
+}
+
+// Applies the escalating penalty for a rejected message: drop it, warn the client, or kick them.
+async fn penalize_peer(
+    peer_id_str: &str,
+    reason: RejectReason,
+    rate_limiter: &parking_lot::Mutex<PeerRateLimiter>,
+    data_channel: &Arc<RTCDataChannel>,
+    peer_connection: &std::sync::Weak<webrtc::peer_connection::RTCPeerConnection>,
+    server_instance: &ServerInstanceRef,
+) {
+    let penalty = rate_limiter.lock().record_violation();
+    server_metrics::record_client_message_rejected(reason.as_str(), penalty.as_str());
+
+    match penalty {
+        Penalty::Drop => {
+            debug!("[{}]: Dropped client message ({}).", peer_id_str, reason.as_str());
+        }
+        Penalty::Warn => {
+            warn!("[{}]: Repeated rejected messages ({}), warning client.", peer_id_str, reason.as_str());
+            let mut builder = flatbuffers::FlatBufferBuilder::with_capacity(256);
+            let player_id_fb = builder.create_string(SYSTEM_SENDER_ID);
+            let username_fb = builder.create_string(SYSTEM_SENDER_NAME);
+            let message_fb = builder.create_string("You are sending too many or invalid messages. Further violations will disconnect you.");
+            let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64;
+            let chat_msg = fb::ChatMessage::create(&mut builder, &fb::ChatMessageArgs {
+                seq: 0,
+                player_id: Some(player_id_fb),
+                username: Some(username_fb),
+                message: Some(message_fb),
+                timestamp,
+                scope: fb::ChatScope::System,
+            });
+            let game_msg = fb::GameMessage::create(&mut builder, &fb::GameMessageArgs {
+                msg_type: fb::MessageType::Chat,
+                actual_message_type: fb::MessagePayload::ChatMessage,
+                actual_message: Some(chat_msg.as_union_value()),
+            });
+            builder.finish(game_msg, None);
+            if let Err(e) = data_channel.send(&Bytes::from(builder.finished_data().to_vec())).await {
+                handle_dc_send_error(&e.to_string(), peer_id_str, "rate limit warning");
+            }
+        }
+        Penalty::Disconnect => {
+            error!("[{}]: Too many rejected messages (last: {}), disconnecting.", peer_id_str, reason.as_str());
+            // A kicked player must not be able to resume their session.
+            server_instance.session_manager.forget(peer_id_str);
+            if let Some(pc) = peer_connection.upgrade() {
+                if let Err(e) = pc.close().await {
+                    error!("[{}]: Error closing PeerConnection after penalty: {}", peer_id_str, e);
+                }
+            }
+        }
     }
 }
 
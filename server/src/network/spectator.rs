@@ -0,0 +1,134 @@
+// massive_game_server/server/src/network/spectator.rs
+// Spectator connections (ws?spectate=follow|free|overview, optionally &target=<player id>).
+// A live view of the whole map is a wallhack for a second client, so unless the server sets
+// `open_spectating` it takes the caster key (&key=<caster key>).
+//
+// A spectator has a data channel and a ClientState but no PlayerState, so it never counts
+// towards `max_players_per_match` or team balance. Each tick its AoI is rebuilt around the
+// view's focus and streamed through the same delta path as a player's.
+use crate::core::constants::*;
+use crate::core::types::PlayerID;
+use crate::flatbuffers_generated::game_protocol as fb;
+use dashmap::DashMap;
+use std::sync::Arc;
+
+pub type SpectatorsMap = Arc<DashMap<String, SpectatorView>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpectatorMode {
+    Follow,     // AoI of a chosen player
+    FreeCamera, // AoI around a client-controlled center
+    Overview,   // Whole map, no projectiles, reduced rate
+}
+
+impl SpectatorMode {
+    pub fn from_query(value: &str) -> Option<Self> {
+        match value {
+            "follow" => Some(SpectatorMode::Follow),
+            "free" => Some(SpectatorMode::FreeCamera),
+            "overview" => Some(SpectatorMode::Overview),
+            _ => None,
+        }
+    }
+
+    pub fn from_fb(mode: fb::SpectatorMode) -> Option<Self> {
+        match mode {
+            fb::SpectatorMode::Follow => Some(SpectatorMode::Follow),
+            fb::SpectatorMode::FreeCamera => Some(SpectatorMode::FreeCamera),
+            fb::SpectatorMode::Overview => Some(SpectatorMode::Overview),
+            _ => None,
+        }
+    }
+}
+
+/// Where a spectator's AoI is centered this tick.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpectatorFocus {
+    Point { x: f32, y: f32 },
+    WholeMap,
+}
+
+#[derive(Debug, Clone)]
+pub struct SpectatorView {
+    pub mode: SpectatorMode,
+    pub follow_target: Option<PlayerID>,
+    pub camera_x: f32,
+    pub camera_y: f32,
+}
+
+impl SpectatorView {
+    pub fn new(mode: SpectatorMode, follow_target: Option<PlayerID>) -> Self {
+        SpectatorView {
+            mode,
+            follow_target,
+            camera_x: (WORLD_MIN_X + WORLD_MAX_X) / 2.0,
+            camera_y: (WORLD_MIN_Y + WORLD_MAX_Y) / 2.0,
+        }
+    }
+
+    /// Applies a SpectatorControl. A `None` target keeps following the current player.
+    pub fn apply_control(&mut self, mode: SpectatorMode, target: Option<PlayerID>, camera_x: f32, camera_y: f32) {
+        self.mode = mode;
+        if target.is_some() {
+            self.follow_target = target;
+        }
+        if mode == SpectatorMode::FreeCamera {
+            self.camera_x = camera_x.clamp(WORLD_MIN_X, WORLD_MAX_X);
+            self.camera_y = camera_y.clamp(WORLD_MIN_Y, WORLD_MAX_Y);
+        }
+    }
+
+    /// Keeps the camera on the followed player. Switching to free camera starts from there.
+    pub fn track(&mut self, target_x: f32, target_y: f32) {
+        if self.mode == SpectatorMode::Follow {
+            self.camera_x = target_x;
+            self.camera_y = target_y;
+        }
+    }
+
+    pub fn focus(&self) -> SpectatorFocus {
+        match self.mode {
+            SpectatorMode::Overview => SpectatorFocus::WholeMap,
+            SpectatorMode::Follow | SpectatorMode::FreeCamera => SpectatorFocus::Point { x: self.camera_x, y: self.camera_y },
+        }
+    }
+
+    /// Whether this spectator is sent a snapshot on `tick`.
+    pub fn is_due(&self, tick: u64) -> bool {
+        self.mode != SpectatorMode::Overview || tick.is_multiple_of(SPECTATOR_OVERVIEW_INTERVAL_TICKS)
+    }
+
+    /// Byte budget for one snapshot. Overview snapshots are rarer, so each may be larger.
+    pub fn bytes_budget(&self, per_tick_budget: usize) -> usize {
+        match self.mode {
+            SpectatorMode::Overview => per_tick_budget * SPECTATOR_OVERVIEW_INTERVAL_TICKS as usize,
+            SpectatorMode::Follow | SpectatorMode::FreeCamera => per_tick_budget,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_free_camera_is_clamped_and_follow_tracks_target() {
+        let mut view = SpectatorView::new(SpectatorMode::Follow, None);
+        view.track(100.0, -50.0);
+        assert_eq!(view.focus(), SpectatorFocus::Point { x: 100.0, y: -50.0 });
+
+        view.apply_control(SpectatorMode::FreeCamera, None, 1.0e6, 0.0);
+        assert_eq!(view.focus(), SpectatorFocus::Point { x: WORLD_MAX_X, y: 0.0 });
+        view.track(0.0, 0.0);
+        assert_eq!(view.camera_x, WORLD_MAX_X);
+    }
+
+    #[test]
+    fn test_overview_is_sent_at_reduced_rate() {
+        let view = SpectatorView::new(SpectatorMode::Overview, None);
+        assert_eq!(view.focus(), SpectatorFocus::WholeMap);
+        let sent = (0..60).filter(|tick| view.is_due(*tick)).count() as u64;
+        assert_eq!(sent, 60 / SPECTATOR_OVERVIEW_INTERVAL_TICKS);
+        assert!(SpectatorView::new(SpectatorMode::FreeCamera, None).is_due(1));
+    }
+}
@@ -25,6 +25,20 @@ impl MetricsSystem {
         describe_histogram!("game_frame_time_seconds", "Frame processing time in seconds");
         describe_histogram!("game_physics_time_seconds", "Physics update time in seconds");
         describe_histogram!("game_network_time_seconds", "Network update time in seconds");
+        describe_histogram!("game_client_rtt_seconds", "Round-trip time samples from client Ping/Pong");
+        describe_counter!("game_client_messages_rejected_total", "Client data channel messages rejected, by reason and penalty");
+        describe_histogram!("game_delta_message_bytes", "Size of per-client delta state messages");
+        describe_counter!("game_delta_entities_deferred_total", "Entity updates deferred to a later tick by the bandwidth budget");
+        describe_gauge!("game_input_buffer_depth_avg", "Average number of buffered inputs per connected player");
+        describe_gauge!("game_input_buffer_depth_max", "Largest input buffer among connected players");
+        describe_counter!("game_input_buffer_underruns_total", "Ticks on which a player's input buffer ran dry and the last input was repeated");
+        describe_gauge!("game_caster_buffer_bytes", "Encoded frames held for the delayed caster feed");
+        describe_counter!("game_caster_frames_dropped_total", "Caster frames dropped because the buffer hit its byte cap");
+        describe_gauge!("game_caster_delay_seconds", "Current delay of the caster feed");
+        describe_counter!("game_chat_reports_total", "Player reports filed with /report");
+        describe_gauge!("game_clients_rtt_avg_seconds", "Average smoothed round-trip time across connected clients");
+        describe_gauge!("game_clients_rtt_max_seconds", "Highest smoothed round-trip time among connected clients");
+        describe_gauge!("game_clients_jitter_avg_seconds", "Average round-trip time variation across connected clients");
         // Add more descriptions as needed
 
         Ok(MetricsSystem {
@@ -56,7 +70,47 @@ impl MetricsSystem {
     }
 }
 
+// Called from the network layer, which has no MetricsSystem handle; the recorder is global.
+pub fn record_client_rtt_sample(rtt_sample_ms: f32) {
+    histogram!("game_client_rtt_seconds").record(rtt_sample_ms as f64 / 1000.0);
+}
+
+pub fn update_client_latency_summary(avg_rtt_ms: f32, max_rtt_ms: f32, avg_jitter_ms: f32) {
+    gauge!("game_clients_rtt_avg_seconds").set(avg_rtt_ms as f64 / 1000.0);
+    gauge!("game_clients_rtt_max_seconds").set(max_rtt_ms as f64 / 1000.0);
+    gauge!("game_clients_jitter_avg_seconds").set(avg_jitter_ms as f64 / 1000.0);
+}
+
+pub fn record_client_message_rejected(reason: &'static str, penalty: &'static str) {
+    counter!("game_client_messages_rejected_total", "reason" => reason, "penalty" => penalty).increment(1);
+}
+
+pub fn record_chat_report() {
+    counter!("game_chat_reports_total").increment(1);
+}
+
+pub fn record_delta_message(bytes: usize, deferred_entities: usize) {
+    histogram!("game_delta_message_bytes").record(bytes as f64);
+    if deferred_entities > 0 {
+        counter!("game_delta_entities_deferred_total").increment(deferred_entities as u64);
+    }
+}
+
+pub fn update_input_buffer_summary(avg_depth: f64, max_depth: usize, underruns: u64) {
+    gauge!("game_input_buffer_depth_avg").set(avg_depth);
+    gauge!("game_input_buffer_depth_max").set(max_depth as f64);
+    if underruns > 0 {
+        counter!("game_input_buffer_underruns_total").increment(underruns);
+    }
+}
+
 // Logging setup
+pub fn update_caster_feed(buffered_bytes: usize, dropped_frames_total: u64, delay_secs: f32) {
+    gauge!("game_caster_buffer_bytes").set(buffered_bytes as f64);
+    counter!("game_caster_frames_dropped_total").absolute(dropped_frames_total);
+    gauge!("game_caster_delay_seconds").set(delay_secs as f64);
+}
+
 pub fn init_logging() -> Result<()> { // Changed return type to anyhow::Result
     use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, fmt};
 
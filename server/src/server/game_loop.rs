@@ -17,8 +17,10 @@ use futures::executor::block_on;
 use crate::core::types::{PlayerID, PlayerAoI, Vec2};
 use tokio::time::sleep; // Add this import
 use std::collections::HashSet; // If not already imported for PlayerAoI
-use crate::core::constants::{AOI_RADIUS, AOI_UPDATE_INTERVAL_SECS}; // Assuming these are in constants
+use crate::core::constants::{AOI_RADIUS, AOI_UPDATE_INTERVAL_SECS, KILL_CAM_SAMPLE_INTERVAL_TICKS}; // Assuming these are in constants
 use crate::network::signaling::{ClientState, ChatMessage};
+use crate::state_sync::interpolation::HitboxSnapshot;
+use crate::network::spectator::{SpectatorFocus, SpectatorMode};
 
 
 const MAX_FRAME_TIME_HISTORY: usize = 100;
@@ -166,6 +168,27 @@ impl MassiveGameServer {
                 frame, player_id.as_str(), player_processing_start.elapsed());
         });
         
+        // Record this tick's hitboxes for lag compensation
+        let hitbox_positions = players_to_update.iter()
+            .map(|(player_id, x, y, _, _)| (player_id.clone(), (*x, *y)))
+            .collect();
+        self.hitbox_history.write().record(HitboxSnapshot {
+            tick: frame,
+            timestamp_ms: self.get_server_timestamp(),
+            positions: hitbox_positions,
+        });
+
+        // Sample trails for kill cams
+        if frame.is_multiple_of(KILL_CAM_SAMPLE_INTERVAL_TICKS) {
+            let mut trail_samples = Vec::new();
+            self.player_manager.for_each_player(|player_id, player_state| {
+                if player_state.alive {
+                    trail_samples.push((player_id.clone(), player_state.x, player_state.y, player_state.rotation));
+                }
+            });
+            self.kill_cam_history.write().record(self.get_server_timestamp(), trail_samples);
+        }
+        
         // Step 2: Process updates that require read access (no write locks held)
         for (player_id, x, y, partition_idx, needs_full_aoi_update) in players_to_update {
             // Update partition status
@@ -187,6 +210,8 @@ impl MassiveGameServer {
             }
         }
         
+        self.update_spectator_aois();
+        
             // Update boundary snapshots less frequently (every 30 frames)
             if frame % 30 == 0 {
                 let boundary_update_start = Instant::now();
@@ -205,8 +230,6 @@ impl MassiveGameServer {
 
 
     pub fn update_player_aoi(&self, player_id: &PlayerID, x: f32, y: f32) {
-        const AOI_RADIUS_SQUARED: f32 = AOI_RADIUS * AOI_RADIUS;
-        
         let player_id_str = player_id.as_str();
         
         // Ensure player exists before updating AoI
@@ -222,8 +245,15 @@ impl MassiveGameServer {
             return;
         }
         
-        let player_aoi = player_aoi_entry.value_mut();
-        
+        self.fill_aoi(player_aoi_entry.value_mut(), Some(player_id), x, y);
+    }
+
+    /// Rebuilds `player_aoi` around (x, y). `viewer` is left out of the visible players;
+    /// spectators have no player of their own and pass `None`.
+    pub(crate) fn fill_aoi(&self, player_aoi: &mut PlayerAoI, viewer: Option<&PlayerID>, x: f32, y: f32) {
+        const AOI_RADIUS_SQUARED: f32 = AOI_RADIUS * AOI_RADIUS;
+        let viewer_str = viewer.map_or("spectator", |id| id.as_str());
+
         // Clear previous data
         player_aoi.visible_players.clear();
         player_aoi.visible_projectiles.clear();
@@ -233,10 +263,22 @@ impl MassiveGameServer {
         // 1. Update visible players (using spatial index)
         let nearby_player_ids = self.spatial_index.query_nearby_players(x, y, AOI_RADIUS);
         for other_id_arc in nearby_player_ids {
-            if &other_id_arc != player_id {
+            if Some(&other_id_arc) != viewer {
                 player_aoi.visible_players.insert(other_id_arc);
             }
         }
+
+        // Enemies the viewer or a teammate scanned are visible wherever they are
+        if let Some(viewer_id) = viewer.filter(|_| !self.revealed_players.is_empty()) {
+            let viewer_team = self.player_manager.get_player_state(viewer_id).map_or(0, |p| p.team_id);
+            for reveal in self.revealed_players.iter() {
+                let (scanner_id, scanner_team) = reveal.value();
+                let shared = scanner_id == viewer_id || (*scanner_team != 0 && *scanner_team == viewer_team);
+                if shared && reveal.key() != viewer_id {
+                    player_aoi.visible_players.insert(reveal.key().clone());
+                }
+            }
+        }
         
         // 2. Update visible projectiles
         let projectiles_guard = self.projectiles.read();
@@ -295,7 +337,7 @@ impl MassiveGameServer {
         // Debug logging
         trace!(
             "[AoI Update] Player {}: {} players, {} projectiles (of {} total), {} pickups (of {} active/{} total), {} walls visible", 
-            player_id_str,
+            viewer_str,
             player_aoi.visible_players.len(),
             player_aoi.visible_projectiles.len(),
             projectile_count,
@@ -308,6 +350,59 @@ impl MassiveGameServer {
         player_aoi.last_update = Instant::now();
     }
 
+    /// Moves follow cameras onto their targets and rebuilds each spectator's AoI.
+    /// Spectators following nobody (or a player who left) are switched to the top scorer.
+    pub fn update_spectator_aois(&self) {
+        for mut spectator_entry in self.spectators.iter_mut() {
+            let (peer_id_str, view) = spectator_entry.pair_mut();
+
+            if view.mode == SpectatorMode::Follow {
+                let target_position = view.follow_target.as_ref()
+                    .and_then(|target| self.player_manager.get_player_state(target).map(|p| (p.x, p.y)));
+                match target_position {
+                    Some((x, y)) => view.track(x, y),
+                    None => view.follow_target = self.top_scoring_player(),
+                }
+            }
+
+            let mut spectator_aoi = self.player_aois.entry(peer_id_str.clone()).or_insert_with(PlayerAoI::new);
+            if spectator_aoi.last_update.elapsed().as_secs_f32() < AOI_UPDATE_INTERVAL_SECS {
+                continue;
+            }
+            match view.focus() {
+                SpectatorFocus::Point { x, y } => self.fill_aoi(spectator_aoi.value_mut(), None, x, y),
+                SpectatorFocus::WholeMap => self.fill_overview_aoi(spectator_aoi.value_mut()),
+            }
+        }
+    }
+
+    /// Every player, active pickup and wall on the map; projectiles are too noisy at this scale.
+    fn fill_overview_aoi(&self, overview_aoi: &mut PlayerAoI) {
+        overview_aoi.visible_players.clear();
+        overview_aoi.visible_projectiles.clear();
+        overview_aoi.visible_pickups.clear();
+        overview_aoi.visible_walls.clear();
+
+        self.player_manager.for_each_player(|player_id, _| {
+            overview_aoi.visible_players.insert(player_id.clone());
+        });
+        overview_aoi.visible_pickups.extend(self.pickups.read().iter().filter(|p| p.is_active).map(|p| p.id));
+        for partition in self.world_partition_manager.get_partitions_for_processing() {
+            overview_aoi.visible_walls.extend(partition.all_walls_in_partition.iter().map(|wall| *wall.key()));
+        }
+        overview_aoi.last_update = Instant::now();
+    }
+
+    fn top_scoring_player(&self) -> Option<PlayerID> {
+        let mut best: Option<(i32, PlayerID)> = None;
+        self.player_manager.for_each_player(|player_id, player_state| {
+            if best.as_ref().is_none_or(|(score, _)| player_state.score > *score) {
+                best = Some((player_state.score, player_id.clone()));
+            }
+        });
+        best.map(|(_, player_id)| player_id)
+    }
+
     fn update_player_aoi_v3(&self, player_id: &PlayerID, x: f32, y: f32) {
         // const AOI_RADIUS: f32 = 600.0; // Defined in constants
         const AOI_RADIUS_SQUARED: f32 = AOI_RADIUS * AOI_RADIUS;
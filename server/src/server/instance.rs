@@ -1,7 +1,7 @@
 // massive_game_server/server/src/server/instance.rs
 use crate::core::types::*;
 use crate::core::config::ServerConfig;
-use crate::core::constants::*; // Import all constants, including MIN_PLAYERS_TO_START
+use crate::core::constants::*;
 use crate::core::error::ServerError;
 use crate::concurrent::thread_pools::ThreadPoolSystem;
 use crate::concurrent::spatial_index::ImprovedSpatialIndex;
@@ -12,6 +12,7 @@ use crate::entities::player::{ImprovedPlayerManager};
 use crate::flatbuffers_generated::game_protocol as fb;
 use crate::network::signaling::{DataChannelsMap, ClientStatesMap, ChatMessagesQueue, ClientState, handle_dc_send_error};
 use crate::world::map_generator::MapGenerator;
+use crate::world::boundary::SafeZone;
 use crate::systems::respawn::{RespawnManager, WallRespawnManager};
 use crate::systems::ai::bot_ai::BotAISystem;
 use crate::systems::ai::optimized_bot_ai::OptimizedBotAI;
@@ -23,6 +24,34 @@ use webrtc::data_channel::data_channel_state::RTCDataChannelState;
 use parking_lot::RwLockReadGuard;
 use crate::core::types::{EntityId, PlayerID, CorePickupType, MatchState};
 use crate::network::signaling::PickupState;
+use crate::network::session::{SessionManager, SessionManagerRef};
+use crate::network::latency::ClientLatenciesMap;
+use crate::network::spectator::{SpectatorMode, SpectatorView, SpectatorsMap};
+use crate::network::caster::{CasterFeed, CasterFrame, CasterSeatsMap};
+use crate::network::chat::{ChatRecipient, WordFilter};
+use crate::systems::physics::ballistics::{apply_shot, ballistic_spec, shot_of, trace_shot, ShotContact};
+use crate::systems::physics::movement::{
+    apply_movement_state, integrate_movement, movement_input_of, movement_state_of, overlaps_wall,
+    step_movement, wall_rects, MovementState,
+};
+use crate::state_sync::delta::{BaselineRing, SnapshotBaseline, SnapshotBaselinesMap};
+use crate::state_sync::interpolation::{compute_rewind_ms, HitboxHistory};
+use crate::state_sync::kill_cam::{KillCam, KillCamHistory, KillShot};
+use crate::systems::combat::abilities::{abilities_fb, cover_rect, forget_reveals, AbilityActivation, AbilityKind, PlayerClass, RevealedPlayersMap};
+use crate::systems::combat::damage::{DamageCause, DamageLog, DeathRecap};
+use crate::systems::combat::effects::{effect_def, effects_fb, EffectKind};
+use crate::systems::combat::explosives::{
+    blast_damage, blast_reaches, closest_point_on_wall, explosive_spec, knockback_offset, Explosion,
+};
+use crate::systems::combat::weapons::weapon_in_slot;
+use crate::systems::objectives::{create_game_mode, GameMode, KillInfo, MatchOutcome, ModeContext};
+use crate::systems::objectives::rules::{apply_tie_rules, clock_expired, MatchRules};
+use crate::systems::objectives::scoring::{assist_ids, MatchSummary, PlayerSummary};
+use crate::state_sync::priority::{
+    estimate_player_state_bytes, estimate_projectile_bytes, player_priority, projectile_priority, sort_candidates,
+    ByteBudget, ClientPrioritiesMap, PriorityCandidate, SyncEntity,
+};
+use crate::operational::monitoring::metrics as server_metrics;
 use flatbuffers::FlatBufferBuilder;
 
 
@@ -56,6 +85,28 @@ pub struct ServerFlagState {
     pub respawn_timer: f32, // If dropped, time until it auto-returns
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub struct ServerZoneState {
+    pub zone_id: u8,
+    pub position: Vec2,
+    pub radius: f32,
+    pub owner_team_id: u8, // 0 while neutral
+    pub capturing_team_id: u8, // Team the progress belongs to, 0 if none
+    pub capture_progress: f32, // 0..1
+    pub contested: bool,
+    pub score_timer: f32, // Time held since the owner last scored from this zone
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ServerRoundState {
+    pub round_number: u8, // 0 during warmup
+    pub total_rounds: u8,
+    pub phase: fb::RoundPhase,
+    pub phase_time_remaining: f32,
+    pub sides_swapped: bool,
+    pub respawn_all: bool, // Set by the mode when everyone goes back to spawn; cleared by the server
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct ServerMatchInfo {
     pub time_remaining: f32,
@@ -63,20 +114,40 @@ pub struct ServerMatchInfo {
     pub game_mode: fb::GameModeType,   // fb from flatbuffers_generated
     pub team_scores: HashMap<u8, i32>, // team_id -> score
     pub flag_states: HashMap<u8, ServerFlagState>, // team_id of flag -> state
+    pub zone_states: Vec<ServerZoneState>, // Capture zones, ordered by zone_id
+    pub safe_zone: Option<SafeZone>,       // Battle royale circle
+    pub round: Option<ServerRoundState>,   // Round-based modes
+    pub rules: MatchRules,                 // Fixed for the match once it starts
+    pub period: fb::MatchPeriod,
+    pub winner_id: Option<String>,         // Team id or player id, set when the match ends
+    pub winner_name: Option<String>,
 }
 
-impl Default for ServerMatchInfo {
-    fn default() -> Self {
+impl ServerMatchInfo {
+    pub fn new(game_mode: fb::GameModeType) -> Self {
         ServerMatchInfo {
-            time_remaining: 300.0, // 5 minutes
+            time_remaining: DEFAULT_MATCH_DURATION_SECS,
             match_state: fb::MatchStateType::Waiting,
-            game_mode: fb::GameModeType::CaptureTheFlag, // Changed to CTF mode
+            game_mode,
             team_scores: HashMap::new(),
             flag_states: HashMap::new(),
+            zone_states: Vec::new(),
+            safe_zone: None,
+            round: None,
+            rules: MatchRules::default(),
+            period: fb::MatchPeriod::Regulation,
+            winner_id: None,
+            winner_name: None,
         }
     }
 }
 
+impl Default for ServerMatchInfo {
+    fn default() -> Self {
+        ServerMatchInfo::new(fb::GameModeType::CaptureTheFlag)
+    }
+}
+
 
 
 #[derive(Clone, Debug, PartialEq)]
@@ -119,15 +190,19 @@ pub struct ServerKillFeedEntry {
     pub victim_name: String,
     pub weapon: ServerWeaponType,
     pub timestamp: u64,
+    pub killer_position: Option<Vec2>, // None when the killer has left
+    pub victim_position: Vec2,
+    pub cause: fb::DeathCause,
 }
 
 
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 struct ProjectileResults {
     total_processed: usize,
-    hits: Vec<(PlayerID, PlayerID, i32, ServerWeaponType)>, // (attacker, target, damage, weapon)
+    hits: Vec<(PlayerID, PlayerID, i32, ServerWeaponType, KillShot)>, // (attacker, target, damage, weapon, shot)
     wall_hits: Vec<(EntityId, i32)>, // (wall_id, damage)
+    detonations: Vec<Explosion>,     // Explosives that went off this tick
     to_remove: Vec<usize>, // Projectile indices to remove
 }
 
@@ -148,13 +223,106 @@ struct PlayerPhysicsResults {
 
 
 // Helper functions (assuming these are already defined as per your project structure)
-fn map_server_weapon_to_fb(server_weapon: ServerWeaponType) -> fb::WeaponType {
+fn kill_feed_positions_fb<'a>(
+    builder: &mut FlatBufferBuilder<'a>,
+    entry: &ServerKillFeedEntry,
+) -> (Option<flatbuffers::WIPOffset<fb::Vec2<'a>>>, flatbuffers::WIPOffset<fb::Vec2<'a>>) {
+    let killer_position = entry.killer_position
+        .map(|pos| fb::Vec2::create(builder, &fb::Vec2Args { x: pos.x, y: pos.y }));
+    let victim_position = fb::Vec2::create(builder, &fb::Vec2Args { x: entry.victim_position.x, y: entry.victim_position.y });
+    (killer_position, victim_position)
+}
+
+fn zone_states_fb<'a>(
+    builder: &mut FlatBufferBuilder<'a>,
+    zone_states: &[ServerZoneState],
+) -> Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a, flatbuffers::ForwardsUOffset<fb::ZoneState<'a>>>>> {
+    if zone_states.is_empty() {
+        return None;
+    }
+    let zones: Vec<_> = zone_states.iter().map(|zone| {
+        let position = fb::Vec2::create(builder, &fb::Vec2Args { x: zone.position.x, y: zone.position.y });
+        fb::ZoneState::create(builder, &fb::ZoneStateArgs {
+            zone_id: zone.zone_id,
+            position: Some(position),
+            radius: zone.radius,
+            owner_team_id: zone.owner_team_id as i8,
+            capturing_team_id: zone.capturing_team_id as i8,
+            capture_progress: zone.capture_progress,
+            contested: zone.contested,
+        })
+    }).collect();
+    Some(builder.create_vector(&zones))
+}
+
+fn safe_zone_fb<'a>(
+    builder: &mut FlatBufferBuilder<'a>,
+    safe_zone: Option<&SafeZone>,
+) -> Option<flatbuffers::WIPOffset<fb::SafeZoneState<'a>>> {
+    let zone = safe_zone?;
+    let center = fb::Vec2::create(builder, &fb::Vec2Args { x: zone.center.x, y: zone.center.y });
+    let next_center = fb::Vec2::create(builder, &fb::Vec2Args { x: zone.next_center.x, y: zone.next_center.y });
+    Some(fb::SafeZoneState::create(builder, &fb::SafeZoneStateArgs {
+        center: Some(center),
+        radius: zone.radius,
+        next_center: Some(next_center),
+        next_radius: zone.next_radius,
+        phase: zone.phase_index.min(u8::MAX as usize) as u8,
+        shrinking: zone.shrinking,
+        phase_time_remaining: zone.phase_time_remaining,
+        damage_per_second: zone.damage_per_sec(),
+    }))
+}
+
+fn round_state_fb<'a>(
+    builder: &mut FlatBufferBuilder<'a>,
+    round: Option<&ServerRoundState>,
+) -> Option<flatbuffers::WIPOffset<fb::RoundState<'a>>> {
+    let round = round?;
+    Some(fb::RoundState::create(builder, &fb::RoundStateArgs {
+        round_number: round.round_number,
+        total_rounds: round.total_rounds,
+        phase: round.phase,
+        phase_time_remaining: round.phase_time_remaining,
+        sides_swapped: round.sides_swapped,
+    }))
+}
+
+fn match_info_fb<'a>(
+    builder: &mut FlatBufferBuilder<'a>,
+    match_info: &ServerMatchInfo,
+) -> flatbuffers::WIPOffset<fb::MatchInfo<'a>> {
+    let team_scores: Vec<_> = match_info.team_scores.iter().map(|(team_id, score)| {
+        fb::TeamScoreEntry::create(builder, &fb::TeamScoreEntryArgs { team_id: *team_id as i8, score: *score })
+    }).collect();
+    let team_scores = builder.create_vector(&team_scores);
+    let safe_zone = safe_zone_fb(builder, match_info.safe_zone.as_ref());
+    let round = round_state_fb(builder, match_info.round.as_ref());
+    let winner_id = match_info.winner_id.as_deref().map(|id| fb_safe_str(builder, id));
+    let winner_name = match_info.winner_name.as_deref().map(|name| fb_safe_str(builder, name));
+    fb::MatchInfo::create(builder, &fb::MatchInfoArgs {
+        time_remaining: match_info.time_remaining,
+        match_state: match_info.match_state,
+        winner_id,
+        winner_name,
+        game_mode: match_info.game_mode,
+        team_scores: Some(team_scores),
+        safe_zone,
+        round,
+        period: match_info.period,
+        score_limit: match_info.rules.score_limit.or(match_info.rules.rounds_to_win).unwrap_or(0),
+    })
+}
+
+pub(crate) fn map_server_weapon_to_fb(server_weapon: ServerWeaponType) -> fb::WeaponType {
     match server_weapon {
         ServerWeaponType::Pistol => fb::WeaponType::Pistol,
         ServerWeaponType::Shotgun => fb::WeaponType::Shotgun,
         ServerWeaponType::Rifle => fb::WeaponType::Rifle,
         ServerWeaponType::Sniper => fb::WeaponType::Sniper,
         ServerWeaponType::Melee => fb::WeaponType::Melee,
+        ServerWeaponType::RocketLauncher => fb::WeaponType::RocketLauncher,
+        ServerWeaponType::GrenadeLauncher => fb::WeaponType::GrenadeLauncher,
     }
 }
 
@@ -191,10 +359,39 @@ fn create_fb_player_state_for_delta<'a>(
     builder: &mut flatbuffers::FlatBufferBuilder<'a>,
     pstate: &PlayerState,
     _changed_fields: u16, // This can be used if we implement partial updates later
+) -> flatbuffers::WIPOffset<fb::PlayerState<'a>> {
+    create_fb_player_state(builder, pstate, false)
+}
+
+/// A player's state as sent to that player, with what only they need, such as ability cooldowns.
+fn create_fb_own_player_state<'a>(
+    builder: &mut flatbuffers::FlatBufferBuilder<'a>,
+    pstate: &PlayerState,
+) -> flatbuffers::WIPOffset<fb::PlayerState<'a>> {
+    create_fb_player_state(builder, pstate, true)
+}
+
+fn create_fb_player_state<'a>(
+    builder: &mut flatbuffers::FlatBufferBuilder<'a>,
+    pstate: &PlayerState,
+    own: bool,
 ) -> flatbuffers::WIPOffset<fb::PlayerState<'a>> {
     let id_fb = fb_safe_str(builder, pstate.id.as_str());
     let username_fb = fb_safe_str(builder, &pstate.username);
     let weapon_fb = map_server_weapon_to_fb(pstate.weapon);
+    let inventory_fb: Vec<_> = pstate.inventory.slots().iter().map(|slot| {
+        // The held weapon's loaded rounds are only tracked in `ammo`
+        let ammo = if slot.weapon == pstate.weapon { pstate.ammo } else { slot.magazine };
+        fb::WeaponSlot::create(builder, &fb::WeaponSlotArgs {
+            weapon: map_server_weapon_to_fb(slot.weapon),
+            ammo,
+            reserve_ammo: slot.reserve,
+        })
+    }).collect();
+    let inventory_fb = builder.create_vector(&inventory_fb);
+    let effects_list_fb = effects_fb(builder, &pstate.effects);
+    let abilities_list_fb = own.then(|| abilities_fb(builder, &pstate.abilities));
+    let effect_secs = |kind| pstate.effects.get(kind).map_or(0.0, |effect| effect.remaining_secs);
 
     fb::PlayerState::create(
         builder,
@@ -206,10 +403,17 @@ fn create_fb_player_state_for_delta<'a>(
             reload_progress: pstate.reload_progress.unwrap_or(-1.0), score: pstate.score,
             kills: pstate.kills, deaths: pstate.deaths,
             team_id: pstate.team_id as i8,
-            speed_boost_remaining: pstate.speed_boost_remaining,
-            damage_boost_remaining: pstate.damage_boost_remaining,
-            shield_current: pstate.shield_current, shield_max: pstate.shield_max,
+            speed_boost_remaining: effect_secs(EffectKind::SpeedBoost),
+            damage_boost_remaining: effect_secs(EffectKind::DamageBoost),
+            shield_current: pstate.effects.absorb_amount(),
+            shield_max: pstate.effects.get(EffectKind::Shield).map_or(0, |_| effect_def(EffectKind::Shield).amount),
             is_carrying_flag_team_id: pstate.is_carrying_flag_team_id as i8,
+            ping_ms: pstate.ping_ms,
+            reserve_ammo: pstate.reserve_ammo(),
+            inventory: Some(inventory_fb),
+            effects: Some(effects_list_fb),
+            player_class: pstate.class.to_fb(),
+            abilities: abilities_list_fb,
         },
     )
 }
@@ -235,27 +439,129 @@ fn build_game_event_fb<'a>(
     })
 }
 
+type FbVector<'a, T> = flatbuffers::WIPOffset<flatbuffers::Vector<'a, flatbuffers::ForwardsUOffset<T>>>;
+
+// Per-frame payload of the first caster chunk.
+struct CasterFrameHeader<'a> {
+    game_events: FbVector<'a, fb::GameEvent<'a>>,
+    kill_feed: FbVector<'a, fb::KillFeedEntry<'a>>,
+    match_info: flatbuffers::WIPOffset<fb::MatchInfo<'a>>,
+    flag_states: FbVector<'a, fb::FlagState<'a>>,
+    zone_states: Option<FbVector<'a, fb::ZoneState<'a>>>,
+}
+
+/// Packs a caster frame into DeltaState messages of at most `CASTER_FRAME_CHUNK_BYTES`. The first
+/// message carries the header and is marked as a full snapshot; the rest only add entities.
+struct CasterFrameChunker<'a> {
+    tick: u64,
+    timestamp_ms: u64,
+    builder: FlatBufferBuilder<'a>,
+    budget: ByteBudget,
+    header: Option<CasterFrameHeader<'a>>,
+    players: Vec<flatbuffers::WIPOffset<fb::PlayerState<'a>>>,
+    projectiles: Vec<flatbuffers::WIPOffset<fb::ProjectileState<'a>>>,
+    pickups: Vec<flatbuffers::WIPOffset<fb::Pickup<'a>>>,
+    destroyed_walls: Vec<flatbuffers::WIPOffset<&'a str>>,
+    updated_walls: Vec<flatbuffers::WIPOffset<fb::Wall<'a>>>,
+    chunks: Vec<Bytes>,
+}
+
+impl<'a> CasterFrameChunker<'a> {
+    fn new(tick: u64, timestamp_ms: u64) -> Self {
+        CasterFrameChunker {
+            tick,
+            timestamp_ms,
+            builder: FlatBufferBuilder::with_capacity(CASTER_FRAME_CHUNK_BYTES),
+            budget: ByteBudget::new(CASTER_FRAME_CHUNK_BYTES - CASTER_CHUNK_ENVELOPE_BYTES),
+            header: None,
+            players: Vec::new(),
+            projectiles: Vec::new(),
+            pickups: Vec::new(),
+            destroyed_walls: Vec::new(),
+            updated_walls: Vec::new(),
+            chunks: Vec::new(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.header.is_none() && self.players.is_empty() && self.projectiles.is_empty()
+            && self.pickups.is_empty() && self.destroyed_walls.is_empty() && self.updated_walls.is_empty()
+    }
+
+    /// Starts a new chunk if an entity of `estimated_bytes` would not fit in the current one.
+    fn reserve(&mut self, estimated_bytes: usize) {
+        if !self.budget.admits(estimated_bytes, false) && !self.is_empty() {
+            self.flush();
+        }
+    }
+
+    /// Charges what was written since `written_before` (plus its vector slot) to the chunk.
+    fn charge(&mut self, written_before: usize) {
+        let written = self.builder.unfinished_data().len() - written_before;
+        self.budget.spend(written + std::mem::size_of::<u32>());
+    }
+
+    fn flush(&mut self) {
+        let header = self.header.take();
+        let builder = &mut self.builder;
+        let players_fb = builder.create_vector(&self.players);
+        let projectiles_fb = builder.create_vector(&self.projectiles);
+        let pickups_fb = builder.create_vector(&self.pickups);
+        let destroyed_walls_fb = builder.create_vector(&self.destroyed_walls);
+        let updated_walls_fb = builder.create_vector(&self.updated_walls);
+        let delta_state = fb::DeltaStateMessage::create(builder, &fb::DeltaStateMessageArgs {
+            players: Some(players_fb),
+            projectiles: Some(projectiles_fb),
+            pickups: Some(pickups_fb),
+            destroyed_wall_ids: Some(destroyed_walls_fb),
+            updated_walls: Some(updated_walls_fb),
+            game_events: header.as_ref().map(|h| h.game_events),
+            kill_feed: header.as_ref().map(|h| h.kill_feed),
+            match_info: header.as_ref().map(|h| h.match_info),
+            flag_states: header.as_ref().map(|h| h.flag_states),
+            zone_states: header.as_ref().and_then(|h| h.zone_states),
+            timestamp: self.timestamp_ms,
+            server_tick: self.tick,
+            full_snapshot: self.chunks.is_empty(),
+            ..Default::default()
+        });
+        let game_msg = fb::GameMessage::create(builder, &fb::GameMessageArgs {
+            msg_type: fb::MessageType::DeltaState,
+            actual_message_type: fb::MessagePayload::DeltaStateMessage,
+            actual_message: Some(delta_state.as_union_value()),
+        });
+        builder.finish(game_msg, None);
+        self.chunks.push(Bytes::from(builder.finished_data().to_vec()));
+
+        builder.reset();
+        self.budget = ByteBudget::new(CASTER_FRAME_CHUNK_BYTES - CASTER_CHUNK_ENVELOPE_BYTES);
+        self.players.clear();
+        self.projectiles.clear();
+        self.pickups.clear();
+        self.destroyed_walls.clear();
+        self.updated_walls.clear();
+    }
+
+    fn finish(mut self) -> Vec<Bytes> {
+        if !self.is_empty() || self.chunks.is_empty() {
+            self.flush();
+        }
+        self.chunks
+    }
+}
+
 // Shared data that's the same for all clients
 #[derive(Clone)]
 struct SharedBroadcastData {
     timestamp_ms: u64,
     events: Vec<GameEvent>,
     destroyed_wall_ids: Vec<EntityId>,
-    updated_walls: HashMap<EntityId, Wall>,
+    destructible_walls: Arc<HashMap<EntityId, Wall>>,
     chat_messages: Vec<ChatMessage>,
-    match_info_snapshot: MatchInfoSnapshot,
+    match_info_snapshot: ServerMatchInfo,
     kill_feed_snapshot: Vec<ServerKillFeedEntry>,
 }
 
-// Lightweight match info snapshot
-#[derive(Clone)]
-struct MatchInfoSnapshot {
-    time_remaining: f32,
-    match_state: fb::MatchStateType,
-    game_mode: fb::GameModeType,
-    team_scores: HashMap<u8, i32>,
-    flag_states: HashMap<u8, ServerFlagState>,
-}
 
 
 pub struct MassiveGameServer {
@@ -283,10 +589,13 @@ pub struct MassiveGameServer {
     pub is_shutting_down: Arc<AtomicBool>,
 
     pub match_info: Arc<ParkingLotRwLock<ServerMatchInfo>>,
+    pub game_mode: Arc<dyn GameMode>,
+    pub next_match_rules: Arc<ParkingLotRwLock<MatchRules>>,
     pub kill_feed: Arc<ParkingLotRwLock<VecDeque<ServerKillFeedEntry>>>,
 
     pub destroyed_wall_ids_this_tick: Arc<ParkingLotRwLock<HashSet<EntityId>>>,
     pub updated_walls_this_tick: Arc<ParkingLotRwLock<HashMap<EntityId, Wall>>>, // To track respawned/updated walls
+    destructible_walls_snapshot: ParkingLotRwLock<Arc<HashMap<EntityId, Wall>>>, // Patched from updated_walls_this_tick
 
 
     pub player_aois: PlayerAoIs,
@@ -300,6 +609,21 @@ pub struct MassiveGameServer {
 
     pub last_broadcast_frame: Arc<AtomicU64>,
     pub player_last_sync_positions: Arc<DashMap<PlayerID, (f32, f32)>>,
+
+    pub session_manager: SessionManagerRef,
+    pub client_latencies: ClientLatenciesMap,
+    pub snapshot_baselines: SnapshotBaselinesMap,
+    pub client_priorities: ClientPrioritiesMap,
+    pub hitbox_history: Arc<ParkingLotRwLock<HitboxHistory>>,
+    pub kill_cam_history: Arc<ParkingLotRwLock<KillCamHistory>>,
+    pub spectators: SpectatorsMap,
+    pub caster_seats: CasterSeatsMap,
+    pub caster_feed: Arc<ParkingLotRwLock<CasterFeed>>,
+    pub word_filter: WordFilter,
+
+    pub ability_activations: Arc<SegQueue<AbilityActivation>>, // Paid for this tick, resolved in the logic update
+    pub deployed_covers: Arc<DashMap<EntityId, f32>>,            // Cover wall id -> seconds left
+    pub revealed_players: RevealedPlayersMap,
 }
 
 const MAX_KILL_FEED_HISTORY: usize = 10;
@@ -398,6 +722,15 @@ impl MassiveGameServer {
         wall_spatial_index.rebuild(&active_walls_for_index, 0);
         info!("Wall spatial index initialized with {} active walls.", wall_spatial_index.size());
 
+        let caster_feed = CasterFeed::new(config.caster_delay_secs, config.caster_buffer_max_bytes);
+        let word_filter = WordFilter::new(&config.chat_word_filter);
+        let game_mode = create_game_mode(&config);
+        let match_rules = config.match_rules.clone().unwrap_or_else(|| game_mode.default_rules());
+        let mut initial_match_info = ServerMatchInfo::new(game_mode.mode_type());
+        initial_match_info.time_remaining = match_rules.time_limit_secs;
+        initial_match_info.rules = match_rules.clone();
+        info!("Game mode: {:?}", game_mode.mode_type());
+
         let server = MassiveGameServer {
             config,
             thread_pools,
@@ -405,6 +738,9 @@ impl MassiveGameServer {
             world_partition_manager,
             spatial_index,
             wall_spatial_index,
+            destructible_walls_snapshot: ParkingLotRwLock::new(Arc::new(
+                destructible_walls_vec.iter().map(|wall| (wall.id, wall.clone())).collect()
+            )),
             projectiles_to_add: Arc::new(SegQueue::new()),
             global_game_events: Arc::new(PriorityEventQueue::new()),
             active_connections: Arc::new(DashMap::new()),
@@ -416,7 +752,9 @@ impl MassiveGameServer {
             client_states_map,
             chat_messages_queue,
             is_shutting_down: Arc::new(AtomicBool::new(false)),
-            match_info: Arc::new(ParkingLotRwLock::new(ServerMatchInfo::default())),
+            match_info: Arc::new(ParkingLotRwLock::new(initial_match_info)),
+            next_match_rules: Arc::new(ParkingLotRwLock::new(match_rules)),
+            game_mode,
             kill_feed: Arc::new(ParkingLotRwLock::new(VecDeque::with_capacity(MAX_KILL_FEED_HISTORY + 5))),
             destroyed_wall_ids_this_tick: Arc::new(ParkingLotRwLock::new(HashSet::new())),
             updated_walls_this_tick: Arc::new(ParkingLotRwLock::new(HashMap::new())),
@@ -428,6 +766,19 @@ impl MassiveGameServer {
             bot_name_counter: Arc::new(AtomicU64::new(0)),
             last_broadcast_frame: Arc::new(AtomicU64::new(0)),
             player_last_sync_positions: Arc::new(DashMap::new()),
+            session_manager: Arc::new(SessionManager::new()),
+            client_latencies: Arc::new(DashMap::new()),
+            snapshot_baselines: Arc::new(DashMap::new()),
+            client_priorities: Arc::new(DashMap::new()),
+            hitbox_history: Arc::new(ParkingLotRwLock::new(HitboxHistory::new())),
+            kill_cam_history: Arc::new(ParkingLotRwLock::new(KillCamHistory::new())),
+            spectators: Arc::new(DashMap::new()),
+            caster_seats: Arc::new(DashMap::new()),
+            caster_feed: Arc::new(ParkingLotRwLock::new(caster_feed)),
+            word_filter,
+            ability_activations: Arc::new(SegQueue::new()),
+            deployed_covers: Arc::new(DashMap::new()),
+            revealed_players: Arc::new(DashMap::new()),
         };
 
         info!("MassiveGameServer initialized successfully.");
@@ -443,6 +794,8 @@ impl MassiveGameServer {
             CorePickupType::WeaponCrate(ServerWeaponType::Rifle),
             CorePickupType::SpeedBoost, CorePickupType::DamageBoost, CorePickupType::Shield,
             CorePickupType::WeaponCrate(ServerWeaponType::Sniper),
+            CorePickupType::WeaponCrate(ServerWeaponType::RocketLauncher),
+            CorePickupType::WeaponCrate(ServerWeaponType::GrenadeLauncher),
         ];
 
         let strategic_locations = [
@@ -453,6 +806,9 @@ impl MassiveGameServer {
             Vec2::new(WORLD_MAX_X / 2.0, WORLD_MAX_Y / 2.0),
             Vec2::new(WORLD_MIN_X + 250.0, 0.0),
             Vec2::new(WORLD_MAX_X - 250.0, 0.0),
+            Vec2::new(0.0, WORLD_MIN_Y + 150.0),
+            Vec2::new(0.0, WORLD_MAX_Y - 150.0),
+            Vec2::new(WORLD_MIN_X / 2.0, 0.0),
         ];
 
         let num_pickups_to_spawn = strategic_locations.len().min(pickup_types.len());
@@ -488,56 +844,29 @@ impl MassiveGameServer {
     pub fn spawn_initial_bots(&self, count: usize) {
         info!("Spawning {} initial bots...", count);
         // No longer reducing count here - use what's passed in
-        let team_spawn_areas = MapGenerator::get_team_spawn_areas();
         let mut rng = rand::thread_rng();
 
-        for i in 0..count {
+        for _ in 0..count {
             let bot_name_num = self.bot_name_counter.fetch_add(1, AtomicOrdering::SeqCst);
             let bot_names = ["Alpha", "Beta", "Gamma", "Delta", "Echo", "Foxtrot", "Golf", "Hotel", "India", "Juliet", "Kilo", "Lima", "Mike", "November", "Oscar", "Papa", "Quebec", "Romeo", "Sierra", "Tango", "Uniform", "Victor", "Whiskey", "Xray", "Yankee", "Zulu"];
             let bot_name = format!("Bot {}", bot_names.get(bot_name_num as usize % bot_names.len()).unwrap_or(&"X"));
             let bot_player_id_str = format!("bot_{}", Uuid::new_v4());
 
-            let team_id = (i % 2) + 1;
-
-            let potential_spawns_for_team: Vec<Vec2> = team_spawn_areas.iter()
-                .filter(|(_, sp_team_id)| *sp_team_id == team_id as u8)
-                .map(|(pos, _)| *pos)
-                .collect();
-
-            let spawn_pos = if !potential_spawns_for_team.is_empty() {
-                // Use team spawn point with some random offset
-                let base_spawn = potential_spawns_for_team[rng.gen_range(0..potential_spawns_for_team.len())];
-                let offset_radius = 50.0; // Small offset to prevent stacking
-                let angle = rng.gen_range(0.0..2.0 * std::f32::consts::PI);
-                let offset_x = offset_radius * angle.cos();
-                let offset_y = offset_radius * angle.sin();
-                Vec2::new(
-                    (base_spawn.x + offset_x).clamp(WORLD_MIN_X + PLAYER_RADIUS, WORLD_MAX_X - PLAYER_RADIUS),
-                    (base_spawn.y + offset_y).clamp(WORLD_MIN_Y + PLAYER_RADIUS, WORLD_MAX_Y - PLAYER_RADIUS)
-                )
-            } else {
-                // Fallback: use respawn manager
-                self.respawn_manager.get_respawn_position(self, &Arc::new(bot_player_id_str.clone()), Some(team_id as u8), &[])
-            };
-
-            if let Some(player_id_arc) = self.player_manager.add_player(bot_player_id_str.clone(), bot_name.clone(), spawn_pos.x, spawn_pos.y) {
-                if let Some(mut p_state) = self.player_manager.get_player_state_mut(&player_id_arc) {
-                    p_state.team_id = team_id as u8;
-                }
-
-            let bot_controller = BotController {
-                player_id: player_id_arc.clone(),
-                target_position: None,
-                target_enemy_id: None,
-                last_decision_time: Instant::now(),
-                behavior_state: BotBehaviorState::Idle,
-                current_path: VecDeque::new(),
-                path_recalculation_timer: Instant::now(),
-                last_position: Vec2::new(spawn_pos.x, spawn_pos.y),
-                stuck_timer: 0.0,
-                stuck_check_position: Vec2::new(spawn_pos.x, spawn_pos.y),
-            };
-                self.bot_players.insert(player_id_arc, bot_controller);
+            let class = PlayerClass::ALL[rng.gen_range(0..PlayerClass::ALL.len())];
+            if let Some((player_id, team_id, spawn_pos)) = self.spawn_new_player(bot_player_id_str.clone(), bot_name.clone(), class) {
+                let bot_controller = BotController {
+                    player_id: player_id.clone(),
+                    target_position: None,
+                    target_enemy_id: None,
+                    last_decision_time: Instant::now(),
+                    behavior_state: BotBehaviorState::Idle,
+                    current_path: VecDeque::new(),
+                    path_recalculation_timer: Instant::now(),
+                    last_position: spawn_pos,
+                    stuck_timer: 0.0,
+                    stuck_check_position: spawn_pos,
+                };
+                self.bot_players.insert(player_id, bot_controller);
                 debug!("Spawned bot: {} (ID: {}) on team {} at ({:.1}, {:.1})", bot_name, bot_player_id_str, team_id, spawn_pos.x, spawn_pos.y);
             } else {
                 error!("Failed to add bot {} to player manager.", bot_name);
@@ -549,76 +878,47 @@ impl MassiveGameServer {
         if !player_state.alive {
             player_state.velocity_x = 0.0;
             player_state.velocity_y = 0.0;
+            player_state.pending_movement = None;
             return;
         }
 
-        if input.sequence <= player_state.last_processed_input_sequence && input.sequence != 0 {
-            // warn!("[{}]: Received out-of-order or duplicate input (seq: {}, last_processed: {}). Ignoring.", player_state.id, input.sequence, player_state.last_processed_input_sequence);
-            return;
+        // The input buffer already drops stale inputs; repeated ones carry the last sequence and don't advance it.
+        if input.sequence > player_state.last_processed_input_sequence {
+            player_state.last_processed_input_sequence = input.sequence;
         }
-        player_state.last_processed_input_sequence = input.sequence;
-        player_state.mark_field_changed(FIELD_POSITION_ROTATION);
-
-        // Calculate movement relative to player rotation
-        let mut forward_intent = 0.0_f32;
-        let mut strafe_intent = 0.0_f32;
 
-        if input.move_forward { forward_intent += 1.0; }
-        if input.move_backward { forward_intent -= 1.0; }
-        if input.move_left { strafe_intent -= 1.0; }
-        if input.move_right { strafe_intent += 1.0; }
-
-        let effective_speed = if player_state.speed_boost_remaining > 0.0 { PLAYER_BASE_SPEED * MAX_PLAYER_SPEED_MULTIPLIER } else { PLAYER_BASE_SPEED };
-
-        if forward_intent != 0.0 || strafe_intent != 0.0 {
-            // Normalize movement vector
-            let move_magnitude = (forward_intent * forward_intent + strafe_intent * strafe_intent).sqrt();
-            forward_intent /= move_magnitude;
-            strafe_intent /= move_magnitude;
-            
-            // Apply rotation to movement direction
-            let cos_rot = player_state.rotation.cos();
-            let sin_rot = player_state.rotation.sin();
-            
-            // Forward movement in the direction of rotation
-            let forward_x = cos_rot * forward_intent;
-            let forward_y = sin_rot * forward_intent;
-            
-            // Strafe movement perpendicular to rotation (90 degrees)
-            let strafe_x = -sin_rot * strafe_intent;
-            let strafe_y = cos_rot * strafe_intent;
-            
-            // Combine forward and strafe movement
-            player_state.velocity_x = (forward_x + strafe_x) * effective_speed;
-            player_state.velocity_y = (forward_y + strafe_y) * effective_speed;
-            
-            // Debug logging for bot movement
-            if player_state.username.starts_with("Bot") {
-                trace!("Bot {} velocity set to ({:.1}, {:.1}) from input forward={:.1} strafe={:.1} rot={:.2}", 
-                    player_state.username, player_state.velocity_x, player_state.velocity_y, forward_intent, strafe_intent, player_state.rotation);
-            }
-        } else {
-            player_state.velocity_x = 0.0;
-            player_state.velocity_y = 0.0;
+        // Movement is stepped with the shared rules clients predict with, in the physics update.
+        // Rotation takes effect now so this tick's shots go where the player aims.
+        let stunned = player_state.effects.modifiers().stunned;
+        let mut movement_input = movement_input_of(input, player_state);
+        if self.game_mode.speed_boosted(player_state.team_id) && !stunned {
+            movement_input.speed_multiplier = movement_input.speed_multiplier.max(INFECTED_SPEED_MULTIPLIER);
         }
+        player_state.rotation = movement_input.rotation;
+        player_state.pending_movement = Some(movement_input);
         player_state.mark_field_changed(FIELD_POSITION_ROTATION);
 
-        if (input.rotation - player_state.rotation).abs() > 0.001 {
-            player_state.rotation = input.rotation;
-            player_state.mark_field_changed(FIELD_POSITION_ROTATION);
-        }
-
         // Shooting logic for firearms
-        if input.shooting && player_state.weapon != ServerWeaponType::Melee && player_state.can_shoot(current_server_time) {
+        let weapon_allowed = self.game_mode.allows_weapon(player_state.team_id, player_state.weapon);
+        if input.shooting && weapon_allowed && !stunned && player_state.weapon != ServerWeaponType::Melee && player_state.can_shoot(current_server_time) {
             player_state.last_shot_time = Some(current_server_time);
             player_state.ammo -= 1;
             player_state.mark_field_changed(FIELD_WEAPON_AMMO);
+            player_state.match_stats.shots_fired += match player_state.weapon {
+                ServerWeaponType::Shotgun => SHOTGUN_PELLET_COUNT as u32,
+                _ => 1,
+            };
 
             let spawn_offset = PLAYER_RADIUS + 5.0;
             let proj_spawn_x = player_state.x + player_state.rotation.cos() * spawn_offset;
             let proj_spawn_y = player_state.y + player_state.rotation.sin() * spawn_offset;
 
-            let damage_multiplier = if player_state.damage_boost_remaining > 0.0 { 1.5 } else { 1.0 };
+            let damage_multiplier = player_state.effects.modifiers().damage_dealt;
+            let rewind_ms = compute_rewind_ms(
+                self.get_server_timestamp(),
+                input.timestamp,
+                self.client_latencies.get(player_state.id.as_str()).as_deref(),
+            );
 
             self.global_game_events.push(
                 GameEvent::WeaponFired { player_id: player_state.id.clone(), weapon: player_state.weapon, position: Vec2{x: proj_spawn_x, y: proj_spawn_y}},
@@ -631,30 +931,34 @@ impl MassiveGameServer {
                         let angle_offset = SHOTGUN_SPREAD_ANGLE_RAD * (2.0 * (rand::random::<f32>()) - 1.0); // Simplified spread
                         let dir_x = player_state.rotation.cos() * angle_offset.cos() - player_state.rotation.sin() * angle_offset.sin();
                         let dir_y = player_state.rotation.sin() * angle_offset.cos() + player_state.rotation.cos() * angle_offset.sin();
-                        self.projectiles_to_add.push(Projectile::new(
+                        let mut pellet = Projectile::new(
                             player_state.id.clone(),
                             player_state.weapon,
                             proj_spawn_x, proj_spawn_y,
                             dir_x, dir_y,
                             damage_multiplier,
-                        ));
+                        );
+                        pellet.rewind_ms = rewind_ms;
+                        self.projectiles_to_add.push(pellet);
                     }
                 }
                 // ServerWeaponType::Melee is handled by the separate melee_attack check below
                 _ => { // Pistol, Rifle, Sniper
-                    self.projectiles_to_add.push(Projectile::new(
+                    let mut projectile = Projectile::new(
                         player_state.id.clone(),
                         player_state.weapon,
                         proj_spawn_x, proj_spawn_y,
                         player_state.rotation.cos(), player_state.rotation.sin(),
                         damage_multiplier,
-                    ));
+                    );
+                    projectile.rewind_ms = rewind_ms;
+                    self.projectiles_to_add.push(projectile);
                 }
             }
         }
 
         // Melee Attack Logic (V key)
-        if input.melee_attack && player_state.can_shoot(current_server_time) { // Using can_shoot for cooldown & alive check
+        if input.melee_attack && !stunned && player_state.can_shoot(current_server_time) { // Using can_shoot for cooldown & alive check
             player_state.last_shot_time = Some(current_server_time); // Apply melee cooldown
 
             // Position for the melee event (e.g., slightly in front of the player)
@@ -676,22 +980,24 @@ impl MassiveGameServer {
             player_state.start_reload(current_server_time);
         }
 
+        // Paid for now so a repeated input can't use it twice; it takes effect in the logic update
+        if input.use_ability_slot != 0 && !stunned {
+            if let Some(ability) = player_state.abilities.activate(input.use_ability_slot, ABILITY_LOCKOUT_SECS) {
+                player_state.mark_field_changed(FIELD_ABILITIES);
+                self.ability_activations.push(AbilityActivation {
+                    player_id: player_state.id.clone(),
+                    slot: input.use_ability_slot,
+                    ability,
+                });
+            }
+        }
+
         if input.change_weapon_slot != 0 {
-            let new_weapon = match input.change_weapon_slot {
-                1 => Some(ServerWeaponType::Pistol),
-                2 => Some(ServerWeaponType::Shotgun),
-                3 => Some(ServerWeaponType::Rifle),
-                4 => Some(ServerWeaponType::Sniper),
-                5 => Some(ServerWeaponType::Melee),
-                _ => None,
-            };
+            // Only owned weapons, and the mode may restrict a team's weapons further
+            let new_weapon = weapon_in_slot(input.change_weapon_slot)
+                .filter(|weapon| self.game_mode.allows_weapon(player_state.team_id, *weapon));
             if let Some(weapon) = new_weapon {
-                if player_state.weapon != weapon {
-                    player_state.weapon = weapon;
-                    player_state.ammo = PlayerState::get_max_ammo_for_weapon(weapon);
-                    player_state.reload_progress = None;
-                    player_state.mark_field_changed(FIELD_WEAPON_AMMO);
-                }
+                player_state.equip_weapon(weapon);
             }
         }
     }
@@ -699,24 +1005,53 @@ impl MassiveGameServer {
     pub async fn process_network_input(&self) {
         let current_server_time = Instant::now();
         
-        // First, collect all player inputs with their IDs
+        // First, take this tick's input for every player. Remote players play back one
+        // buffered input per tick; bots generate theirs locally, so there is no jitter to absorb.
         let mut all_inputs = Vec::new();
+        let mut buffered_players = 0usize;
+        let mut total_depth = 0usize;
+        let mut max_depth = 0usize;
+        let mut underruns = 0u64;
         self.player_manager.for_each_player_mut(|player_id, player_state| {
             player_state.clear_changed_fields();
-            let inputs: Vec<PlayerInputData> = player_state.input_queue.drain(..).collect();
-            if !inputs.is_empty() {
-                all_inputs.push((player_id.clone(), inputs));
+            if self.bot_players.contains_key(player_id) {
+                for input in player_state.input_buffer.drain() {
+                    all_inputs.push((player_id.clone(), input));
+                }
+                return;
             }
+
+            let underruns_before = player_state.input_buffer.underruns();
+            if let Some(buffered) = player_state.input_buffer.next_input() {
+                all_inputs.push((player_id.clone(), buffered.input));
+            }
+            underruns += player_state.input_buffer.underruns() - underruns_before;
+            let depth = player_state.input_buffer.depth();
+            buffered_players += 1;
+            total_depth += depth;
+            max_depth = max_depth.max(depth);
         });
         
-        // Then process each player's inputs
-        for (player_id, inputs) in all_inputs {
-            if let Some(mut player_state_entry) = self.player_manager.get_player_state_mut(&player_id) {
-                for input in inputs {
-                    self.apply_input_to_player_state(&mut *player_state_entry, &input, current_server_time);
+        // Then process each player's inputs. While the mode locks input, players may only look around.
+        let input_locked = self.game_mode.input_locked(&self.match_info.read());
+        for (player_id, input) in all_inputs {
+            let input = if input_locked {
+                PlayerInputData {
+                    move_forward: false, move_backward: false, move_left: false, move_right: false,
+                    shooting: false, reload: false, melee_attack: false, change_weapon_slot: 0, use_ability_slot: 0,
+                    ..input
                 }
+            } else {
+                input
+            };
+            if let Some(mut player_state_entry) = self.player_manager.get_player_state_mut(&player_id) {
+                self.apply_input_to_player_state(&mut player_state_entry, &input, current_server_time);
             }
         }
+        
+        if buffered_players > 0 {
+            server_metrics::update_input_buffer_summary(total_depth as f64 / buffered_players as f64, max_depth, underruns);
+        }
     }
 
     pub async fn run_ai_update(&self) {
@@ -775,7 +1110,7 @@ impl MassiveGameServer {
         
         // Stage 5: Process Projectiles
         let projectiles_start = Instant::now();
-        let projectile_results = self.process_projectiles_optimized(&active_walls, delta_time).await; // 
+        let projectile_results = self.process_projectiles_optimized(delta_time).await; // 
         // CHANGED to debug!
         debug!("Frame {}: Processed {} projectiles, {} hits, {} removed (took {:?})", frame, projectile_results.total_processed, projectile_results.hits.len(), projectile_results.to_remove.len(), projectiles_start.elapsed());
     
@@ -993,18 +1328,19 @@ fn collect_active_walls_optimized(&self) -> Vec<Wall> {
     }
 
     pub(crate) async fn send_chat_messages_static(
-        _peer_id_str: &str,
+        peer_id_str: &str,
         data_channel: &Arc<crate::core::types::RTCDataChannel>,
         client_state: &mut ClientState,
         chat_messages: &[ChatMessage],
-        _chat_messages_queue: &ChatMessagesQueue,
+        player_manager: &ImprovedPlayerManager,
     ) {
         let last_seq_sent = client_state.last_chat_message_seq_sent;
         let mut max_seq_in_batch = last_seq_sent;
+        let recipient = ChatRecipient::lookup(player_manager, peer_id_str);
         
         let messages_to_send: Vec<&ChatMessage> = chat_messages
             .iter()
-            .filter(|msg| msg.seq > last_seq_sent)
+            .filter(|msg| msg.seq > last_seq_sent && recipient.can_see(msg))
             .take(10) // Limit messages per update
             .collect();
         
@@ -1021,6 +1357,7 @@ fn collect_active_walls_optimized(&self) -> Vec<Wall> {
                 username: Some(username_fb),
                 message: Some(message_fb),
                 timestamp: chat_entry.timestamp,
+                scope: chat_entry.scope.to_fb(),
             });
             
             let game_message_offset = fb::GameMessage::create(&mut chat_builder, &fb::GameMessageArgs {
@@ -1170,8 +1507,12 @@ fn collect_active_walls_optimized(&self) -> Vec<Wall> {
                 total_alive += 1;
                 // Process movement with optimized collision
                 self.process_player_movement_optimized(player_state, &wall_arc, delta_time);
-            } else if player_state.respawn_timer == Some(0.0) {
-                all_to_respawn.push((player_id.clone(), player_state.team_id));
+            } else {
+                // Input buffered before dying mustn't move the player once respawned
+                player_state.pending_movement = None;
+                if player_state.respawn_timer == Some(0.0) {
+                    all_to_respawn.push((player_id.clone(), player_state.team_id));
+                }
             }
         });
         
@@ -1191,51 +1532,26 @@ fn collect_active_walls_optimized(&self) -> Vec<Wall> {
                 player_state.username, old_x, old_y, player_state.velocity_x, player_state.velocity_y, delta_time);
         }
         
-        // Apply velocity
-        player_state.x += player_state.velocity_x * delta_time;
-        player_state.y += player_state.velocity_y * delta_time;
+        // Walls the player can reach this tick, at up to the speed this tick's input may set
+        let pending_movement = player_state.pending_movement.take();
+        let input_speed = pending_movement.map_or(0.0, |input| PLAYER_BASE_SPEED * input.speed_multiplier.max(0.0) * std::f32::consts::SQRT_2);
+        let speed = (player_state.velocity_x.abs() + player_state.velocity_y.abs()).max(input_speed);
+        let check_radius = PLAYER_RADIUS + speed * delta_time + 10.0;
+        let nearby_walls = wall_rects(&self.wall_spatial_index.query_radius(old_x, old_y, check_radius));
+        
+        // The same step clients predict with; without a new input the player keeps its velocity
+        let state = movement_state_of(player_state);
+        let movement = match pending_movement {
+            Some(input) => step_movement(&state, &input, &nearby_walls, delta_time),
+            None => integrate_movement(&state, &nearby_walls, delta_time),
+        };
+        apply_movement_state(player_state, &movement);
         
         // Log position after velocity application
         if player_state.username.starts_with("Bot") && (old_x != player_state.x || old_y != player_state.y) {
             trace!("Bot {} moved to ({:.1},{:.1})", player_state.username, player_state.x, player_state.y);
         }
         
-        // Quick bounds check first
-        let half_radius = PLAYER_RADIUS;
-        if player_state.x < WORLD_MIN_X + half_radius || 
-           player_state.x > WORLD_MAX_X - half_radius ||
-           player_state.y < WORLD_MIN_Y + half_radius || 
-           player_state.y > WORLD_MAX_Y - half_radius {
-            
-            player_state.x = player_state.x.clamp(WORLD_MIN_X + half_radius, WORLD_MAX_X - half_radius);
-            player_state.y = player_state.y.clamp(WORLD_MIN_Y + half_radius, WORLD_MAX_Y - half_radius);
-            player_state.velocity_x = 0.0;
-            player_state.velocity_y = 0.0;
-            player_state.mark_field_changed(FIELD_POSITION_ROTATION);
-            return;
-        }
-        
-        // Use spatial index to query nearby walls
-        let check_radius = PLAYER_RADIUS + 10.0; // Reduced from 50.0 since spatial index is efficient
-        let nearby_walls = self.wall_spatial_index.query_radius(player_state.x, player_state.y, check_radius);
-        
-        // Check collision with nearby walls only
-        for wall in nearby_walls.iter() {
-            let closest_x = player_state.x.clamp(wall.x, wall.x + wall.width);
-            let closest_y = player_state.y.clamp(wall.y, wall.y + wall.height);
-            
-            let dist_sq = (player_state.x - closest_x).powi(2) + (player_state.y - closest_y).powi(2);
-            if dist_sq < PLAYER_RADIUS.powi(2) {
-                // Collision detected - revert position
-                player_state.x = old_x;
-                player_state.y = old_y;
-                player_state.velocity_x = 0.0;
-                player_state.velocity_y = 0.0;
-                player_state.mark_field_changed(FIELD_POSITION_ROTATION);
-                return;
-            }
-        }
-        
         // Anti-cheat validation
         let max_dist = PLAYER_BASE_SPEED * MAX_PLAYER_SPEED_MULTIPLIER * delta_time + MAX_POSITION_DELTA_SLACK;
         let actual_dist = ((player_state.x - player_state.last_valid_position.0).powi(2) + 
@@ -1328,7 +1644,11 @@ fn collect_active_walls_optimized(&self) -> Vec<Wall> {
                                     proj.owner_id.clone(),
                                     target_id.clone(),
                                     proj.damage,
-                                    proj.weapon_type
+                                    proj.weapon_type,
+                                    KillShot {
+                                        origin: Vec2::new(proj.origin_x, proj.origin_y),
+                                        impact: Vec2::new(proj.x, proj.y),
+                                    },
                                 ));
                                 results.to_remove.push(idx);
                                 break;
@@ -1354,31 +1674,51 @@ fn collect_active_walls_optimized(&self) -> Vec<Wall> {
     }*/
     
     async fn apply_player_updates(&self, updates: PlayerPhysicsResults) {
+        // Some modes keep the dead out for the rest of the match or round
+        if !self.game_mode.respawns_allowed(&self.match_info.read()) {
+            return;
+        }
         // Batch respawns
         for (player_id, team_id) in updates.players_to_respawn {
-            let enemies = self.get_enemy_positions_for_team(team_id);
-            let spawn_pos = self.respawn_manager.get_respawn_position(
-                self,
-                &player_id,
-                Some(team_id),
-                &enemies
-            );
-            
-            if let Some(mut p_state) = self.player_manager.get_player_state_mut(&player_id) {
-                p_state.respawn(spawn_pos.x, spawn_pos.y);
-                self.global_game_events.push(
-                    GameEvent::PlayerJoined { player_id: player_id.clone() },
-                    EventPriority::High
-                );
+            self.respawn_player(&player_id, team_id);
+        }
+    }
+
+    fn respawn_player(&self, player_id: &PlayerID, team_id: u8) {
+        let enemies = self.get_enemy_positions_for_team(team_id);
+        let spawn_pos = self.respawn_manager.get_respawn_position(
+            self,
+            player_id,
+            self.spawn_team(team_id),
+            &enemies
+        );
+
+        if let Some(mut p_state) = self.player_manager.get_player_state_mut(player_id) {
+            p_state.respawn(spawn_pos.x, spawn_pos.y, &self.config.spawn_loadout);
+            if let Some(weapon) = self.game_mode.spawn_weapon(team_id) {
+                p_state.grant_weapon(weapon, 0);
+                p_state.equip_weapon(weapon);
             }
+            self.global_game_events.push(
+                GameEvent::PlayerJoined { player_id: player_id.clone() },
+                EventPriority::High
+            );
+        }
+    }
+
+    /// Puts every player, alive or dead, back at a spawn with a fresh life.
+    fn respawn_all_players(&self) {
+        let mut players = Vec::new();
+        self.player_manager.for_each_player(|player_id, state| players.push((player_id.clone(), state.team_id)));
+        for (player_id, team_id) in players {
+            self.respawn_player(&player_id, team_id);
         }
     }
 
     // In massive_game_server/server/src/server/instance.rs
 
-    async fn process_projectiles_optimized(&self, walls: &[Wall], delta_time: f32) -> ProjectileResults {
+    async fn process_projectiles_optimized(&self, delta_time: f32) -> ProjectileResults {
         use rayon::prelude::*;
-        use std::sync::Mutex;
         
         let frame = self.frame_counter.load(AtomicOrdering::Relaxed);
         trace!("[Frame {}] Starting optimized projectile processing", frame);
@@ -1392,162 +1732,172 @@ fn collect_active_walls_optimized(&self) -> Vec<Wall> {
         let total_projectiles = all_projectiles.len();
         trace!("[Frame {}] Processing {} projectiles", frame, total_projectiles);
         
-        // Shared results that will be updated by parallel workers
-        let hits = Arc::new(Mutex::new(Vec::new()));
-        let wall_hits = Arc::new(Mutex::new(Vec::new()));
-        let spatial_updates = Arc::new(Mutex::new(Vec::new()));
+        // Hitbox history for lag-compensated hit tests
+        let hitbox_history = self.hitbox_history.read();
+        let now_ms = self.get_server_timestamp();
         
         // Process projectiles in parallel chunks
         let chunk_size = 50.max(total_projectiles / rayon::current_num_threads());
         
-        let to_remove: Vec<usize> = all_projectiles
+        // Each chunk keeps its own results so they can be joined back in projectile order; hits
+        // applied in whatever order the workers finished would make kills depend on scheduling.
+        let chunk_results: Vec<_> = all_projectiles
             .par_chunks_mut(chunk_size)
             .enumerate()
-            .flat_map(|(chunk_idx, chunk)| {
-                let mut chunk_to_remove = Vec::new();
+            .map(|(chunk_idx, chunk)| {
+                let mut chunk_results = ProjectileResults::default();
+                let mut chunk_spatial_updates = Vec::new();
                 let chunk_start_idx = chunk_idx * chunk_size;
                 
                 for (local_idx, proj) in chunk.iter_mut().enumerate() {
                     let global_idx = chunk_start_idx + local_idx;
                     
-                    // Update position
-                    let old_x = proj.x;
-                    let old_y = proj.y;
-                    proj.x += proj.velocity_x * delta_time;
-                    proj.y += proj.velocity_y * delta_time;
-                    
-                    // Collect spatial update (will be applied after parallel phase)
-                    spatial_updates.lock().unwrap().push((proj.id, proj.x, proj.y));
-                    
-                    // Check bounds
-                    if proj.x < WORLD_MIN_X || proj.x > WORLD_MAX_X || 
-                    proj.y < WORLD_MIN_Y || proj.y > WORLD_MAX_Y {
-                        chunk_to_remove.push(global_idx);
-                        continue;
-                    }
-                    
-                    // Check lifetime
-                    if proj.should_remove() {
-                        chunk_to_remove.push(global_idx);
-                        continue;
+                    let explosive = explosive_spec(proj.weapon_type);
+                    let end = Vec2::new(proj.x + proj.velocity_x * delta_time, proj.y + proj.velocity_y * delta_time);
+                    let path_length = (end.x - proj.x).hypot(end.y - proj.y);
+
+                    // Grenades roll past players and wait for their fuse
+                    let targets: Vec<(PlayerID, Vec2)> = if explosive.is_none_or(|spec| spec.detonate_on_impact) {
+                        // Targets may have moved since the shooter's view time, so widen the query by how far they could have gone.
+                        let rewind_secs = proj.rewind_ms as f32 / 1000.0;
+                        let rewind_margin = PLAYER_BASE_SPEED * MAX_PLAYER_SPEED_MULTIPLIER * rewind_secs;
+                        let view_time_ms = now_ms.saturating_sub(proj.rewind_ms as u64);
+                        self.spatial_index.query_nearby_players(proj.x, proj.y, path_length + PLAYER_RADIUS + rewind_margin)
+                            .into_iter()
+                            .filter(|target_id| *target_id != proj.owner_id) // Can't hit yourself
+                            .filter_map(|target_id| {
+                                let target_state = self.player_manager.get_player_state(&target_id).filter(|p| p.alive)?;
+                                // Test against where the shooter saw the target, not where it is now
+                                let (target_x, target_y) = if proj.rewind_ms > 0 {
+                                    hitbox_history.position_at(&target_id, view_time_ms)
+                                        .unwrap_or((target_state.x, target_state.y))
+                                } else {
+                                    (target_state.x, target_state.y)
+                                };
+                                drop(target_state);
+                                Some((target_id, Vec2::new(target_x, target_y)))
+                            })
+                            .collect()
+                    } else {
+                        Vec::new()
+                    };
+
+                    if !self.config.projectile_ricochet {
+                        proj.ricochets_left = 0;
                     }
-                    
-                    // Optimize wall collision by checking partition
-                    let partition_idx = self.world_partition_manager.get_partition_index_for_point(proj.x, proj.y);
-                    
-                    if let Some(partition) = self.world_partition_manager.get_partition(partition_idx) {
-                        let mut hit_wall = false;
-                        
-                        // Use ray casting for better collision detection
-                        let ray_length = ((proj.x - old_x).powi(2) + (proj.y - old_y).powi(2)).sqrt();
-                        let ray_steps = (ray_length / 5.0).ceil() as usize; // Check every 5 units
-                        
-                        'wall_check: for step in 0..=ray_steps {
-                            let t = step as f32 / ray_steps.max(1) as f32;
-                            let check_x = old_x + (proj.x - old_x) * t;
-                            let check_y = old_y + (proj.y - old_y) * t;
-                            
-                            // Check walls in current position
-                            for wall_entry in partition.all_walls_in_partition.iter() {
-                                let wall = wall_entry.value();
-                                
-                                // Skip destroyed destructible walls
-                                if wall.is_destructible && wall.current_health <= 0 {
-                                    continue;
-                                }
-                                
-                                // Projectile-wall collision
-                                if check_x >= wall.x && check_x <= wall.x + wall.width &&
-                                check_y >= wall.y && check_y <= wall.y + wall.height {
-                                    
-                                    // Set projectile position to collision point
-                                    proj.x = check_x;
-                                    proj.y = check_y;
-                                    
-                                    if wall.is_destructible {
-                                        wall_hits.lock().unwrap().push((wall.id, proj.damage));
-                                        
-                                        // Generate impact event
-                                        self.global_game_events.push(
-                                            GameEvent::WallImpact {
-                                                position: Vec2::new(check_x, check_y),
-                                                wall_id: wall.id,
-                                                damage: proj.damage,
-                                            },
-                                            EventPriority::Normal
-                                        );
-                                    }
-                                    
-                                    chunk_to_remove.push(global_idx);
-                                    hit_wall = true;
-                                    break 'wall_check;
+                    let mut shot = shot_of(proj);
+                    let trace = trace_shot(
+                        &mut shot,
+                        &ballistic_spec(proj.weapon_type),
+                        end,
+                        |from, to| self.wall_spatial_index.query_line_segment(from.x, from.y, to.x, to.y),
+                        &targets,
+                    );
+                    apply_shot(proj, &shot);
+
+                    let mut spent = false;
+                    for contact in trace.contacts {
+                        match contact {
+                            ShotContact::Player { target_id, point, damage } => {
+                                if explosive.is_some() {
+                                    chunk_results.detonations.push(explosion_of(proj, point));
+                                } else {
+                                    debug!(
+                                        target: "lag_compensation",
+                                        "Hit: shooter={} target={} weapon={:?} rewind={}ms impact=({:.1}, {:.1})",
+                                        proj.owner_id, target_id, proj.weapon_type, proj.rewind_ms, point.x, point.y
+                                    );
+                                    chunk_results.hits.push((
+                                        proj.owner_id.clone(),
+                                        target_id,
+                                        damage,
+                                        proj.weapon_type,
+                                        KillShot {
+                                            origin: Vec2::new(proj.origin_x, proj.origin_y),
+                                            impact: point,
+                                        },
+                                    ));
                                 }
+                                spent = true;
                             }
-                        }
-                        
-                        if !hit_wall {
-                            // Check player collisions using spatial index
-                            let nearby_players = self.spatial_index.query_nearby_players(
-                                proj.x, 
-                                proj.y, 
-                                PLAYER_RADIUS + 20.0 // Small buffer for fast projectiles
-                            );
-                            
-                            for target_id in nearby_players {
-                                if target_id == proj.owner_id {
-                                    continue; // Can't hit yourself
-                                }
-                                
-                                if let Some(target_state) = self.player_manager.get_player_state(&target_id) {
-                                    if !target_state.alive {
-                                        continue;
-                                    }
-                                    
-                                    // More accurate collision using ray casting
-                                    let mut hit = false;
-                                    for step in 0..=ray_steps {
-                                        let t = step as f32 / ray_steps.max(1) as f32;
-                                        let check_x = old_x + (proj.x - old_x) * t;
-                                        let check_y = old_y + (proj.y - old_y) * t;
-                                        
-                                        let dx = target_state.x - check_x;
-                                        let dy = target_state.y - check_y;
-                                        let dist_sq = dx * dx + dy * dy;
-                                        
-                                        if dist_sq <= PLAYER_RADIUS * PLAYER_RADIUS {
-                                            proj.x = check_x;
-                                            proj.y = check_y;
-                                            hit = true;
-                                            break;
-                                        }
-                                    }
-                                    
-                                    if hit {
-                                        hits.lock().unwrap().push((
-                                            proj.owner_id.clone(),
-                                            target_id.clone(),
-                                            proj.damage,
-                                            proj.weapon_type
-                                        ));
-                                        chunk_to_remove.push(global_idx);
-                                        break;
+                            ShotContact::Wall { wall_id, point, damage, is_destructible } => {
+                                if let Some(spec) = explosive {
+                                    // Explosives stop just short of the wall, so it doesn't shelter the blast from itself
+                                    let speed = proj.velocity_x.hypot(proj.velocity_y).max(f32::EPSILON);
+                                    proj.x = point.x - proj.velocity_x / speed * EXPLOSIVE_WALL_STANDOFF;
+                                    proj.y = point.y - proj.velocity_y / speed * EXPLOSIVE_WALL_STANDOFF;
+                                    if spec.detonate_on_impact {
+                                        chunk_results.detonations.push(explosion_of(proj, Vec2::new(proj.x, proj.y)));
+                                        spent = true;
+                                    } else {
+                                        proj.velocity_x = 0.0;
+                                        proj.velocity_y = 0.0;
                                     }
+                                    continue;
+                                }
+                                if is_destructible {
+                                    chunk_results.wall_hits.push((wall_id, damage));
+                                    self.global_game_events.push(GameEvent::WallImpact { position: point, wall_id, damage }, EventPriority::Normal);
                                 }
+                                spent = true;
                             }
+                            ShotContact::Penetrated { wall_id, point, damage } => {
+                                chunk_results.wall_hits.push((wall_id, damage));
+                                self.global_game_events.push(GameEvent::WallImpact { position: point, wall_id, damage }, EventPriority::Normal);
+                            }
+                            ShotContact::Ricochet { wall_id, point } => {
+                                self.global_game_events.push(
+                                    GameEvent::ProjectileHitWall { projectile_id: proj.id, wall_id, position: point },
+                                    EventPriority::Normal,
+                                );
+                            }
+                        }
+                    }
+
+                    // Collect spatial update (will be applied after parallel phase)
+                    chunk_spatial_updates.push((proj.id, proj.x, proj.y));
+                    if spent {
+                        chunk_results.to_remove.push(global_idx);
+                        continue;
+                    }
+
+                    // Check bounds
+                    if proj.x < WORLD_MIN_X || proj.x > WORLD_MAX_X ||
+                    proj.y < WORLD_MIN_Y || proj.y > WORLD_MAX_Y {
+                        chunk_results.to_remove.push(global_idx);
+                        continue;
+                    }
+
+                    // Check lifetime; explosives go off when it runs out
+                    if proj.should_remove() {
+                        if explosive.is_some() {
+                            chunk_results.detonations.push(explosion_of(proj, Vec2::new(proj.x, proj.y)));
                         }
+                        chunk_results.to_remove.push(global_idx);
                     }
                 }
                 
-                chunk_to_remove
+                (chunk_results, chunk_spatial_updates)
             })
             .collect();
         
+        drop(hitbox_history);
+        
+        let mut results = ProjectileResults { total_processed: total_projectiles, ..ProjectileResults::default() };
+        let mut spatial_updates = Vec::new();
+        for (chunk, chunk_spatial_updates) in chunk_results {
+            results.hits.extend(chunk.hits);
+            results.wall_hits.extend(chunk.wall_hits);
+            results.detonations.extend(chunk.detonations);
+            results.to_remove.extend(chunk.to_remove);
+            spatial_updates.extend(chunk_spatial_updates);
+        }
+        
         // Apply spatial updates
-        let spatial_updates_vec = spatial_updates.lock().unwrap().clone();
-        self.spatial_index.batch_update_projectiles(&spatial_updates_vec);
+        self.spatial_index.batch_update_projectiles(&spatial_updates);
         
         // Remove dead projectiles
-        let to_remove_set: HashSet<_> = to_remove.into_iter().collect();
+        let to_remove_set: HashSet<_> = std::mem::take(&mut results.to_remove).into_iter().collect();
         let mut kept_projectiles = Vec::with_capacity(all_projectiles.len());
         let mut removed_ids = Vec::new();
         
@@ -1568,45 +1918,16 @@ fn collect_active_walls_optimized(&self) -> Vec<Wall> {
         *self.projectiles.write() = kept_projectiles;
         
         // Process wall damage
-        let wall_hits_vec = wall_hits.lock().unwrap().clone();
-        for (wall_id, damage) in &wall_hits_vec {
-            let partition_idx = self.world_partition_manager
-                .get_partitions_for_processing()
-                .iter()
-                .position(|p| p.all_walls_in_partition.contains_key(wall_id));
-                
-            if let Some(idx) = partition_idx {
-                if let Some(partition) = self.world_partition_manager.get_partition(idx) {
-                    if let Some((destroyed, pos)) = partition.damage_destructible_wall(*wall_id, *damage) {
-                        if destroyed {
-                            self.global_game_events.push(
-                                GameEvent::WallDestroyed {
-                                    wall_id: *wall_id,
-                                    position: pos,
-                                },
-                                EventPriority::High
-                            );
-                            self.destroyed_wall_ids_this_tick.write().insert(*wall_id);
-                            self.wall_respawn_manager.wall_destroyed(*wall_id);
-                        }
-                    }
-                }
-            }
+        for (wall_id, damage) in &results.wall_hits {
+            self.damage_wall(*wall_id, *damage);
         }
         
-        let hits_vec = hits.lock().unwrap().clone();
-        
         trace!(
             "[Frame {}] Projectile processing complete: {} processed, {} hits, {} wall hits, {} removed",
-            frame, total_projectiles, hits_vec.len(), wall_hits_vec.len(), removed_ids.len()
+            frame, total_projectiles, results.hits.len(), results.wall_hits.len(), removed_ids.len()
         );
         
-        ProjectileResults {
-            total_processed: total_projectiles,
-            hits: hits_vec,
-            wall_hits: wall_hits_vec,
-            to_remove: Vec::new(), // Already handled
-        }
+        results // Removals already handled
     }
 
 
@@ -1616,11 +1937,33 @@ fn collect_active_walls_optimized(&self) -> Vec<Wall> {
         // Track if we need to rebuild spatial index
         let mut walls_destroyed = false;
         
+        // Blasts add their own hits, handled like any other. Each projectile counts toward its
+        // owner's accuracy once, however many players its blast catches.
+        let mut all_hits: Vec<_> = results.hits.into_iter().map(|hit| (hit, true)).collect();
+        for explosion in &results.detonations {
+            let mut counted = false;
+            for hit in self.detonate(explosion) {
+                let counts = !counted && hit.0 != hit.1;
+                counted |= counts;
+                all_hits.push((hit, counts));
+            }
+        }
+
         // Process hits - reuse existing game logic
-        for (attacker_id, target_id, damage, weapon) in results.hits {
+        let current_tick = self.frame_counter.load(AtomicOrdering::Relaxed);
+        let hit_timestamp_ms = self.get_server_timestamp();
+        for ((attacker_id, target_id, damage, weapon, shot), counts_toward_accuracy) in all_hits {
+            // The victim's client should see its attacker at full rate for a while.
+            if attacker_id != target_id {
+                if let Some(mut victim_priorities) = self.client_priorities.get_mut(target_id.as_str()) {
+                    victim_priorities.note_attacker(attacker_id.clone(), current_tick);
+                }
+            }
+            let mut hit_landed = false;
             if let Some(mut target_state_entry) = self.player_manager.get_player_state_mut(&target_id) {
                 if target_state_entry.alive {
-                    let died = target_state_entry.apply_damage(damage);
+                    let died = target_state_entry.take_hit(damage, Some(attacker_id.clone()), DamageCause::Weapon(weapon), shot.distance(), hit_timestamp_ms);
+                    hit_landed = true;
                     let target_pos = Vec2::new(target_state_entry.x, target_state_entry.y);
                     
                     self.global_game_events.push(GameEvent::PlayerDamaged {
@@ -1635,6 +1978,7 @@ fn collect_active_walls_optimized(&self) -> Vec<Wall> {
                         // Store flag carry state before clearing it
                         let victim_was_carrying_flag_id = target_state_entry.is_carrying_flag_team_id;
                         let victim_username = target_state_entry.username.clone();
+                        let damage_log = std::mem::take(&mut target_state_entry.damage_log);
                         
                         // Clear flag carry state on the victim
                         if victim_was_carrying_flag_id != 0 {
@@ -1642,54 +1986,24 @@ fn collect_active_walls_optimized(&self) -> Vec<Wall> {
                             target_state_entry.mark_field_changed(FIELD_FLAG);
                         }
                         
-                        // Handle death (existing logic from run_physics_update)
-                        if attacker_id != target_id {
-                            // Get team information for friendly fire check
-                            let attacker_team = self.player_manager.get_player_state(&attacker_id)
-                                .map(|p| p.team_id)
-                                .unwrap_or(0);
-                            let victim_team = target_state_entry.team_id;
-                            
-                            if let Some(mut attacker_state_entry) = self.player_manager.get_player_state_mut(&attacker_id) {
-                                attacker_state_entry.kills += 1;
-                                
-                                // Check for friendly fire
-                                if attacker_team != 0 && victim_team != 0 && attacker_team == victim_team {
-                                    // Friendly fire: double negative score
-                                    attacker_state_entry.score -= 200;
-                                    info!("Friendly fire penalty: {} killed teammate {}, -200 score", 
-                                          attacker_state_entry.username, victim_username);
-                                } else {
-                                    // Normal kill: positive score
-                                    attacker_state_entry.score += 100;
-                                }
-                                
-                                attacker_state_entry.mark_field_changed(FIELD_SCORE_STATS);
-                            }
-                        }
-                        
-                        // Update team scores for TeamDeathmatch
-                        {
-                            let match_info_guard = self.match_info.read();
-                            if match_info_guard.game_mode == fb::GameModeType::TeamDeathmatch {
-                                drop(match_info_guard);
-                                
-                                // Get attacker and victim team IDs
-                                let attacker_team = self.player_manager.get_player_state(&attacker_id)
-                                    .map(|p| p.team_id)
-                                    .unwrap_or(0);
-                                let victim_team = target_state_entry.team_id;
-                                
-                                // Award point to attacker's team if it's a valid team kill
-                                if attacker_team != 0 && victim_team != 0 && attacker_team != victim_team {
-                                    let mut match_info_write = self.match_info.write();
-                                    let team_score = match_info_write.team_scores.entry(attacker_team).or_insert(0);
-                                    *team_score += 1;
-                                    info!("Team {} scored! New score: {} (kill by player on victim from team {})", 
-                                          attacker_team, *team_score, victim_team);
-                                }
-                            }
-                        }
+                        let victim_team = target_state_entry.team_id;
+
+                        // Scoring and the mode may change the victim's state on a kill, so release it
+                        // first. A blast can kill its own thrower, so the attacker is only looked up after.
+                        drop(target_state_entry);
+                        let attacker_team = self.player_manager.get_player_state(&attacker_id)
+                            .map(|p| p.team_id)
+                            .unwrap_or(0);
+                        let kill = KillInfo {
+                            killer_id: attacker_id.clone(),
+                            victim_id: target_id.clone(),
+                            killer_team: attacker_team,
+                            victim_team,
+                            weapon,
+                            victim_position: target_pos,
+                            victim_flag_team_id: victim_was_carrying_flag_id,
+                        };
+                        self.credit_kill(&kill, &damage_log);
                         
                         self.global_game_events.push(GameEvent::PlayerKilled {
                             victim_id: target_id.clone(),
@@ -1698,52 +2012,295 @@ fn collect_active_walls_optimized(&self) -> Vec<Wall> {
                             position: target_pos,
                         }, EventPriority::High);
                         
-                        // Update kill feed
-                        let killer_username = self.player_manager.get_player_state(&attacker_id)
-                            .map_or_else(|| "World".to_string(), |p| p.username.clone());
-                        
-                        let mut kill_feed_guard = self.kill_feed.write();
-                        kill_feed_guard.push_back(ServerKillFeedEntry {
-                            killer_name: killer_username.clone(),
-                            victim_name: victim_username.clone(),
-                            weapon,
-                            timestamp: self.frame_counter.load(AtomicOrdering::Relaxed),
-                        });
-                        if kill_feed_guard.len() > MAX_KILL_FEED_HISTORY {
-                            kill_feed_guard.pop_front();
-                        }
-                        drop(kill_feed_guard);
-                        
-                        // Handle flag dropping if victim was carrying a flag
-                        if victim_was_carrying_flag_id != 0 {
-                            let mut match_info_guard = self.match_info.write();
-                            
-                            // Drop the flag
-                            if let Some(flag_state) = match_info_guard.flag_states.get_mut(&victim_was_carrying_flag_id) {
-                                flag_state.status = fb::FlagStatus::Dropped;
-                                flag_state.position = target_pos;
-                                flag_state.carrier_id = None;
-                                flag_state.respawn_timer = 30.0;
-                                
-                                // Push flag dropped event after releasing match_info lock
-                                drop(match_info_guard);
-                                
-                                self.global_game_events.push(GameEvent::FlagDropped {
-                                    player_id: target_id.clone(), 
-                                    flag_team_id: victim_was_carrying_flag_id, 
-                                    position: target_pos
-                                }, EventPriority::High);
-                                
-                                info!("(Projectile Kill) Flag of team {} dropped at ({:.1}, {:.1}) by {} killing {}", 
-                                      victim_was_carrying_flag_id, target_pos.x, target_pos.y, killer_username, victim_username);
-                            }
-                        }
+                        self.record_kill(&attacker_id, &target_id, victim_username.clone(), target_pos, weapon, shot);
+                        self.send_death_recap(&target_id, Some(&attacker_id), &damage_log);
+                        self.notify_kill(&kill);
                     }
                 }
             }
+            if hit_landed && attacker_id != target_id {
+                self.credit_hit(&attacker_id, damage, counts_toward_accuracy);
+            }
         }
     }
     
+    /// Damages a destructible wall, announcing it if it breaks.
+    fn damage_wall(&self, wall_id: EntityId, damage: i32) {
+        let partition_idx = self.world_partition_manager
+            .get_partitions_for_processing()
+            .iter()
+            .position(|p| p.all_walls_in_partition.contains_key(&wall_id));
+        let Some(partition) = partition_idx.and_then(|idx| self.world_partition_manager.get_partition(idx)) else { return };
+        let damaged = partition.damage_destructible_wall(wall_id, damage);
+        if let Some(wall) = damaged.and_then(|_| partition.get_wall(wall_id)) {
+            self.updated_walls_this_tick.write().insert(wall_id, wall);
+        }
+        if let Some((true, pos)) = damaged {
+            self.global_game_events.push(GameEvent::WallDestroyed { wall_id, position: pos }, EventPriority::High);
+            self.destroyed_wall_ids_this_tick.write().insert(wall_id);
+            // Deployed cover is gone for good; map walls come back
+            if self.deployed_covers.remove(&wall_id).is_some() {
+                self.remove_cover(wall_id);
+            } else {
+                self.wall_respawn_manager.wall_destroyed(wall_id);
+                // Shots and blasts later this tick must not be stopped by it
+                self.refresh_wall_index();
+            }
+        }
+    }
+
+    fn find_wall(&self, wall_id: EntityId) -> Option<Wall> {
+        self.world_partition_manager.get_partitions_for_processing().iter()
+            .find_map(|partition| partition.get_wall(wall_id))
+    }
+
+    /// Sets off an explosive: damages destructible walls in range, knocks players back and
+    /// returns the blast's player hits. Solid walls shelter whatever is behind them, and
+    /// teammates are spared, as with melee; the owner is not.
+    fn detonate(&self, explosion: &Explosion) -> Vec<(PlayerID, PlayerID, i32, ServerWeaponType, KillShot)> {
+        let Some(spec) = explosive_spec(explosion.weapon) else { return Vec::new() };
+        let center = explosion.position;
+        self.global_game_events.push(GameEvent::Explosion {
+            owner_id: explosion.owner_id.clone(),
+            weapon: explosion.weapon,
+            position: center,
+            radius: spec.radius,
+        }, EventPriority::High);
+
+        for wall in self.wall_spatial_index.query_radius(center.x, center.y, spec.radius) {
+            if !wall.is_destructible || wall.current_health <= 0 {
+                continue;
+            }
+            let point = closest_point_on_wall(center, &wall);
+            let damage = blast_damage(&spec, explosion.damage, (point.x - center.x).hypot(point.y - center.y));
+            let blockers = self.wall_spatial_index.query_line_segment(center.x, center.y, point.x, point.y);
+            if damage > 0 && blast_reaches(center, point, &blockers) {
+                self.global_game_events.push(GameEvent::WallImpact { wall_id: wall.id, position: point, damage }, EventPriority::Normal);
+                self.damage_wall(wall.id, damage);
+            }
+        }
+
+        let owner_team = self.player_manager.get_player_state(&explosion.owner_id).map_or(0, |p| p.team_id);
+        let mut hits = Vec::new();
+        for target_id in self.spatial_index.query_nearby_players(center.x, center.y, spec.radius + PLAYER_RADIUS) {
+            let Some(mut target_state) = self.player_manager.get_player_state_mut(&target_id) else { continue };
+            let is_owner = target_id == explosion.owner_id;
+            if !target_state.alive || (!is_owner && owner_team != 0 && target_state.team_id == owner_team) {
+                continue;
+            }
+            let position = Vec2::new(target_state.x, target_state.y);
+            let distance = ((position.x - center.x).hypot(position.y - center.y) - PLAYER_RADIUS).max(0.0);
+            let blockers = self.wall_spatial_index.query_line_segment(center.x, center.y, position.x, position.y);
+            if distance > spec.radius || !blast_reaches(center, position, &blockers) {
+                continue;
+            }
+
+            self.displace_player(&mut target_state, knockback_offset(&spec, center, position));
+
+            let mut damage = blast_damage(&spec, explosion.damage, distance);
+            if is_owner {
+                damage = (damage as f32 * EXPLOSION_SELF_DAMAGE_FRACTION).round() as i32;
+            }
+            if damage > 0 {
+                hits.push((
+                    explosion.owner_id.clone(),
+                    target_id.clone(),
+                    damage,
+                    explosion.weapon,
+                    KillShot { origin: explosion.origin, impact: center },
+                ));
+            }
+        }
+        hits
+    }
+
+    /// Moves a player by `offset`, stopping at walls. It goes in steps no longer than the player's
+    /// radius so thin walls can't be skipped over.
+    fn displace_player(&self, player_state: &mut PlayerState, offset: Vec2) {
+        let distance = offset.x.hypot(offset.y);
+        if distance < f32::EPSILON {
+            return;
+        }
+        let nearby_walls = wall_rects(&self.wall_spatial_index.query_radius(player_state.x, player_state.y, distance + PLAYER_RADIUS));
+        let steps = (distance / PLAYER_RADIUS).ceil() as u32;
+        let mut state = MovementState { velocity_x: offset.x, velocity_y: offset.y, ..movement_state_of(player_state) };
+        for _ in 0..steps {
+            state = integrate_movement(&state, &nearby_walls, 1.0 / steps as f32);
+        }
+        player_state.x = state.x;
+        player_state.y = state.y;
+        // Not the player's own movement, so it mustn't trip the speed check
+        player_state.last_valid_position = (state.x, state.y);
+        player_state.mark_field_changed(FIELD_POSITION_ROTATION);
+    }
+
+    /// Resolves the abilities paid for this tick. Each returns where it took effect, or None if it
+    /// couldn't, in which case the charge is given back.
+    fn process_ability_activations(&self) {
+        while let Some(activation) = self.ability_activations.pop() {
+            let position = match activation.ability {
+                AbilityKind::Dash => self.dash(&activation.player_id),
+                AbilityKind::DeployCover => self.deploy_cover(&activation.player_id),
+                AbilityKind::AreaHeal => self.area_heal(&activation.player_id),
+                AbilityKind::Scan => self.scan(&activation.player_id),
+            };
+            match position {
+                Some(position) => self.global_game_events.push(GameEvent::AbilityUsed {
+                    player_id: activation.player_id,
+                    ability: activation.ability,
+                    position,
+                }, EventPriority::Normal),
+                None => {
+                    if let Some(mut player_state) = self.player_manager.get_player_state_mut(&activation.player_id) {
+                        player_state.abilities.refund(activation.slot);
+                        player_state.mark_field_changed(FIELD_ABILITIES);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Moves the player the way they're running, or facing if they're standing still.
+    fn dash(&self, player_id: &PlayerID) -> Option<Vec2> {
+        let mut player_state = self.player_manager.get_player_state_mut(player_id)?;
+        if !player_state.alive {
+            return None;
+        }
+        let start = Vec2::new(player_state.x, player_state.y);
+        let speed = player_state.velocity_x.hypot(player_state.velocity_y);
+        let (dir_x, dir_y) = if speed > f32::EPSILON {
+            (player_state.velocity_x / speed, player_state.velocity_y / speed)
+        } else {
+            (player_state.rotation.cos(), player_state.rotation.sin())
+        };
+        self.displace_player(&mut player_state, Vec2::new(dir_x * DASH_DISTANCE, dir_y * DASH_DISTANCE));
+        Some(start)
+    }
+
+    /// Puts up a temporary destructible wall in front of the player, if there's room for it.
+    fn deploy_cover(&self, player_id: &PlayerID) -> Option<Vec2> {
+        let (position, rotation) = self.player_manager.get_player_state(player_id)
+            .filter(|p| p.alive)
+            .map(|p| (Vec2::new(p.x, p.y), p.rotation))?;
+        let (corner, width, height) = cover_rect(position, rotation, COVER_DISTANCE, COVER_LENGTH, COVER_THICKNESS);
+        let wall = Wall {
+            id: Uuid::new_v4().as_u128() as u64,
+            x: corner.x,
+            y: corner.y,
+            width,
+            height,
+            is_destructible: true,
+            current_health: COVER_HEALTH,
+            max_health: COVER_HEALTH,
+        };
+        let center = Vec2::new(wall.x + width / 2.0, wall.y + height / 2.0);
+
+        // It must fit inside the world, clear of other walls and of everyone
+        let in_world = wall.x >= WORLD_MIN_X && wall.y >= WORLD_MIN_Y && wall.x + width <= WORLD_MAX_X && wall.y + height <= WORLD_MAX_Y;
+        if !in_world || !self.wall_spatial_index.query_aabb(wall.x, wall.y, wall.x + width, wall.y + height).is_empty() {
+            return None;
+        }
+        let rect = wall_rects(std::slice::from_ref(&wall)).pop()?;
+        let reach = width.max(height) / 2.0 + PLAYER_RADIUS;
+        let blocked_by_player = self.spatial_index.query_nearby_players(center.x, center.y, reach).iter()
+            .filter_map(|id| self.player_manager.get_player_state(id).filter(|p| p.alive).map(|p| (p.x, p.y)))
+            .any(|(x, y)| overlaps_wall(x, y, &rect));
+        if blocked_by_player {
+            return None;
+        }
+
+        let partition_idx = self.world_partition_manager.get_partition_index_for_point(center.x, center.y);
+        let partition = self.world_partition_manager.get_partition(partition_idx)?;
+        partition.add_wall_on_load(wall.clone());
+        self.deployed_covers.insert(wall.id, COVER_LIFETIME_SECS);
+        for mut aoi_entry in self.player_aois.iter_mut() {
+            aoi_entry.value_mut().visible_walls.insert(wall.id);
+        }
+        self.updated_walls_this_tick.write().insert(wall.id, wall);
+        self.refresh_wall_index();
+        Some(center)
+    }
+
+    /// Heals the player and teammates around them; in free-for-all, only the player.
+    fn area_heal(&self, player_id: &PlayerID) -> Option<Vec2> {
+        let (center, team_id) = self.player_manager.get_player_state(player_id)
+            .filter(|p| p.alive)
+            .map(|p| (Vec2::new(p.x, p.y), p.team_id))?;
+        for target_id in self.spatial_index.query_nearby_players(center.x, center.y, AREA_HEAL_RADIUS) {
+            let Some(mut target_state) = self.player_manager.get_player_state_mut(&target_id) else { continue };
+            let is_ally = target_id == *player_id || (team_id != 0 && target_state.team_id == team_id);
+            let in_range = (target_state.x - center.x).hypot(target_state.y - center.y) <= AREA_HEAL_RADIUS;
+            if is_ally && in_range {
+                target_state.heal(AREA_HEAL_AMOUNT);
+            }
+        }
+        Some(center)
+    }
+
+    /// Reveals enemies around the player to the player's team (to just the player in free-for-all).
+    fn scan(&self, player_id: &PlayerID) -> Option<Vec2> {
+        let (center, team_id) = self.player_manager.get_player_state(player_id)
+            .filter(|p| p.alive)
+            .map(|p| (Vec2::new(p.x, p.y), p.team_id))?;
+        for target_id in self.spatial_index.query_nearby_players(center.x, center.y, SCAN_RADIUS) {
+            if target_id == *player_id {
+                continue;
+            }
+            let Some(mut target_state) = self.player_manager.get_player_state_mut(&target_id) else { continue };
+            let is_enemy = team_id == 0 || target_state.team_id != team_id;
+            let in_range = (target_state.x - center.x).hypot(target_state.y - center.y) <= SCAN_RADIUS;
+            if !target_state.alive || !is_enemy || !in_range {
+                continue;
+            }
+            target_state.apply_effect(EffectKind::Revealed, Some(player_id.clone()));
+            drop(target_state);
+            self.revealed_players.insert(target_id, (player_id.clone(), team_id));
+        }
+        Some(center)
+    }
+
+    /// Counts down deployed cover and takes away what has run out.
+    fn update_deployed_covers(&self, delta_time: f32) {
+        let mut expired = Vec::new();
+        for mut cover in self.deployed_covers.iter_mut() {
+            *cover.value_mut() -= delta_time;
+            if *cover.value() <= 0.0 {
+                expired.push(*cover.key());
+            }
+        }
+        for wall_id in expired {
+            self.deployed_covers.remove(&wall_id);
+            if let Some(wall) = self.remove_cover(wall_id) {
+                let position = Vec2::new(wall.x + wall.width / 2.0, wall.y + wall.height / 2.0);
+                self.global_game_events.push(GameEvent::WallDestroyed { wall_id, position }, EventPriority::High);
+                self.destroyed_wall_ids_this_tick.write().insert(wall_id);
+            }
+        }
+    }
+
+    /// Takes deployed cover out of the world, whether it was destroyed or ran out of time.
+    fn remove_cover(&self, wall_id: EntityId) -> Option<Wall> {
+        let removed = self.world_partition_manager.get_partitions_for_processing().iter()
+            .find_map(|partition| partition.all_walls_in_partition.remove(&wall_id))
+            .map(|(_, wall)| wall);
+        if let Some(wall) = &removed {
+            // Drops it from the broadcast wall snapshot, so clients are told it's gone
+            self.updated_walls_this_tick.write().insert(wall_id, wall.clone());
+        }
+        self.refresh_wall_index();
+        removed
+    }
+
+    /// Rebuilds the wall index right away, for walls added or removed outside the map's own
+    /// destroy and respawn cycle.
+    fn refresh_wall_index(&self) {
+        if let Some(cache) = CACHED_WALLS.get() {
+            cache.write().0 = u64::MAX; // Stale; the next collection rebuilds it
+        }
+        let frame = self.frame_counter.load(AtomicOrdering::Relaxed);
+        self.wall_spatial_index.rebuild(&self.collect_active_walls_optimized(), frame);
+    }
+
     async fn process_pickup_respawns(&self, delta_time: f32) {
         let mut pickups_guard = self.pickups.write();
         for pickup in pickups_guard.iter_mut() {
@@ -1762,7 +2319,8 @@ fn collect_active_walls_optimized(&self) -> Vec<Wall> {
     fn get_enemy_positions_for_team(&self, team_id: u8) -> Vec<(Vec2, PlayerID)> {
         let mut enemies = Vec::with_capacity(50);
         self.player_manager.for_each_player(|id, state| {
-            if state.alive && state.team_id != team_id && state.team_id != 0 {
+            // Without teams (team 0) every other living player is an enemy
+            if state.alive && (team_id == 0 || (state.team_id != team_id && state.team_id != 0)) {
                 enemies.push((Vec2::new(state.x, state.y), id.clone()));
             }
         });
@@ -1799,48 +2357,37 @@ fn collect_active_walls_optimized(&self) -> Vec<Wall> {
 
             match match_info_guard.match_state {
                 fb::MatchStateType::Waiting => {
-                    if player_count >= MIN_PLAYERS_TO_START {
-                        match_info_guard.match_state = fb::MatchStateType::Active;
-                        match_info_guard.time_remaining = 300.0;
-                        info!("Match starting! Mode: {:?}", match_info_guard.game_mode);
-                        if match_info_guard.game_mode == fb::GameModeType::CaptureTheFlag {
-                            self.initialize_ctf_flags(&mut match_info_guard);
+                    // Rules changed since the last match apply from this one
+                    match_info_guard.rules = self.next_match_rules.read().clone();
+                    if player_count >= match_info_guard.rules.min_players {
+                        if match_info_guard.rules.start_countdown_secs > 0.0 {
+                            match_info_guard.match_state = fb::MatchStateType::Starting;
+                            match_info_guard.time_remaining = match_info_guard.rules.start_countdown_secs;
+                            info!("Match starting in {:.0}s.", match_info_guard.time_remaining);
+                        } else {
+                            self.start_match(&mut match_info_guard);
                         }
-                        self.player_manager.for_each_player_mut(|_id, p_state| {
-                            p_state.score = 0;
-                            p_state.kills = 0;
-                            p_state.deaths = 0;
-                            p_state.is_carrying_flag_team_id = 0;
-                            p_state.mark_field_changed(FIELD_SCORE_STATS | FIELD_FLAG);
-                        });
-                        self.kill_feed.write().clear();
                     }
                 }
-                fb::MatchStateType::Active => {
+                fb::MatchStateType::Starting => {
                     match_info_guard.time_remaining -= delta_time;
-                    if match_info_guard.time_remaining <= 0.0 {
-                        match_info_guard.match_state = fb::MatchStateType::Ended;
-                        info!("Match ended! (Time up)");
-                        if match_info_guard.game_mode == fb::GameModeType::TeamDeathmatch || match_info_guard.game_mode == fb::GameModeType::CaptureTheFlag {
-                            let team1_score = match_info_guard.team_scores.get(&1).cloned().unwrap_or(0);
-                            let team2_score = match_info_guard.team_scores.get(&2).cloned().unwrap_or(0);
-                            
-                            // Determine and announce the winner
-                            if team1_score > team2_score {
-                                info!("Team 1 wins with {} points vs Team 2's {} points!", team1_score, team2_score);
-                            } else if team2_score > team1_score {
-                                info!("Team 2 wins with {} points vs Team 1's {} points!", team2_score, team1_score);
-                            } else if team1_score == team2_score && team1_score > 0 {
-                                info!("Match ended in a draw! Both teams scored {} points.", team1_score);
-                            } else {
-                                info!("Match ended with no winner (0-0).");
-                            }
-                        }
+                    if player_count < match_info_guard.rules.min_players {
+                        match_info_guard.match_state = fb::MatchStateType::Waiting;
+                        match_info_guard.time_remaining = match_info_guard.rules.time_limit_secs;
+                        info!("Match start cancelled: not enough players.");
+                    } else if match_info_guard.time_remaining <= 0.0 {
+                        self.start_match(&mut match_info_guard);
+                    }
+                }
+                fb::MatchStateType::Active => {
+                    // Without a time limit the clock only runs in overtime and sudden death
+                    if match_info_guard.rules.time_limit_secs > 0.0 || match_info_guard.period != fb::MatchPeriod::Regulation {
+                        match_info_guard.time_remaining -= delta_time;
                     }
                 }
                 fb::MatchStateType::Ended => {
                     match_info_guard.time_remaining -= delta_time;
-                    if match_info_guard.time_remaining <= -10.0 {
+                    if match_info_guard.time_remaining <= -match_info_guard.rules.post_match_secs {
                         match_info_guard.match_state = fb::MatchStateType::Waiting;
                         self.reset_match_state(&mut match_info_guard);
                         info!("Match reset to Waiting.");
@@ -1877,33 +2424,26 @@ fn collect_active_walls_optimized(&self) -> Vec<Wall> {
                                 }
                             }
                             CorePickupType::Ammo => {
-                                player_state_for_pickup.ammo = PlayerState::get_max_ammo_for_weapon(player_state_for_pickup.weapon);
-                                player_state_for_pickup.mark_field_changed(FIELD_WEAPON_AMMO);
-                                collected = true;
+                                collected = player_state_for_pickup.add_reserve_magazines(AMMO_PICKUP_MAGAZINES);
                             }
-                            CorePickupType::WeaponCrate(weapon) => {
-                                player_state_for_pickup.weapon = *weapon;
-                                player_state_for_pickup.ammo = PlayerState::get_max_ammo_for_weapon(*weapon);
-                                player_state_for_pickup.reload_progress = None;
-                                player_state_for_pickup.mark_field_changed(FIELD_WEAPON_AMMO);
-                                collected = true;
+                            CorePickupType::WeaponCrate(weapon) if self.game_mode.allows_weapon(player_state_for_pickup.team_id, *weapon) => {
+                                let reserve = PlayerState::get_max_ammo_for_weapon(*weapon) * WEAPON_CRATE_RESERVE_MAGAZINES;
+                                let newly_owned = !player_state_for_pickup.inventory.owns(*weapon);
+                                collected = player_state_for_pickup.grant_weapon(*weapon, reserve);
+                                if newly_owned {
+                                    player_state_for_pickup.equip_weapon(*weapon);
+                                }
                             }
                             CorePickupType::SpeedBoost => {
-                                player_state_for_pickup.speed_boost_remaining = 10.0;
-                                player_state_for_pickup.mark_field_changed(FIELD_POWERUPS);
-                                collected = true;
+                                collected = player_state_for_pickup.apply_effect(EffectKind::SpeedBoost, None);
                             }
                             CorePickupType::DamageBoost => {
-                                player_state_for_pickup.damage_boost_remaining = 10.0;
-                                player_state_for_pickup.mark_field_changed(FIELD_POWERUPS);
-                                collected = true;
+                                collected = player_state_for_pickup.apply_effect(EffectKind::DamageBoost, None);
                             }
                             CorePickupType::Shield => {
-                                player_state_for_pickup.shield_max = 50;
-                                player_state_for_pickup.shield_current = player_state_for_pickup.shield_max;
-                                player_state_for_pickup.mark_field_changed(FIELD_SHIELD);
-                                collected = true;
+                                collected = player_state_for_pickup.apply_effect(EffectKind::Shield, None);
                             }
+                            CorePickupType::WeaponCrate(_) => {} // Not usable by this player's team
                         }
 
                         if collected {
@@ -1922,133 +2462,51 @@ fn collect_active_walls_optimized(&self) -> Vec<Wall> {
             }
         });
 
-        // CTF Logic
+        // Game mode objectives and win check
         let mut match_info_write_guard = self.match_info.write();
-        if match_info_write_guard.game_mode == fb::GameModeType::CaptureTheFlag && match_info_write_guard.match_state == fb::MatchStateType::Active {
-            for flag_state in match_info_write_guard.flag_states.values_mut() {
-                if flag_state.status == fb::FlagStatus::Dropped && flag_state.respawn_timer > 0.0 {
-                    flag_state.respawn_timer -= delta_time;
-                    if flag_state.respawn_timer <= 0.0 {
-                        flag_state.respawn_timer = 0.0;
-                        flag_state.status = fb::FlagStatus::AtBase;
-                        flag_state.position = Self::get_flag_base_position(flag_state.team_id);
-                        flag_state.carrier_id = None;
-                        self.global_game_events.push(GameEvent::FlagReturned {
-                            player_id: Arc::new("server".to_string()),
-                            flag_team_id: flag_state.team_id,
-                            position: flag_state.position
-                        }, EventPriority::High);
-                        info!("Flag of team {} auto-returned to base.", flag_state.team_id);
-                    }
-                }
-            }
-
-            let mut player_snapshots: HashMap<PlayerID, PlayerState> = HashMap::new();
-             self.player_manager.for_each_player(|id, state| {
-                player_snapshots.insert(id.clone(), state.clone());
-            });
-
-            for (player_id_arc, player_state_snapshot) in &player_snapshots {
-                if !player_state_snapshot.alive { continue; }
-
-                if player_state_snapshot.is_carrying_flag_team_id == 0 {
-                    for flag_state in match_info_write_guard.flag_states.values_mut() {
-                        // Check if flag can be interacted with
-                        let can_interact = match flag_state.status {
-                            fb::FlagStatus::AtBase => true,
-                            fb::FlagStatus::Dropped => {
-                                // Enemy can pick up after timer expires, own team can return immediately
-                                if flag_state.team_id == player_state_snapshot.team_id {
-                                    true // Own team can always return their dropped flag
-                                } else {
-                                    flag_state.respawn_timer <= 0.0 // Enemy must wait for timer
-                                }
-                            },
-                            _ => false
-                        };
-                        
-                        if can_interact {
-                            let dx = player_state_snapshot.x - flag_state.position.x;
-                            let dy = player_state_snapshot.y - flag_state.position.y;
-                            if (dx * dx + dy * dy) < (PICKUP_COLLECTION_RADIUS * PICKUP_COLLECTION_RADIUS) {
-                                if flag_state.team_id != player_state_snapshot.team_id {
-                                    // Enemy picking up flag
-                                    flag_state.status = fb::FlagStatus::Carried;
-                                    flag_state.carrier_id = Some(player_id_arc.clone());
-                                    if let Some(mut p_state_mut_entry) = self.player_manager.get_player_state_mut(player_id_arc) {
-                                        let p_state_mut = &mut *p_state_mut_entry;
-                                        p_state_mut.is_carrying_flag_team_id = flag_state.team_id;
-                                        p_state_mut.mark_field_changed(FIELD_FLAG);
-                                    }
-                                    self.global_game_events.push(GameEvent::FlagGrabbed { player_id: player_id_arc.clone(), flag_team_id: flag_state.team_id, position: flag_state.position }, EventPriority::High);
-                                    info!("Player {} grabbed flag of team {}", player_state_snapshot.username, flag_state.team_id);
-                                    break;
-                                } else if flag_state.status == fb::FlagStatus::Dropped && flag_state.team_id == player_state_snapshot.team_id {
-                                    // Own team returning flag
-                                    flag_state.status = fb::FlagStatus::AtBase;
-                                    flag_state.position = Self::get_flag_base_position(flag_state.team_id);
-                                    flag_state.carrier_id = None;
-                                    flag_state.respawn_timer = 0.0;
-                                    self.global_game_events.push(GameEvent::FlagReturned { player_id: player_id_arc.clone(), flag_team_id: flag_state.team_id, position: flag_state.position }, EventPriority::High);
-                                    info!("Player {} returned own team {}'s flag.", player_state_snapshot.username, flag_state.team_id);
-                                    break;
-                                }
-                            }
-                        }
-                    }
-                }
-
-                if player_state_snapshot.is_carrying_flag_team_id != 0 &&
-                   player_state_snapshot.is_carrying_flag_team_id != player_state_snapshot.team_id {
-                    let own_player_team_id = player_state_snapshot.team_id;
-
-                    let own_flag_at_base = match_info_write_guard.flag_states.get(&own_player_team_id)
-                        .map_or(false, |ofs| ofs.status == fb::FlagStatus::AtBase);
-
-                    if own_flag_at_base {
-                        let own_flag_base_pos = Self::get_flag_base_position(own_player_team_id);
-                        let dx = player_state_snapshot.x - own_flag_base_pos.x;
-                        let dy = player_state_snapshot.y - own_flag_base_pos.y;
-
-                        if (dx*dx + dy*dy) < (PICKUP_COLLECTION_RADIUS * PICKUP_COLLECTION_RADIUS) {
-                            let captured_flag_team_id = player_state_snapshot.is_carrying_flag_team_id;
-
-                            if let Some(captured_flag) = match_info_write_guard.flag_states.get_mut(&captured_flag_team_id) {
-                                captured_flag.status = fb::FlagStatus::AtBase;
-                                captured_flag.position = Self::get_flag_base_position(captured_flag_team_id);
-                                captured_flag.carrier_id = None;
-                            }
-
-                            if let Some(mut p_state_mut_entry) = self.player_manager.get_player_state_mut(player_id_arc) {
-                                let p_state_mut = &mut *p_state_mut_entry;
-                                p_state_mut.is_carrying_flag_team_id = 0;
-                                p_state_mut.mark_field_changed(FIELD_FLAG);
-                                p_state_mut.score += 100;
-                                p_state_mut.mark_field_changed(FIELD_SCORE_STATS);
-                            }
-
-                            let team_score_mut_ref = match_info_write_guard.team_scores.entry(own_player_team_id).or_insert(0);
-                            *team_score_mut_ref += 1;
-                            let current_score = *team_score_mut_ref;
-
-                            self.global_game_events.push(GameEvent::FlagCaptured {
-                                capturer_id: player_id_arc.clone(),
-                                captured_flag_team_id,
-                                capturing_team_id: own_player_team_id,
-                                position: own_flag_base_pos
-                            }, EventPriority::High);
-                            info!("Player {} captured team {}'s flag for team {}! (Score: {})", player_state_snapshot.username, captured_flag_team_id, own_player_team_id, current_score);
-
-                            if current_score >= 3 {
-                                 match_info_write_guard.match_state = fb::MatchStateType::Ended;
-                                 info!("Team {} wins by capturing {} flags!", own_player_team_id, current_score);
-                            }
-                        }
-                    }
+        let mut safe_zone_damage = None;
+        let mut respawn_all = false;
+        let mut match_ended = false;
+        if match_info_write_guard.match_state == fb::MatchStateType::Active {
+            let time_up = clock_expired(&match_info_write_guard) || match_info_write_guard.period == fb::MatchPeriod::SuddenDeath;
+            let mut ctx = ModeContext {
+                match_info: &mut match_info_write_guard,
+                player_manager: &self.player_manager,
+                events: &self.global_game_events,
+            };
+            self.game_mode.on_tick(&mut ctx, delta_time);
+            safe_zone_damage = ctx.match_info.safe_zone.as_mut()
+                .and_then(|zone| zone.damage_due(delta_time).map(|damage| (zone.clone(), damage)));
+            respawn_all = ctx.match_info.round.as_mut().is_some_and(|round| std::mem::take(&mut round.respawn_all));
+            let outcome = self.game_mode.check_win(&ctx, time_up);
+            let period = match_info_write_guard.period;
+            if let Some(outcome) = apply_tie_rules(&mut match_info_write_guard, outcome) {
+                match_info_write_guard.match_state = fb::MatchStateType::Ended;
+                // The results screen counts down from zero to the reset
+                match_info_write_guard.time_remaining = 0.0;
+                self.record_winner(&mut match_info_write_guard, &outcome);
+                match_ended = true;
+                match &outcome {
+                    MatchOutcome::TeamWin { team_id, score } => info!("Match ended! Team {} wins with {} points.", team_id, score),
+                    MatchOutcome::PlayerWin { player_id, score } => info!("Match ended! Player {} wins with {} points.", player_id, score),
+                    MatchOutcome::Draw { score } => info!("Match ended in a draw at {} points.", score),
+                    MatchOutcome::NoWinner => info!("Match ended with no winner."),
                 }
+            } else if match_info_write_guard.period != period {
+                info!("Match tied, going to {:?}.", match_info_write_guard.period);
             }
         }
         drop(match_info_write_guard);
+        self.apply_status_effects(delta_time);
+        if let Some((zone, damage)) = safe_zone_damage {
+            self.apply_safe_zone_damage(&zone, damage);
+        }
+        if respawn_all {
+            self.respawn_all_players();
+        }
+        if match_ended {
+            self.send_match_summary();
+        }
 
         // Melee Event Processing - Fix 1
         let mut melee_hit_events_to_process = Vec::new();
@@ -2075,6 +2533,10 @@ fn collect_active_walls_optimized(&self) -> Vec<Wall> {
         }
         // End of Fix 1 for Melee
 
+        self.process_ability_activations();
+        self.update_deployed_covers(delta_time);
+
+        self.expire_disconnected_sessions();
         self.manage_bot_population();
         // self.destroyed_wall_ids_this_tick.write().clear(); // Moved to process_game_tick
     }
@@ -2089,7 +2551,12 @@ fn collect_active_walls_optimized(&self) -> Vec<Wall> {
         
         trace!("[Frame {}] Starting broadcast for client {}", frame, peer_id_str);
         
-        let player_exists = {
+        let spectator_due = server.spectators.get(peer_id_str).map(|view| view.is_due(frame));
+        if spectator_due == Some(false) && !client_info.needs_initial_state {
+            return Ok(());
+        }
+        
+        let player_exists = spectator_due.is_some() || {
             let player_id_arc = server.player_manager.id_pool.get_or_create(peer_id_str);
             server.player_manager.get_player_state(&player_id_arc).is_some()
         };
@@ -2101,18 +2568,12 @@ fn collect_active_walls_optimized(&self) -> Vec<Wall> {
         
         let state_result = if client_info.needs_initial_state {
             trace!("[Frame {}] Building initial state for {}", frame, peer_id_str);
+            // A fresh initial state invalidates every baseline the client may have acked before.
+            server.snapshot_baselines.insert(peer_id_str.to_string(), BaselineRing::new());
             server.build_initial_state_optimized(peer_id_str, shared_data).await
         } else {
             trace!("[Frame {}] Building delta state for {}", frame, peer_id_str);
-            let client_state_snapshot = server.client_states_map
-                .read() // Acquire read lock
-                .get(peer_id_str)
-                .map(|cs_state_ref| cs_state_ref.clone()) // Clone the ClientState from the &ClientState
-                .unwrap_or_else(|| {
-                    warn!("[Frame {}] ClientState not found for {} during delta build, using default. This might indicate a logic issue.", server.frame_counter.load(AtomicOrdering::Relaxed), peer_id_str);
-                    ClientState::default() 
-                });
-            server.build_delta_state_optimized(peer_id_str, &client_state_snapshot, shared_data).await
+            server.build_delta_state_optimized(peer_id_str, shared_data).await
         };
         
         let bytes_to_send = match state_result {
@@ -2181,13 +2642,308 @@ fn collect_active_walls_optimized(&self) -> Vec<Wall> {
                 ).await;
             }
         }
-        
+
+        if !client_info.needs_initial_state {
+            server.send_ping_if_due(peer_id_str, &client_info.data_channel).await;
+        }
         
         trace!("[Frame {}] Broadcast processing complete for client {}", frame, peer_id_str);
         Ok(())
     }
 
-    fn get_server_timestamp(&self) -> u64 {
+    async fn send_ping_if_due(&self, peer_id_str: &str, data_channel: &Arc<crate::core::types::RTCDataChannel>) {
+        let server_timestamp = self.get_server_timestamp();
+        let ping_snapshot = {
+            let mut tracker = self.client_latencies.entry(peer_id_str.to_string()).or_default();
+            if !tracker.ping_due() {
+                return;
+            }
+            let sequence = tracker.register_ping(server_timestamp);
+            (sequence, tracker.smoothed_rtt_ms, tracker.jitter_ms, tracker.clock_offset_ms)
+        };
+        let (sequence, rtt_ms, jitter_ms, clock_offset_ms) = ping_snapshot;
+
+        let mut builder = FlatBufferBuilder::with_capacity(128);
+        let ping = fb::Ping::create(&mut builder, &fb::PingArgs {
+            sequence,
+            server_timestamp,
+            rtt_ms,
+            jitter_ms,
+            clock_offset_ms,
+        });
+        let game_msg = fb::GameMessage::create(&mut builder, &fb::GameMessageArgs {
+            msg_type: fb::MessageType::Ping,
+            actual_message_type: fb::MessagePayload::Ping,
+            actual_message: Some(ping.as_union_value()),
+        });
+        builder.finish(game_msg, None);
+
+        if let Err(e) = data_channel.send(&Bytes::from(builder.finished_data().to_vec())).await {
+            handle_dc_send_error(&e, peer_id_str, "ping");
+        }
+    }
+
+    /// Folds the client clock stamped on an input into the peer's RTT estimate.
+    pub fn record_input_timestamp(&self, peer_id_str: &str, client_timestamp: u64) {
+        let now_ms = self.get_server_timestamp();
+        if let Some(mut tracker) = self.client_latencies.get_mut(peer_id_str) {
+            tracker.on_input_timestamp(client_timestamp, now_ms);
+        }
+    }
+
+    pub fn handle_pong(&self, peer_id_str: &str, sequence: u32, echoed_server_timestamp: u64, client_timestamp: u64) {
+        let now_ms = self.get_server_timestamp();
+        let measured = self.client_latencies.get_mut(peer_id_str).and_then(|mut tracker| {
+            tracker.on_pong(sequence, echoed_server_timestamp, client_timestamp, now_ms)
+                .map(|sample_ms| (sample_ms, tracker.ping_ms()))
+        });
+
+        let Some((sample_ms, ping_ms)) = measured else {
+            debug!("[{}]: Ignoring unmatched pong (seq: {}).", peer_id_str, sequence);
+            return;
+        };
+        server_metrics::record_client_rtt_sample(sample_ms);
+
+        let player_id = self.player_manager.id_pool.get_or_create(peer_id_str);
+        if let Some(mut player_state) = self.player_manager.get_player_state_mut(&player_id) {
+            if player_state.ping_ms != ping_ms {
+                player_state.ping_ms = ping_ms;
+                player_state.mark_field_changed(FIELD_SCORE_STATS);
+            }
+        }
+        trace!("[{}]: RTT sample {:.1}ms, smoothed ping {}ms.", peer_id_str, sample_ms, ping_ms);
+    }
+
+    /// Changes the caster feed delay (clamped to the allowed range). Returns the delay applied.
+    pub fn set_caster_delay_secs(&self, delay_secs: f32) -> f32 {
+        let applied = self.caster_feed.write().set_delay_secs(delay_secs);
+        info!("Caster feed delay set to {:.0}s.", applied);
+        applied
+    }
+
+    fn record_caster_frame(&self, shared_data: &SharedBroadcastData) {
+        if self.config.caster_key.is_none() {
+            return;
+        }
+        let tick = self.frame_counter.load(AtomicOrdering::Relaxed);
+        let mut feed = self.caster_feed.write();
+        feed.queue_events(&shared_data.events);
+        if !tick.is_multiple_of(CASTER_FRAME_INTERVAL_TICKS) {
+            return;
+        }
+        let events = feed.take_pending_events();
+        let chunks = self.build_caster_frame(tick, shared_data, &events);
+        feed.record(CasterFrame { tick, recorded_at_ms: shared_data.timestamp_ms, chunks });
+        server_metrics::update_caster_feed(feed.buffered_bytes(), feed.dropped_frames(), feed.delay_secs());
+    }
+
+    async fn replay_caster_feed(&self) {
+        let now_ms = self.get_server_timestamp();
+        let seats: Vec<(String, crate::network::caster::CasterSeat)> = self.caster_seats.iter()
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect();
+
+        for (peer_id_str, mut seat) in seats {
+            let Some(data_channel) = self.data_channels_map.get(&peer_id_str).map(|dc| Arc::clone(dc.value())) else { continue };
+
+            if !seat.walls_sent {
+                let walls_msg = self.build_caster_walls_message(&peer_id_str);
+                if let Err(e) = data_channel.send(&walls_msg).await {
+                    handle_dc_send_error(&e.to_string(), &peer_id_str, "caster walls");
+                    continue;
+                }
+                seat.walls_sent = true;
+            }
+
+            let due_frames = self.caster_feed.read().due_frames(now_ms, seat.last_sent_tick);
+            'frames: for frame in due_frames {
+                for chunk in &frame.chunks {
+                    if let Err(e) = data_channel.send(chunk).await {
+                        handle_dc_send_error(&e.to_string(), &peer_id_str, "caster frame");
+                        break 'frames;
+                    }
+                }
+                seat.last_sent_tick = Some(frame.tick);
+            }
+            if let Some(mut stored_seat) = self.caster_seats.get_mut(&peer_id_str) {
+                *stored_seat = seat;
+            }
+        }
+
+        self.caster_feed.write().prune(now_ms);
+    }
+
+    /// Map geometry for a new caster. Wall health arrives with the first replayed frame,
+    /// so nothing here is newer than the delayed feed.
+    fn build_caster_walls_message(&self, peer_id_str: &str) -> Bytes {
+        let mut builder = flatbuffers::FlatBufferBuilder::with_capacity(16384);
+        let walls_fb_vec: Vec<_> = self.collect_all_walls_current_state().iter().map(|wall| {
+            let id_fb = fb_safe_str(&mut builder, &wall.id.to_string());
+            fb::Wall::create(&mut builder, &fb::WallArgs {
+                id: Some(id_fb), x: wall.x, y: wall.y, width: wall.width, height: wall.height,
+                is_destructible: wall.is_destructible,
+                current_health: wall.max_health,
+                max_health: wall.max_health,
+            })
+        }).collect();
+        let walls_fb = builder.create_vector(&walls_fb_vec);
+        let player_id_fb = fb_safe_str(&mut builder, peer_id_str);
+        let map_name_fb = fb_safe_str(&mut builder, "Massive Arena");
+        let initial_state = fb::InitialStateMessage::create(&mut builder, &fb::InitialStateMessageArgs {
+            player_id: Some(player_id_fb),
+            walls: Some(walls_fb),
+            map_name: Some(map_name_fb),
+            ..Default::default()
+        });
+        let game_msg = fb::GameMessage::create(&mut builder, &fb::GameMessageArgs {
+            msg_type: fb::MessageType::InitialState,
+            actual_message_type: fb::MessagePayload::InitialStateMessage,
+            actual_message: Some(initial_state.as_union_value()),
+        });
+        builder.finish(game_msg, None);
+        Bytes::from(builder.finished_data().to_vec())
+    }
+
+    /// A full-world snapshot for the caster feed: every player, projectile, active pickup and
+    /// destructible wall, plus the events since the previous frame, split into messages that
+    /// each fit `CASTER_FRAME_CHUNK_BYTES`.
+    fn build_caster_frame(&self, tick: u64, shared_data: &SharedBroadcastData, events: &[GameEvent]) -> Vec<Bytes> {
+        let mut chunker = CasterFrameChunker::new(tick, shared_data.timestamp_ms);
+
+        let builder = &mut chunker.builder;
+        let header_written_before = builder.unfinished_data().len();
+        let events_vec: Vec<_> = events.iter().map(|event| build_game_event_fb(builder, event)).collect();
+        let game_events_fb = builder.create_vector(&events_vec);
+
+        let kill_feed_vec: Vec<_> = shared_data.kill_feed_snapshot.iter().map(|entry| {
+            let killer_name_fb = fb_safe_str(builder, &entry.killer_name);
+            let victim_name_fb = fb_safe_str(builder, &entry.victim_name);
+            let (killer_position_fb, victim_position_fb) = kill_feed_positions_fb(builder, entry);
+            fb::KillFeedEntry::create(builder, &fb::KillFeedEntryArgs {
+                killer_name: Some(killer_name_fb),
+                victim_name: Some(victim_name_fb),
+                weapon: map_server_weapon_to_fb(entry.weapon),
+                timestamp: entry.timestamp as f32,
+                killer_position: killer_position_fb,
+                victim_position: Some(victim_position_fb),
+                is_headshot: false,
+                cause: entry.cause,
+            })
+        }).collect();
+        let kill_feed_fb = builder.create_vector(&kill_feed_vec);
+
+        let match_snapshot = &shared_data.match_info_snapshot;
+        let match_info_fb = match_info_fb(builder, match_snapshot);
+        let flag_states_vec: Vec<_> = match_snapshot.flag_states.values().map(|fs| {
+            let carrier_id_fb = fs.carrier_id.as_ref().map(|id| fb_safe_str(builder, id.as_str()));
+            let pos_fb = fb::Vec2::create(builder, &fb::Vec2Args { x: fs.position.x, y: fs.position.y });
+            fb::FlagState::create(builder, &fb::FlagStateArgs {
+                team_id: fs.team_id as i8, status: fs.status, position: Some(pos_fb),
+                carrier_id: carrier_id_fb, respawn_timer: fs.respawn_timer,
+            })
+        }).collect();
+        let flag_states_fb = builder.create_vector(&flag_states_vec);
+        let zone_states_fb = zone_states_fb(builder, &match_snapshot.zone_states);
+        chunker.header = Some(CasterFrameHeader {
+            game_events: game_events_fb,
+            kill_feed: kill_feed_fb,
+            match_info: match_info_fb,
+            flag_states: flag_states_fb,
+            zone_states: zone_states_fb,
+        });
+        chunker.charge(header_written_before);
+
+        self.player_manager.for_each_player(|_, player_state| {
+            chunker.reserve(estimate_player_state_bytes(player_state));
+            let written_before = chunker.builder.unfinished_data().len();
+            let player_fb = create_fb_player_state_for_delta(&mut chunker.builder, player_state, 0xFFFF);
+            chunker.players.push(player_fb);
+            chunker.charge(written_before);
+        });
+
+        for proj in self.projectiles.read().iter() {
+            chunker.reserve(estimate_projectile_bytes(proj));
+            let written_before = chunker.builder.unfinished_data().len();
+            let builder = &mut chunker.builder;
+            let id_fb = fb_safe_str(builder, &proj.id.to_string());
+            let owner_id_fb = fb_safe_str(builder, proj.owner_id.as_str());
+            let projectile_fb = fb::ProjectileState::create(builder, &fb::ProjectileStateArgs {
+                id: Some(id_fb), x: proj.x, y: proj.y, owner_id: Some(owner_id_fb),
+                weapon_type: map_server_weapon_to_fb(proj.weapon_type),
+                velocity_x: proj.velocity_x, velocity_y: proj.velocity_y,
+            });
+            chunker.projectiles.push(projectile_fb);
+            chunker.charge(written_before);
+        }
+
+        for pickup in self.pickups.read().iter().filter(|pickup| pickup.is_active) {
+            let id_str = pickup.id.to_string();
+            chunker.reserve(ESTIMATED_PICKUP_BYTES + id_str.len());
+            let written_before = chunker.builder.unfinished_data().len();
+            let builder = &mut chunker.builder;
+            let (pickup_type_fb, weapon_type_fb) = map_core_pickup_to_fb(&pickup.pickup_type);
+            let id_fb = fb_safe_str(builder, &id_str);
+            let pickup_fb = fb::Pickup::create(builder, &fb::PickupArgs {
+                id: Some(id_fb), x: pickup.x, y: pickup.y, pickup_type: pickup_type_fb,
+                weapon_type: weapon_type_fb.unwrap_or(fb::WeaponType::Pistol),
+                is_active: pickup.is_active,
+            });
+            chunker.pickups.push(pickup_fb);
+            chunker.charge(written_before);
+        }
+
+        for wall in shared_data.destructible_walls.values() {
+            let id_str = wall.id.to_string();
+            chunker.reserve(ESTIMATED_WALL_BYTES + id_str.len());
+            let written_before = chunker.builder.unfinished_data().len();
+            let builder = &mut chunker.builder;
+            let id_fb = fb_safe_str(builder, &id_str);
+            if wall.current_health <= 0 {
+                chunker.destroyed_walls.push(id_fb);
+            } else {
+                let wall_fb = fb::Wall::create(builder, &fb::WallArgs {
+                    id: Some(id_fb), x: wall.x, y: wall.y, width: wall.width, height: wall.height,
+                    is_destructible: wall.is_destructible,
+                    current_health: wall.current_health,
+                    max_health: wall.max_health,
+                });
+                chunker.updated_walls.push(wall_fb);
+            }
+            chunker.charge(written_before);
+        }
+
+        chunker.finish()
+    }
+
+    pub fn acknowledge_snapshot(&self, peer_id_str: &str, tick: u64) {
+        if let Some(mut ring) = self.snapshot_baselines.get_mut(peer_id_str) {
+            if !ring.acknowledge(tick) {
+                trace!("[{}]: Ignoring ack for tick {} (last acked: {:?}).", peer_id_str, tick, ring.last_acked_tick());
+            }
+        }
+    }
+
+    // Drops trackers of disconnected clients and publishes aggregate latency metrics.
+    fn update_latency_summary(&self) {
+        self.client_latencies.retain(|peer_id, _| self.data_channels_map.contains_key(peer_id));
+
+        let measured: Vec<(f32, f32)> = self.client_latencies.iter()
+            .filter(|entry| entry.samples > 0)
+            .map(|entry| (entry.smoothed_rtt_ms, entry.jitter_ms))
+            .collect();
+        if measured.is_empty() {
+            return;
+        }
+
+        let count = measured.len() as f32;
+        let avg_rtt = measured.iter().map(|(rtt, _)| rtt).sum::<f32>() / count;
+        let max_rtt = measured.iter().map(|(rtt, _)| *rtt).fold(0.0_f32, f32::max);
+        let avg_jitter = measured.iter().map(|(_, jitter)| jitter).sum::<f32>() / count;
+        server_metrics::update_client_latency_summary(avg_rtt, max_rtt, avg_jitter);
+        debug!("Client latency: avg {:.1}ms, max {:.1}ms, jitter {:.1}ms over {} clients.", avg_rtt, max_rtt, avg_jitter, measured.len());
+    }
+
+    pub(crate) fn get_server_timestamp(&self) -> u64 {
         use std::time::{SystemTime, UNIX_EPOCH};
         SystemTime::now()
             .duration_since(UNIX_EPOCH)
@@ -2195,23 +2951,230 @@ fn collect_active_walls_optimized(&self) -> Vec<Wall> {
             .as_millis() as u64
     }
 
+    /// Adds a kill to the kill feed and sends the victim its kill cam. Returns the killer's name.
+    fn record_kill(
+        &self,
+        killer_id: &PlayerID,
+        victim_id: &PlayerID,
+        victim_name: String,
+        victim_position: Vec2,
+        weapon: ServerWeaponType,
+        shot: KillShot,
+    ) -> String {
+        let killer = self.player_manager.get_player_state(killer_id)
+            .map(|p| (p.username.clone(), Vec2::new(p.x, p.y), p.health, p.max_health, p.weapon));
+        let killer_name = killer.as_ref().map_or_else(|| "World".to_string(), |k| k.0.clone());
+
+        self.push_kill_feed_entry(ServerKillFeedEntry {
+            killer_name: killer_name.clone(),
+            victim_name,
+            weapon,
+            timestamp: self.frame_counter.load(AtomicOrdering::Relaxed),
+            killer_position: killer.as_ref().map(|k| k.1),
+            victim_position,
+            cause: fb::DeathCause::Weapon,
+        });
+
+        // Bots have no data channel, and there is nothing to replay without a killer.
+        let Some((_, _, killer_health, killer_max_health, killer_weapon)) = killer else { return killer_name };
+        let Some(data_channel) = self.data_channels_map.get(victim_id.as_str()).map(|dc| dc.clone()) else { return killer_name };
+
+        let death_timestamp_ms = self.get_server_timestamp();
+        let kill_cam = {
+            let history = self.kill_cam_history.read();
+            KillCam {
+                killer_id: killer_id.clone(),
+                killer_name: killer_name.clone(),
+                weapon,
+                killer_trail: history.trail(killer_id, death_timestamp_ms),
+                victim_trail: history.trail(victim_id, death_timestamp_ms),
+                shot,
+                killer_health,
+                killer_max_health,
+                killer_weapon,
+                death_timestamp_ms,
+            }
+        };
+        let bytes = kill_cam.to_message();
+        let victim_id_for_log = victim_id.to_string();
+        tokio::spawn(async move {
+            if let Err(e) = data_channel.send(&bytes).await {
+                handle_dc_send_error(&e, &victim_id_for_log, "kill cam");
+            }
+        });
+        killer_name
+    }
+
+    fn push_kill_feed_entry(&self, entry: ServerKillFeedEntry) {
+        let mut kill_feed_guard = self.kill_feed.write();
+        kill_feed_guard.push_back(entry);
+        if kill_feed_guard.len() > MAX_KILL_FEED_HISTORY {
+            kill_feed_guard.pop_front();
+        }
+    }
+
+    /// Deals a safe zone damage tick to every living player outside the circle. Zone deaths get
+    /// a kill feed entry and a death recap like any other death, with no killer.
+    fn apply_safe_zone_damage(&self, zone: &SafeZone, damage: i32) {
+        let timestamp_ms = self.get_server_timestamp();
+        let mut deaths = Vec::new();
+        self.player_manager.for_each_player_mut(|player_id, player_state| {
+            let position = Vec2::new(player_state.x, player_state.y);
+            if !player_state.alive || zone.contains(position) {
+                return;
+            }
+            let died = player_state.take_hit(damage, None, DamageCause::SafeZone, 0.0, timestamp_ms);
+            self.global_game_events.push(GameEvent::PlayerDamaged {
+                target_id: player_id.clone(),
+                attacker_id: None,
+                damage,
+                weapon: ServerWeaponType::default(),
+                position,
+            }, EventPriority::Normal);
+            if died {
+                let victim_flag_team_id = std::mem::take(&mut player_state.is_carrying_flag_team_id);
+                if victim_flag_team_id != 0 {
+                    player_state.mark_field_changed(FIELD_FLAG);
+                }
+                let damage_log = std::mem::take(&mut player_state.damage_log);
+                deaths.push((player_id.clone(), player_state.username.clone(), player_state.team_id, position, victim_flag_team_id, damage_log));
+            }
+        });
+
+        for (victim_id, victim_name, victim_team, position, victim_flag_team_id, damage_log) in deaths {
+            info!("{} was killed by the safe zone.", victim_name);
+            self.push_kill_feed_entry(ServerKillFeedEntry {
+                killer_name: "Zone".to_string(),
+                victim_name,
+                weapon: ServerWeaponType::default(),
+                timestamp: self.frame_counter.load(AtomicOrdering::Relaxed),
+                killer_position: None,
+                victim_position: position,
+                cause: fb::DeathCause::SafeZone,
+            });
+            // The zone has no killer, so the death counts as the victim's own
+            let kill = KillInfo {
+                killer_id: victim_id.clone(),
+                victim_id: victim_id.clone(),
+                killer_team: victim_team,
+                victim_team,
+                weapon: ServerWeaponType::default(),
+                victim_position: position,
+                victim_flag_team_id,
+            };
+            self.credit_kill(&kill, &damage_log);
+            self.global_game_events.push(GameEvent::PlayerKilled {
+                victim_id: victim_id.clone(),
+                killer_id: victim_id.clone(),
+                weapon: ServerWeaponType::default(),
+                position,
+            }, EventPriority::High);
+            self.send_death_recap(&victim_id, None, &damage_log);
+            self.notify_kill(&kill);
+        }
+    }
+
+    /// Advances every living player's status effects and deals their damage over time. A death
+    /// is credited to whoever applied the effect, if anyone, and handled like any other kill.
+    fn apply_status_effects(&self, delta_time: f32) {
+        let timestamp_ms = self.get_server_timestamp();
+        let mut deaths = Vec::new();
+        self.player_manager.for_each_player_mut(|player_id, player_state| {
+            if !self.revealed_players.is_empty() && player_state.effects.get(EffectKind::Revealed).is_none() {
+                self.revealed_players.remove(player_id);
+            }
+            if !player_state.alive {
+                return;
+            }
+            for (damage, source, effect) in player_state.update_effects(delta_time) {
+                let position = Vec2::new(player_state.x, player_state.y);
+                let died = player_state.take_hit(damage, source.clone(), DamageCause::Effect(effect), 0.0, timestamp_ms);
+                self.global_game_events.push(GameEvent::PlayerDamaged {
+                    target_id: player_id.clone(),
+                    attacker_id: source.clone(),
+                    damage,
+                    weapon: ServerWeaponType::default(),
+                    position,
+                }, EventPriority::Normal);
+                if died {
+                    let victim_flag_team_id = std::mem::take(&mut player_state.is_carrying_flag_team_id);
+                    if victim_flag_team_id != 0 {
+                        player_state.mark_field_changed(FIELD_FLAG);
+                    }
+                    let damage_log = std::mem::take(&mut player_state.damage_log);
+                    deaths.push((player_id.clone(), player_state.username.clone(), player_state.team_id, position, victim_flag_team_id, damage_log, source));
+                    break;
+                }
+            }
+        });
+
+        for (victim_id, victim_name, victim_team, position, victim_flag_team_id, damage_log, source) in deaths {
+            let killer = source.filter(|source_id| *source_id != victim_id)
+                .and_then(|source_id| self.player_manager.get_player_state(&source_id)
+                    .map(|p| (source_id.clone(), p.username.clone(), p.team_id, Vec2::new(p.x, p.y))));
+            info!("{} died to a status effect.", victim_name);
+            self.push_kill_feed_entry(ServerKillFeedEntry {
+                killer_name: killer.as_ref().map_or_else(|| "Effect".to_string(), |k| k.1.clone()),
+                victim_name,
+                weapon: ServerWeaponType::default(),
+                timestamp: self.frame_counter.load(AtomicOrdering::Relaxed),
+                killer_position: killer.as_ref().map(|k| k.3),
+                victim_position: position,
+                cause: fb::DeathCause::StatusEffect,
+            });
+            // Without a killer the death counts as the victim's own, so carried flags still drop
+            let (killer_id, killer_team) = killer.as_ref().map_or((victim_id.clone(), victim_team), |k| (k.0.clone(), k.2));
+            let kill = KillInfo {
+                killer_id: killer_id.clone(),
+                victim_id: victim_id.clone(),
+                killer_team,
+                victim_team,
+                weapon: ServerWeaponType::default(),
+                victim_position: position,
+                victim_flag_team_id,
+            };
+            self.credit_kill(&kill, &damage_log);
+            self.global_game_events.push(GameEvent::PlayerKilled {
+                victim_id: victim_id.clone(),
+                killer_id: killer_id.clone(),
+                weapon: ServerWeaponType::default(),
+                position,
+            }, EventPriority::High);
+            self.send_death_recap(&victim_id, killer.as_ref().map(|k| &k.0), &damage_log);
+            self.notify_kill(&kill);
+        }
+    }
+
+    /// Sends a dead player the damage they took during the life that just ended.
+    fn send_death_recap(&self, victim_id: &PlayerID, killer_id: Option<&PlayerID>, damage_log: &DamageLog) {
+        let Some(data_channel) = self.data_channels_map.get(victim_id.as_str()).map(|dc| dc.clone()) else { return };
+        let recap = DeathRecap::new(damage_log, killer_id.cloned(), self.get_server_timestamp(), |attacker_id| {
+            self.player_manager.get_player_state(attacker_id).map(|p| p.username.clone())
+        });
+        let bytes = recap.to_message();
+        let victim_id_for_log = victim_id.to_string();
+        tokio::spawn(async move {
+            if let Err(e) = data_channel.send(&bytes).await {
+                handle_dc_send_error(&e, &victim_id_for_log, "death recap");
+            }
+        });
+    }
+
     // Extracted melee processing logic
     fn process_melee_hits(&self, melee_hit_events: Vec<GameEvent>) {
         for event in melee_hit_events {
             if let GameEvent::MeleeHit { attacker_id, position: _attack_pos, .. } = event {
                 let melee_range_sq = 50.0 * 50.0;
                 let melee_arc_angle_rad = std::f32::consts::FRAC_PI_3;
-                let melee_damage = 30;
-
                 // Get attacker info
-                let (attacker_pos_x, attacker_pos_y, attacker_rot, attacker_team_id, attacker_username) = {
+                let (attacker_pos_x, attacker_pos_y, attacker_rot, attacker_team_id, melee_damage) = {
                     if let Some(attacker_state_guard) = self.player_manager.get_player_state(&attacker_id) {
                         (
                             attacker_state_guard.x,
                             attacker_state_guard.y,
                             attacker_state_guard.rotation,
                             attacker_state_guard.team_id,
-                            attacker_state_guard.username.clone()
+                            PlayerState::get_weapon_damage(ServerWeaponType::Melee, attacker_state_guard.effects.modifiers().damage_dealt),
                         )
                     } else {
                         continue; // Attacker not found
@@ -2258,10 +3221,11 @@ fn collect_active_walls_optimized(&self) -> Vec<Wall> {
                                   attacker_id.as_str(), target_id_arc_nearby.as_str(), dist_sq, angle_diff);
 
                             // Apply damage and collect necessary data
-                            let died = target_state.apply_damage(melee_damage);
+                            let died = target_state.take_hit(melee_damage, Some(attacker_id.clone()), DamageCause::Weapon(ServerWeaponType::Melee), dist_sq.sqrt(), self.get_server_timestamp());
                             let target_position = Vec2::new(target_state.x, target_state.y);
                             let target_username = target_state.username.clone();
                             let victim_was_carrying_flag_id = if died { target_state.is_carrying_flag_team_id } else { 0 };
+                            let damage_log = if died { std::mem::take(&mut target_state.damage_log) } else { DamageLog::default() };
                             
                             if died {
                                 // Reset flag carry state on the victim
@@ -2269,14 +3233,14 @@ fn collect_active_walls_optimized(&self) -> Vec<Wall> {
                                 target_state.mark_field_changed(FIELD_FLAG);
                             }
 
-                            Some((died, target_position, target_username, victim_was_carrying_flag_id))
+                            Some((died, target_position, target_username, victim_was_carrying_flag_id, damage_log))
                         } else {
                             None
                         }
                     };
 
                     // Now process the hit results without holding any mutable borrows
-                    if let Some((died, target_position, target_username, victim_was_carrying_flag_id)) = target_hit_data {
+                    if let Some((died, target_position, target_username, victim_was_carrying_flag_id, damage_log)) = target_hit_data {
                         // Push damage event
                         self.global_game_events.push(GameEvent::PlayerDamaged {
                             target_id: target_id_arc_nearby.clone(),
@@ -2285,33 +3249,25 @@ fn collect_active_walls_optimized(&self) -> Vec<Wall> {
                             weapon: ServerWeaponType::Melee,
                             position: target_position,
                         }, EventPriority::Normal);
+                        if attacker_id != target_id_arc_nearby {
+                            self.credit_hit(&attacker_id, melee_damage, false);
+                        }
 
                         if died {
-                            // Update attacker stats
-                            if attacker_id != target_id_arc_nearby {
-                                // Get victim team for friendly fire check
-                                let victim_team = self.player_manager.get_player_state(&target_id_arc_nearby)
-                                    .map(|p| p.team_id)
-                                    .unwrap_or(0);
-                                
-                                if let Some(mut attacker_mut_state_entry) = self.player_manager.get_player_state_mut(&attacker_id) {
-                                    let attacker_mut_state = &mut *attacker_mut_state_entry;
-                                    attacker_mut_state.kills += 1;
-                                    
-                                    // Check for friendly fire
-                                    if attacker_team_id != 0 && victim_team != 0 && attacker_team_id == victim_team {
-                                        // Friendly fire: double negative score
-                                        attacker_mut_state.score -= 200;
-                                        info!("Friendly fire penalty (melee): {} killed teammate {}, -200 score", 
-                                              attacker_username, target_username);
-                                    } else {
-                                        // Normal kill: positive score
-                                        attacker_mut_state.score += 100;
-                                    }
-                                    
-                                    attacker_mut_state.mark_field_changed(FIELD_SCORE_STATS);
-                                }
-                            }
+                            // Get victim team for friendly fire check and the game mode
+                            let victim_team = self.player_manager.get_player_state(&target_id_arc_nearby)
+                                .map(|p| p.team_id)
+                                .unwrap_or(0);
+                            let kill = KillInfo {
+                                killer_id: attacker_id.clone(),
+                                victim_id: target_id_arc_nearby.clone(),
+                                killer_team: attacker_team_id,
+                                victim_team,
+                                weapon: ServerWeaponType::Melee,
+                                victim_position: target_position,
+                                victim_flag_team_id: victim_was_carrying_flag_id,
+                            };
+                            self.credit_kill(&kill, &damage_log);
 
                             // Push kill event
                             self.global_game_events.push(GameEvent::PlayerKilled {
@@ -2321,113 +3277,240 @@ fn collect_active_walls_optimized(&self) -> Vec<Wall> {
                                 position: target_position,
                             }, EventPriority::High);
 
-                            // Update kill feed
-                            {
-                                let mut kill_feed_guard = self.kill_feed.write();
-                                kill_feed_guard.push_back(ServerKillFeedEntry {
-                                    killer_name: attacker_username.clone(),
-                                    victim_name: target_username,
-                                    weapon: ServerWeaponType::Melee,
-                                    timestamp: self.frame_counter.load(std::sync::atomic::Ordering::Relaxed),
-                                });
-                                if kill_feed_guard.len() > MAX_KILL_FEED_HISTORY { 
-                                    kill_feed_guard.pop_front(); 
-                                }
-                            }
+                            let shot = KillShot { origin: Vec2::new(attacker_pos_x, attacker_pos_y), impact: target_position };
+                            self.record_kill(&attacker_id, &target_id_arc_nearby, target_username, target_position, ServerWeaponType::Melee, shot);
+                            self.send_death_recap(&target_id_arc_nearby, Some(&attacker_id), &damage_log);
+                            self.notify_kill(&kill);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+
+
+    // Removes players whose reconnect grace period ran out, letting the game mode drop anything they still carry.
+    fn expire_disconnected_sessions(&self) {
+        let grace_period = Duration::from_secs_f32(self.config.reconnect_grace_period_secs.max(0.0));
+        for player_id_str in self.session_manager.take_expired(grace_period) {
+            let player_id: PlayerID = Arc::new(player_id_str.clone());
+            let last_position = self.player_manager.get_player_state(&player_id)
+                .map(|p| Vec2::new(p.x, p.y));
+
+            if let Some(position) = last_position {
+                self.notify_player_leave(&player_id, position);
+            }
 
-                            // Handle flag dropping if victim was carrying a flag
-                            if victim_was_carrying_flag_id != 0 {
-                                let mut match_info_guard = self.match_info.write();
-
-                                // Award score to attacker's team if applicable
-                                if let Some(attacker_state_for_score) = self.player_manager.get_player_state(&attacker_id) {
-                                    if attacker_state_for_score.team_id != 0 && 
-                                       attacker_state_for_score.team_id != victim_was_carrying_flag_id {
-                                        let team_score_mut_ref = match_info_guard
-                                            .team_scores
-                                            .entry(attacker_state_for_score.team_id)
-                                            .or_insert(0);
-                                        *team_score_mut_ref += 1;
-                                        info!("Team {} scored +1 via melee kill on flag carrier by {}", 
-                                              attacker_state_for_score.team_id, attacker_id.as_str());
-                                    }
-                                }
+            self.player_manager.remove_player(&player_id_str);
+            self.player_aois.remove(&player_id_str);
+            self.player_last_sync_positions.remove(&player_id);
+            info!("[{}]: Reconnect grace period expired, player removed.", player_id_str);
+        }
+    }
 
-                                // Drop the flag
-                                if let Some(flag_state) = match_info_guard.flag_states.get_mut(&victim_was_carrying_flag_id) {
-                                    flag_state.status = fb::FlagStatus::Dropped;
-                                    flag_state.position = target_position;
-                                    flag_state.carrier_id = None;
-                                    flag_state.respawn_timer = 30.0;
-                                    
-                                    // Push flag dropped event after releasing match_info lock
-                                    drop(match_info_guard);
-                                    
-                                    self.global_game_events.push(GameEvent::FlagDropped {
-                                        player_id: target_id_arc_nearby.clone(), 
-                                        flag_team_id: victim_was_carrying_flag_id, 
-                                        position: target_position
-                                    }, EventPriority::High);
-                                    
-                                    info!("(Melee Kill) Flag of team {} dropped at ({:.1}, {:.1})", 
-                                          victim_was_carrying_flag_id, target_position.x, target_position.y);
-                                }
-                            }
-                        }
-                    }
+    /// Runs the game mode's leave hook (e.g. dropping a carried flag) before a player is removed,
+    /// and drops any scan reveals they were part of.
+    pub(crate) fn notify_player_leave(&self, player_id: &PlayerID, last_position: Vec2) {
+        forget_reveals(&self.revealed_players, player_id);
+        let mut match_info_guard = self.match_info.write();
+        self.game_mode.on_player_leave(&mut ModeContext {
+            match_info: &mut match_info_guard,
+            player_manager: &self.player_manager,
+            events: &self.global_game_events,
+        }, player_id, last_position);
+    }
+
+    /// Scores a kill for the killer, and an assist for anyone else who damaged the victim lately.
+    fn credit_kill(&self, kill: &KillInfo, damage_log: &DamageLog) {
+        if kill.killer_id == kill.victim_id {
+            return;
+        }
+        let policy = self.game_mode.scoring_policy();
+        let now_ms = self.get_server_timestamp();
+        let team_kill = kill.killer_team != 0 && kill.killer_team == kill.victim_team;
+        if let Some(mut killer_state) = self.player_manager.get_player_state_mut(&kill.killer_id) {
+            killer_state.kills += 1;
+            if team_kill {
+                killer_state.score += policy.team_kill;
+                info!("Friendly fire penalty: {} killed teammate {}, {} score", killer_state.username, kill.victim_id, policy.team_kill);
+            } else {
+                let bonus = killer_state.match_stats.record_kill(now_ms, &policy);
+                killer_state.score += policy.kill + bonus;
+            }
+            killer_state.mark_field_changed(FIELD_SCORE_STATS);
+        }
+        if team_kill {
+            return;
+        }
+        for assister_id in assist_ids(damage_log, &kill.killer_id, now_ms) {
+            if let Some(mut assister_state) = self.player_manager.get_player_state_mut(&assister_id) {
+                // Friendly fire doesn't earn an assist
+                if assister_state.team_id != 0 && assister_state.team_id == kill.victim_team {
+                    continue;
                 }
+                assister_state.match_stats.assists += 1;
+                assister_state.score += policy.assist;
+                assister_state.mark_field_changed(FIELD_SCORE_STATS);
             }
         }
     }
 
+    /// Counts damage an attacker landed; with `counts_toward_accuracy` it is also a shot hit.
+    fn credit_hit(&self, attacker_id: &PlayerID, damage: i32, counts_toward_accuracy: bool) {
+        if let Some(mut attacker_state) = self.player_manager.get_player_state_mut(attacker_id) {
+            attacker_state.match_stats.damage_dealt += damage;
+            if counts_toward_accuracy {
+                attacker_state.match_stats.shots_hit += 1;
+            }
+        }
+    }
 
+    /// Sends every client the final scoreboard, awards and MVP of the match that just ended.
+    fn send_match_summary(&self) {
+        let mut players = Vec::new();
+        self.player_manager.for_each_player(|player_id, state| players.push(PlayerSummary::from_state(player_id, state)));
+        let summary = MatchSummary::new(&self.match_info.read(), players);
+        if let Some(mvp_id) = summary.mvp_id() {
+            info!("Match MVP: {}", mvp_id);
+        }
+        for entry in self.data_channels_map.iter() {
+            let data_channel = entry.value().clone();
+            let peer_id = entry.key().clone();
+            let bytes = summary.to_message(&peer_id);
+            tokio::spawn(async move {
+                if let Err(e) = data_channel.send(&bytes).await {
+                    handle_dc_send_error(&e, &peer_id, "match summary");
+                }
+            });
+        }
+    }
 
-    fn initialize_ctf_flags(&self, match_info: &mut ServerMatchInfo) {
-        match_info.flag_states.clear();
-        let team1_flag_pos = Self::get_flag_base_position(1);
-        match_info.flag_states.insert(1, ServerFlagState {
-            team_id: 1,
-            status: fb::FlagStatus::AtBase,
-            position: team1_flag_pos,
-            carrier_id: None,
-            respawn_timer: 0.0,
-        });
-        let team2_flag_pos = Self::get_flag_base_position(2);
-        match_info.flag_states.insert(2, ServerFlagState {
-            team_id: 2,
-            status: fb::FlagStatus::AtBase,
-            position: team2_flag_pos,
-            carrier_id: None,
-            respawn_timer: 0.0,
-        });
-        info!("CTF Flags initialized. T1 at {:?}, T2 at {:?}", team1_flag_pos, team2_flag_pos);
+    fn notify_kill(&self, kill: &KillInfo) {
+        let mut match_info_guard = self.match_info.write();
+        if match_info_guard.match_state != fb::MatchStateType::Active {
+            return;
+        }
+        self.game_mode.on_kill(&mut ModeContext {
+            match_info: &mut match_info_guard,
+            player_manager: &self.player_manager,
+            events: &self.global_game_events,
+        }, kill);
+    }
+
+    pub(crate) fn notify_player_join(&self, player_id: &PlayerID) {
+        let mut match_info_guard = self.match_info.write();
+        self.game_mode.on_player_join(&mut ModeContext {
+            match_info: &mut match_info_guard,
+            player_manager: &self.player_manager,
+            events: &self.global_game_events,
+        }, player_id);
+    }
+
+    /// Team for a joining or respawning player: 0 in modes without teams.
+    pub(crate) fn assign_team(&self) -> u8 {
+        if let Some(team_id) = self.game_mode.team_for_new_player(&self.match_info.read()) {
+            team_id
+        } else if self.game_mode.spawn_rules().teams {
+            self.player_manager.assign_team_to_new_player()
+        } else {
+            0
+        }
+    }
+
+    /// Adds a new player, human or bot, on the team the mode or balancing picks and at a spawn
+    /// for that team, then lets the mode have its say. Returns their id, team and spawn position.
+    pub(crate) fn spawn_new_player(&self, id_str: String, username: String, class: PlayerClass) -> Option<(PlayerID, u8, Vec2)> {
+        let team_id = self.assign_team();
+        let spawn_pos = self.respawn_manager.get_respawn_position(
+            self,
+            &self.player_manager.id_pool.get_or_create(&id_str),
+            self.spawn_team(team_id),
+            &[] // No specific enemy positions for initial spawn balancing here
+        );
+        let player_id = self.player_manager.add_player(id_str, username, spawn_pos.x, spawn_pos.y)?;
+        if let Some(mut p_state) = self.player_manager.get_player_state_mut(&player_id) {
+            p_state.team_id = team_id;
+            p_state.class = class;
+            p_state.reset_abilities();
+            if let Some(weapon) = self.game_mode.spawn_weapon(team_id) {
+                p_state.grant_weapon(weapon, 0);
+                p_state.equip_weapon(weapon);
+            }
+            p_state.mark_field_changed(FIELD_SCORE_STATS | FIELD_FLAG);
+        }
+        self.notify_player_join(&player_id);
+        Some((player_id, team_id, spawn_pos))
     }
 
-    pub fn get_flag_base_position(team_id: u8) -> Vec2 {
-        if team_id == 1 {
-            Vec2::new(WORLD_MIN_X + 100.0, 0.0)
-        } else if team_id == 2 {
-            Vec2::new(WORLD_MAX_X - 100.0, 0.0)
+    /// The team whose spawn area a player should use, if the mode spawns by team.
+    pub(crate) fn spawn_team(&self, team_id: u8) -> Option<u8> {
+        if self.game_mode.spawn_rules().use_team_spawns && team_id != 0 {
+            Some(self.game_mode.spawn_side(&self.match_info.read(), team_id))
         } else {
-            Vec2::new(0.0, 0.0)
+            None
         }
     }
 
+    fn start_match(&self, match_info: &mut ServerMatchInfo) {
+        match_info.match_state = fb::MatchStateType::Active;
+        match_info.time_remaining = match_info.rules.time_limit_secs;
+        match_info.period = fb::MatchPeriod::Regulation;
+        info!("Match starting! Mode: {:?}, rules: {:?}", match_info.game_mode, match_info.rules);
+        self.game_mode.on_match_start(&mut ModeContext {
+            match_info,
+            player_manager: &self.player_manager,
+            events: &self.global_game_events,
+        });
+        self.player_manager.for_each_player_mut(|_id, p_state| {
+            p_state.reset_match_stats();
+            p_state.is_carrying_flag_team_id = 0;
+            p_state.mark_field_changed(FIELD_FLAG);
+        });
+        self.kill_feed.write().clear();
+    }
+
+    /// Fills in the winner shown on the results screen.
+    fn record_winner(&self, match_info: &mut ServerMatchInfo, outcome: &MatchOutcome) {
+        let (winner_id, winner_name) = match outcome {
+            MatchOutcome::TeamWin { team_id, .. } => {
+                (Some(team_id.to_string()), Some(self.game_mode.team_name(*team_id)))
+            }
+            MatchOutcome::PlayerWin { player_id, .. } => {
+                let name = self.player_manager.get_player_state(player_id)
+                    .map_or_else(|| player_id.to_string(), |state| state.username.clone());
+                (Some(player_id.to_string()), Some(name))
+            }
+            MatchOutcome::Draw { .. } => (None, Some("Draw".to_string())),
+            MatchOutcome::NoWinner => (None, None),
+        };
+        match_info.winner_id = winner_id;
+        match_info.winner_name = winner_name;
+    }
+
+    /// Replaces the rules used from the next match on; the current match keeps its own.
+    pub fn set_next_match_rules(&self, rules: MatchRules) {
+        info!("Match rules for the next match: {:?}", rules);
+        *self.next_match_rules.write() = rules;
+    }
+
     fn reset_match_state(&self, match_info: &mut ServerMatchInfo) {
-        match_info.time_remaining = 300.0;
+        match_info.time_remaining = self.next_match_rules.read().time_limit_secs;
+        match_info.period = fb::MatchPeriod::Regulation;
+        match_info.winner_id = None;
+        match_info.winner_name = None;
         // Don't clear team scores - preserve them between rounds
         // match_info.team_scores.clear();
+        // Objective state is rebuilt by the mode when the next match starts
         match_info.flag_states.clear();
-        if match_info.match_state == fb::MatchStateType::Waiting && match_info.game_mode == fb::GameModeType::CaptureTheFlag {
-            self.initialize_ctf_flags(match_info);
-        }
+        match_info.zone_states.clear();
+        match_info.safe_zone = None;
+        match_info.round = None;
         self.player_manager.for_each_player_mut(|_id, pstate| {
             // Reset individual player stats but keep their contribution to team score
-            pstate.score = 0;
-            pstate.kills = 0;
-            pstate.deaths = 0;
+            pstate.reset_match_stats();
             pstate.is_carrying_flag_team_id = 0;
-            pstate.mark_field_changed(FIELD_SCORE_STATS | FIELD_FLAG);
+            pstate.mark_field_changed(FIELD_FLAG);
         });
         self.kill_feed.write().clear();
     }
@@ -2453,10 +3536,23 @@ fn collect_active_walls_optimized(&self) -> Vec<Wall> {
             .cloned()
             .collect();
         
-        // Snapshot updated walls
-        let updated_walls = self.updated_walls_this_tick
-            .read()
-            .clone();
+        // Snapshot destructible walls (deltas diff their health against each client's baseline).
+        // Only walls that changed this tick are re-read; the rest carry over from the last tick.
+        let destructible_walls = {
+            let updated_walls = self.updated_walls_this_tick.read();
+            let mut snapshot = self.destructible_walls_snapshot.write();
+            if !updated_walls.is_empty() {
+                let walls = Arc::make_mut(&mut *snapshot);
+                for wall_id in updated_walls.keys() {
+                    match self.find_wall(*wall_id) {
+                        Some(wall) if wall.is_destructible => { walls.insert(*wall_id, wall); }
+                        Some(_) => {}
+                        None => { walls.remove(wall_id); } // Gone from the world entirely
+                    }
+                }
+            }
+            snapshot.clone()
+        };
         
         // Snapshot chat messages
         let chat_messages = self.chat_messages_queue
@@ -2467,15 +3563,7 @@ fn collect_active_walls_optimized(&self) -> Vec<Wall> {
             .collect();
         
         // Snapshot match info (read once)
-        let match_info_guard = self.match_info.read();
-        let match_info_snapshot = MatchInfoSnapshot {
-            time_remaining: match_info_guard.time_remaining,
-            match_state: match_info_guard.match_state,
-            game_mode: match_info_guard.game_mode,
-            team_scores: match_info_guard.team_scores.clone(),
-            flag_states: match_info_guard.flag_states.clone(),
-        };
-        drop(match_info_guard);
+        let match_info_snapshot = self.match_info.read().clone();
         
         // Snapshot kill feed
         let kill_feed_snapshot = self.kill_feed
@@ -2488,7 +3576,7 @@ fn collect_active_walls_optimized(&self) -> Vec<Wall> {
             timestamp_ms: current_timestamp_ms,
             events,
             destroyed_wall_ids,
-            updated_walls,
+            destructible_walls,
             chat_messages,
             match_info_snapshot,
             kill_feed_snapshot,
@@ -2568,7 +3656,7 @@ fn collect_active_walls_optimized(&self) -> Vec<Wall> {
         _pickups: &Arc<ParkingLotRwLock<Vec<Pickup>>>, 
         projectiles: &Arc<ParkingLotRwLock<Vec<Projectile>>>,
         kill_feed: &Arc<ParkingLotRwLock<VecDeque<ServerKillFeedEntry>>>,
-        chat_messages_queue: &ChatMessagesQueue,
+        _chat_messages_queue: &ChatMessagesQueue,
         frame_num: u64, 
     ) {
         let mut client_state_copy = client_states_map
@@ -2604,7 +3692,7 @@ fn collect_active_walls_optimized(&self) -> Vec<Wall> {
             &data_channel,
             &mut client_state_copy,
             &shared_data.chat_messages,
-            chat_messages_queue,
+            player_manager,
         ).await;
         
         Self::update_client_state_after_delta_static(
@@ -2673,7 +3761,9 @@ fn spawn_additional_bots(&self, count_to_add: usize) {
             else if p_state.team_id == 2 { team2_player_count +=1; }
         });
 
-        let team_id = if team1_player_count <= team2_player_count { 1 } else { 2 };
+        let team_id = if !self.game_mode.spawn_rules().teams {
+            0
+        } else if team1_player_count <= team2_player_count { 1 } else { 2 };
 
         // Get spawn points for the selected team
         let potential_spawns_for_team: Vec<Vec2> = team_spawn_areas.iter()
@@ -2694,7 +3784,7 @@ fn spawn_additional_bots(&self, count_to_add: usize) {
             )
         } else {
             // Fallback: use respawn manager
-            self.respawn_manager.get_respawn_position(self, &Arc::new(bot_player_id_str.clone()), Some(team_id as u8), &[])
+            self.respawn_manager.get_respawn_position(self, &Arc::new(bot_player_id_str.clone()), self.spawn_team(team_id as u8), &[])
         };
 
 
@@ -2702,6 +3792,8 @@ fn spawn_additional_bots(&self, count_to_add: usize) {
             if let Some(mut p_state_entry) = self.player_manager.get_player_state_mut(&player_id_arc) {
                 let p_state = &mut *p_state_entry;
                 p_state.team_id = team_id;
+                p_state.class = PlayerClass::ALL[rand::thread_rng().gen_range(0..PlayerClass::ALL.len())];
+                p_state.reset_abilities();
                 p_state.mark_field_changed(FIELD_SCORE_STATS | FIELD_FLAG);
             }
 
@@ -2734,6 +3826,10 @@ fn remove_bots(&self, count: usize) {
 
     for bot_key in bot_keys_to_remove {
         if self.bot_players.remove(&bot_key).is_some() {
+            let last_position = self.player_manager.get_player_state(&bot_key).map(|p| Vec2::new(p.x, p.y));
+            if let Some(position) = last_position {
+                self.notify_player_leave(&bot_key, position);
+            }
             self.player_manager.remove_player(bot_key.as_str());
             info!("[Bot Management] Removed bot {} to adjust match population.", bot_key);
             removed_count += 1;
@@ -2749,11 +3845,19 @@ fn remove_bots(&self, count: usize) {
 pub async fn build_delta_state_optimized(
     &self,
     peer_id_str: &str,
-    client_state: &ClientState,
     shared_data: &SharedBroadcastData,
 ) -> Result<Bytes, Box<dyn std::error::Error + Send + Sync>> {
     use std::cell::RefCell;
 
+    // Encode against the last baseline the client acked; without one, send a full AoI snapshot.
+    let current_tick = self.frame_counter.load(AtomicOrdering::Relaxed);
+    let acked_baseline = self.snapshot_baselines
+        .get(peer_id_str)
+        .and_then(|ring| ring.acked_baseline().cloned());
+    let full_snapshot = acked_baseline.is_none();
+    let baseline = acked_baseline.unwrap_or_default();
+    let mut next_baseline = SnapshotBaseline { tick: current_tick, ..Default::default() };
+
     thread_local! {
         static BUILDER: RefCell<flatbuffers::FlatBufferBuilder<'static>> = 
             RefCell::new(flatbuffers::FlatBufferBuilder::with_capacity(16384));
@@ -2774,42 +3878,131 @@ pub async fn build_delta_state_optimized(
             .map(|entry| entry.value().clone())
             .unwrap_or_else(|| PlayerAoI::new());
         
-        // Build player deltas - fix the method call
+        // Build player deltas
         let mut players_fb_vec = Vec::new();
         let mut removed_player_ids_vec = Vec::new();
+        let mut new_projectiles_vec = Vec::new();
+        let mut removed_projectile_ids_vec = Vec::new();
+        let spectator_view = self.spectators.get(peer_id_str).map(|view| view.clone());
+        let mut budget = ByteBudget::new(spectator_view.as_ref().map_or(
+            self.config.client_bytes_per_tick_budget,
+            |view| view.bytes_budget(self.config.client_bytes_per_tick_budget),
+        ));
         
-        // Add self player
-        if let Some(self_state) = self.player_manager.get_player_state(&player_id) {
-            players_fb_vec.push(create_fb_player_state_for_delta(&mut builder, &self_state, 0xFFFF));
+        // Priorities are scored from the viewer's point of view. A spectator sees through
+        // the player they follow, or from a team-less stand-in at the camera position.
+        let viewer_state = match spectator_view.as_ref() {
+            Some(view) => Some(self.spectator_viewer_state(peer_id_str, view)),
+            None => self.player_manager.get_player_state(&player_id).map(|state| state.clone()),
+        };
+        let followed_player = spectator_view.as_ref().and_then(|view| view.follow_target.clone());
+        
+        // The payload every client gets is charged before any entity
+        let shared_written_before = builder.unfinished_data().len();
+        // Build events
+        let events_vec: Vec<_> = shared_data.events.iter().take(50).map(|event| {
+            build_game_event_fb(&mut builder, event)
+        }).collect();
+        let game_events_fb = builder.create_vector(&events_vec);
+        
+        // Build kill feed
+        let kill_feed_vec: Vec<_> = shared_data.kill_feed_snapshot.iter().map(|entry| {
+            let killer_name_fb = builder.create_string(&entry.killer_name);
+            let victim_name_fb = builder.create_string(&entry.victim_name);
+            let (killer_position_fb, victim_position_fb) = kill_feed_positions_fb(&mut builder, entry);
+            fb::KillFeedEntry::create(&mut builder, &fb::KillFeedEntryArgs {
+                killer_name: Some(killer_name_fb),
+                victim_name: Some(victim_name_fb),
+                weapon: map_server_weapon_to_fb(entry.weapon),
+                timestamp: entry.timestamp as f32,
+                killer_position: killer_position_fb,
+                victim_position: Some(victim_position_fb),
+                is_headshot: false,
+                cause: entry.cause,
+            })
+        }).collect();
+        let kill_feed_fb = builder.create_vector(&kill_feed_vec);
+        
+        // Build match info if changed
+        let match_info_fb = Some(match_info_fb(&mut builder, &shared_data.match_info_snapshot));
+        let zone_states_fb = zone_states_fb(&mut builder, &shared_data.match_info_snapshot.zone_states);
+        budget.spend(builder.unfinished_data().len() - shared_written_before);
+
+        // Add self player; always sent, but it counts against the budget
+        if let (Some(self_state), None) = (viewer_state.as_ref(), spectator_view.as_ref()) {
+            let written_before = builder.unfinished_data().len();
+            players_fb_vec.push(create_fb_own_player_state(&mut builder, self_state));
+            budget.spend(builder.unfinished_data().len() - written_before);
         }
         
-        // Add visible players
+        // Entities the client already has stay in its baseline whether or not they are re-sent.
         for visible_player_id in &player_aoi.visible_players {
-            if visible_player_id != &player_id {
-                if let Some(player_state) = self.player_manager.get_player_state(visible_player_id) {
-                    players_fb_vec.push(create_fb_player_state_for_delta(&mut builder, &player_state, 0xFFFF));
-                }
+            if visible_player_id == &player_id || baseline.players.contains(visible_player_id) {
+                next_baseline.players.insert(visible_player_id.clone());
             }
         }
-        
-        // Find removed players
-        for known_player_id in &client_state.last_known_players {
-            if !player_aoi.visible_players.contains(known_player_id) && known_player_id != &player_id {
-                removed_player_ids_vec.push(builder.create_string(known_player_id.as_str()));
+        for proj_id in &player_aoi.visible_projectiles {
+            if baseline.projectile_ids.contains(proj_id) {
+                next_baseline.projectile_ids.insert(*proj_id);
             }
         }
         
-        let players_fb = builder.create_vector(&players_fb_vec);
-        let removed_players_fb = builder.create_vector(&removed_player_ids_vec);
-        
-        // Build projectile deltas
-        let mut new_projectiles_vec = Vec::new();
-        let mut removed_projectile_ids_vec = Vec::new();
+        // Score visible players and projectiles the client doesn't have yet
+        let mut priority_state = self.client_priorities.entry(peer_id_str.to_string()).or_default();
+        let mut candidates = Vec::with_capacity(player_aoi.visible_players.len() + player_aoi.visible_projectiles.len());
+        let mut candidate_projectiles = HashMap::new();
+        if let Some(viewer) = viewer_state.as_ref() {
+            for visible_player_id in &player_aoi.visible_players {
+                if visible_player_id == &player_id {
+                    continue;
+                }
+                let Some(other) = self.player_manager.get_player_state(visible_player_id) else { continue };
+                let entity = SyncEntity::Player(visible_player_id.clone());
+                let ticks_since_sent = priority_state.ticks_since_sent(&entity, current_tick);
+                let is_attacker = priority_state.is_recent_attacker(visible_player_id, current_tick);
+                candidates.push(PriorityCandidate {
+                    score: player_priority(viewer, &other, ticks_since_sent, is_attacker),
+                    estimated_bytes: estimate_player_state_bytes(&other),
+                    mandatory: ticks_since_sent >= MAX_TICKS_BETWEEN_ENTITY_UPDATES
+                        || followed_player.as_ref() == Some(visible_player_id),
+                    entity,
+                });
+            }
+            
+            let projectiles_guard = self.projectiles.read();
+            for proj in projectiles_guard.iter() {
+                if !player_aoi.visible_projectiles.contains(&proj.id) || baseline.projectile_ids.contains(&proj.id) {
+                    continue;
+                }
+                let entity = SyncEntity::Projectile(proj.id);
+                let ticks_since_sent = priority_state.ticks_since_sent(&entity, current_tick);
+                candidates.push(PriorityCandidate {
+                    score: projectile_priority(viewer, proj, ticks_since_sent),
+                    estimated_bytes: estimate_projectile_bytes(proj),
+                    mandatory: ticks_since_sent >= MAX_TICKS_BETWEEN_ENTITY_UPDATES,
+                    entity,
+                });
+                candidate_projectiles.insert(proj.id, proj.clone());
+            }
+        }
         
-        let projectiles_guard = self.projectiles.read();
-        for proj_id in &player_aoi.visible_projectiles {
-            if !client_state.last_known_projectile_ids.contains(proj_id) {
-                if let Some(proj) = projectiles_guard.iter().find(|p| p.id == *proj_id) {
+        // Fill the budget in priority order
+        sort_candidates(&mut candidates);
+        let mut deferred_count = 0;
+        for candidate in &candidates {
+            if !budget.admits(candidate.estimated_bytes, candidate.mandatory) {
+                deferred_count += 1;
+                continue;
+            }
+            let written_before = builder.unfinished_data().len();
+            match &candidate.entity {
+                SyncEntity::Player(other_id) => {
+                    let Some(player_state) = self.player_manager.get_player_state(other_id) else { continue };
+                    players_fb_vec.push(create_fb_player_state_for_delta(&mut builder, &player_state, 0xFFFF));
+                    next_baseline.players.insert(other_id.clone());
+                }
+                SyncEntity::Projectile(proj_id) => {
+                    let Some(proj) = candidate_projectiles.get(proj_id) else { continue };
                     let id_str = builder.create_string(&proj.id.to_string());
                     let owner_str = builder.create_string(proj.owner_id.as_str());
                     
@@ -2823,17 +4016,31 @@ pub async fn build_delta_state_optimized(
                         velocity_y: proj.velocity_y,  // not vy
                     });
                     new_projectiles_vec.push(proj_fb);
+                    next_baseline.projectile_ids.insert(*proj_id);
                 }
             }
+            budget.spend(builder.unfinished_data().len() - written_before);
+            priority_state.mark_sent(candidate.entity.clone(), current_tick);
+        }
+        priority_state.retain_candidates(&candidates, current_tick);
+        drop(priority_state);
+        
+        // Find removed players
+        for known_player_id in &baseline.players {
+            if !player_aoi.visible_players.contains(known_player_id) && known_player_id != &player_id {
+                removed_player_ids_vec.push(builder.create_string(known_player_id.as_str()));
+            }
         }
         
-        for known_proj_id in &client_state.last_known_projectile_ids {
+        let players_fb = builder.create_vector(&players_fb_vec);
+        let removed_players_fb = builder.create_vector(&removed_player_ids_vec);
+        
+        for known_proj_id in &baseline.projectile_ids {
             if !player_aoi.visible_projectiles.contains(known_proj_id) {
                 let id_str = builder.create_string(&known_proj_id.to_string());
                 removed_projectile_ids_vec.push(id_str);
             }
         }
-        drop(projectiles_guard);
         
         let projectiles_fb = builder.create_vector(&new_projectiles_vec);
         let removed_projectiles_fb = builder.create_vector(&removed_projectile_ids_vec);
@@ -2845,11 +4052,8 @@ pub async fn build_delta_state_optimized(
         let pickups_guard = self.pickups.read();
         for pickup_id in &player_aoi.visible_pickups {
             if let Some(pickup) = pickups_guard.iter().find(|p| p.id == *pickup_id) {
-                let should_send = if let Some(last_known_state) = client_state.last_known_pickup_states.get(pickup_id) {
-                    last_known_state.is_active != pickup.is_active
-                } else {
-                    true
-                };
+                next_baseline.pickup_states.insert(*pickup_id, pickup.is_active);
+                let should_send = baseline.pickup_states.get(pickup_id) != Some(&pickup.is_active);
                 
                 if should_send {
                     let (pickup_type_fb, weapon_type_fb) = map_core_pickup_to_fb(&pickup.pickup_type);
@@ -2868,7 +4072,7 @@ pub async fn build_delta_state_optimized(
             }
         }
         
-        for (known_pickup_id, _) in &client_state.last_known_pickup_states {
+        for known_pickup_id in baseline.pickup_states.keys() {
             if !player_aoi.visible_pickups.contains(known_pickup_id) {
                 let id_str = builder.create_string(&known_pickup_id.to_string());
                 deactivated_pickup_ids_vec.push(id_str);
@@ -2879,68 +4083,29 @@ pub async fn build_delta_state_optimized(
         let pickups_fb = builder.create_vector(&pickups_delta_vec);
         let deactivated_pickups_fb = builder.create_vector(&deactivated_pickup_ids_vec);
         
-        // Build events
-        let events_vec: Vec<_> = shared_data.events.iter().take(50).map(|event| {
-            build_game_event_fb(&mut builder, event)
-        }).collect();
-        let game_events_fb = builder.create_vector(&events_vec);
-        
-        // Build kill feed
-        let kill_feed_vec: Vec<_> = shared_data.kill_feed_snapshot.iter().map(|entry| {
-            let killer_name_fb = builder.create_string(&entry.killer_name);
-            let victim_name_fb = builder.create_string(&entry.victim_name);
-            fb::KillFeedEntry::create(&mut builder, &fb::KillFeedEntryArgs {
-                killer_name: Some(killer_name_fb),
-                victim_name: Some(victim_name_fb),
-                weapon: map_server_weapon_to_fb(entry.weapon),
-                timestamp: entry.timestamp as f32,
-                killer_position: None,
-                victim_position: None,
-                is_headshot: false,
-            })
-        }).collect();
-        let kill_feed_fb = builder.create_vector(&kill_feed_vec);
-        
-        // Build match info if changed
-        let match_info_fb = {
-            let match_snapshot = &shared_data.match_info_snapshot;
-            let team_scores_vec: Vec<_> = match_snapshot.team_scores.iter().map(|(team_id, score)| {
-                fb::TeamScoreEntry::create(&mut builder, &fb::TeamScoreEntryArgs {
-                    team_id: *team_id as i8,
-                    score: *score,
-                })
-            }).collect();
-            let team_scores_fb = builder.create_vector(&team_scores_vec);
-            
-            Some(fb::MatchInfo::create(&mut builder, &fb::MatchInfoArgs {
-                time_remaining: match_snapshot.time_remaining,
-                match_state: match_snapshot.match_state,
-                winner_id: None,
-                winner_name: None,
-                game_mode: match_snapshot.game_mode,
-                team_scores: Some(team_scores_fb),
-            }))
-        };
-        
-        // Build destroyed wall IDs
-        let destroyed_walls_vec: Vec<_> = shared_data.destroyed_wall_ids.iter()
-            .map(|id| builder.create_string(&id.to_string()))
-            .collect();
-        let destroyed_wall_ids_fb = if !destroyed_walls_vec.is_empty() {
-            Some(builder.create_vector(&destroyed_walls_vec))
-        } else {
-            None
-        };
-        
-        // Build updated walls (respawned walls)
+        // Destructible walls whose health differs from what the client acked; walls it knew of
+        // that are gone from the world count as destroyed
+        let mut destroyed_walls_vec = Vec::new();
         let mut updated_walls_vec = Vec::new();
-        
-        // Get updated walls from shared data (not from instance to avoid race condition)
-        for (wall_id, wall_data) in shared_data.updated_walls.iter() {
-            // Check if this wall is visible to the player
-            if player_aoi.visible_walls.contains(wall_id) {
-                info!("[{}] Sending updated wall {} to client (health: {}/{})", peer_id_str, wall_id, wall_data.current_health, wall_data.max_health);
-                let id_fb = builder.create_string(&wall_data.id.to_string());
+        for wall_id in baseline.wall_health.keys() {
+            if !shared_data.destructible_walls.contains_key(wall_id) {
+                destroyed_walls_vec.push(builder.create_string(&wall_id.to_string()));
+            }
+        }
+        for (wall_id, wall_data) in shared_data.destructible_walls.iter() {
+            if !player_aoi.visible_walls.contains(wall_id) {
+                continue;
+            }
+            next_baseline.wall_health.insert(*wall_id, wall_data.current_health);
+            if baseline.wall_health.get(wall_id) == Some(&wall_data.current_health) {
+                continue;
+            }
+
+            let id_fb = builder.create_string(&wall_data.id.to_string());
+            if wall_data.current_health <= 0 {
+                destroyed_walls_vec.push(id_fb);
+            } else {
+                trace!("[{}] Sending updated wall {} to client (health: {}/{})", peer_id_str, wall_id, wall_data.current_health, wall_data.max_health);
                 let wall_fb = fb::Wall::create(&mut builder, &fb::WallArgs {
                     id: Some(id_fb),
                     x: wall_data.x,
@@ -2954,6 +4119,11 @@ pub async fn build_delta_state_optimized(
                 updated_walls_vec.push(wall_fb);
             }
         }
+        let destroyed_wall_ids_fb = if !destroyed_walls_vec.is_empty() {
+            Some(builder.create_vector(&destroyed_walls_vec))
+        } else {
+            None
+        };
         
         let updated_walls_fb = if !updated_walls_vec.is_empty() {
             Some(builder.create_vector(&updated_walls_vec))
@@ -2978,6 +4148,10 @@ pub async fn build_delta_state_optimized(
             flag_states: None,
             removed_player_ids: Some(removed_players_fb),
             updated_walls: updated_walls_fb,
+            server_tick: current_tick,
+            baseline_tick: baseline.tick,
+            full_snapshot,
+            zone_states: zone_states_fb,
         };
         
         let delta_state = fb::DeltaStateMessage::create(&mut builder, &delta_state_args);
@@ -2992,9 +4166,19 @@ pub async fn build_delta_state_optimized(
         builder.finish(game_msg, None);
         let bytes = Bytes::from(builder.finished_data().to_vec());
         
-        trace!("[{}] DeltaBuilder: Completed in {:?}", peer_id_str, build_start.elapsed());
+        server_metrics::record_delta_message(bytes.len(), deferred_count);
+        if deferred_count > 0 {
+            trace!("[{}] DeltaBuilder: Deferred {} entity updates ({} bytes spent)", peer_id_str, deferred_count, budget.spent());
+        }
+        trace!("[{}] DeltaBuilder: Completed in {:?} (tick {}, baseline {})", peer_id_str, build_start.elapsed(), current_tick, baseline.tick);
         Ok(bytes)
     })
+    .inspect(|_| {
+        self.snapshot_baselines
+            .entry(peer_id_str.to_string())
+            .or_default()
+            .record(next_baseline);
+    })
 }
 
     // 1. Fix build_projectile_deltas_optimized - add the missing method
@@ -3104,7 +4288,7 @@ fn build_events_fb<'a>(
                 let self_player_state = &*self_player_state_guard;
                 last_processed_input_for_client = self_player_state.last_processed_input_sequence;
                 if self_player_state.changed_fields > 0 {
-                    players_delta_fb_vec.push(create_fb_player_state_for_delta(&mut builder, self_player_state, self_player_state.changed_fields));
+                    players_delta_fb_vec.push(create_fb_own_player_state(&mut builder, self_player_state));
                     player_fields_mask_fb_vec.push(self_player_state.changed_fields as u8);
                 }
             }
@@ -3186,6 +4370,10 @@ fn build_events_fb<'a>(
                 flag_states: None,
                 removed_player_ids: None,
                 updated_walls: None,
+                server_tick: 0,
+                baseline_tick: 0,
+                full_snapshot: false,
+                zone_states: None,
             };
             
             let delta_state_msg = fb::DeltaStateMessage::create(&mut builder, &delta_state_args);
@@ -3201,6 +4389,16 @@ fn build_events_fb<'a>(
     }
     
     // Fast AoI data retrieval with minimal locking
+    fn spectator_viewer_state(&self, peer_id_str: &str, view: &SpectatorView) -> PlayerState {
+        let followed = view.follow_target.as_ref()
+            .filter(|_| view.mode == SpectatorMode::Follow)
+            .and_then(|target| self.player_manager.get_player_state(target));
+        match followed {
+            Some(followed_state) => followed_state.clone(),
+            None => PlayerState::new(peer_id_str.to_string(), String::new(), view.camera_x, view.camera_y),
+        }
+    }
+
     fn get_player_aoi_data_fast(&self, player_id: &PlayerID) -> PlayerAoI {
         if let Some(aoi_entry) = self.player_aois.get(player_id.as_str()) {
             PlayerAoI {
@@ -3334,7 +4532,7 @@ fn build_events_fb<'a>(
         // Process self first
         if let Some(self_state) = self.player_manager.get_player_state(self_player_id) {
             if self_state.changed_fields > 0 {
-                players_fb_vec.push(create_fb_player_state_for_delta(builder, &self_state, self_state.changed_fields));
+                players_fb_vec.push(create_fb_own_player_state(builder, &self_state));
             }
         }
         
@@ -3447,6 +4645,10 @@ fn build_events_fb<'a>(
             .collect();
     
         for (peer_id_str, data_channel_arc) in client_entries {
+            // Casters only ever get the delayed feed
+            if self.caster_seats.contains_key(&peer_id_str) {
+                continue;
+            }
             let needs_initial = !self.client_states_map
                 .read() // Acquire read lock first
                 .get(&peer_id_str)
@@ -3465,6 +4667,15 @@ fn build_events_fb<'a>(
             }
         }
         debug!("[Frame {}] Broadcast processing loop complete.", current_frame);
+
+        self.record_caster_frame(&shared_broadcast_data);
+        self.replay_caster_feed().await;
+
+        if current_frame.is_multiple_of(self.config.tick_rate.max(1)) {
+            self.snapshot_baselines.retain(|peer_id, _| self.data_channels_map.contains_key(peer_id));
+            self.client_priorities.retain(|peer_id, _| self.data_channels_map.contains_key(peer_id));
+            self.update_latency_summary();
+        }
     }
 
     
@@ -3535,9 +4746,12 @@ fn build_events_fb<'a>(
             let mut player_aoi_data_for_initial_state = Self::get_empty_player_aoi(); // Default empty
 
             if let Some(self_pstate_guard) = self.player_manager.get_player_state(&self_player_id_arc) {
-                players_fb_vec.push(create_fb_player_state_for_delta(&mut builder, &*self_pstate_guard, 0xFFFF));
+                players_fb_vec.push(create_fb_own_player_state(&mut builder, &self_pstate_guard));
                 // Fetch AoI based on self's current position for other entities
                 player_aoi_data_for_initial_state = self.get_player_aoi_data_fast(&self_player_id_arc);
+            } else if self.spectators.contains_key(peer_id_str) {
+                // Spectators have no player; their AoI follows the spectator view.
+                player_aoi_data_for_initial_state = self.get_player_aoi_data_fast(&self_player_id_arc);
             } else {
                 warn!("[Frame {} Client {}] InitialState: Self player state not found!", frame, peer_id_str);
             }
@@ -3590,19 +4804,7 @@ fn build_events_fb<'a>(
 
             // 5. Match Info (from shared_data snapshot)
             let match_snapshot = &shared_data.match_info_snapshot;
-            let fb_team_scores_vec: Vec<_> = match_snapshot.team_scores.iter().map(|(team_id, score)| {
-                fb::TeamScoreEntry::create(&mut builder, &fb::TeamScoreEntryArgs { team_id: *team_id as i8, score: *score })
-            }).collect();
-            let team_scores_fb = builder.create_vector(&fb_team_scores_vec);
-
-            let match_info_fb = fb::MatchInfo::create(&mut builder, &fb::MatchInfoArgs {
-                time_remaining: match_snapshot.time_remaining,
-                match_state: match_snapshot.match_state,
-                winner_id: None, // Typically not known at initial state
-                winner_name: None,
-                game_mode: match_snapshot.game_mode,
-                team_scores: Some(team_scores_fb),
-            });
+            let match_info_fb = match_info_fb(&mut builder, match_snapshot);
 
             // 6. Flag States (from shared_data snapshot)
             let fb_flag_states_vec: Vec<_> = match_snapshot.flag_states.values().map(|fs| {
@@ -3614,6 +4816,7 @@ fn build_events_fb<'a>(
                 })
             }).collect();
             let flag_states_fb = builder.create_vector(&fb_flag_states_vec);
+            let zone_states_fb = zone_states_fb(&mut builder, &match_snapshot.zone_states);
 
             // 7. Map Name
             let map_name_fb = fb_safe_str(&mut builder, "Massive Arena"); // Or get from config/state
@@ -3635,6 +4838,7 @@ fn build_events_fb<'a>(
                 flag_states: Some(flag_states_fb),
                 timestamp: timestamp_initial,
                 map_name: Some(map_name_fb),
+                zone_states: zone_states_fb,
             };
             let initial_state_msg = fb::InitialStateMessage::create(&mut builder, &initial_state_args);
 
@@ -3669,7 +4873,7 @@ fn build_events_fb<'a>(
         if let Some(aoi_entry) = self.player_aois.get(peer_id_str) {
             let p_aoi = aoi_entry.value();
             if let Some(self_pstate_guard) = self.player_manager.get_player_state(&self_player_id_arc) {
-                 players_fb_vec.push(create_fb_player_state_for_delta(&mut builder, &*self_pstate_guard, 0xFFFF));
+                 players_fb_vec.push(create_fb_own_player_state(&mut builder, &self_pstate_guard));
             }
             for visible_player_id in p_aoi.visible_players.iter() {
                 if visible_player_id != &self_player_id_arc {
@@ -3680,7 +4884,7 @@ fn build_events_fb<'a>(
             }
         } else {
              if let Some(self_pstate_guard) = self.player_manager.get_player_state(&self_player_id_arc) {
-                 players_fb_vec.push(create_fb_player_state_for_delta(&mut builder, &*self_pstate_guard, 0xFFFF));
+                 players_fb_vec.push(create_fb_own_player_state(&mut builder, &self_pstate_guard));
             }
         }
         let players_fb = builder.create_vector(&players_fb_vec);
@@ -3736,10 +4940,6 @@ fn build_events_fb<'a>(
 
         let player_id_fb_initial = builder.create_string(peer_id_str);
         let match_info_guard = self.match_info.read();
-        let fb_team_scores_vec: Vec<_> = match_info_guard.team_scores.iter().map(|(team_id, score)| {
-            fb::TeamScoreEntry::create(&mut builder, &fb::TeamScoreEntryArgs { team_id: *team_id as i8, score: *score })
-        }).collect();
-        let team_scores_fb = builder.create_vector(&fb_team_scores_vec);
 
         let fb_flag_states_vec: Vec<_> = match_info_guard.flag_states.values().map(|fs| {
             let carrier_id_fb = fs.carrier_id.as_ref().map(|id| builder.create_string(id.as_str()));
@@ -3750,15 +4950,8 @@ fn build_events_fb<'a>(
             })
         }).collect();
         let flag_states_fb = builder.create_vector(&fb_flag_states_vec);
-
-        let match_info_fb = fb::MatchInfo::create(&mut builder, &fb::MatchInfoArgs {
-            time_remaining: match_info_guard.time_remaining,
-            match_state: match_info_guard.match_state,
-            winner_id: None,
-            winner_name: None,
-            game_mode: match_info_guard.game_mode,
-            team_scores: Some(team_scores_fb),
-        });
+        let zone_states_fb = zone_states_fb(&mut builder, &match_info_guard.zone_states);
+        let match_info_fb = match_info_fb(&mut builder, &match_info_guard);
         drop(match_info_guard);
 
         let map_name_fb = builder.create_string("Massive Arena 10v10");
@@ -3774,6 +4967,7 @@ fn build_events_fb<'a>(
             flag_states: Some(flag_states_fb),
             timestamp: timestamp_initial,
             map_name: Some(map_name_fb),
+            zone_states: zone_states_fb,
         };
         let initial_state_msg = fb::InitialStateMessage::create(&mut builder, &initial_state_args);
 
@@ -3863,7 +5057,7 @@ fn build_events_fb<'a>(
             last_processed_input_for_client = self_player_state.last_processed_input_sequence;
 
             if self_player_state.changed_fields > 0 || client_state.last_known_player_states.get(&self_player_id_arc).map_or(true, |old| old.changed_fields == 0xFFFF) {
-                players_delta_fb_vec.push(create_fb_player_state_for_delta(&mut builder, self_player_state, self_player_state.changed_fields));
+                players_delta_fb_vec.push(create_fb_own_player_state(&mut builder, self_player_state));
                 player_fields_mask_fb_vec.push(self_player_state.changed_fields as u8);
                 client_state.last_known_player_states.insert(self_player_id_arc.clone(), self_player_state.clone());
             }
@@ -4000,38 +5194,7 @@ fn build_events_fb<'a>(
             client_state.last_known_match_time_remaining = Some(current_match_info_guard.time_remaining);
             client_state.last_known_team_scores = current_match_info_guard.team_scores.clone();
 
-            let fb_team_scores_vec_delta: Vec<_> = current_match_info_guard.team_scores.iter().map(|(team_id_ref, score_ref)| {
-                fb::TeamScoreEntry::create(&mut builder, &fb::TeamScoreEntryArgs { team_id: *team_id_ref as i8, score: *score_ref })
-            }).collect();
-            let team_scores_fb_delta = builder.create_vector(&fb_team_scores_vec_delta);
-
-            let mut winner_id_fb = None;
-            let mut winner_name_fb = None;
-            if current_match_info_guard.match_state == fb::MatchStateType::Ended {
-                if current_match_info_guard.game_mode == fb::GameModeType::TeamDeathmatch || current_match_info_guard.game_mode == fb::GameModeType::CaptureTheFlag {
-                    let t1_score = current_match_info_guard.team_scores.get(&1).cloned().unwrap_or(0);
-                    let t2_score = current_match_info_guard.team_scores.get(&2).cloned().unwrap_or(0);
-                    if t1_score > t2_score {
-                        winner_id_fb = Some(builder.create_string("1"));
-                        winner_name_fb = Some(builder.create_string("Red Team"));
-                    } else if t2_score > t1_score {
-                        winner_id_fb = Some(builder.create_string("2"));
-                        winner_name_fb = Some(builder.create_string("Blue Team"));
-                    } else if t1_score == t2_score && t1_score > 0 {
-                        // Only a draw if both teams have equal non-zero scores
-                        winner_name_fb = Some(builder.create_string("Draw"));
-                    }
-                    // If 0-0, leave winner_name_fb as None (no winner)
-                }
-            }
-            Some(fb::MatchInfo::create(&mut builder, &fb::MatchInfoArgs{
-                time_remaining: current_match_info_guard.time_remaining,
-                match_state: current_match_info_guard.match_state,
-                winner_id: winner_id_fb,
-                winner_name: winner_name_fb,
-                game_mode: current_match_info_guard.game_mode,
-                team_scores: Some(team_scores_fb_delta),
-            }))
+            Some(match_info_fb(&mut builder, &current_match_info_guard))
         } else { None };
 
         let flag_states_delta_fb_vec: Vec<_> = if match_info_fb_offset.is_some() && current_match_info_guard.game_mode == fb::GameModeType::CaptureTheFlag {
@@ -4045,6 +5208,7 @@ fn build_events_fb<'a>(
             }).collect()
         } else { Vec::new() };
         let flag_states_delta_fb = if !flag_states_delta_fb_vec.is_empty() { Some(builder.create_vector(&flag_states_delta_fb_vec)) } else { None };
+        let zone_states_delta_fb = if match_info_fb_offset.is_some() { zone_states_fb(&mut builder, &current_match_info_guard.zone_states) } else { None };
         drop(current_match_info_guard);
 
         // Kill Feed
@@ -4054,10 +5218,12 @@ fn build_events_fb<'a>(
             .map(|kf_entry| {
                 let killer_fb = builder.create_string(&kf_entry.killer_name);
                 let victim_fb = builder.create_string(&kf_entry.victim_name);
+                let (killer_position_fb, victim_position_fb) = kill_feed_positions_fb(&mut builder, kf_entry);
                 fb::KillFeedEntry::create(&mut builder, &fb::KillFeedEntryArgs{
                     killer_name: Some(killer_fb), victim_name: Some(victim_fb),
                     weapon: map_server_weapon_to_fb(kf_entry.weapon), timestamp: kf_entry.timestamp as f32,
-                    killer_position: None, victim_position: None, is_headshot: false,
+                    killer_position: killer_position_fb, victim_position: Some(victim_position_fb), is_headshot: false,
+                    cause: kf_entry.cause,
                 })
             }).collect::<Vec<_>>();
         client_state.last_kill_feed_count_sent = kill_feed_guard.len();
@@ -4200,6 +5366,10 @@ fn build_events_fb<'a>(
             flag_states: flag_states_delta_fb,
             removed_player_ids: removed_players_fb,
             updated_walls: updated_walls_fb,
+            server_tick: 0,
+            baseline_tick: 0,
+            full_snapshot: false,
+            zone_states: zone_states_delta_fb,
         };
         let delta_state_msg = fb::DeltaStateMessage::create(&mut builder, &delta_state_args);
 
@@ -4230,10 +5400,11 @@ fn build_events_fb<'a>(
         let last_seq_sent = client_state.last_chat_message_seq_sent;
         let mut max_seq_in_batch = last_seq_sent;
 
+        let recipient = ChatRecipient::lookup(&self.player_manager, peer_id_str);
         let chat_messages_to_send: Vec<ChatMessage> = {
             let chat_guard = self.chat_messages_queue.read().await;
             chat_guard.iter()
-                .filter(|msg| msg.seq > last_seq_sent)
+                .filter(|msg| msg.seq > last_seq_sent && recipient.can_see(msg))
                 .cloned()
                 .collect()
         };
@@ -4252,6 +5423,7 @@ fn build_events_fb<'a>(
                     username: Some(username_fb),
                     message: Some(message_fb),
                     timestamp: chat_entry.timestamp,
+                    scope: chat_entry.scope.to_fb(),
                 });
 
                 let game_message_offset = fb::GameMessage::create(&mut chat_builder, &fb::GameMessageArgs {
@@ -4458,7 +5630,7 @@ fn build_events_fb<'a>(
             data_channel,
             &mut client_state_copy,
             chat_messages,
-            &self.chat_messages_queue,
+            &self.player_manager,
         ).await;
         // Update the client state
         self.client_states_map.write().insert(peer_id_str.to_string(), client_state_copy);
@@ -4467,6 +5639,16 @@ fn build_events_fb<'a>(
 }
 
 
+fn explosion_of(proj: &Projectile, position: Vec2) -> Explosion {
+    Explosion {
+        owner_id: proj.owner_id.clone(),
+        weapon: proj.weapon_type,
+        position,
+        origin: Vec2::new(proj.origin_x, proj.origin_y),
+        damage: proj.damage,
+    }
+}
+
 fn event_position(event: &GameEvent) -> Vec2 {
     match event {
         GameEvent::PlayerDamaged { position, .. } => *position,
@@ -4482,6 +5664,10 @@ fn event_position(event: &GameEvent) -> Vec2 {
         GameEvent::FlagDropped { position, .. } => *position,
         GameEvent::FlagReturned { position, .. } => *position,
         GameEvent::FlagCaptured { position, .. } => *position,
+        GameEvent::ZoneCaptured { position, .. } => *position,
+        GameEvent::Explosion { position, .. } => *position,
+        GameEvent::AbilityUsed { position, .. } => *position,
+        GameEvent::PlayerInfected { position, .. } => *position,
         _ => Vec2::zero(),
     }
 }
@@ -4493,6 +5679,9 @@ fn event_instigator_id(event: &GameEvent) -> Option<PlayerID> {
         GameEvent::PowerupCollected { player_id, .. } => Some(player_id.clone()),
         GameEvent::FlagGrabbed { player_id, .. } => Some(player_id.clone()),
         GameEvent::FlagCaptured { capturer_id, .. } => Some(capturer_id.clone()),
+        GameEvent::Explosion { owner_id, .. } => Some(owner_id.clone()),
+        GameEvent::AbilityUsed { player_id, .. } => Some(player_id.clone()),
+        GameEvent::PlayerInfected { infected_by, .. } => Some(infected_by.clone()),
         _ => None,
     }
 }
@@ -4507,6 +5696,8 @@ fn event_target_id(event: &GameEvent) -> Option<String> {
         GameEvent::MeleeHit { target_id, .. } => target_id.as_ref().map(|id| id.to_string()),
         GameEvent::FlagDropped { flag_team_id, .. } => Some(flag_team_id.to_string()),
         GameEvent::FlagReturned { flag_team_id, .. } => Some(flag_team_id.to_string()),
+        GameEvent::ZoneCaptured { zone_id, .. } => Some(zone_id.to_string()),
+        GameEvent::PlayerInfected { player_id, .. } => Some(player_id.to_string()),
         _ => None,
     }
 }
@@ -4515,6 +5706,7 @@ fn event_weapon_type(event: &GameEvent) -> Option<ServerWeaponType> {
         GameEvent::PlayerDamaged { weapon, .. } => Some(*weapon),
         GameEvent::PlayerKilled { weapon, .. } => Some(*weapon),
         GameEvent::WeaponFired { weapon, .. } => Some(*weapon),
+        GameEvent::Explosion { weapon, .. } => Some(*weapon),
         _ => None,
     }
 }
@@ -4522,6 +5714,9 @@ fn event_value(event: &GameEvent) -> Option<f32> {
     match event {
         GameEvent::PlayerDamaged { damage, .. } => Some(*damage as f32),
         GameEvent::WallImpact { damage, .. } => Some(*damage as f32),
+        GameEvent::ZoneCaptured { capturing_team_id, .. } => Some(*capturing_team_id as f32),
+        GameEvent::Explosion { radius, .. } => Some(*radius),
+        GameEvent::AbilityUsed { ability, .. } => Some(ability.to_fb().0 as f32),
         _ => None,
     }
 }
@@ -4538,8 +5733,179 @@ fn map_game_event_type_to_fb(event: &GameEvent) -> fb::GameEventType {
          GameEvent::FlagDropped { .. } => fb::GameEventType::FlagDropped,
          GameEvent::FlagReturned { .. } => fb::GameEventType::FlagReturned,
          GameEvent::FlagCaptured { .. } => fb::GameEventType::FlagCaptured,
+         GameEvent::ZoneCaptured { .. } => fb::GameEventType::ZoneCaptured,
+         GameEvent::Explosion { .. } => fb::GameEventType::Explosion,
+         GameEvent::AbilityUsed { .. } => fb::GameEventType::AbilityUsed,
+         GameEvent::PlayerInfected { .. } => fb::GameEventType::PlayerInfected,
          GameEvent::PlayerJoined { .. } | GameEvent::PlayerLeft { .. } => fb::GameEventType::BulletImpact, // Placeholder, consider specific events
          GameEvent::MeleeHit { .. } => fb::GameEventType::PlayerDamageEffect, // Could be a specific MeleeImpact event type
          GameEvent::Footstep { .. } => fb::GameEventType::BulletImpact,  // Placeholder, consider specific events
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::systems::physics::ballistics::Shot;
+
+    fn test_server() -> MassiveGameServer {
+        let config = Arc::new(ServerConfig::default());
+        let thread_pools = Arc::new(ThreadPoolSystem::new(config.clone()).unwrap());
+        MassiveGameServer::new(
+            config,
+            thread_pools,
+            Arc::new(DashMap::new()),
+            Arc::new(ParkingLotRwLock::new(HashMap::new())),
+            Arc::new(tokio::sync::RwLock::new(VecDeque::new())),
+            Arc::new(DashMap::new()),
+        )
+    }
+
+    fn add_test_player(server: &MassiveGameServer, name: &str, position: Vec2) -> PlayerID {
+        let player_id = server.player_manager.add_player(name.to_string(), name.to_string(), position.x, position.y).unwrap();
+        server.spatial_index.update_player_position(player_id.clone(), position.x, position.y);
+        player_id
+    }
+
+    #[test]
+    fn test_server_movement_matches_shared_step() {
+        let server = test_server();
+        let mover = add_test_player(&server, "mover", Vec2::new(0.0, 0.0));
+        let input = PlayerInputData {
+            timestamp: 0, sequence: 1, rotation: 0.6,
+            move_forward: true, move_backward: false, move_left: false, move_right: true,
+            shooting: false, reload: false, melee_attack: false, change_weapon_slot: 0, use_ability_slot: 0,
+        };
+        let delta_time = TICK_DURATION.as_secs_f32();
+        let mut state = server.player_manager.get_player_state_mut(&mover).unwrap();
+        let reach = PLAYER_RADIUS + PLAYER_BASE_SPEED * 2.0 * delta_time + 10.0;
+        let walls = wall_rects(&server.wall_spatial_index.query_radius(state.x, state.y, reach));
+        let expected = step_movement(&movement_state_of(&state), &movement_input_of(&input, &state), &walls, delta_time);
+
+        server.apply_input_to_player_state(&mut state, &input, Instant::now());
+        server.process_player_movement_optimized(&mut state, &[], delta_time);
+        assert_eq!(movement_state_of(&state), expected);
+    }
+
+    #[test]
+    fn test_bots_join_through_team_balancing() {
+        let server = test_server();
+        assert!(server.game_mode.spawn_rules().teams);
+        let human = add_test_player(&server, "human", Vec2::new(0.0, 0.0));
+        server.player_manager.get_player_state_mut(&human).unwrap().team_id = 1;
+
+        server.spawn_initial_bots(3);
+        let mut team_sizes = [0; 2];
+        server.player_manager.for_each_player(|_id, state| team_sizes[state.team_id as usize - 1] += 1);
+        assert_eq!(team_sizes, [2, 2]);
+    }
+
+    #[test]
+    fn test_grenade_can_kill_its_thrower() {
+        let server = test_server();
+        let position = Vec2::new(100.0, 100.0);
+        let thrower = add_test_player(&server, "thrower", position);
+        server.player_manager.get_player_state_mut(&thrower).unwrap().health = 1;
+
+        let explosion = Explosion {
+            owner_id: thrower.clone(),
+            weapon: ServerWeaponType::GrenadeLauncher,
+            position,
+            origin: position,
+            damage: 100,
+        };
+        executor::block_on(server.apply_projectile_results(ProjectileResults {
+            total_processed: 1,
+            hits: Vec::new(),
+            wall_hits: Vec::new(),
+            detonations: vec![explosion],
+            to_remove: Vec::new(),
+        }));
+
+        let state = server.player_manager.get_player_state(&thrower).unwrap();
+        assert!(!state.alive);
+        assert_eq!((state.deaths, state.kills), (1, 0));
+    }
+
+    #[test]
+    fn test_projectile_hits_come_back_in_projectile_order() {
+        let server = test_server();
+        // Somewhere the generated map left open
+        let position = (0..100)
+            .map(|i| Vec2::new(WORLD_MIN_X + 200.0 + (i % 10) as f32 * 300.0, WORLD_MIN_Y + 200.0 + (i / 10) as f32 * 300.0))
+            .find(|p| server.wall_spatial_index.query_radius(p.x, p.y, 100.0).is_empty())
+            .unwrap();
+        add_test_player(&server, "target", position);
+
+        // Enough projectiles to be split across several parallel chunks
+        let shooters: Vec<PlayerID> = (0..200).map(|i| Arc::new(format!("shooter {}", i))).collect();
+        *server.projectiles.write() = shooters.iter()
+            .map(|shooter| Projectile::new(shooter.clone(), ServerWeaponType::Rifle, position.x - PLAYER_RADIUS - 1.0, position.y, 1.0, 0.0, 1.0))
+            .collect();
+        let results = executor::block_on(server.process_projectiles_optimized(TICK_DURATION.as_secs_f32()));
+
+        let attackers: Vec<PlayerID> = results.hits.into_iter().map(|hit| hit.0).collect();
+        assert_eq!(attackers, shooters);
+    }
+
+    #[test]
+    fn test_shot_passes_through_a_wall_once_destroyed() {
+        let server = test_server();
+        let wall = Wall { id: 999_999, x: 0.0, y: 0.0, width: 20.0, height: 200.0, is_destructible: true, current_health: 50, max_health: 50 };
+        let partition_idx = server.world_partition_manager.get_partition_index_for_point(10.0, 100.0);
+        server.world_partition_manager.get_partition(partition_idx).unwrap().add_wall_on_load(wall.clone());
+        server.refresh_wall_index();
+
+        let trace_across = || {
+            let spec = ballistic_spec(ServerWeaponType::Rifle);
+            let mut shot = Shot { position: Vec2::new(-100.0, 100.0), velocity: Vec2::new(1000.0, 0.0), damage: 10, penetrations_left: 0, ricochets_left: 0, inside_wall: None };
+            // Only the test wall, whatever else the generated map put along the path
+            trace_shot(&mut shot, &spec, Vec2::new(120.0, 100.0), |from, to| {
+                server.wall_spatial_index.query_line_segment(from.x, from.y, to.x, to.y).into_iter().filter(|w| w.id == wall.id).collect()
+            }, &[])
+        };
+        assert!(trace_across().stopped);
+        server.damage_wall(wall.id, wall.max_health);
+        assert!(!trace_across().stopped);
+    }
+
+    #[test]
+    fn test_player_estimate_covers_what_other_players_are_sent() {
+        let mut player = PlayerState::new("player".to_string(), "a longer username".to_string(), 0.0, 0.0);
+        for step in 0..3 {
+            match step {
+                1 => { player.inventory.grant(ServerWeaponType::Sniper, 0); player.inventory.grant(ServerWeaponType::Shotgun, 0); }
+                2 => { player.effects.apply(EffectKind::Burn, None); player.effects.apply(EffectKind::Slow, None); }
+                _ => {}
+            }
+            let mut builder = flatbuffers::FlatBufferBuilder::new();
+            create_fb_player_state(&mut builder, &player, false);
+            let encoded = builder.unfinished_data().len();
+            let estimate = estimate_player_state_bytes(&player);
+            // Enough to keep the budget honest without crowding out other entities
+            assert!(estimate >= encoded && estimate <= encoded * 5 / 4, "step {}: estimate {} for {} bytes", step, estimate, encoded);
+        }
+    }
+
+    #[test]
+    fn test_caster_frame_splits_into_chunks() {
+        let mut chunker = CasterFrameChunker::new(7, 1_000);
+        for id in 0..(CASTER_FRAME_CHUNK_BYTES / 4) {
+            chunker.reserve(ESTIMATED_PICKUP_BYTES);
+            let written_before = chunker.builder.unfinished_data().len();
+            let id_fb = fb_safe_str(&mut chunker.builder, &id.to_string());
+            chunker.destroyed_walls.push(id_fb);
+            chunker.charge(written_before);
+        }
+        let chunks = chunker.finish();
+
+        assert!(chunks.len() > 1);
+        for (index, chunk) in chunks.iter().enumerate() {
+            assert!(chunk.len() <= CASTER_FRAME_CHUNK_BYTES);
+            let msg = flatbuffers::root::<fb::GameMessage>(chunk).unwrap();
+            let delta = msg.actual_message_as_delta_state_message().unwrap();
+            assert_eq!(delta.server_tick(), 7);
+            assert_eq!(delta.full_snapshot(), index == 0);
+        }
+    }
+}
@@ -0,0 +1,122 @@
+// massive_game_server/server/src/state_sync/delta.rs
+// Per-client snapshot baselines for acknowledgement-based delta encoding.
+//
+// Every delta sent at tick T records the state the client will hold after applying it.
+// Clients ack the last server tick they applied, and the next delta is encoded against
+// that acked baseline. Without a usable baseline the server sends a full AoI snapshot
+// (baseline_tick = 0), which the client applies as a replacement.
+use crate::core::constants::SNAPSHOT_BASELINE_RING_SIZE;
+use crate::core::types::{EntityId, PlayerID};
+use dashmap::DashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+
+pub type SnapshotBaselinesMap = Arc<DashMap<String, BaselineRing>>;
+
+#[derive(Clone, Debug, Default)]
+pub struct SnapshotBaseline {
+    pub tick: u64,
+    pub players: HashSet<PlayerID>,
+    pub projectile_ids: HashSet<EntityId>,
+    pub pickup_states: HashMap<EntityId, bool>, // pickup_id -> is_active
+    pub wall_health: HashMap<EntityId, i32>,    // destructible walls only
+}
+
+#[derive(Clone, Debug)]
+pub struct BaselineRing {
+    baselines: VecDeque<SnapshotBaseline>,
+    last_acked_tick: Option<u64>,
+}
+
+impl BaselineRing {
+    pub fn new() -> Self {
+        BaselineRing {
+            baselines: VecDeque::with_capacity(SNAPSHOT_BASELINE_RING_SIZE),
+            last_acked_tick: None,
+        }
+    }
+
+    pub fn record(&mut self, baseline: SnapshotBaseline) {
+        if self.baselines.len() >= SNAPSHOT_BASELINE_RING_SIZE {
+            self.baselines.pop_front();
+        }
+        self.baselines.push_back(baseline);
+    }
+
+    /// Marks `tick` as applied by the client. Acks for ticks we no longer (or never)
+    /// had, and acks older than the current one, are ignored.
+    pub fn acknowledge(&mut self, tick: u64) -> bool {
+        if self.last_acked_tick.is_some_and(|acked| tick <= acked) {
+            return false;
+        }
+        if !self.baselines.iter().any(|b| b.tick == tick) {
+            return false;
+        }
+        // Older baselines can never be acked any more.
+        while self.baselines.front().is_some_and(|b| b.tick < tick) {
+            self.baselines.pop_front();
+        }
+        self.last_acked_tick = Some(tick);
+        true
+    }
+
+    /// The baseline the next delta should be encoded against, if it is still in the ring.
+    pub fn acked_baseline(&self) -> Option<&SnapshotBaseline> {
+        let acked_tick = self.last_acked_tick?;
+        self.baselines.iter().find(|b| b.tick == acked_tick)
+    }
+
+    pub fn last_acked_tick(&self) -> Option<u64> {
+        self.last_acked_tick
+    }
+}
+
+impl Default for BaselineRing {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn baseline_at(tick: u64) -> SnapshotBaseline {
+        SnapshotBaseline { tick, ..Default::default() }
+    }
+
+    #[test]
+    fn test_baseline_ring_acknowledge() {
+        let mut ring = BaselineRing::new();
+        assert!(ring.acked_baseline().is_none());
+
+        for tick in 1..=5 {
+            ring.record(baseline_at(tick));
+        }
+
+        // Unknown and stale acks are ignored
+        assert!(!ring.acknowledge(42));
+        assert!(ring.acknowledge(3));
+        assert!(!ring.acknowledge(2));
+        assert_eq!(ring.acked_baseline().map(|b| b.tick), Some(3));
+
+        // Baselines before the acked tick are discarded
+        assert!(!ring.acknowledge(1));
+        assert!(ring.acknowledge(5));
+        assert_eq!(ring.last_acked_tick(), Some(5));
+    }
+
+    #[test]
+    fn test_baseline_ring_drops_acked_baseline_on_overflow() {
+        let mut ring = BaselineRing::new();
+        ring.record(baseline_at(1));
+        assert!(ring.acknowledge(1));
+
+        for tick in 2..=(SNAPSHOT_BASELINE_RING_SIZE as u64 + 1) {
+            ring.record(baseline_at(tick));
+        }
+
+        // The client fell too far behind: no baseline, so a full snapshot is needed
+        assert!(ring.acked_baseline().is_none());
+    }
+}
@@ -0,0 +1,116 @@
+// massive_game_server/server/src/state_sync/interpolation.rs
+// Hitbox history for server-side lag compensation.
+//
+// Player positions are recorded once per tick, as broadcast. Shots are resolved against
+// positions rewound to the time the shooter was looking at: the input's timestamp mapped
+// to server time, plus half the shooter's RTT for the snapshot to reach them, capped at
+// `MAX_LAG_COMPENSATION_MS`.
+use crate::core::constants::{HITBOX_HISTORY_TICKS, MAX_LAG_COMPENSATION_MS};
+use crate::core::types::PlayerID;
+use crate::network::latency::LatencyTracker;
+use std::collections::{HashMap, VecDeque};
+
+#[derive(Clone, Debug)]
+pub struct HitboxSnapshot {
+    pub tick: u64,
+    pub timestamp_ms: u64,
+    pub positions: HashMap<PlayerID, (f32, f32)>,
+}
+
+#[derive(Clone, Debug)]
+pub struct HitboxHistory {
+    snapshots: VecDeque<HitboxSnapshot>,
+}
+
+impl HitboxHistory {
+    pub fn new() -> Self {
+        HitboxHistory {
+            snapshots: VecDeque::with_capacity(HITBOX_HISTORY_TICKS),
+        }
+    }
+
+    pub fn record(&mut self, snapshot: HitboxSnapshot) {
+        if self.snapshots.len() >= HITBOX_HISTORY_TICKS {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(snapshot);
+    }
+
+    /// Position of `player_id` at `timestamp_ms`, interpolated between the two recorded
+    /// ticks around it. Times older than the history are clamped to the oldest tick.
+    pub fn position_at(&self, player_id: &PlayerID, timestamp_ms: u64) -> Option<(f32, f32)> {
+        let after_idx = self.snapshots.iter().position(|s| s.timestamp_ms >= timestamp_ms);
+        let after = match after_idx {
+            Some(idx) => &self.snapshots[idx],
+            // Newer than anything recorded: the latest tick is the best we have.
+            None => return self.snapshots.back()?.positions.get(player_id).copied(),
+        };
+        let after_pos = after.positions.get(player_id).copied();
+
+        let before = after_idx
+            .and_then(|idx| idx.checked_sub(1))
+            .map(|idx| &self.snapshots[idx]);
+        let Some(before) = before else { return after_pos };
+        let Some(before_pos) = before.positions.get(player_id).copied() else { return after_pos };
+        let Some(after_pos) = after_pos else { return Some(before_pos) };
+
+        let span = after.timestamp_ms.saturating_sub(before.timestamp_ms).max(1) as f32;
+        let t = (timestamp_ms.saturating_sub(before.timestamp_ms) as f32 / span).clamp(0.0, 1.0);
+        Some((
+            before_pos.0 + (after_pos.0 - before_pos.0) * t,
+            before_pos.1 + (after_pos.1 - before_pos.1) * t,
+        ))
+    }
+}
+
+impl Default for HitboxHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// How far back a shot fired by input stamped `input_client_timestamp_ms` should be
+/// resolved. Clients without latency samples (bots, fresh connections) get no rewind.
+pub fn compute_rewind_ms(now_ms: u64, input_client_timestamp_ms: u64, latency: Option<&LatencyTracker>) -> u32 {
+    let Some(latency) = latency.filter(|l| l.samples > 0) else { return 0 };
+
+    let input_server_time_ms = input_client_timestamp_ms as i64 + latency.clock_offset_ms;
+    let since_input_ms = (now_ms as i64 - input_server_time_ms).max(0) as f32;
+    let rewind_ms = since_input_ms + latency.smoothed_rtt_ms / 2.0;
+    rewind_ms.clamp(0.0, MAX_LAG_COMPENSATION_MS as f32) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn snapshot(tick: u64, timestamp_ms: u64, player: &PlayerID, x: f32) -> HitboxSnapshot {
+        let mut positions = HashMap::new();
+        positions.insert(player.clone(), (x, 0.0));
+        HitboxSnapshot { tick, timestamp_ms, positions }
+    }
+
+    #[test]
+    fn test_position_at_interpolates_between_ticks() {
+        let player: PlayerID = Arc::new("p1".to_string());
+        let mut history = HitboxHistory::new();
+        history.record(snapshot(1, 1000, &player, 0.0));
+        history.record(snapshot(2, 1020, &player, 20.0));
+
+        assert_eq!(history.position_at(&player, 1010), Some((10.0, 0.0)));
+        assert_eq!(history.position_at(&player, 900), Some((0.0, 0.0)));
+        assert_eq!(history.position_at(&player, 2000), Some((20.0, 0.0)));
+    }
+
+    #[test]
+    fn test_rewind_is_capped() {
+        let mut latency = LatencyTracker::new();
+        assert_eq!(compute_rewind_ms(10_000, 9_900, Some(&latency)), 0);
+
+        latency.samples = 1;
+        latency.smoothed_rtt_ms = 100.0;
+        assert_eq!(compute_rewind_ms(10_000, 9_980, Some(&latency)), 70);
+        assert_eq!(compute_rewind_ms(10_000, 1_000, Some(&latency)), MAX_LAG_COMPENSATION_MS);
+    }
+}
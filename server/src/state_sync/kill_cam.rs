@@ -0,0 +1,181 @@
+// massive_game_server/server/src/state_sync/kill_cam.rs
+// Position history for kill cams.
+//
+// Every few ticks each living player's position and aim are sampled into a trail covering
+// the last `KILL_CAM_HISTORY_MS`. When a player dies, the killer's and victim's trails are
+// cut from it and sent to the victim with the killing shot, so the client can replay the
+// moments before the death without having had the killer in its AoI.
+use crate::core::constants::KILL_CAM_HISTORY_MS;
+use crate::core::types::{PlayerID, ServerWeaponType, Vec2};
+use crate::flatbuffers_generated::game_protocol as fb;
+use crate::server::instance::map_server_weapon_to_fb;
+use bytes::Bytes;
+use flatbuffers::FlatBufferBuilder;
+use std::collections::{HashMap, VecDeque};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TrailSample {
+    pub timestamp_ms: u64,
+    pub x: f32,
+    pub y: f32,
+    pub rotation: f32,
+}
+
+#[derive(Debug, Default)]
+pub struct KillCamHistory {
+    trails: HashMap<PlayerID, VecDeque<TrailSample>>,
+}
+
+impl KillCamHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends one sample per player and forgets anything older than the kill cam window,
+    /// including the trails of players that have died or left.
+    pub fn record(&mut self, timestamp_ms: u64, samples: impl IntoIterator<Item = (PlayerID, f32, f32, f32)>) {
+        for (player_id, x, y, rotation) in samples {
+            self.trails.entry(player_id).or_default().push_back(TrailSample { timestamp_ms, x, y, rotation });
+        }
+        let cutoff_ms = timestamp_ms.saturating_sub(KILL_CAM_HISTORY_MS);
+        self.trails.retain(|_, trail| {
+            while trail.front().is_some_and(|sample| sample.timestamp_ms < cutoff_ms) {
+                trail.pop_front();
+            }
+            !trail.is_empty()
+        });
+    }
+
+    /// The player's samples from the last `KILL_CAM_HISTORY_MS` before `until_ms`, oldest first.
+    pub fn trail(&self, player_id: &PlayerID, until_ms: u64) -> Vec<TrailSample> {
+        let since_ms = until_ms.saturating_sub(KILL_CAM_HISTORY_MS);
+        self.trails.get(player_id)
+            .map(|trail| trail.iter().filter(|s| s.timestamp_ms >= since_ms && s.timestamp_ms <= until_ms).copied().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// The shot that killed a player.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct KillShot {
+    pub origin: Vec2,
+    pub impact: Vec2,
+}
+
+impl KillShot {
+    pub fn distance(&self) -> f32 {
+        (self.impact.x - self.origin.x).hypot(self.impact.y - self.origin.y)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct KillCam {
+    pub killer_id: PlayerID,
+    pub killer_name: String,
+    pub weapon: ServerWeaponType,
+    pub killer_trail: Vec<TrailSample>,
+    pub victim_trail: Vec<TrailSample>,
+    pub shot: KillShot,
+    pub killer_health: i32,
+    pub killer_max_health: i32,
+    pub killer_weapon: ServerWeaponType,
+    pub death_timestamp_ms: u64,
+}
+
+impl KillCam {
+    pub fn to_message(&self) -> Bytes {
+        let mut builder = FlatBufferBuilder::with_capacity(2048);
+        let killer_id_fb = builder.create_string(self.killer_id.as_str());
+        let killer_name_fb = builder.create_string(&self.killer_name);
+        let killer_trail_fb = encode_trail(&mut builder, &self.killer_trail, self.death_timestamp_ms);
+        let victim_trail_fb = encode_trail(&mut builder, &self.victim_trail, self.death_timestamp_ms);
+        let shot_origin_fb = fb::Vec2::create(&mut builder, &fb::Vec2Args { x: self.shot.origin.x, y: self.shot.origin.y });
+        let shot_impact_fb = fb::Vec2::create(&mut builder, &fb::Vec2Args { x: self.shot.impact.x, y: self.shot.impact.y });
+
+        let kill_cam_fb = fb::KillCamData::create(&mut builder, &fb::KillCamDataArgs {
+            killer_id: Some(killer_id_fb),
+            killer_name: Some(killer_name_fb),
+            weapon: map_server_weapon_to_fb(self.weapon),
+            killer_trail: Some(killer_trail_fb),
+            victim_trail: Some(victim_trail_fb),
+            shot_origin: Some(shot_origin_fb),
+            shot_impact: Some(shot_impact_fb),
+            killer_health: self.killer_health,
+            killer_max_health: self.killer_max_health,
+            killer_weapon: map_server_weapon_to_fb(self.killer_weapon),
+            death_timestamp: self.death_timestamp_ms,
+        });
+        let game_msg = fb::GameMessage::create(&mut builder, &fb::GameMessageArgs {
+            msg_type: fb::MessageType::KillCamData,
+            actual_message_type: fb::MessagePayload::KillCamData,
+            actual_message: Some(kill_cam_fb.as_union_value()),
+        });
+        builder.finish(game_msg, None);
+        Bytes::from(builder.finished_data().to_vec())
+    }
+}
+
+fn encode_trail<'a>(
+    builder: &mut FlatBufferBuilder<'a>,
+    trail: &[TrailSample],
+    death_timestamp_ms: u64,
+) -> flatbuffers::WIPOffset<flatbuffers::Vector<'a, flatbuffers::ForwardsUOffset<fb::KillCamSample<'a>>>> {
+    let samples: Vec<_> = trail.iter().map(|sample| {
+        fb::KillCamSample::create(builder, &fb::KillCamSampleArgs {
+            time_offset_ms: (sample.timestamp_ms as i64 - death_timestamp_ms as i64) as i32,
+            x: sample.x,
+            y: sample.y,
+            rotation: sample.rotation,
+        })
+    }).collect();
+    builder.create_vector(&samples)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_trails_cover_only_the_kill_cam_window() {
+        let alive: PlayerID = Arc::new("alive".to_string());
+        let died: PlayerID = Arc::new("died".to_string());
+        let mut history = KillCamHistory::new();
+        history.record(1_000, [(alive.clone(), 0.0, 0.0, 0.0), (died.clone(), 5.0, 5.0, 0.0)]);
+        history.record(1_050, [(alive.clone(), 1.0, 0.0, 0.0), (died.clone(), 6.0, 5.0, 0.0)]);
+
+        let trail = history.trail(&died, 1_050);
+        assert_eq!(trail.len(), 2);
+        assert_eq!(trail[1].x, 6.0);
+
+        // The dead player stops being sampled and their trail ages out.
+        history.record(1_000 + KILL_CAM_HISTORY_MS + 100, [(alive.clone(), 2.0, 0.0, 0.0)]);
+        assert!(history.trail(&died, 1_050 + KILL_CAM_HISTORY_MS).is_empty());
+        assert_eq!(history.trail(&alive, 1_000 + KILL_CAM_HISTORY_MS + 100).len(), 1);
+    }
+
+    #[test]
+    fn test_kill_cam_message_round_trips() {
+        let killer: PlayerID = Arc::new("killer".to_string());
+        let kill_cam = KillCam {
+            killer_id: killer.clone(),
+            killer_name: "Killer".to_string(),
+            weapon: ServerWeaponType::Sniper,
+            killer_trail: vec![TrailSample { timestamp_ms: 9_500, x: 1.0, y: 2.0, rotation: 0.5 }],
+            victim_trail: Vec::new(),
+            shot: KillShot { origin: Vec2::new(1.0, 2.0), impact: Vec2::new(100.0, 2.0) },
+            killer_health: 40,
+            killer_max_health: 100,
+            killer_weapon: ServerWeaponType::Sniper,
+            death_timestamp_ms: 10_000,
+        };
+
+        let bytes = kill_cam.to_message();
+        let msg = fb::root_as_game_message(&bytes).unwrap();
+        let data = msg.actual_message_as_kill_cam_data().unwrap();
+        assert_eq!(data.killer_id(), Some("killer"));
+        assert_eq!(data.killer_trail().unwrap().get(0).time_offset_ms(), -500);
+        assert_eq!(data.shot_impact().unwrap().x(), 100.0);
+        assert_eq!(data.killer_health(), 40);
+    }
+}
@@ -0,0 +1,5 @@
+// massive_game_server/server/src/state_sync/mod.rs
+pub mod delta;
+pub mod interpolation;
+pub mod priority;
+pub mod kill_cam;
@@ -0,0 +1,210 @@
+// massive_game_server/server/src/state_sync/priority.rs
+// Per-client bandwidth budget and entity prioritisation for delta snapshots.
+//
+// Each broadcast, the entities in a client's AoI are scored by distance, relevance
+// (recent attacker, flag carrier, teammate), speed and time since they were last sent.
+// The highest scores fill the client's byte budget, after the payload every client gets
+// (events, kill feed, match info) has been charged to it. Entities that have gone
+// `MAX_TICKS_BETWEEN_ENTITY_UPDATES` without an update go first and may overdraw the budget by
+// `MANDATORY_OVERDRAFT_PERCENT`; any beyond that wait for the next tick, stalest first.
+use crate::core::constants::*;
+use crate::core::types::{EntityId, PlayerID, PlayerState, Projectile};
+use dashmap::DashMap;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+pub type ClientPrioritiesMap = Arc<DashMap<String, ClientPriorityState>>;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SyncEntity {
+    Player(PlayerID),
+    Projectile(EntityId),
+}
+
+#[derive(Clone, Debug)]
+pub struct PriorityCandidate {
+    pub entity: SyncEntity,
+    pub score: f32,
+    pub estimated_bytes: usize,
+    pub mandatory: bool,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ClientPriorityState {
+    // entity -> tick it was last sent (or first became a candidate, if never sent)
+    last_sent: HashMap<SyncEntity, u64>,
+    // attacker -> tick they last damaged this client
+    recent_attackers: HashMap<PlayerID, u64>,
+}
+
+impl ClientPriorityState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ticks since `entity` was last sent. A new entity starts counting from now.
+    pub fn ticks_since_sent(&mut self, entity: &SyncEntity, current_tick: u64) -> u64 {
+        let since = *self.last_sent.entry(entity.clone()).or_insert(current_tick);
+        current_tick.saturating_sub(since)
+    }
+
+    pub fn mark_sent(&mut self, entity: SyncEntity, current_tick: u64) {
+        self.last_sent.insert(entity, current_tick);
+    }
+
+    pub fn note_attacker(&mut self, attacker_id: PlayerID, current_tick: u64) {
+        self.recent_attackers.insert(attacker_id, current_tick);
+    }
+
+    pub fn is_recent_attacker(&self, player_id: &PlayerID, current_tick: u64) -> bool {
+        self.recent_attackers
+            .get(player_id)
+            .is_some_and(|&tick| current_tick.saturating_sub(tick) <= RECENT_ATTACKER_WINDOW_TICKS)
+    }
+
+    /// Forgets entities that were not candidates this tick, and attackers that went quiet.
+    pub fn retain_candidates(&mut self, candidates: &[PriorityCandidate], current_tick: u64) {
+        let live: std::collections::HashSet<&SyncEntity> = candidates.iter().map(|c| &c.entity).collect();
+        self.last_sent.retain(|entity, _| live.contains(entity));
+        self.recent_attackers
+            .retain(|_, tick| current_tick.saturating_sub(*tick) <= RECENT_ATTACKER_WINDOW_TICKS);
+    }
+}
+
+fn distance_score(viewer: &PlayerState, x: f32, y: f32) -> f32 {
+    let distance = ((x - viewer.x).powi(2) + (y - viewer.y).powi(2)).sqrt();
+    1.0 - (distance / AOI_RADIUS).clamp(0.0, 1.0)
+}
+
+fn staleness_score(ticks_since_sent: u64) -> f32 {
+    ticks_since_sent as f32 * PRIORITY_STALENESS_WEIGHT
+}
+
+pub fn player_priority(viewer: &PlayerState, other: &PlayerState, ticks_since_sent: u64, is_recent_attacker: bool) -> f32 {
+    let mut score = distance_score(viewer, other.x, other.y) * PRIORITY_DISTANCE_WEIGHT;
+    if is_recent_attacker {
+        score += PRIORITY_ATTACKER_BONUS;
+    }
+    if other.is_carrying_flag_team_id != 0 {
+        score += PRIORITY_FLAG_CARRIER_BONUS;
+    }
+    if viewer.team_id != 0 && viewer.team_id == other.team_id {
+        score += PRIORITY_TEAMMATE_BONUS;
+    }
+    let speed = (other.velocity_x.powi(2) + other.velocity_y.powi(2)).sqrt();
+    score += (speed / PLAYER_BASE_SPEED).min(2.0) * PRIORITY_VELOCITY_WEIGHT;
+    score + staleness_score(ticks_since_sent)
+}
+
+pub fn projectile_priority(viewer: &PlayerState, projectile: &Projectile, ticks_since_sent: u64) -> f32 {
+    let mut score = distance_score(viewer, projectile.x, projectile.y) * PRIORITY_DISTANCE_WEIGHT;
+    // Incoming shots matter more than ones flying away from the viewer.
+    let to_viewer_x = viewer.x - projectile.x;
+    let to_viewer_y = viewer.y - projectile.y;
+    if to_viewer_x * projectile.velocity_x + to_viewer_y * projectile.velocity_y > 0.0 {
+        score += PRIORITY_VELOCITY_WEIGHT;
+    }
+    score + staleness_score(ticks_since_sent)
+}
+
+pub fn estimate_player_state_bytes(player: &PlayerState) -> usize {
+    ESTIMATED_PLAYER_STATE_BYTES
+        + player.id.len()
+        + player.username.len()
+        + ESTIMATED_WEAPON_SLOT_BYTES * player.inventory.slots().len()
+        + ESTIMATED_STATUS_EFFECT_BYTES * player.effects.active().len()
+        + ESTIMATED_PLAYER_CLASS_BYTES
+}
+
+pub fn estimate_projectile_bytes(projectile: &Projectile) -> usize {
+    ESTIMATED_PROJECTILE_BYTES + projectile.owner_id.len()
+}
+
+/// Orders candidates for sending: overdue entities first, then by descending score.
+pub fn sort_candidates(candidates: &mut [PriorityCandidate]) {
+    candidates.sort_by(|a, b| {
+        b.mandatory
+            .cmp(&a.mandatory)
+            .then_with(|| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal))
+    });
+}
+
+/// Tracks bytes spent against a client's per-tick budget.
+#[derive(Clone, Debug)]
+pub struct ByteBudget {
+    limit: usize,
+    spent: usize,
+}
+
+impl ByteBudget {
+    pub fn new(limit: usize) -> Self {
+        ByteBudget { limit, spent: 0 }
+    }
+
+    /// Whether an entity of `estimated_bytes` still fits. Mandatory entities may overdraw the
+    /// budget, up to a cap.
+    pub fn admits(&self, estimated_bytes: usize, mandatory: bool) -> bool {
+        let limit = if mandatory { self.limit + self.limit * MANDATORY_OVERDRAFT_PERCENT / 100 } else { self.limit };
+        self.spent + estimated_bytes <= limit
+    }
+
+    pub fn spend(&mut self, bytes: usize) {
+        self.spent += bytes;
+    }
+
+    pub fn spent(&self) -> usize {
+        self.spent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::types::ServerWeaponType;
+    use crate::systems::combat::effects::EffectKind;
+
+    fn candidate(id: EntityId, score: f32, mandatory: bool) -> PriorityCandidate {
+        PriorityCandidate { entity: SyncEntity::Projectile(id), score, estimated_bytes: 10, mandatory }
+    }
+
+    #[test]
+    fn test_overdue_entities_sort_first() {
+        let mut candidates = vec![candidate(1, 5.0, false), candidate(2, 1.0, true), candidate(3, 9.0, false)];
+        sort_candidates(&mut candidates);
+        let order: Vec<_> = candidates.iter().map(|c| c.entity.clone()).collect();
+        assert_eq!(order, vec![SyncEntity::Projectile(2), SyncEntity::Projectile(3), SyncEntity::Projectile(1)]);
+    }
+
+    #[test]
+    fn test_overdue_entities_overdraw_up_to_cap() {
+        let mut budget = ByteBudget::new(100);
+        budget.spend(95);
+        assert!(!budget.admits(10, false));
+        assert!(budget.admits(10, true));
+        budget.spend(50);
+        assert!(!budget.admits(10, true)); // Past the overdraft; it waits for the next tick
+        assert!(ByteBudget::new(100).admits(100, false));
+    }
+
+    #[test]
+    fn test_closer_and_stale_players_score_higher() {
+        let viewer = PlayerState::new("viewer".to_string(), "viewer".to_string(), 0.0, 0.0);
+        let near = PlayerState::new("near".to_string(), "near".to_string(), 50.0, 0.0);
+        let far = PlayerState::new("far".to_string(), "far".to_string(), 500.0, 0.0);
+
+        assert!(player_priority(&viewer, &near, 0, false) > player_priority(&viewer, &far, 0, false));
+        assert!(player_priority(&viewer, &far, 30, false) > player_priority(&viewer, &far, 0, false));
+        assert!(player_priority(&viewer, &far, 0, true) > player_priority(&viewer, &near, 0, false));
+    }
+
+    #[test]
+    fn test_player_estimate_covers_variable_fields() {
+        let mut player = PlayerState::new("p".to_string(), "p".to_string(), 0.0, 0.0);
+        let base = estimate_player_state_bytes(&player);
+        player.inventory.grant(ServerWeaponType::Sniper, 0);
+        assert_eq!(estimate_player_state_bytes(&player), base + ESTIMATED_WEAPON_SLOT_BYTES);
+        player.effects.apply(EffectKind::Burn, None);
+        assert_eq!(estimate_player_state_bytes(&player), base + ESTIMATED_WEAPON_SLOT_BYTES + ESTIMATED_STATUS_EFFECT_BYTES);
+    }
+}
@@ -7,6 +7,8 @@ use crate::core::types::{
 use crate::core::constants::*;
 use crate::server::instance::{BotController, BotBehaviorState, MassiveGameServer};
 use crate::flatbuffers_generated::game_protocol as fb;
+use crate::systems::combat::weapons::weapon_slot;
+use crate::systems::objectives::ctf::flag_base_position;
 use crate::world::partition::WorldPartitionManager;
 
 use std::sync::Arc;
@@ -171,7 +173,7 @@ impl BotAISystem {
         if is_ctf && bot_state.team_id != 0 {
             // If carrying enemy flag, rush to base!
             if bot_state.is_carrying_flag_team_id != 0 && bot_state.is_carrying_flag_team_id != bot_state.team_id {
-                let home_base = MassiveGameServer::get_flag_base_position(bot_state.team_id);
+                let home_base = flag_base_position(bot_state.team_id);
                 bot_controller.behavior_state = BotBehaviorState::MovingToObjective;
                 bot_controller.target_position = Some(home_base);
                 bot_controller.target_enemy_id = None;
@@ -722,7 +724,11 @@ impl BotAISystem {
 
         // Reload management
         if bot_state.weapon != ServerWeaponType::Melee {
-            if bot_state.ammo == 0 && bot_state.reload_progress.is_none() {
+            if bot_state.ammo == 0 && bot_state.reserve_ammo() == 0 {
+                // Dry: move on to another gun, or fall back to melee
+                let fallback = bot_state.inventory.next_gun_with_ammo(bot_state.weapon).unwrap_or(ServerWeaponType::Melee);
+                input.change_weapon_slot = weapon_slot(fallback);
+            } else if bot_state.ammo == 0 && bot_state.reload_progress.is_none() {
                 input.reload = true;
             } else if bot_state.ammo < PlayerState::get_max_ammo_for_weapon(bot_state.weapon) / 3 && 
                       bot_controller.behavior_state != BotBehaviorState::Engaging && 
@@ -748,14 +754,8 @@ impl BotAISystem {
                 }
             };
             
-            if new_weapon != bot_state.weapon {
-                input.change_weapon_slot = match new_weapon {
-                    ServerWeaponType::Pistol => 1,
-                    ServerWeaponType::Shotgun => 2,
-                    ServerWeaponType::Rifle => 3,
-                    ServerWeaponType::Sniper => 4,
-                    ServerWeaponType::Melee => 5,
-                };
+            if new_weapon != bot_state.weapon && bot_state.inventory.owns(new_weapon) {
+                input.change_weapon_slot = weapon_slot(new_weapon);
             }
         }
     }
@@ -767,6 +767,8 @@ impl BotAISystem {
             ServerWeaponType::Rifle => 700.0,
             ServerWeaponType::Sniper => 1200.0,
             ServerWeaponType::Melee => BOT_MELEE_RANGE,
+            ServerWeaponType::RocketLauncher => 600.0,
+            ServerWeaponType::GrenadeLauncher => 400.0,
         }
     }
 
@@ -777,6 +779,8 @@ impl BotAISystem {
             ServerWeaponType::Rifle => RIFLE_PROJECTILE_SPEED,
             ServerWeaponType::Sniper => SNIPER_PROJECTILE_SPEED,
             ServerWeaponType::Melee => 0.0,
+            ServerWeaponType::RocketLauncher => ROCKET_PROJECTILE_SPEED,
+            ServerWeaponType::GrenadeLauncher => GRENADE_PROJECTILE_SPEED,
         }
     }
     
@@ -2,8 +2,12 @@
 
 use crate::core::types::{PlayerID, PlayerInputData, Vec2, PlayerState, ServerWeaponType};
 use crate::core::constants::*;
-use crate::server::instance::{BotController, BotBehaviorState, MassiveGameServer};
+use crate::server::instance::{BotController, BotBehaviorState, MassiveGameServer, ServerZoneState};
 use crate::flatbuffers_generated::game_protocol as fb;
+use crate::systems::combat::abilities::{bot_ability_slot, BotAbilityContext};
+use crate::systems::combat::weapons::weapon_slot;
+use crate::systems::objectives::ctf::flag_base_position;
+use crate::systems::objectives::infection::{INFECTED_TEAM, SURVIVOR_TEAM};
 
 use std::sync::Arc;
 use std::time::{Instant, Duration, SystemTime, UNIX_EPOCH};
@@ -24,6 +28,8 @@ const BOT_MOVEMENT_TOLERANCE: f32 = 50.0; // Distance to consider "at target"
 const BOT_STUCK_THRESHOLD: f32 = 10.0; // Min distance to move to not be considered stuck
 const BOT_STUCK_TIME_THRESHOLD: f32 = 2.0; // Seconds before considering bot stuck
 const BOT_STUCK_CHECK_INTERVAL: f32 = 0.5; // Check every half second
+const BOT_INFECTED_FLEE_RANGE: f32 = 500.0; // Survivors run from infected closer than this
+const BOT_FLEE_DISTANCE: f32 = 400.0; // How far a survivor runs before deciding again
 
 #[derive(Debug, Clone)]
 enum BotObjective {
@@ -33,6 +39,10 @@ enum BotObjective {
     ProtectFriendlyCarrier, // Protect teammate with enemy flag
     PatrolMidfield,       // General patrol
     EngageNearbyEnemy,    // Fight nearby enemy
+    CaptureZone(u8),      // Take a zone the team doesn't hold
+    DefendZone(u8),       // Hold or save one of the team's zones
+    HuntSurvivor,         // Infected: run down the nearest survivor
+    EvadeInfected,        // Survivor: keep away from the nearest infected
 }
 
 pub struct OptimizedBotAI;
@@ -61,6 +71,7 @@ impl OptimizedBotAI {
         let game_mode = match_info_guard.game_mode;
         let match_state = match_info_guard.match_state;
         let flag_states = match_info_guard.flag_states.clone();
+        let zone_states = match_info_guard.zone_states.clone();
         drop(match_info_guard);
         
         // Process ALL bots every frame
@@ -85,6 +96,10 @@ impl OptimizedBotAI {
                         
                         if game_mode == fb::GameModeType::CaptureTheFlag && match_state == fb::MatchStateType::Active {
                             Self::make_ctf_decision(bot_controller, &bot_state, &flag_states, server_instance);
+                        } else if game_mode == fb::GameModeType::Domination && match_state == fb::MatchStateType::Active {
+                            Self::make_zone_decision(bot_controller, &bot_state, &zone_states);
+                        } else if game_mode == fb::GameModeType::Infection && match_state == fb::MatchStateType::Active {
+                            Self::make_infection_decision(bot_controller, &bot_state, server_instance);
                         } else {
                             Self::make_simple_movement_decision(bot_controller, &bot_state);
                         }
@@ -134,7 +149,7 @@ impl OptimizedBotAI {
                 // If carrying flag, go to own base. Otherwise attack enemy flag
                 if bot_state.is_carrying_flag_team_id != 0 {
                     // Bot has enemy flag, return to own base
-                    let own_base = MassiveGameServer::get_flag_base_position(bot_team);
+                    let own_base = flag_base_position(bot_team);
                     bot_controller.target_position = Some(own_base);
                     bot_controller.behavior_state = BotBehaviorState::MovingToObjective;
                     debug!("Bot {} carrying flag, returning to base at {:?}", bot_state.username, own_base);
@@ -147,7 +162,7 @@ impl OptimizedBotAI {
             }
             BotObjective::DefendOwnFlag => {
                 // Stay near own flag base with some variation
-                let base_pos = MassiveGameServer::get_flag_base_position(bot_team);
+                let base_pos = flag_base_position(bot_team);
                 let defend_radius = 150.0;
                 let angle = rng.gen_range(0.0..2.0 * std::f32::consts::PI);
                 let distance = rng.gen_range(50.0..defend_radius);
@@ -201,6 +216,8 @@ impl OptimizedBotAI {
                     bot_controller.behavior_state = BotBehaviorState::Engaging;
                 }
             }
+            BotObjective::CaptureZone(_) | BotObjective::DefendZone(_) => {} // Domination only
+            BotObjective::HuntSurvivor | BotObjective::EvadeInfected => {} // Infection only
         }
     }
     
@@ -255,7 +272,7 @@ impl OptimizedBotAI {
         
         server_instance.player_manager.for_each_player(|_, player| {
             if player.team_id == bot_team && player.alive {
-                let own_base = MassiveGameServer::get_flag_base_position(bot_team);
+                let own_base = flag_base_position(bot_team);
                 let dist_to_base = ((player.x - own_base.x).powi(2) + 
                                    (player.y - own_base.y).powi(2)).sqrt();
                 if dist_to_base < 200.0 {
@@ -292,13 +309,131 @@ impl OptimizedBotAI {
         }
     }
     
+    /// Make Domination decisions: move onto a zone to capture it or to hold it
+    fn make_zone_decision(
+        bot_controller: &mut BotController,
+        bot_state: &PlayerState,
+        zone_states: &[ServerZoneState],
+    ) {
+        let mut rng = rand::thread_rng();
+        let objective = Self::determine_zone_objective(bot_state, zone_states);
+        debug!("Bot {} (Team {}) objective: {:?}", bot_state.username, bot_state.team_id, objective);
+
+        let (zone_id, behavior_state) = match objective {
+            BotObjective::CaptureZone(zone_id) => (zone_id, BotBehaviorState::MovingToObjective),
+            BotObjective::DefendZone(zone_id) => (zone_id, BotBehaviorState::Defending),
+            _ => {
+                Self::make_simple_movement_decision(bot_controller, bot_state);
+                return;
+            }
+        };
+        let Some(zone) = zone_states.iter().find(|zone| zone.zone_id == zone_id) else {
+            return;
+        };
+
+        // Spread out inside the zone rather than stacking on its center
+        let angle = rng.gen_range(0.0..2.0 * std::f32::consts::PI);
+        let distance = rng.gen_range(0.0..zone.radius * 0.6);
+        bot_controller.target_position = Some(Vec2::new(
+            zone.position.x + distance * angle.cos(),
+            zone.position.y + distance * angle.sin()
+        ));
+        bot_controller.behavior_state = behavior_state;
+    }
+
+    /// Determine the best zone objective for a bot in Domination mode
+    fn determine_zone_objective(bot_state: &PlayerState, zone_states: &[ServerZoneState]) -> BotObjective {
+        let bot_team = bot_state.team_id;
+        let dist_sq = |zone: &ServerZoneState| (zone.position.x - bot_state.x).powi(2) + (zone.position.y - bot_state.y).powi(2);
+        let nearest = |zones: Vec<&ServerZoneState>| zones.into_iter()
+            .min_by(|a, b| dist_sq(a).total_cmp(&dist_sq(b)))
+            .map(|zone| zone.zone_id);
+
+        // Priority 1: save one of our zones the enemy is taking or fighting over
+        let threatened: Vec<_> = zone_states.iter()
+            .filter(|zone| zone.owner_team_id == bot_team && (zone.contested || zone.capturing_team_id != 0))
+            .collect();
+        if let Some(zone_id) = nearest(threatened) {
+            return BotObjective::DefendZone(zone_id);
+        }
+
+        // Priority 2: most bots push the nearest zone we don't hold
+        let mut rng = rand::thread_rng();
+        let not_held: Vec<_> = zone_states.iter().filter(|zone| zone.owner_team_id != bot_team).collect();
+        let held: Vec<_> = zone_states.iter().filter(|zone| zone.owner_team_id == bot_team).collect();
+        if !not_held.is_empty() && (held.is_empty() || rng.gen_range(0..100) < 70) {
+            if let Some(zone_id) = nearest(not_held) {
+                return BotObjective::CaptureZone(zone_id);
+            }
+        }
+
+        // Otherwise guard one of our zones
+        if !held.is_empty() {
+            return BotObjective::DefendZone(held[rng.gen_range(0..held.len())].zone_id);
+        }
+        BotObjective::PatrolMidfield
+    }
+
+    /// Make Infection decisions: the infected chase survivors, survivors keep their distance
+    fn make_infection_decision(
+        bot_controller: &mut BotController,
+        bot_state: &PlayerState,
+        server_instance: &MassiveGameServer,
+    ) {
+        let hunted_team = if bot_state.team_id == INFECTED_TEAM { SURVIVOR_TEAM } else { INFECTED_TEAM };
+        let mut nearest: Option<(Vec2, f32)> = None;
+        server_instance.player_manager.for_each_player(|_id, player| {
+            if player.alive && player.team_id == hunted_team {
+                let dist_sq = (player.x - bot_state.x).powi(2) + (player.y - bot_state.y).powi(2);
+                if nearest.is_none_or(|(_, best)| dist_sq < best) {
+                    nearest = Some((Vec2::new(player.x, player.y), dist_sq));
+                }
+            }
+        });
+
+        let objective = match nearest {
+            Some(_) if bot_state.team_id == INFECTED_TEAM => BotObjective::HuntSurvivor,
+            Some((_, dist_sq)) if dist_sq < BOT_INFECTED_FLEE_RANGE.powi(2) => BotObjective::EvadeInfected,
+            _ => BotObjective::PatrolMidfield,
+        };
+        debug!("Bot {} (Team {}) objective: {:?}", bot_state.username, bot_state.team_id, objective);
+
+        match (objective, nearest) {
+            (BotObjective::HuntSurvivor, Some((survivor_pos, _))) => {
+                bot_controller.target_position = Some(survivor_pos);
+                bot_controller.behavior_state = BotBehaviorState::Engaging;
+            }
+            (BotObjective::EvadeInfected, Some((infected_pos, _))) => {
+                // Run straight away from the threat, staying inside the map
+                let away_x = bot_state.x - infected_pos.x;
+                let away_y = bot_state.y - infected_pos.y;
+                let len = (away_x * away_x + away_y * away_y).sqrt().max(1.0);
+                bot_controller.target_position = Some(Vec2::new(
+                    (bot_state.x + away_x / len * BOT_FLEE_DISTANCE).clamp(WORLD_MIN_X + 100.0, WORLD_MAX_X - 100.0),
+                    (bot_state.y + away_y / len * BOT_FLEE_DISTANCE).clamp(WORLD_MIN_Y + 100.0, WORLD_MAX_Y - 100.0)
+                ));
+                bot_controller.behavior_state = BotBehaviorState::MovingToPosition;
+            }
+            _ => Self::make_simple_movement_decision(bot_controller, bot_state),
+        }
+    }
+
+    /// Everyone is an enemy in modes without teams (team 0)
+    fn is_enemy(bot_state: &PlayerState, other: &PlayerState) -> bool {
+        if bot_state.team_id == 0 {
+            true
+        } else {
+            other.team_id != bot_state.team_id && other.team_id != 0
+        }
+    }
+
     /// Find nearest enemy to the bot
     fn find_nearest_enemy(bot_state: &PlayerState, server_instance: &MassiveGameServer) -> Option<(Vec2, PlayerID)> {
         let mut nearest_enemy = None;
         let mut nearest_dist_sq = f32::MAX;
         
         server_instance.player_manager.for_each_player(|id, player| {
-            if player.alive && player.team_id != bot_state.team_id && player.team_id != 0 {
+            if player.alive && *id != bot_state.id && Self::is_enemy(bot_state, player) {
                 let dist_sq = (player.x - bot_state.x).powi(2) + (player.y - bot_state.y).powi(2);
                 if dist_sq < nearest_dist_sq && dist_sq < BOT_TARGET_ACQUISITION_RANGE.powi(2) {
                     nearest_dist_sq = dist_sq;
@@ -414,14 +549,19 @@ impl OptimizedBotAI {
             use_ability_slot: 0,
         };
         
-        // Weapon switching logic
-        if current_time.duration_since(bot_controller.path_recalculation_timer) < Duration::from_secs(1) {
+        let is_infected = game_mode == fb::GameModeType::Infection && bot_state.team_id == INFECTED_TEAM;
+
+        // Weapon switching logic (the infected only have their hands)
+        if !is_infected && current_time.duration_since(bot_controller.path_recalculation_timer) < Duration::from_secs(1) {
             input.change_weapon_slot = rng.gen_range(1..=4);
         }
         
-        // Reload if low on ammo
-        if bot_state.ammo == 0 {
+        // Reload if low on ammo, or switch away once there is nothing left to load
+        if bot_state.ammo == 0 && bot_state.reserve_ammo() > 0 {
             input.reload = true;
+        } else if bot_state.ammo == 0 && !is_infected && bot_state.weapon != ServerWeaponType::Melee {
+            let fallback = bot_state.inventory.next_gun_with_ammo(bot_state.weapon).unwrap_or(ServerWeaponType::Melee);
+            input.change_weapon_slot = weapon_slot(fallback);
         }
         
         // Find nearby enemies for combat
@@ -430,8 +570,8 @@ impl OptimizedBotAI {
         let mut has_enemy_target = false;
         let mut enemy_position = Vec2::zero();
         
-        server_instance.player_manager.for_each_player(|_, player| {
-            if player.alive && player.team_id != bot_state.team_id && player.team_id != 0 {
+        server_instance.player_manager.for_each_player(|id, player| {
+            if player.alive && *id != bot_state.id && Self::is_enemy(bot_state, player) {
                 let dx = player.x - bot_state.x;
                 let dy = player.y - bot_state.y;
                 let dist_sq = dx * dx + dy * dy;
@@ -559,6 +699,16 @@ impl OptimizedBotAI {
                     }
                 }
             }
+
+            // The infected close in and swing at anything in reach
+            if is_infected {
+                input.use_ability_slot = Self::choose_ability(bot_state, bot_controller, Some(nearest_enemy_dist.sqrt()));
+                input.move_forward = true;
+                input.move_backward = false;
+                input.shooting = false;
+                input.melee_attack = nearest_enemy_dist < 60.0 * 60.0;
+                return input;
+            }
             
             // Tactical movement during combat
             if has_enemy_target && !movement_handled {
@@ -581,9 +731,19 @@ impl OptimizedBotAI {
                 }
             }
         }
-        
+
+        input.use_ability_slot = Self::choose_ability(bot_state, bot_controller, has_enemy_target.then(|| nearest_enemy_dist.sqrt()));
         input
     }
+
+    /// Pick an ability slot for this tick, or 0 for none
+    fn choose_ability(bot_state: &PlayerState, bot_controller: &BotController, enemy_distance: Option<f32>) -> u8 {
+        bot_ability_slot(&bot_state.abilities, &BotAbilityContext {
+            health_fraction: bot_state.health as f32 / bot_state.max_health.max(1) as f32,
+            enemy_distance,
+            stuck: bot_controller.stuck_timer >= BOT_STUCK_TIME_THRESHOLD / 2.0,
+        })
+    }
     
     /// Check if bot is stuck and needs to change direction
     fn check_stuck_status(bot_controller: &mut BotController, bot_state: &PlayerState, delta_time: f32) {
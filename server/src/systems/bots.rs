@@ -289,6 +289,7 @@ impl BotManager {
                         ServerWeaponType::Rifle => 400.0,
                         ServerWeaponType::Sniper => 600.0,
                         ServerWeaponType::Melee => 50.0,
+                        ServerWeaponType::RocketLauncher | ServerWeaponType::GrenadeLauncher => 350.0,
                     };
                     
                     if dist < effective_range {
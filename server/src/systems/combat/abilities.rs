@@ -0,0 +1,312 @@
+// massive_game_server/server/src/systems/combat/abilities.rs
+// Active abilities: what a player's class gives them, and the charges and cooldowns that gate them.
+//
+// A player picks a class (`/class <name>`, applied at the next spawn), and the class decides the
+// abilities in their slots. Each ability holds a few charges that come back one at a time; using
+// one also starts a short lockout shared by every slot. The server checks and spends the charge
+// when the input arrives and resolves the ability with the tick's other deferred actions. An
+// activation that can't take place, such as cover with no room for it, is refunded.
+use crate::core::types::{PlayerID, Vec2};
+use crate::flatbuffers_generated::game_protocol as fb;
+use dashmap::DashMap;
+use flatbuffers::{FlatBufferBuilder, ForwardsUOffset, Vector, WIPOffset};
+use std::sync::Arc;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AbilityKind {
+    Dash,
+    DeployCover,
+    AreaHeal,
+    Scan,
+}
+
+impl AbilityKind {
+    pub fn to_fb(self) -> fb::AbilityType {
+        match self {
+            AbilityKind::Dash => fb::AbilityType::Dash,
+            AbilityKind::DeployCover => fb::AbilityType::DeployCover,
+            AbilityKind::AreaHeal => fb::AbilityType::AreaHeal,
+            AbilityKind::Scan => fb::AbilityType::Scan,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AbilityDef {
+    pub max_charges: u8,
+    pub recharge_secs: f32, // Per charge
+}
+
+pub fn ability_def(kind: AbilityKind) -> AbilityDef {
+    match kind {
+        AbilityKind::Dash => AbilityDef { max_charges: 2, recharge_secs: 6.0 },
+        AbilityKind::DeployCover => AbilityDef { max_charges: 1, recharge_secs: 20.0 },
+        AbilityKind::AreaHeal => AbilityDef { max_charges: 1, recharge_secs: 18.0 },
+        AbilityKind::Scan => AbilityDef { max_charges: 1, recharge_secs: 25.0 },
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PlayerClass {
+    #[default]
+    Assault,
+    Engineer,
+    Medic,
+    Recon,
+}
+
+impl PlayerClass {
+    pub const ALL: [PlayerClass; 4] = [PlayerClass::Assault, PlayerClass::Engineer, PlayerClass::Medic, PlayerClass::Recon];
+
+    /// The class's abilities, in slot order starting at slot 1.
+    pub fn abilities(self) -> &'static [AbilityKind] {
+        match self {
+            PlayerClass::Assault => &[AbilityKind::Dash, AbilityKind::Scan],
+            PlayerClass::Engineer => &[AbilityKind::DeployCover, AbilityKind::Dash],
+            PlayerClass::Medic => &[AbilityKind::AreaHeal, AbilityKind::Dash],
+            PlayerClass::Recon => &[AbilityKind::Scan, AbilityKind::DeployCover],
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PlayerClass::Assault => "Assault",
+            PlayerClass::Engineer => "Engineer",
+            PlayerClass::Medic => "Medic",
+            PlayerClass::Recon => "Recon",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<PlayerClass> {
+        PlayerClass::ALL.into_iter().find(|class| class.name().eq_ignore_ascii_case(name.trim()))
+    }
+
+    pub fn to_fb(self) -> fb::PlayerClass {
+        match self {
+            PlayerClass::Assault => fb::PlayerClass::Assault,
+            PlayerClass::Engineer => fb::PlayerClass::Engineer,
+            PlayerClass::Medic => fb::PlayerClass::Medic,
+            PlayerClass::Recon => fb::PlayerClass::Recon,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AbilitySlot {
+    pub kind: AbilityKind,
+    pub charges: u8,
+    pub recharge_remaining: f32, // Until the next charge comes back; 0 while full
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Abilities {
+    slots: Vec<AbilitySlot>,   // Slot 1 first
+    lockout_remaining: f32,    // No ability can be used until it runs out
+}
+
+impl Abilities {
+    /// The class's abilities with every charge ready.
+    pub fn for_class(class: PlayerClass) -> Self {
+        let slots = class.abilities().iter().map(|&kind| AbilitySlot {
+            kind,
+            charges: ability_def(kind).max_charges,
+            recharge_remaining: 0.0,
+        }).collect();
+        Abilities { slots, lockout_remaining: 0.0 }
+    }
+
+    pub fn slots(&self) -> &[AbilitySlot] {
+        &self.slots
+    }
+
+    fn slot_mut(&mut self, slot: u8) -> Option<&mut AbilitySlot> {
+        self.slots.get_mut((slot as usize).checked_sub(1)?)
+    }
+
+    /// The ability in `slot` (1-based) if it can be used right now.
+    pub fn ready(&self, slot: u8) -> Option<AbilityKind> {
+        let ability = self.slots.get((slot as usize).checked_sub(1)?)?;
+        (ability.charges > 0 && self.lockout_remaining <= 0.0).then_some(ability.kind)
+    }
+
+    /// Spends a charge of the ability in `slot` if it's ready. Returns the ability used.
+    pub fn activate(&mut self, slot: u8, lockout_secs: f32) -> Option<AbilityKind> {
+        let kind = self.ready(slot)?;
+        let ability = self.slot_mut(slot)?;
+        if ability.charges == ability_def(kind).max_charges {
+            ability.recharge_remaining = ability_def(kind).recharge_secs;
+        }
+        ability.charges -= 1;
+        self.lockout_remaining = lockout_secs;
+        Some(kind)
+    }
+
+    /// Gives back a charge spent on an activation that didn't take place.
+    pub fn refund(&mut self, slot: u8) {
+        self.lockout_remaining = 0.0;
+        let Some(ability) = self.slot_mut(slot) else { return };
+        let max_charges = ability_def(ability.kind).max_charges;
+        ability.charges = (ability.charges + 1).min(max_charges);
+        if ability.charges == max_charges {
+            ability.recharge_remaining = 0.0;
+        }
+    }
+
+    /// Advances recharges and the lockout. Returns true if any charge came back.
+    pub fn update(&mut self, delta_time: f32) -> bool {
+        self.lockout_remaining = (self.lockout_remaining - delta_time).max(0.0);
+        let mut recharged = false;
+        for ability in self.slots.iter_mut() {
+            let def = ability_def(ability.kind);
+            if ability.charges >= def.max_charges {
+                continue;
+            }
+            ability.recharge_remaining -= delta_time;
+            if ability.recharge_remaining <= 0.0 {
+                ability.charges += 1;
+                recharged = true;
+                ability.recharge_remaining = if ability.charges < def.max_charges {
+                    ability.recharge_remaining + def.recharge_secs
+                } else {
+                    0.0
+                };
+            }
+        }
+        recharged
+    }
+}
+
+/// An activation paid for at input time, resolved later in the tick.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AbilityActivation {
+    pub player_id: PlayerID,
+    pub slot: u8,
+    pub ability: AbilityKind,
+}
+
+/// What a bot weighs when deciding on an ability.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BotAbilityContext {
+    pub health_fraction: f32,
+    pub enemy_distance: Option<f32>, // Nearest visible enemy
+    pub stuck: bool,
+}
+
+/// The slot a bot should use this tick, or 0 for none: heal when hurt, put up cover or dash away
+/// when hurt and under fire, dash to get unstuck and scan when there's nobody to fight.
+pub fn bot_ability_slot(abilities: &Abilities, context: &BotAbilityContext) -> u8 {
+    let hurt = context.health_fraction < 0.5;
+    let close_enemy = context.enemy_distance.is_some_and(|distance| distance < 250.0);
+    let wanted = |kind: AbilityKind| match kind {
+        AbilityKind::AreaHeal => hurt,
+        AbilityKind::DeployCover => hurt && context.enemy_distance.is_some(),
+        AbilityKind::Dash => context.stuck || (hurt && close_enemy),
+        AbilityKind::Scan => context.enemy_distance.is_none(),
+    };
+    (1..=abilities.slots().len() as u8)
+        .find(|&slot| abilities.ready(slot).is_some_and(wanted))
+        .unwrap_or(0)
+}
+
+/// The ability slots sent to their owner.
+pub fn abilities_fb<'a>(builder: &mut FlatBufferBuilder<'a>, abilities: &Abilities) -> WIPOffset<Vector<'a, ForwardsUOffset<fb::AbilityState<'a>>>> {
+    let entries: Vec<_> = abilities.slots().iter().enumerate().map(|(index, ability)| {
+        let def = ability_def(ability.kind);
+        fb::AbilityState::create(builder, &fb::AbilityStateArgs {
+            slot: index as u8 + 1,
+            ability: ability.kind.to_fb(),
+            charges: ability.charges,
+            max_charges: def.max_charges,
+            recharge_remaining: ability.recharge_remaining.max(0.0),
+            recharge_secs: def.recharge_secs,
+        })
+    }).collect();
+    builder.create_vector(&entries)
+}
+
+/// Revealed player -> (scanner, scanner's team).
+pub type RevealedPlayersMap = Arc<DashMap<PlayerID, (PlayerID, u8)>>;
+
+/// Drops the reveals a leaving player was part of, whether they were revealed or did the scanning.
+pub fn forget_reveals(revealed: &RevealedPlayersMap, player_id: &PlayerID) {
+    revealed.remove(player_id);
+    revealed.retain(|_, (scanner_id, _)| scanner_id != player_id);
+}
+
+/// Where cover goes for a player at `position` facing `rotation`: a slab across the facing
+/// direction, `distance` in front. Returns the top-left corner and size.
+pub fn cover_rect(position: Vec2, rotation: f32, distance: f32, length: f32, thickness: f32) -> (Vec2, f32, f32) {
+    let (dir_x, dir_y) = (rotation.cos(), rotation.sin());
+    let center = Vec2::new(position.x + dir_x * distance, position.y + dir_y * distance);
+    // Walls are axis-aligned, so the slab faces whichever axis is closer to the facing
+    let (width, height) = if dir_x.abs() >= dir_y.abs() { (thickness, length) } else { (length, thickness) };
+    (Vec2::new(center.x - width / 2.0, center.y - height / 2.0), width, height)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reveals_are_forgotten_when_either_side_leaves() {
+        let id = |name: &str| -> PlayerID { Arc::new(name.to_string()) };
+        let revealed: RevealedPlayersMap = Arc::new(DashMap::new());
+        revealed.insert(id("target"), (id("scanner"), 1));
+        revealed.insert(id("other"), (id("scanner"), 1));
+        revealed.insert(id("scanner"), (id("enemy"), 2));
+
+        forget_reveals(&revealed, &id("target"));
+        assert!(!revealed.contains_key(&id("target")));
+        forget_reveals(&revealed, &id("scanner"));
+        assert!(revealed.is_empty());
+    }
+
+    #[test]
+    fn test_charges_recharge_one_at_a_time() {
+        let mut abilities = Abilities::for_class(PlayerClass::Assault);
+        assert_eq!(abilities.activate(1, 0.5), Some(AbilityKind::Dash));
+        assert_eq!(abilities.activate(1, 0.5), None); // Locked out
+        abilities.update(0.5);
+        assert_eq!(abilities.activate(1, 0.5), Some(AbilityKind::Dash));
+        abilities.update(0.5);
+        assert_eq!(abilities.ready(1), None);
+        assert_eq!(abilities.ready(3), None);
+        assert_eq!(abilities.ready(0), None);
+
+        // The first charge has been recharging for a second already
+        assert!(abilities.update(5.0));
+        assert_eq!(abilities.slots()[0].charges, 1);
+        assert_eq!(abilities.slots()[0].recharge_remaining, 6.0);
+        abilities.update(6.0);
+        assert_eq!(abilities.slots()[0].charges, 2);
+        assert_eq!(abilities.slots()[0].recharge_remaining, 0.0);
+        assert!(!abilities.update(1.0));
+    }
+
+    #[test]
+    fn test_refund_and_classes() {
+        let mut abilities = Abilities::for_class(PlayerClass::Engineer);
+        assert_eq!(abilities.activate(1, 0.5), Some(AbilityKind::DeployCover));
+        abilities.refund(1);
+        assert_eq!(abilities.slots()[0].charges, 1);
+        assert_eq!(abilities.slots()[0].recharge_remaining, 0.0);
+        assert_eq!(abilities.ready(2), Some(AbilityKind::Dash));
+
+        assert_eq!(PlayerClass::parse(" medic "), Some(PlayerClass::Medic));
+        assert_eq!(PlayerClass::parse("wizard"), None);
+    }
+
+    #[test]
+    fn test_bot_ability_choice() {
+        let medic = Abilities::for_class(PlayerClass::Medic);
+        let calm = BotAbilityContext { health_fraction: 1.0, enemy_distance: Some(300.0), stuck: false };
+        assert_eq!(bot_ability_slot(&medic, &calm), 0);
+        assert_eq!(bot_ability_slot(&medic, &BotAbilityContext { health_fraction: 0.3, ..calm }), 1);
+        assert_eq!(bot_ability_slot(&medic, &BotAbilityContext { stuck: true, ..calm }), 2);
+        let recon = Abilities::for_class(PlayerClass::Recon);
+        assert_eq!(bot_ability_slot(&recon, &BotAbilityContext { enemy_distance: None, ..calm }), 1);
+
+        let (corner, width, height) = cover_rect(Vec2::zero(), 0.0, 50.0, 80.0, 16.0);
+        assert_eq!((corner, width, height), (Vec2::new(42.0, -40.0), 16.0, 80.0));
+    }
+}
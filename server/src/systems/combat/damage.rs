@@ -0,0 +1,243 @@
+// massive_game_server/server/src/systems/combat/damage.rs
+// Per-life damage log and the death recap built from it.
+//
+// Every hit a player takes is recorded on their PlayerState until they die. On death the
+// log is taken off the victim, grouped by attacker (most damage first) and sent to them as a
+// DeathRecap, which answers "how did I die so fast" with the actual numbers.
+use crate::core::constants::MAX_DAMAGE_LOG_ENTRIES;
+use crate::core::types::{PlayerID, ServerWeaponType};
+use crate::flatbuffers_generated::game_protocol as fb;
+use crate::systems::combat::effects::EffectKind;
+use crate::server::instance::map_server_weapon_to_fb;
+use bytes::Bytes;
+use flatbuffers::FlatBufferBuilder;
+
+/// What dealt a hit.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DamageCause {
+    Weapon(ServerWeaponType),
+    SafeZone,           // Outside the battle royale circle
+    Effect(EffectKind), // Damage over time, e.g. burning
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DamageTaken {
+    pub attacker_id: Option<PlayerID>, // None for world damage
+    pub cause: DamageCause,
+    pub shield_damage: i32,
+    pub health_damage: i32,
+    pub is_melee: bool,
+    pub distance: f32,
+    pub timestamp_ms: u64,
+}
+
+impl DamageTaken {
+    pub fn total(&self) -> i32 {
+        self.shield_damage + self.health_damage
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DamageLog {
+    hits: Vec<DamageTaken>,
+}
+
+impl DamageLog {
+    /// Records a hit. Past the cap, the oldest hits are dropped; they matter least for a recap.
+    pub fn record(&mut self, hit: DamageTaken) {
+        if self.hits.len() >= MAX_DAMAGE_LOG_ENTRIES {
+            self.hits.remove(0);
+        }
+        self.hits.push(hit);
+    }
+
+    pub fn hits(&self) -> &[DamageTaken] {
+        &self.hits
+    }
+
+    pub fn clear(&mut self) {
+        self.hits.clear();
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AttackerDamage {
+    pub attacker_id: Option<PlayerID>,
+    pub attacker_name: String,
+    pub shield_damage: i32,
+    pub health_damage: i32,
+    pub hits: Vec<DamageTaken>, // Oldest first
+}
+
+impl AttackerDamage {
+    pub fn total(&self) -> i32 {
+        self.shield_damage + self.health_damage
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct DeathRecap {
+    pub killer_id: Option<PlayerID>,
+    pub attackers: Vec<AttackerDamage>, // Most damage first
+    pub death_timestamp_ms: u64,
+}
+
+impl DeathRecap {
+    /// Groups a life's damage by attacker. `attacker_name` resolves names for players still
+    /// connected; attackers who have left are listed by id.
+    pub fn new(
+        log: &DamageLog,
+        killer_id: Option<PlayerID>,
+        death_timestamp_ms: u64,
+        attacker_name: impl Fn(&PlayerID) -> Option<String>,
+    ) -> Self {
+        let mut attackers: Vec<AttackerDamage> = Vec::new();
+        for hit in log.hits() {
+            let idx = match attackers.iter().position(|a| a.attacker_id == hit.attacker_id) {
+                Some(idx) => idx,
+                None => {
+                    let name = match &hit.attacker_id {
+                        Some(id) => attacker_name(id).unwrap_or_else(|| id.to_string()),
+                        None => "World".to_string(),
+                    };
+                    attackers.push(AttackerDamage {
+                        attacker_id: hit.attacker_id.clone(),
+                        attacker_name: name,
+                        shield_damage: 0,
+                        health_damage: 0,
+                        hits: Vec::new(),
+                    });
+                    attackers.len() - 1
+                }
+            };
+            let attacker = &mut attackers[idx];
+            attacker.shield_damage += hit.shield_damage;
+            attacker.health_damage += hit.health_damage;
+            attacker.hits.push(hit.clone());
+        }
+        attackers.sort_by_key(|a| std::cmp::Reverse(a.total()));
+        DeathRecap { killer_id, attackers, death_timestamp_ms }
+    }
+
+    pub fn total_damage(&self) -> i32 {
+        self.attackers.iter().map(AttackerDamage::total).sum()
+    }
+
+    pub fn to_message(&self) -> Bytes {
+        let mut builder = FlatBufferBuilder::with_capacity(1024);
+        let attackers_fb: Vec<_> = self.attackers.iter().map(|attacker| {
+            let hits_fb: Vec<_> = attacker.hits.iter().map(|hit| {
+                let (weapon, cause, effect) = match hit.cause {
+                    DamageCause::Weapon(weapon) => (map_server_weapon_to_fb(weapon), fb::DeathCause::Weapon, fb::StatusEffectType::default()),
+                    DamageCause::SafeZone => (fb::WeaponType::default(), fb::DeathCause::SafeZone, fb::StatusEffectType::default()),
+                    DamageCause::Effect(kind) => (fb::WeaponType::default(), fb::DeathCause::StatusEffect, kind.to_fb()),
+                };
+                fb::DamageInstance::create(&mut builder, &fb::DamageInstanceArgs {
+                    weapon,
+                    shield_damage: hit.shield_damage,
+                    health_damage: hit.health_damage,
+                    is_melee: hit.is_melee,
+                    distance: hit.distance,
+                    time_offset_ms: (hit.timestamp_ms as i64 - self.death_timestamp_ms as i64) as i32,
+                    cause,
+                    effect,
+                })
+            }).collect();
+            let hits_vec = builder.create_vector(&hits_fb);
+            let attacker_id_fb = attacker.attacker_id.as_ref().map(|id| builder.create_string(id.as_str()));
+            let attacker_name_fb = builder.create_string(&attacker.attacker_name);
+            fb::AttackerDamage::create(&mut builder, &fb::AttackerDamageArgs {
+                attacker_id: attacker_id_fb,
+                attacker_name: Some(attacker_name_fb),
+                total_damage: attacker.total(),
+                shield_damage: attacker.shield_damage,
+                health_damage: attacker.health_damage,
+                hits: Some(hits_vec),
+            })
+        }).collect();
+        let attackers_vec = builder.create_vector(&attackers_fb);
+        let killer_id_fb = self.killer_id.as_ref().map(|id| builder.create_string(id.as_str()));
+
+        let recap_fb = fb::DeathRecap::create(&mut builder, &fb::DeathRecapArgs {
+            killer_id: killer_id_fb,
+            attackers: Some(attackers_vec),
+            total_damage: self.total_damage(),
+            death_timestamp: self.death_timestamp_ms,
+        });
+        let game_msg = fb::GameMessage::create(&mut builder, &fb::GameMessageArgs {
+            msg_type: fb::MessageType::DeathRecap,
+            actual_message_type: fb::MessagePayload::DeathRecap,
+            actual_message: Some(recap_fb.as_union_value()),
+        });
+        builder.finish(game_msg, None);
+        Bytes::from(builder.finished_data().to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn hit(attacker: &PlayerID, weapon: ServerWeaponType, shield_damage: i32, health_damage: i32) -> DamageTaken {
+        DamageTaken {
+            attacker_id: Some(attacker.clone()),
+            cause: DamageCause::Weapon(weapon),
+            shield_damage,
+            health_damage,
+            is_melee: weapon == ServerWeaponType::Melee,
+            distance: 10.0,
+            timestamp_ms: 1_000,
+        }
+    }
+
+    #[test]
+    fn test_recap_groups_by_attacker_most_damage_first() {
+        let sniper: PlayerID = Arc::new("sniper".to_string());
+        let brawler: PlayerID = Arc::new("brawler".to_string());
+        let mut log = DamageLog::default();
+        log.record(hit(&brawler, ServerWeaponType::Melee, 20, 10));
+        log.record(hit(&sniper, ServerWeaponType::Sniper, 0, 70));
+        log.record(hit(&brawler, ServerWeaponType::Melee, 0, 30));
+
+        let recap = DeathRecap::new(&log, Some(brawler.clone()), 1_500, |id| Some(id.to_uppercase()));
+        assert_eq!(recap.total_damage(), 130);
+        assert_eq!(recap.attackers[0].attacker_name, "SNIPER");
+        assert_eq!(recap.attackers[1].hits.len(), 2);
+        assert_eq!((recap.attackers[1].shield_damage, recap.attackers[1].health_damage), (20, 40));
+
+        let bytes = recap.to_message();
+        let msg = fb::root_as_game_message(&bytes).unwrap();
+        let recap_fb = msg.actual_message_as_death_recap().unwrap();
+        assert_eq!(recap_fb.killer_id(), Some("brawler"));
+        assert_eq!(recap_fb.attackers().unwrap().get(1).hits().unwrap().get(0).time_offset_ms(), -500);
+    }
+
+    #[test]
+    fn test_recap_reports_zone_and_effect_damage_as_such() {
+        let burner: PlayerID = Arc::new("burner".to_string());
+        let mut log = DamageLog::default();
+        log.record(DamageTaken { attacker_id: None, cause: DamageCause::SafeZone, ..hit(&burner, ServerWeaponType::Pistol, 0, 10) });
+        log.record(DamageTaken { cause: DamageCause::Effect(EffectKind::Burn), ..hit(&burner, ServerWeaponType::Pistol, 0, 6) });
+
+        let bytes = DeathRecap::new(&log, None, 1_000, |_| None).to_message();
+        let recap_fb = fb::root_as_game_message(&bytes).unwrap().actual_message_as_death_recap().unwrap();
+        let cause_of = |attacker: usize| {
+            let hit = recap_fb.attackers().unwrap().get(attacker).hits().unwrap().get(0);
+            (hit.cause(), hit.effect())
+        };
+        assert_eq!(cause_of(0), (fb::DeathCause::SafeZone, fb::StatusEffectType::default()));
+        assert_eq!(cause_of(1), (fb::DeathCause::StatusEffect, fb::StatusEffectType::Burn));
+    }
+
+    #[test]
+    fn test_log_is_capped() {
+        let attacker: PlayerID = Arc::new("a".to_string());
+        let mut log = DamageLog::default();
+        for damage in 0..(MAX_DAMAGE_LOG_ENTRIES as i32 + 5) {
+            log.record(hit(&attacker, ServerWeaponType::Pistol, 0, damage));
+        }
+        assert_eq!(log.hits().len(), MAX_DAMAGE_LOG_ENTRIES);
+        assert_eq!(log.hits()[0].health_damage, 5);
+    }
+}
@@ -0,0 +1,349 @@
+// massive_game_server/server/src/systems/combat/effects.rs
+// Status effects: timed buffs and debuffs on a player and the modifiers they add up to.
+//
+// Each kind of effect is described once, by its `EffectDef`: how long it lasts, what reapplying
+// it does, the modifiers it contributes and an optional tick callback for healing or damage over
+// time. Power-ups are effects applied by a pickup, so a new one needs a kind and a definition
+// here rather than new fields on PlayerState.
+use crate::core::constants::MAX_PLAYER_SPEED_MULTIPLIER;
+use crate::core::types::PlayerID;
+use crate::flatbuffers_generated::game_protocol as fb;
+use flatbuffers::{FlatBufferBuilder, ForwardsUOffset, Vector, WIPOffset};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EffectKind {
+    SpeedBoost,
+    DamageBoost,
+    Shield,
+    Regen,
+    Burn,
+    Slow,
+    Stun,
+    Revealed, // Seen by the team that scanned it, wherever it is
+}
+
+impl EffectKind {
+    pub fn to_fb(self) -> fb::StatusEffectType {
+        match self {
+            EffectKind::SpeedBoost => fb::StatusEffectType::SpeedBoost,
+            EffectKind::DamageBoost => fb::StatusEffectType::DamageBoost,
+            EffectKind::Shield => fb::StatusEffectType::Shield,
+            EffectKind::Regen => fb::StatusEffectType::Regen,
+            EffectKind::Burn => fb::StatusEffectType::Burn,
+            EffectKind::Slow => fb::StatusEffectType::Slow,
+            EffectKind::Stun => fb::StatusEffectType::Stun,
+            EffectKind::Revealed => fb::StatusEffectType::Revealed,
+        }
+    }
+}
+
+/// What applying an effect that is already active does.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StackRule {
+    Refresh,                      // Restart the duration and restore the amount
+    Extend { max_secs: f32 },     // Add the duration on top, up to a cap
+    Intensify { max_stacks: u8 }, // Add a stack and restart the duration
+    Ignore,                       // Keep the one already running
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EffectModifiers {
+    pub move_speed: f32, // Multipliers; 1.0 leaves the stat alone
+    pub damage_dealt: f32,
+    pub damage_taken: f32,
+    pub stunned: bool,   // No moving, shooting or melee
+}
+
+impl EffectModifiers {
+    pub const NONE: EffectModifiers = EffectModifiers { move_speed: 1.0, damage_dealt: 1.0, damage_taken: 1.0, stunned: false };
+
+    fn with(self, other: EffectModifiers, stacks: u8) -> EffectModifiers {
+        let stacks = stacks.max(1) as i32;
+        EffectModifiers {
+            move_speed: self.move_speed * other.move_speed.powi(stacks),
+            damage_dealt: self.damage_dealt * other.damage_dealt.powi(stacks),
+            damage_taken: self.damage_taken * other.damage_taken.powi(stacks),
+            stunned: self.stunned || other.stunned,
+        }
+    }
+}
+
+impl Default for EffectModifiers {
+    fn default() -> Self {
+        EffectModifiers::NONE
+    }
+}
+
+/// Health an effect's tick restores or takes away.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct EffectTick {
+    pub heal: i32,
+    pub damage: i32,
+}
+
+pub type EffectTickFn = fn(&ActiveEffect) -> EffectTick;
+
+#[derive(Clone, Copy, Debug)]
+pub struct EffectDef {
+    pub duration_secs: f32,        // 0 lasts until its amount is used up or it is cleared
+    pub amount: i32,               // Shield capacity, or health per tick
+    pub stacking: StackRule,
+    pub modifiers: EffectModifiers, // Per stack
+    pub absorbs_damage: bool,      // Damage comes out of `amount` before health
+    pub tick_interval_secs: f32,
+    pub on_tick: Option<EffectTickFn>,
+}
+
+const BASE_DEF: EffectDef = EffectDef {
+    duration_secs: 0.0,
+    amount: 0,
+    stacking: StackRule::Refresh,
+    modifiers: EffectModifiers::NONE,
+    absorbs_damage: false,
+    tick_interval_secs: 0.0,
+    on_tick: None,
+};
+
+fn heal_tick(effect: &ActiveEffect) -> EffectTick {
+    EffectTick { heal: effect.amount * effect.stacks as i32, damage: 0 }
+}
+
+fn damage_tick(effect: &ActiveEffect) -> EffectTick {
+    EffectTick { heal: 0, damage: effect.amount * effect.stacks as i32 }
+}
+
+pub fn effect_def(kind: EffectKind) -> EffectDef {
+    match kind {
+        EffectKind::SpeedBoost => EffectDef {
+            duration_secs: 10.0,
+            modifiers: EffectModifiers { move_speed: MAX_PLAYER_SPEED_MULTIPLIER, ..EffectModifiers::NONE },
+            ..BASE_DEF
+        },
+        EffectKind::DamageBoost => EffectDef {
+            duration_secs: 10.0,
+            modifiers: EffectModifiers { damage_dealt: 1.5, ..EffectModifiers::NONE },
+            ..BASE_DEF
+        },
+        EffectKind::Shield => EffectDef { amount: 50, absorbs_damage: true, ..BASE_DEF },
+        EffectKind::Regen => EffectDef {
+            duration_secs: 5.0,
+            amount: 5,
+            stacking: StackRule::Extend { max_secs: 15.0 },
+            tick_interval_secs: 1.0,
+            on_tick: Some(heal_tick),
+            ..BASE_DEF
+        },
+        EffectKind::Burn => EffectDef {
+            duration_secs: 4.0,
+            amount: 3,
+            stacking: StackRule::Intensify { max_stacks: 3 },
+            tick_interval_secs: 0.5,
+            on_tick: Some(damage_tick),
+            ..BASE_DEF
+        },
+        EffectKind::Slow => EffectDef {
+            duration_secs: 3.0,
+            modifiers: EffectModifiers { move_speed: 0.6, ..EffectModifiers::NONE },
+            ..BASE_DEF
+        },
+        EffectKind::Stun => EffectDef {
+            duration_secs: 1.5,
+            stacking: StackRule::Ignore,
+            modifiers: EffectModifiers { move_speed: 0.0, stunned: true, ..EffectModifiers::NONE },
+            ..BASE_DEF
+        },
+        EffectKind::Revealed => EffectDef { duration_secs: 5.0, ..BASE_DEF },
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ActiveEffect {
+    pub kind: EffectKind,
+    pub remaining_secs: f32, // Unused for effects without a duration
+    pub stacks: u8,
+    pub amount: i32,
+    pub source: Option<PlayerID>, // Who applied it; None for pickups and the world
+    tick_timer: f32,
+}
+
+/// A tick's healing or damage, with whoever applied the effect.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SourcedTick {
+    pub tick: EffectTick,
+    pub kind: EffectKind,
+    pub source: Option<PlayerID>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StatusEffects {
+    active: Vec<ActiveEffect>,
+}
+
+impl StatusEffects {
+    pub fn active(&self) -> &[ActiveEffect] {
+        &self.active
+    }
+
+    pub fn get(&self, kind: EffectKind) -> Option<&ActiveEffect> {
+        self.active.iter().find(|effect| effect.kind == kind)
+    }
+
+    /// Applies an effect, following its stacking rule if it's already active. Returns false if
+    /// nothing changed.
+    pub fn apply(&mut self, kind: EffectKind, source: Option<PlayerID>) -> bool {
+        let def = effect_def(kind);
+        let Some(effect) = self.active.iter_mut().find(|effect| effect.kind == kind) else {
+            self.active.push(ActiveEffect {
+                kind,
+                remaining_secs: def.duration_secs,
+                stacks: 1,
+                amount: def.amount,
+                source,
+                tick_timer: def.tick_interval_secs,
+            });
+            return true;
+        };
+        match def.stacking {
+            StackRule::Ignore => return false,
+            StackRule::Refresh => {
+                effect.remaining_secs = def.duration_secs;
+                effect.amount = def.amount;
+            }
+            StackRule::Extend { max_secs } => {
+                effect.remaining_secs = (effect.remaining_secs + def.duration_secs).min(max_secs);
+            }
+            StackRule::Intensify { max_stacks } => {
+                effect.stacks = (effect.stacks + 1).min(max_stacks);
+                effect.remaining_secs = def.duration_secs;
+            }
+        }
+        effect.source = source;
+        true
+    }
+
+    pub fn remove(&mut self, kind: EffectKind) -> bool {
+        let before = self.active.len();
+        self.active.retain(|effect| effect.kind != kind);
+        self.active.len() != before
+    }
+
+    pub fn clear(&mut self) {
+        self.active.clear();
+    }
+
+    /// Every active effect's modifiers combined. Speed never exceeds what anti-cheat allows.
+    pub fn modifiers(&self) -> EffectModifiers {
+        let combined = self.active.iter()
+            .fold(EffectModifiers::NONE, |acc, effect| acc.with(effect_def(effect.kind).modifiers, effect.stacks));
+        EffectModifiers { move_speed: combined.move_speed.min(MAX_PLAYER_SPEED_MULTIPLIER), ..combined }
+    }
+
+    /// Damage-absorbing capacity left, e.g. from a shield.
+    pub fn absorb_amount(&self) -> i32 {
+        self.active.iter().filter(|effect| effect_def(effect.kind).absorbs_damage).map(|effect| effect.amount).sum()
+    }
+
+    /// Takes damage out of absorbing effects, dropping the ones used up. Returns what gets through.
+    pub fn absorb(&mut self, damage: i32) -> i32 {
+        let mut remaining = damage;
+        for effect in self.active.iter_mut().filter(|effect| effect_def(effect.kind).absorbs_damage) {
+            let absorbed = remaining.min(effect.amount);
+            effect.amount -= absorbed;
+            remaining -= absorbed;
+        }
+        self.active.retain(|effect| !effect_def(effect.kind).absorbs_damage || effect.amount > 0);
+        remaining
+    }
+
+    /// Advances durations and tick timers, dropping expired effects. Returns the ticks that fired.
+    pub fn update(&mut self, delta_time: f32) -> Vec<SourcedTick> {
+        let mut ticks = Vec::new();
+        for effect in self.active.iter_mut() {
+            let def = effect_def(effect.kind);
+            if let Some(on_tick) = def.on_tick.filter(|_| def.tick_interval_secs > 0.0) {
+                effect.tick_timer -= delta_time;
+                while effect.tick_timer <= 0.0 {
+                    effect.tick_timer += def.tick_interval_secs;
+                    ticks.push(SourcedTick { tick: on_tick(effect), kind: effect.kind, source: effect.source.clone() });
+                }
+            }
+            if def.duration_secs > 0.0 {
+                effect.remaining_secs -= delta_time;
+            }
+        }
+        self.active.retain(|effect| effect_def(effect.kind).duration_secs <= 0.0 || effect.remaining_secs > 0.0);
+        ticks
+    }
+}
+
+/// The compact effect list sent with a player's state.
+pub fn effects_fb<'a>(builder: &mut FlatBufferBuilder<'a>, effects: &StatusEffects) -> WIPOffset<Vector<'a, ForwardsUOffset<fb::StatusEffect<'a>>>> {
+    let entries: Vec<_> = effects.active().iter().map(|effect| {
+        fb::StatusEffect::create(builder, &fb::StatusEffectArgs {
+            effect: effect.kind.to_fb(),
+            remaining: effect.remaining_secs.max(0.0),
+            stacks: effect.stacks,
+            amount: effect.amount,
+        })
+    }).collect();
+    builder.create_vector(&entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stacking_rules() {
+        let mut effects = StatusEffects::default();
+        assert!(effects.apply(EffectKind::Stun, None));
+        effects.update(1.0);
+        assert!(!effects.apply(EffectKind::Stun, None));
+        assert_eq!(effects.get(EffectKind::Stun).unwrap().remaining_secs, 0.5);
+
+        effects.apply(EffectKind::Regen, None);
+        effects.apply(EffectKind::Regen, None);
+        effects.apply(EffectKind::Regen, None);
+        effects.apply(EffectKind::Regen, None);
+        assert_eq!(effects.get(EffectKind::Regen).unwrap().remaining_secs, 15.0);
+
+        for _ in 0..5 {
+            effects.apply(EffectKind::Burn, None);
+        }
+        assert_eq!(effects.get(EffectKind::Burn).unwrap().stacks, 3);
+    }
+
+    #[test]
+    fn test_modifiers_combine_and_expire() {
+        let mut effects = StatusEffects::default();
+        effects.apply(EffectKind::SpeedBoost, None);
+        effects.apply(EffectKind::Slow, None);
+        effects.apply(EffectKind::DamageBoost, None);
+        let modifiers = effects.modifiers();
+        assert!((modifiers.move_speed - MAX_PLAYER_SPEED_MULTIPLIER * 0.6).abs() < 1e-5);
+        assert_eq!(modifiers.damage_dealt, 1.5);
+
+        effects.update(5.0);
+        assert_eq!(effects.get(EffectKind::Slow), None);
+        assert_eq!(effects.modifiers().move_speed, MAX_PLAYER_SPEED_MULTIPLIER);
+        effects.update(5.0);
+        assert_eq!(effects.modifiers(), EffectModifiers::NONE);
+    }
+
+    #[test]
+    fn test_shield_absorbs_and_burn_ticks() {
+        let burner: PlayerID = std::sync::Arc::new("burner".to_string());
+        let mut effects = StatusEffects::default();
+        effects.apply(EffectKind::Shield, None);
+        assert_eq!(effects.absorb(30), 0);
+        assert_eq!(effects.absorb_amount(), 20);
+        assert_eq!(effects.absorb(30), 10);
+        assert_eq!(effects.get(EffectKind::Shield), None);
+
+        effects.apply(EffectKind::Burn, Some(burner.clone()));
+        effects.apply(EffectKind::Burn, Some(burner.clone()));
+        let ticks = effects.update(1.0);
+        assert_eq!(ticks.len(), 2);
+        assert_eq!(ticks[0], SourcedTick { tick: EffectTick { heal: 0, damage: 6 }, kind: EffectKind::Burn, source: Some(burner) });
+    }
+}
@@ -0,0 +1,143 @@
+// massive_game_server/server/src/systems/combat/explosives.rs
+// Explosive weapons and the blast they leave: radius damage with falloff, walls that shelter
+// from it and the knockback it deals.
+//
+// Rockets go off when they hit a player or a wall; grenades stop against walls and go off when
+// their fuse (the projectile lifetime) runs out. Only solid walls block a blast. Destructible
+// walls take the damage instead, so explosives are the quick way through them.
+use crate::core::types::{PlayerID, ServerWeaponType, Vec2, Wall};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ExplosiveSpec {
+    pub radius: f32,
+    pub edge_damage_fraction: f32, // Share of the full damage left at the edge of the radius
+    pub knockback: f32,            // Distance a target at the center is pushed
+    pub detonate_on_impact: bool,  // Otherwise it waits out its fuse
+}
+
+pub fn explosive_spec(weapon: ServerWeaponType) -> Option<ExplosiveSpec> {
+    match weapon {
+        ServerWeaponType::RocketLauncher => Some(ExplosiveSpec {
+            radius: 120.0,
+            edge_damage_fraction: 0.2,
+            knockback: 60.0,
+            detonate_on_impact: true,
+        }),
+        ServerWeaponType::GrenadeLauncher => Some(ExplosiveSpec {
+            radius: 150.0,
+            edge_damage_fraction: 0.25,
+            knockback: 80.0,
+            detonate_on_impact: false,
+        }),
+        _ => None,
+    }
+}
+
+/// A detonation waiting to be applied this tick.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Explosion {
+    pub owner_id: PlayerID,
+    pub weapon: ServerWeaponType,
+    pub position: Vec2,
+    pub origin: Vec2, // Where the projectile was fired from, for the kill cam
+    pub damage: i32,  // At the center
+}
+
+/// Damage at `distance` from the center: full at the center, falling off linearly to the
+/// edge fraction at the radius, and nothing beyond it.
+pub fn blast_damage(spec: &ExplosiveSpec, damage: i32, distance: f32) -> i32 {
+    if distance > spec.radius {
+        return 0;
+    }
+    let t = (distance / spec.radius).clamp(0.0, 1.0);
+    let fraction = 1.0 - t * (1.0 - spec.edge_damage_fraction);
+    (damage as f32 * fraction).round() as i32
+}
+
+/// How far to push a target, away from the center and weaker with distance.
+pub fn knockback_offset(spec: &ExplosiveSpec, center: Vec2, target: Vec2) -> Vec2 {
+    let (dx, dy) = (target.x - center.x, target.y - center.y);
+    let distance = dx.hypot(dy);
+    if distance > spec.radius {
+        return Vec2::zero();
+    }
+    let strength = spec.knockback * (1.0 - distance / spec.radius);
+    // Straight on top of the blast there is no direction to push; pick one
+    let (dir_x, dir_y) = if distance < f32::EPSILON { (1.0, 0.0) } else { (dx / distance, dy / distance) };
+    Vec2::new(dir_x * strength, dir_y * strength)
+}
+
+/// The point of a wall closest to `point`; `point` itself if it's inside.
+pub fn closest_point_on_wall(point: Vec2, wall: &Wall) -> Vec2 {
+    Vec2::new(point.x.clamp(wall.x, wall.x + wall.width), point.y.clamp(wall.y, wall.y + wall.height))
+}
+
+/// Whether the segment from `from` to `to` crosses the wall's rectangle.
+pub fn segment_hits_wall(from: Vec2, to: Vec2, wall: &Wall) -> bool {
+    let (mut t_min, mut t_max) = (0.0f32, 1.0f32);
+    let axes = [
+        (from.x, to.x - from.x, wall.x, wall.x + wall.width),
+        (from.y, to.y - from.y, wall.y, wall.y + wall.height),
+    ];
+    for (start, delta, low, high) in axes {
+        if delta.abs() < f32::EPSILON {
+            if start < low || start > high {
+                return false;
+            }
+            continue;
+        }
+        let (t1, t2) = ((low - start) / delta, (high - start) / delta);
+        t_min = t_min.max(t1.min(t2));
+        t_max = t_max.min(t1.max(t2));
+        if t_min > t_max {
+            return false;
+        }
+    }
+    true
+}
+
+/// Whether a blast at `center` reaches `target`: no solid wall stands in between.
+pub fn blast_reaches(center: Vec2, target: Vec2, walls: &[Wall]) -> bool {
+    !walls.iter()
+        .filter(|wall| !wall.is_destructible)
+        .any(|wall| segment_hits_wall(center, target, wall))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wall(x: f32, y: f32, width: f32, height: f32, is_destructible: bool) -> Wall {
+        Wall { id: 1, x, y, width, height, is_destructible, current_health: 100, max_health: 100 }
+    }
+
+    #[test]
+    fn test_blast_damage_falls_off() {
+        let spec = explosive_spec(ServerWeaponType::RocketLauncher).unwrap();
+        assert_eq!(blast_damage(&spec, 100, 0.0), 100);
+        assert_eq!(blast_damage(&spec, 100, spec.radius / 2.0), 60);
+        assert_eq!(blast_damage(&spec, 100, spec.radius), 20);
+        assert_eq!(blast_damage(&spec, 100, spec.radius + 1.0), 0);
+        assert_eq!(explosive_spec(ServerWeaponType::Rifle), None);
+    }
+
+    #[test]
+    fn test_only_solid_walls_block() {
+        let center = Vec2::new(0.0, 0.0);
+        let target = Vec2::new(100.0, 0.0);
+        assert!(!blast_reaches(center, target, &[wall(40.0, -10.0, 20.0, 20.0, false)]));
+        assert!(blast_reaches(center, target, &[wall(40.0, -10.0, 20.0, 20.0, true)]));
+        assert!(blast_reaches(center, target, &[wall(40.0, 5.0, 20.0, 20.0, false)]));
+        assert!(!segment_hits_wall(center, Vec2::new(30.0, 0.0), &wall(40.0, -10.0, 20.0, 20.0, false)));
+        assert_eq!(closest_point_on_wall(center, &wall(40.0, -10.0, 20.0, 20.0, true)), Vec2::new(40.0, 0.0));
+    }
+
+    #[test]
+    fn test_knockback_pushes_away() {
+        let spec = explosive_spec(ServerWeaponType::GrenadeLauncher).unwrap();
+        let push = knockback_offset(&spec, Vec2::new(10.0, 10.0), Vec2::new(10.0, 10.0 - spec.radius / 2.0));
+        assert!(push.x.abs() < 1e-4);
+        assert!((push.y + spec.knockback / 2.0).abs() < 1e-4);
+        assert_eq!(knockback_offset(&spec, Vec2::zero(), Vec2::new(spec.radius * 2.0, 0.0)), Vec2::zero());
+    }
+}
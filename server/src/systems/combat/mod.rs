@@ -0,0 +1,6 @@
+// massive_game_server/server/src/systems/combat/mod.rs
+pub mod abilities;
+pub mod damage;
+pub mod effects;
+pub mod explosives;
+pub mod weapons;
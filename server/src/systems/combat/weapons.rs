@@ -0,0 +1,219 @@
+// massive_game_server/server/src/systems/combat/weapons.rs
+// Weapon inventory: the weapons a player owns, each with its own magazine and reserve ammo.
+//
+// Slots are fixed per weapon (1 pistol, 2 shotgun, 3 rifle, 4 sniper, 5 melee, 6 rockets,
+// 7 grenades), so a slot key always means the same gun; switching to a slot the player doesn't own does nothing.
+// Melee is always owned. The held weapon's loaded rounds live in `PlayerState::ammo` and are
+// written back to its slot on a switch; reloads draw from the slot's reserve.
+use crate::core::constants::*;
+use crate::core::types::{PlayerState, ServerWeaponType};
+
+/// The inventory slot a weapon sits in, as sent in `change_weapon_slot`.
+pub fn weapon_slot(weapon: ServerWeaponType) -> u8 {
+    match weapon {
+        ServerWeaponType::Pistol => 1,
+        ServerWeaponType::Shotgun => 2,
+        ServerWeaponType::Rifle => 3,
+        ServerWeaponType::Sniper => 4,
+        ServerWeaponType::Melee => 5,
+        ServerWeaponType::RocketLauncher => 6,
+        ServerWeaponType::GrenadeLauncher => 7,
+    }
+}
+
+pub fn weapon_in_slot(slot: u8) -> Option<ServerWeaponType> {
+    match slot {
+        1 => Some(ServerWeaponType::Pistol),
+        2 => Some(ServerWeaponType::Shotgun),
+        3 => Some(ServerWeaponType::Rifle),
+        4 => Some(ServerWeaponType::Sniper),
+        5 => Some(ServerWeaponType::Melee),
+        6 => Some(ServerWeaponType::RocketLauncher),
+        7 => Some(ServerWeaponType::GrenadeLauncher),
+        _ => None,
+    }
+}
+
+pub fn max_reserve_for_weapon(weapon: ServerWeaponType) -> i32 {
+    PlayerState::get_max_ammo_for_weapon(weapon) * MAX_RESERVE_MAGAZINES
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct WeaponSlot {
+    pub weapon: ServerWeaponType,
+    pub magazine: i32, // Stale for the held weapon; see PlayerState::ammo
+    pub reserve: i32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LoadoutEntry {
+    pub weapon: ServerWeaponType,
+    pub reserve: i32,
+}
+
+/// A pistol with a couple of spare magazines, plus melee.
+pub fn default_spawn_loadout() -> Vec<LoadoutEntry> {
+    vec![LoadoutEntry {
+        weapon: ServerWeaponType::Pistol,
+        reserve: PlayerState::get_max_ammo_for_weapon(ServerWeaponType::Pistol) * DEFAULT_SPAWN_RESERVE_MAGAZINES,
+    }]
+}
+
+/// Parses weapons written as `weapon:reserve`, comma separated, as used in `SPAWN_LOADOUT`. The
+/// first weapon listed is the one held at spawn. Returns None if any entry is malformed.
+pub fn parse_spawn_loadout(spec: &str) -> Option<Vec<LoadoutEntry>> {
+    spec.split(',').map(|entry| {
+        let (weapon, reserve) = entry.trim().split_once(':')?;
+        let weapon = match weapon.trim().to_ascii_lowercase().as_str() {
+            "pistol" => ServerWeaponType::Pistol,
+            "shotgun" => ServerWeaponType::Shotgun,
+            "rifle" => ServerWeaponType::Rifle,
+            "sniper" => ServerWeaponType::Sniper,
+            "melee" | "knife" => ServerWeaponType::Melee,
+            "rocket" | "rockets" => ServerWeaponType::RocketLauncher,
+            "grenade" | "grenades" => ServerWeaponType::GrenadeLauncher,
+            _ => return None,
+        };
+        let reserve = reserve.trim().parse::<i32>().ok().filter(|rounds| *rounds >= 0)?;
+        Some(LoadoutEntry { weapon, reserve })
+    }).collect()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct WeaponInventory {
+    slots: Vec<WeaponSlot>, // Ordered by slot number
+}
+
+impl Default for WeaponInventory {
+    fn default() -> Self {
+        WeaponInventory::from_loadout(&default_spawn_loadout())
+    }
+}
+
+impl WeaponInventory {
+    /// Every loadout weapon with a full magazine and its reserve, capped, plus melee.
+    pub fn from_loadout(loadout: &[LoadoutEntry]) -> Self {
+        let mut inventory = WeaponInventory { slots: Vec::new() };
+        inventory.grant(ServerWeaponType::Melee, 0);
+        for entry in loadout {
+            inventory.grant(entry.weapon, entry.reserve);
+        }
+        inventory
+    }
+
+    pub fn slots(&self) -> &[WeaponSlot] {
+        &self.slots
+    }
+
+    pub fn get(&self, weapon: ServerWeaponType) -> Option<&WeaponSlot> {
+        self.slots.iter().find(|slot| slot.weapon == weapon)
+    }
+
+    fn get_mut(&mut self, weapon: ServerWeaponType) -> Option<&mut WeaponSlot> {
+        self.slots.iter_mut().find(|slot| slot.weapon == weapon)
+    }
+
+    pub fn owns(&self, weapon: ServerWeaponType) -> bool {
+        self.get(weapon).is_some()
+    }
+
+    pub fn reserve(&self, weapon: ServerWeaponType) -> i32 {
+        self.get(weapon).map_or(0, |slot| slot.reserve)
+    }
+
+    /// Adds a weapon with a full magazine, or just the reserve if it's already owned. Returns
+    /// true if the weapon is new.
+    pub fn grant(&mut self, weapon: ServerWeaponType, reserve: i32) -> bool {
+        if self.owns(weapon) {
+            self.add_reserve(weapon, reserve);
+            return false;
+        }
+        self.slots.push(WeaponSlot {
+            weapon,
+            magazine: PlayerState::get_max_ammo_for_weapon(weapon),
+            reserve: reserve.clamp(0, max_reserve_for_weapon(weapon)),
+        });
+        self.slots.sort_by_key(|slot| weapon_slot(slot.weapon));
+        true
+    }
+
+    /// Adds up to `rounds` to a weapon's reserve, up to the cap. Returns how many were added.
+    pub fn add_reserve(&mut self, weapon: ServerWeaponType, rounds: i32) -> i32 {
+        let Some(slot) = self.get_mut(weapon) else { return 0 };
+        let added = rounds.clamp(0, (max_reserve_for_weapon(weapon) - slot.reserve).max(0));
+        slot.reserve += added;
+        added
+    }
+
+    /// Takes up to `rounds` out of a weapon's reserve. Returns how many were taken.
+    pub fn take_reserve(&mut self, weapon: ServerWeaponType, rounds: i32) -> i32 {
+        let Some(slot) = self.get_mut(weapon) else { return 0 };
+        let taken = rounds.clamp(0, slot.reserve);
+        slot.reserve -= taken;
+        taken
+    }
+
+    /// Records the rounds left in a weapon's magazine when it's put away.
+    pub fn stash_magazine(&mut self, weapon: ServerWeaponType, magazine: i32) {
+        if let Some(slot) = self.get_mut(weapon) {
+            slot.magazine = magazine;
+        }
+    }
+
+    /// The lowest-slot gun that isn't `except` and still has rounds loaded or in reserve.
+    pub fn next_gun_with_ammo(&self, except: ServerWeaponType) -> Option<ServerWeaponType> {
+        self.slots.iter()
+            .find(|slot| slot.weapon != except && slot.weapon != ServerWeaponType::Melee && slot.magazine + slot.reserve > 0)
+            .map(|slot| slot.weapon)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_spawn_loadout() {
+        let loadout = parse_spawn_loadout("rifle:60, pistol:7").unwrap();
+        assert_eq!(loadout[0], LoadoutEntry { weapon: ServerWeaponType::Rifle, reserve: 60 });
+        assert_eq!(loadout[1].weapon, ServerWeaponType::Pistol);
+        assert_eq!(parse_spawn_loadout("rifle"), None);
+        assert_eq!(parse_spawn_loadout("railgun:10"), None);
+        assert_eq!(parse_spawn_loadout("pistol:-1"), None);
+    }
+
+    #[test]
+    fn test_inventory_grants_and_caps_reserve() {
+        let mut inventory = WeaponInventory::from_loadout(&[LoadoutEntry { weapon: ServerWeaponType::Shotgun, reserve: 1000 }]);
+        let weapons: Vec<_> = inventory.slots().iter().map(|slot| slot.weapon).collect();
+        assert_eq!(weapons, vec![ServerWeaponType::Shotgun, ServerWeaponType::Melee]);
+        assert_eq!(inventory.reserve(ServerWeaponType::Shotgun), max_reserve_for_weapon(ServerWeaponType::Shotgun));
+        assert_eq!(inventory.add_reserve(ServerWeaponType::Shotgun, 5), 0);
+
+        assert!(inventory.grant(ServerWeaponType::Pistol, 3));
+        assert!(!inventory.grant(ServerWeaponType::Pistol, 4));
+        assert_eq!(inventory.reserve(ServerWeaponType::Pistol), 7);
+        assert_eq!(inventory.slots()[0].weapon, ServerWeaponType::Pistol);
+        assert_eq!(inventory.take_reserve(ServerWeaponType::Pistol, 10), 7);
+        assert_eq!(inventory.add_reserve(ServerWeaponType::Sniper, 5), 0);
+    }
+
+    #[test]
+    fn test_reload_draws_from_reserve() {
+        let mut state = PlayerState::new("p".to_string(), "p".to_string(), 0.0, 0.0);
+        state.reset_loadout(&[LoadoutEntry { weapon: ServerWeaponType::Pistol, reserve: 3 }]);
+        state.ammo = 1;
+        state.start_reload(std::time::Instant::now());
+        state.update_reload_progress(10.0);
+        assert_eq!((state.ammo, state.reserve_ammo()), (4, 0));
+
+        // Nothing left to load, so no reload starts
+        state.ammo = 0;
+        state.start_reload(std::time::Instant::now());
+        assert_eq!(state.reload_progress, None);
+        assert!(!state.equip_weapon(ServerWeaponType::Rifle));
+        assert!(state.equip_weapon(ServerWeaponType::Melee));
+        assert!(state.equip_weapon(ServerWeaponType::Pistol));
+        assert_eq!(state.ammo, 0);
+    }
+}
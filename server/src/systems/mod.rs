@@ -1,4 +1,6 @@
 pub mod respawn;
 pub mod ai; // Ensure this line exists and is public
 pub mod physics; 
+pub mod combat;
 
+pub mod objectives;
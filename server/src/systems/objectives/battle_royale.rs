@@ -0,0 +1,184 @@
+// massive_game_server/server/src/systems/objectives/battle_royale.rs
+// Battle royale: one life each inside a shrinking safe zone; the last player (or team) standing wins.
+//
+// The mode only moves the circle. The server deals the zone's damage to everyone outside it, so
+// zone deaths go through the same damage log, kill feed and death recap as any other death.
+use super::rules::{MatchRules, SupportedRules};
+use super::{sit_out_late_joiner, GameMode, MatchOutcome, ModeContext, SpawnRules};
+use crate::core::constants::BATTLE_ROYALE_OVERTIME_SECS;
+use crate::core::types::PlayerID;
+use crate::entities::player::ImprovedPlayerManager;
+use crate::flatbuffers_generated::game_protocol as fb;
+use crate::world::boundary::{total_phase_secs, SafeZone, SafeZonePhase};
+use std::collections::{HashMap, HashSet};
+use tracing::info;
+
+pub struct BattleRoyale {
+    phases: Vec<SafeZonePhase>,
+    teams: bool,
+}
+
+impl BattleRoyale {
+    pub fn new(phases: Vec<SafeZonePhase>, teams: bool) -> Self {
+        BattleRoyale { phases, teams }
+    }
+}
+
+/// Living players as (id, team, kills), and how many sides are in the match: teams when playing
+/// in teams, otherwise players.
+fn survivors(player_manager: &ImprovedPlayerManager, teams: bool) -> (Vec<(PlayerID, u8, i32)>, usize) {
+    let mut alive = Vec::new();
+    let mut sides = HashSet::new();
+    let mut total_players = 0;
+    player_manager.for_each_player(|player_id, state| {
+        total_players += 1;
+        sides.insert(state.team_id);
+        if state.alive {
+            alive.push((player_id.clone(), state.team_id, state.kills));
+        }
+    });
+    (alive, if teams { sides.len() } else { total_players })
+}
+
+/// Decides a match from who is still alive. `time_up` forces a result from whoever remains.
+pub fn last_standing_outcome(alive: &[(PlayerID, u8, i32)], sides: usize, teams: bool, time_up: bool) -> Option<MatchOutcome> {
+    if teams {
+        let mut alive_per_team: HashMap<u8, i32> = HashMap::new();
+        for (_, team_id, _) in alive {
+            *alive_per_team.entry(*team_id).or_insert(0) += 1;
+        }
+        return match alive_per_team.len() {
+            0 if sides > 0 => Some(MatchOutcome::NoWinner),
+            1 if sides > 1 || time_up => {
+                let (team_id, count) = alive_per_team.into_iter().next()?;
+                Some(MatchOutcome::TeamWin { team_id, score: count })
+            }
+            _ if time_up => {
+                let best = alive_per_team.values().copied().max().unwrap_or(0);
+                let leaders: Vec<u8> = alive_per_team.iter().filter(|(_, c)| **c == best).map(|(t, _)| *t).collect();
+                Some(match leaders.as_slice() {
+                    [team_id] => MatchOutcome::TeamWin { team_id: *team_id, score: best },
+                    [] => MatchOutcome::NoWinner,
+                    _ => MatchOutcome::Draw { score: best },
+                })
+            }
+            _ => None,
+        };
+    }
+
+    match alive {
+        [] if sides > 0 => Some(MatchOutcome::NoWinner),
+        [(player_id, _, kills)] if sides > 1 || time_up => {
+            Some(MatchOutcome::PlayerWin { player_id: player_id.clone(), score: *kills })
+        }
+        _ if time_up => Some(MatchOutcome::Draw { score: alive.len() as i32 }),
+        _ => None,
+    }
+}
+
+impl GameMode for BattleRoyale {
+    fn mode_type(&self) -> fb::GameModeType {
+        fb::GameModeType::BattleRoyale
+    }
+
+    fn default_rules(&self) -> MatchRules {
+        MatchRules {
+            // Once the last circle closes, the overtime lets the zone finish off any holdouts
+            time_limit_secs: total_phase_secs(&self.phases) + BATTLE_ROYALE_OVERTIME_SECS,
+            ..MatchRules::default()
+        }
+    }
+
+    /// Last one standing wins; there is no score to reach.
+    fn supported_rules(&self) -> SupportedRules {
+        SupportedRules { score_limit: false, mercy: false, rounds: false }
+    }
+
+    fn spawn_rules(&self) -> SpawnRules {
+        SpawnRules { teams: self.teams, use_team_spawns: self.teams, respawns: false }
+    }
+
+    fn on_match_start(&self, ctx: &mut ModeContext) {
+        ctx.match_info.team_scores.clear();
+        ctx.match_info.safe_zone = Some(SafeZone::new(self.phases.clone(), &mut rand::thread_rng()));
+        info!("Battle royale started with {} zone phases.", self.phases.len());
+    }
+
+    fn on_tick(&self, ctx: &mut ModeContext, delta_time: f32) {
+        if let Some(zone) = ctx.match_info.safe_zone.as_mut() {
+            zone.tick(delta_time, &mut rand::thread_rng());
+        }
+    }
+
+    fn on_player_join(&self, ctx: &mut ModeContext, player_id: &PlayerID) {
+        sit_out_late_joiner(self, ctx, player_id);
+    }
+
+    fn check_win(&self, ctx: &ModeContext, time_up: bool) -> Option<MatchOutcome> {
+        let (alive, sides) = survivors(ctx.player_manager, self.teams);
+        last_standing_outcome(&alive, sides, self.teams, time_up)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::concurrent::event_queue::PriorityEventQueue;
+    use crate::concurrent::spatial_index::ImprovedSpatialIndex;
+    use crate::core::constants::*;
+    use crate::server::instance::ServerMatchInfo;
+    use std::sync::Arc;
+
+    fn survivor(name: &str, team_id: u8, kills: i32) -> (PlayerID, u8, i32) {
+        (Arc::new(name.to_string()), team_id, kills)
+    }
+
+    #[test]
+    fn test_last_player_standing_wins() {
+        let alive = vec![survivor("a", 0, 2), survivor("b", 0, 1)];
+        assert_eq!(last_standing_outcome(&alive, 4, false, false), None);
+        assert_eq!(last_standing_outcome(&alive[..1], 4, false, false),
+                   Some(MatchOutcome::PlayerWin { player_id: Arc::new("a".to_string()), score: 2 }));
+        // A lone player waiting for opponents hasn't won anything yet
+        assert_eq!(last_standing_outcome(&alive[..1], 1, false, false), None);
+        assert_eq!(last_standing_outcome(&[], 4, false, false), Some(MatchOutcome::NoWinner));
+    }
+
+    #[test]
+    fn test_last_team_standing_wins() {
+        let alive = vec![survivor("a", 1, 0), survivor("b", 1, 0), survivor("c", 2, 0)];
+        assert_eq!(last_standing_outcome(&alive, 2, true, false), None);
+        assert_eq!(last_standing_outcome(&alive[..2], 2, true, false),
+                   Some(MatchOutcome::TeamWin { team_id: 1, score: 2 }));
+        // Out of time: the team with more survivors takes it
+        assert_eq!(last_standing_outcome(&alive, 2, true, true),
+                   Some(MatchOutcome::TeamWin { team_id: 1, score: 2 }));
+    }
+
+    #[test]
+    fn test_joining_mid_round_sits_out() {
+        let spatial_index = Arc::new(ImprovedSpatialIndex::new(
+            WORLD_MAX_X - WORLD_MIN_X, WORLD_MAX_Y - WORLD_MIN_Y, WORLD_MIN_X, WORLD_MIN_Y, SPATIAL_INDEX_CELL_SIZE,
+        ));
+        let player_manager = ImprovedPlayerManager::new(4, spatial_index);
+        let events = PriorityEventQueue::new();
+        let mode = BattleRoyale::new(Vec::new(), false);
+        let mut match_info = ServerMatchInfo::new(fb::GameModeType::BattleRoyale);
+        let mut ctx = ModeContext { match_info: &mut match_info, player_manager: &player_manager, events: &events };
+
+        // Before the round starts anyone can join in
+        let early = player_manager.add_player("early".to_string(), "early".to_string(), 0.0, 0.0).unwrap();
+        mode.on_player_join(&mut ctx, &early);
+        assert!(player_manager.get_player_state(&early).unwrap().alive);
+
+        ctx.match_info.match_state = fb::MatchStateType::Active;
+        let late = player_manager.add_player("late".to_string(), "late".to_string(), 0.0, 0.0).unwrap();
+        mode.on_player_join(&mut ctx, &late);
+        let late_state = player_manager.get_player_state(&late).unwrap();
+        assert!(!late_state.alive);
+        assert_eq!(late_state.deaths, 0);
+        drop(late_state);
+        // The one player who started the round is its last one standing
+        assert_eq!(mode.check_win(&ctx, false), Some(MatchOutcome::PlayerWin { player_id: early, score: 0 }));
+    }
+}
@@ -0,0 +1,296 @@
+// massive_game_server/server/src/systems/objectives/ctf.rs
+// Capture the flag: grab the enemy flag and bring it to your own while yours is at base.
+use super::rules::{team_limit_outcome, MatchRules};
+use super::scoring::{credit_objective_time, ScoringPolicy};
+use super::{team_score_outcome, GameMode, KillInfo, MatchOutcome, ModeContext, SpawnRules};
+use crate::core::constants::*;
+use crate::core::types::{GameEvent, EventPriority, PlayerID, PlayerState, ServerWeaponType, Vec2, FIELD_FLAG, FIELD_SCORE_STATS};
+use crate::flatbuffers_generated::game_protocol as fb;
+use crate::server::instance::ServerFlagState;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::info;
+
+pub struct CaptureTheFlag;
+
+pub fn flag_base_position(team_id: u8) -> Vec2 {
+    match team_id {
+        1 => Vec2::new(WORLD_MIN_X + 100.0, 0.0),
+        2 => Vec2::new(WORLD_MAX_X - 100.0, 0.0),
+        _ => Vec2::new(0.0, 0.0),
+    }
+}
+
+fn within_pickup_radius(player: &PlayerState, position: Vec2) -> bool {
+    let dx = player.x - position.x;
+    let dy = player.y - position.y;
+    (dx * dx + dy * dy) < (PICKUP_COLLECTION_RADIUS * PICKUP_COLLECTION_RADIUS)
+}
+
+/// Drops every flag `carrier_id` holds at `position`. Returns the teams of the dropped flags.
+fn drop_carried_flags(ctx: &mut ModeContext, carrier_id: &PlayerID, position: Vec2) -> Vec<u8> {
+    let mut dropped = Vec::new();
+    for flag_state in ctx.match_info.flag_states.values_mut() {
+        if flag_state.carrier_id.as_ref() == Some(carrier_id) {
+            flag_state.status = fb::FlagStatus::Dropped;
+            flag_state.position = position;
+            flag_state.carrier_id = None;
+            flag_state.respawn_timer = FLAG_AUTO_RETURN_SECS;
+            dropped.push(flag_state.team_id);
+        }
+    }
+    for &flag_team_id in &dropped {
+        ctx.events.push(GameEvent::FlagDropped {
+            player_id: carrier_id.clone(),
+            flag_team_id,
+            position,
+        }, EventPriority::High);
+    }
+    dropped
+}
+
+impl CaptureTheFlag {
+    fn return_dropped_flags(ctx: &mut ModeContext, delta_time: f32) {
+        for flag_state in ctx.match_info.flag_states.values_mut() {
+            if flag_state.status == fb::FlagStatus::Dropped && flag_state.respawn_timer > 0.0 {
+                flag_state.respawn_timer -= delta_time;
+                if flag_state.respawn_timer <= 0.0 {
+                    flag_state.respawn_timer = 0.0;
+                    flag_state.status = fb::FlagStatus::AtBase;
+                    flag_state.position = flag_base_position(flag_state.team_id);
+                    flag_state.carrier_id = None;
+                    ctx.events.push(GameEvent::FlagReturned {
+                        player_id: Arc::new("server".to_string()),
+                        flag_team_id: flag_state.team_id,
+                        position: flag_state.position
+                    }, EventPriority::High);
+                    info!("Flag of team {} auto-returned to base.", flag_state.team_id);
+                }
+            }
+        }
+    }
+
+    /// Grabs an enemy flag or returns the player's own dropped flag if they are standing on it.
+    fn interact_with_flags(ctx: &mut ModeContext, policy: &ScoringPolicy, player_id: &PlayerID, player: &PlayerState) {
+        for flag_state in ctx.match_info.flag_states.values_mut() {
+            let can_interact = match flag_state.status {
+                fb::FlagStatus::AtBase => true,
+                // Own team can always return their dropped flag; the enemy must wait for the timer
+                fb::FlagStatus::Dropped => flag_state.team_id == player.team_id || flag_state.respawn_timer <= 0.0,
+                _ => false,
+            };
+            if !can_interact || !within_pickup_radius(player, flag_state.position) {
+                continue;
+            }
+
+            if flag_state.team_id != player.team_id {
+                flag_state.status = fb::FlagStatus::Carried;
+                flag_state.carrier_id = Some(player_id.clone());
+                if let Some(mut p_state_mut) = ctx.player_manager.get_player_state_mut(player_id) {
+                    p_state_mut.is_carrying_flag_team_id = flag_state.team_id;
+                    p_state_mut.mark_field_changed(FIELD_FLAG);
+                }
+                ctx.events.push(GameEvent::FlagGrabbed { player_id: player_id.clone(), flag_team_id: flag_state.team_id, position: flag_state.position }, EventPriority::High);
+                info!("Player {} grabbed flag of team {}", player.username, flag_state.team_id);
+                break;
+            } else if flag_state.status == fb::FlagStatus::Dropped {
+                flag_state.status = fb::FlagStatus::AtBase;
+                flag_state.position = flag_base_position(flag_state.team_id);
+                flag_state.carrier_id = None;
+                flag_state.respawn_timer = 0.0;
+                if let Some(mut p_state_mut) = ctx.player_manager.get_player_state_mut(player_id) {
+                    p_state_mut.match_stats.flag_returns += 1;
+                    p_state_mut.score += policy.objective_return;
+                    p_state_mut.mark_field_changed(FIELD_SCORE_STATS);
+                }
+                ctx.events.push(GameEvent::FlagReturned { player_id: player_id.clone(), flag_team_id: flag_state.team_id, position: flag_state.position }, EventPriority::High);
+                info!("Player {} returned own team {}'s flag.", player.username, flag_state.team_id);
+                break;
+            }
+        }
+    }
+
+    /// Scores a capture if the player brings an enemy flag to their own flag at base.
+    fn try_capture(ctx: &mut ModeContext, policy: &ScoringPolicy, player_id: &PlayerID, player: &PlayerState) {
+        let own_team_id = player.team_id;
+        let captured_flag_team_id = player.is_carrying_flag_team_id;
+        let own_flag_at_base = ctx.match_info.flag_states.get(&own_team_id)
+            .is_some_and(|own_flag| own_flag.status == fb::FlagStatus::AtBase);
+        let own_base = flag_base_position(own_team_id);
+        if !own_flag_at_base || !within_pickup_radius(player, own_base) {
+            return;
+        }
+
+        if let Some(captured_flag) = ctx.match_info.flag_states.get_mut(&captured_flag_team_id) {
+            captured_flag.status = fb::FlagStatus::AtBase;
+            captured_flag.position = flag_base_position(captured_flag_team_id);
+            captured_flag.carrier_id = None;
+        }
+        if let Some(mut p_state_mut) = ctx.player_manager.get_player_state_mut(player_id) {
+            p_state_mut.is_carrying_flag_team_id = 0;
+            p_state_mut.score += policy.objective_capture;
+            p_state_mut.match_stats.captures += 1;
+            p_state_mut.mark_field_changed(FIELD_FLAG | FIELD_SCORE_STATS);
+        }
+
+        let team_score = ctx.match_info.team_scores.entry(own_team_id).or_insert(0);
+        *team_score += 1;
+        let current_score = *team_score;
+        ctx.events.push(GameEvent::FlagCaptured {
+            capturer_id: player_id.clone(),
+            captured_flag_team_id,
+            capturing_team_id: own_team_id,
+            position: own_base
+        }, EventPriority::High);
+        info!("Player {} captured team {}'s flag for team {}! (Score: {})", player.username, captured_flag_team_id, own_team_id, current_score);
+    }
+}
+
+impl GameMode for CaptureTheFlag {
+    fn mode_type(&self) -> fb::GameModeType {
+        fb::GameModeType::CaptureTheFlag
+    }
+
+    fn default_rules(&self) -> MatchRules {
+        MatchRules { score_limit: Some(CTF_CAPTURES_TO_WIN), ..MatchRules::default() }
+    }
+
+    fn spawn_rules(&self) -> SpawnRules {
+        SpawnRules { teams: true, use_team_spawns: true, respawns: true }
+    }
+
+    fn scoring_policy(&self) -> ScoringPolicy {
+        ScoringPolicy {
+            objective_capture: 100,
+            objective_return: 50,
+            carrier_kill: 50,
+            objective_defense: 25,
+            objective_points_per_sec: 1,
+            ..ScoringPolicy::default()
+        }
+    }
+
+    fn on_match_start(&self, ctx: &mut ModeContext) {
+        // Captures count toward this match's limit only; carrying them over would end the next match on its first tick
+        ctx.match_info.team_scores.clear();
+        ctx.match_info.flag_states = (1..=2).map(|team_id| (team_id, ServerFlagState {
+            team_id,
+            status: fb::FlagStatus::AtBase,
+            position: flag_base_position(team_id),
+            carrier_id: None,
+            respawn_timer: 0.0,
+        })).collect();
+        info!("CTF Flags initialized. T1 at {:?}, T2 at {:?}", flag_base_position(1), flag_base_position(2));
+    }
+
+    fn on_tick(&self, ctx: &mut ModeContext, delta_time: f32) {
+        Self::return_dropped_flags(ctx, delta_time);
+        let policy = self.scoring_policy();
+
+        let mut player_snapshots: HashMap<PlayerID, PlayerState> = HashMap::new();
+        ctx.player_manager.for_each_player(|id, state| {
+            if state.alive {
+                player_snapshots.insert(id.clone(), state.clone());
+            }
+        });
+
+        for (player_id, player) in &player_snapshots {
+            if player.is_carrying_flag_team_id == 0 {
+                Self::interact_with_flags(ctx, &policy, player_id, player);
+            } else if player.is_carrying_flag_team_id != player.team_id {
+                if let Some(mut p_state_mut) = ctx.player_manager.get_player_state_mut(player_id) {
+                    credit_objective_time(&mut p_state_mut, delta_time, &policy);
+                }
+                Self::try_capture(ctx, &policy, player_id, player);
+            }
+        }
+    }
+
+    fn on_kill(&self, ctx: &mut ModeContext, kill: &KillInfo) {
+        let enemy_kill = kill.killer_team != 0 && kill.killer_team != kill.victim_team;
+        // Stopping a carrier counts for more than stopping someone near your flag
+        let defended = enemy_kill && kill.victim_flag_team_id == 0 && ctx.match_info.flag_states.get(&kill.killer_team)
+            .is_some_and(|own_flag| {
+                let (dx, dy) = (own_flag.position.x - kill.victim_position.x, own_flag.position.y - kill.victim_position.y);
+                dx.hypot(dy) <= FLAG_DEFENSE_RADIUS
+            });
+        if enemy_kill && (kill.victim_flag_team_id != 0 || defended) {
+            let policy = self.scoring_policy();
+            if let Some(mut killer_state) = ctx.player_manager.get_player_state_mut(&kill.killer_id) {
+                if defended {
+                    killer_state.match_stats.defenses += 1;
+                    killer_state.score += policy.objective_defense;
+                } else {
+                    killer_state.match_stats.carrier_kills += 1;
+                    killer_state.score += policy.carrier_kill;
+                }
+                killer_state.mark_field_changed(FIELD_SCORE_STATS);
+            }
+        }
+        if kill.victim_flag_team_id == 0 {
+            return;
+        }
+        // Knifing a flag carrier is worth a point to the attacker's team.
+        if kill.weapon == ServerWeaponType::Melee && kill.killer_team != 0 && kill.killer_team != kill.victim_flag_team_id {
+            *ctx.match_info.team_scores.entry(kill.killer_team).or_insert(0) += 1;
+            info!("Team {} scored +1 via melee kill on flag carrier by {}", kill.killer_team, kill.killer_id);
+        }
+        for flag_team_id in drop_carried_flags(ctx, &kill.victim_id, kill.victim_position) {
+            info!("Flag of team {} dropped at ({:.1}, {:.1}) by {} killing {}",
+                  flag_team_id, kill.victim_position.x, kill.victim_position.y, kill.killer_id, kill.victim_id);
+        }
+    }
+
+    fn on_player_leave(&self, ctx: &mut ModeContext, player_id: &PlayerID, last_position: Vec2) {
+        for flag_team_id in drop_carried_flags(ctx, player_id, last_position) {
+            info!("(Player Left) Flag of team {} dropped at ({:.1}, {:.1})", flag_team_id, last_position.x, last_position.y);
+        }
+    }
+
+    fn check_win(&self, ctx: &ModeContext, time_up: bool) -> Option<MatchOutcome> {
+        team_limit_outcome(ctx.match_info).or_else(|| time_up.then(|| team_score_outcome(ctx.match_info)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::concurrent::event_queue::PriorityEventQueue;
+    use crate::concurrent::spatial_index::ImprovedSpatialIndex;
+    use crate::entities::player::ImprovedPlayerManager;
+    use crate::server::instance::ServerMatchInfo;
+
+    #[test]
+    fn test_carrier_kill_drops_flag_and_captures_win() {
+        let spatial_index = Arc::new(ImprovedSpatialIndex::new(
+            WORLD_MAX_X - WORLD_MIN_X, WORLD_MAX_Y - WORLD_MIN_Y, WORLD_MIN_X, WORLD_MIN_Y, SPATIAL_INDEX_CELL_SIZE,
+        ));
+        let player_manager = ImprovedPlayerManager::new(4, spatial_index);
+        let events = PriorityEventQueue::new();
+        let mode = CaptureTheFlag;
+        let mut match_info = ServerMatchInfo::new(fb::GameModeType::CaptureTheFlag);
+        match_info.rules = mode.default_rules();
+        let mut ctx = ModeContext { match_info: &mut match_info, player_manager: &player_manager, events: &events };
+        mode.on_match_start(&mut ctx);
+
+        let carrier: PlayerID = Arc::new("carrier".to_string());
+        ctx.match_info.flag_states.get_mut(&2).unwrap().status = fb::FlagStatus::Carried;
+        ctx.match_info.flag_states.get_mut(&2).unwrap().carrier_id = Some(carrier.clone());
+        mode.on_kill(&mut ctx, &KillInfo {
+            killer_id: Arc::new("killer".to_string()),
+            victim_id: carrier,
+            killer_team: 2,
+            victim_team: 1,
+            weapon: ServerWeaponType::Rifle,
+            victim_position: Vec2::new(10.0, 20.0),
+            victim_flag_team_id: 2,
+        });
+        let flag = &ctx.match_info.flag_states[&2];
+        assert_eq!(flag.status, fb::FlagStatus::Dropped);
+        assert_eq!(flag.position, Vec2::new(10.0, 20.0));
+
+        assert_eq!(mode.check_win(&ctx, false), None);
+        ctx.match_info.team_scores.insert(1, CTF_CAPTURES_TO_WIN);
+        assert_eq!(mode.check_win(&ctx, false), Some(MatchOutcome::TeamWin { team_id: 1, score: CTF_CAPTURES_TO_WIN }));
+    }
+}
@@ -0,0 +1,70 @@
+// massive_game_server/server/src/systems/objectives/deathmatch.rs
+// Free-for-all and team deathmatch: kills are the only objective.
+use super::rules::{team_limit_outcome, SupportedRules};
+use super::{team_score_outcome, GameMode, KillInfo, MatchOutcome, ModeContext, SpawnRules};
+use crate::core::types::PlayerID;
+use crate::flatbuffers_generated::game_protocol as fb;
+
+pub struct FreeForAll;
+
+impl GameMode for FreeForAll {
+    fn mode_type(&self) -> fb::GameModeType {
+        fb::GameModeType::FreeForAll
+    }
+
+    /// No teams, so no team lead for the mercy rule.
+    fn supported_rules(&self) -> SupportedRules {
+        SupportedRules { score_limit: true, mercy: false, rounds: false }
+    }
+
+    fn spawn_rules(&self) -> SpawnRules {
+        SpawnRules { teams: false, use_team_spawns: false, respawns: true }
+    }
+
+    fn check_win(&self, ctx: &ModeContext, time_up: bool) -> Option<MatchOutcome> {
+        let mut best: Option<(PlayerID, i32)> = None;
+        let mut tied = false;
+        ctx.player_manager.for_each_player(|player_id, player_state| {
+            match best.as_ref().map(|(_, score)| player_state.score.cmp(score)) {
+                None | Some(std::cmp::Ordering::Greater) => {
+                    best = Some((player_id.clone(), player_state.score));
+                    tied = false;
+                }
+                Some(std::cmp::Ordering::Equal) => tied = true,
+                Some(std::cmp::Ordering::Less) => {}
+            }
+        });
+        let limit_reached = ctx.match_info.rules.score_limit
+            .is_some_and(|limit| best.as_ref().is_some_and(|(_, score)| *score >= limit));
+        if !time_up && !limit_reached {
+            return None;
+        }
+        Some(match best {
+            Some((_, score)) if score > 0 && tied => MatchOutcome::Draw { score },
+            Some((player_id, score)) if score > 0 => MatchOutcome::PlayerWin { player_id, score },
+            _ => MatchOutcome::NoWinner,
+        })
+    }
+}
+
+pub struct TeamDeathmatch;
+
+impl GameMode for TeamDeathmatch {
+    fn mode_type(&self) -> fb::GameModeType {
+        fb::GameModeType::TeamDeathmatch
+    }
+
+    fn spawn_rules(&self) -> SpawnRules {
+        SpawnRules { teams: true, use_team_spawns: true, respawns: true }
+    }
+
+    fn on_kill(&self, ctx: &mut ModeContext, kill: &KillInfo) {
+        if kill.killer_team != 0 && kill.victim_team != 0 && kill.killer_team != kill.victim_team {
+            *ctx.match_info.team_scores.entry(kill.killer_team).or_insert(0) += 1;
+        }
+    }
+
+    fn check_win(&self, ctx: &ModeContext, time_up: bool) -> Option<MatchOutcome> {
+        team_limit_outcome(ctx.match_info).or_else(|| time_up.then(|| team_score_outcome(ctx.match_info)))
+    }
+}
@@ -0,0 +1,198 @@
+// massive_game_server/server/src/systems/objectives/domination.rs
+// Domination: teams fight over the map's capture zones, and every held zone ticks points to its owner.
+//
+// Capture speed grows with the number of teammates inside, up to ZONE_MAX_CAPTURE_MULTIPLIER.
+// A zone with both teams inside is contested and its progress is frozen. Progress toward the
+// other team's capture has to be undone before a team can start its own.
+use super::rules::{team_limit_outcome, MatchRules};
+use super::scoring::{credit_objective_time, ScoringPolicy};
+use super::{team_score_outcome, GameMode, MatchOutcome, ModeContext, SpawnRules};
+use crate::core::constants::*;
+use crate::core::types::{GameEvent, EventPriority, PlayerID, FIELD_SCORE_STATS};
+use crate::flatbuffers_generated::game_protocol as fb;
+use crate::server::instance::ServerZoneState;
+use crate::world::map_generator::MapGenerator;
+use tracing::info;
+
+pub struct Domination;
+
+pub fn initial_zone_states() -> Vec<ServerZoneState> {
+    MapGenerator::get_capture_zones().into_iter().enumerate().map(|(idx, (position, radius))| ServerZoneState {
+        zone_id: idx as u8,
+        position,
+        radius,
+        owner_team_id: 0,
+        capturing_team_id: 0,
+        capture_progress: 0.0,
+        contested: false,
+        score_timer: 0.0,
+    }).collect()
+}
+
+fn lose_progress(zone: &mut ServerZoneState, amount: f32) {
+    zone.capture_progress = (zone.capture_progress - amount).max(0.0);
+    if zone.capture_progress == 0.0 {
+        zone.capturing_team_id = 0;
+    }
+}
+
+/// Advances a zone by `delta_time` given the living players of teams 1 and 2 inside it.
+/// Returns the team that captured the zone this tick, if any.
+pub fn update_zone(zone: &mut ServerZoneState, team_counts: [u32; 2], delta_time: f32) -> Option<u8> {
+    zone.contested = team_counts[0] > 0 && team_counts[1] > 0;
+    if zone.contested {
+        return None;
+    }
+
+    let (team_id, count) = match team_counts {
+        [0, 0] => {
+            lose_progress(zone, ZONE_DECAY_PER_SEC * delta_time);
+            return None;
+        }
+        [count, 0] => (1, count),
+        [_, count] => (2, count),
+    };
+    let rate = (count as f32).min(ZONE_MAX_CAPTURE_MULTIPLIER) / ZONE_CAPTURE_SECS * delta_time;
+
+    // Owners standing on their zone, or a team undoing the other's progress, push it back
+    if team_id == zone.owner_team_id || (zone.capturing_team_id != team_id && zone.capture_progress > 0.0) {
+        lose_progress(zone, rate);
+        return None;
+    }
+
+    zone.capturing_team_id = team_id;
+    zone.capture_progress += rate;
+    if zone.capture_progress < 1.0 {
+        return None;
+    }
+    zone.owner_team_id = team_id;
+    zone.capturing_team_id = 0;
+    zone.capture_progress = 0.0;
+    zone.score_timer = 0.0;
+    Some(team_id)
+}
+
+impl GameMode for Domination {
+    fn mode_type(&self) -> fb::GameModeType {
+        fb::GameModeType::Domination
+    }
+
+    fn default_rules(&self) -> MatchRules {
+        MatchRules { score_limit: Some(DOMINATION_SCORE_TO_WIN), ..MatchRules::default() }
+    }
+
+    fn spawn_rules(&self) -> SpawnRules {
+        SpawnRules { teams: true, use_team_spawns: true, respawns: true }
+    }
+
+    fn scoring_policy(&self) -> ScoringPolicy {
+        // Capture points go to each capturer inside the zone when it flips
+        ScoringPolicy { objective_capture: 50, objective_points_per_sec: 1, ..ScoringPolicy::default() }
+    }
+
+    fn on_match_start(&self, ctx: &mut ModeContext) {
+        // Points count toward this match's limit only
+        ctx.match_info.team_scores.clear();
+        ctx.match_info.zone_states = initial_zone_states();
+        info!("Domination zones initialized: {}", ctx.match_info.zone_states.len());
+    }
+
+    fn on_tick(&self, ctx: &mut ModeContext, delta_time: f32) {
+        let zone_count = ctx.match_info.zone_states.len();
+        let mut occupants: Vec<Vec<(PlayerID, u8)>> = vec![Vec::new(); zone_count];
+        let zones = &ctx.match_info.zone_states;
+        ctx.player_manager.for_each_player(|id, state| {
+            if !state.alive || !(1..=2).contains(&state.team_id) {
+                return;
+            }
+            for (idx, zone) in zones.iter().enumerate() {
+                let dx = state.x - zone.position.x;
+                let dy = state.y - zone.position.y;
+                if dx * dx + dy * dy <= zone.radius * zone.radius {
+                    occupants[idx].push((id.clone(), state.team_id));
+                }
+            }
+        });
+
+        let policy = self.scoring_policy();
+        for player_id in occupants.iter().flatten().map(|(player_id, _)| player_id) {
+            if let Some(mut p_state_mut) = ctx.player_manager.get_player_state_mut(player_id) {
+                credit_objective_time(&mut p_state_mut, delta_time, &policy);
+            }
+        }
+
+        for (zone, zone_occupants) in ctx.match_info.zone_states.iter_mut().zip(&occupants) {
+            let mut team_counts = [0u32; 2];
+            for (_, team_id) in zone_occupants {
+                team_counts[*team_id as usize - 1] += 1;
+            }
+
+            if let Some(capturing_team_id) = update_zone(zone, team_counts, delta_time) {
+                for (player_id, _) in zone_occupants.iter().filter(|(_, team_id)| *team_id == capturing_team_id) {
+                    if let Some(mut p_state_mut) = ctx.player_manager.get_player_state_mut(player_id) {
+                        p_state_mut.score += policy.objective_capture;
+                        p_state_mut.match_stats.captures += 1;
+                        p_state_mut.mark_field_changed(FIELD_SCORE_STATS);
+                    }
+                }
+                ctx.events.push(GameEvent::ZoneCaptured {
+                    zone_id: zone.zone_id,
+                    capturing_team_id,
+                    position: zone.position,
+                }, EventPriority::High);
+                info!("Team {} captured zone {}.", capturing_team_id, zone.zone_id);
+            }
+
+            if zone.owner_team_id != 0 {
+                zone.score_timer += delta_time;
+                while zone.score_timer >= ZONE_SCORE_INTERVAL_SECS {
+                    zone.score_timer -= ZONE_SCORE_INTERVAL_SECS;
+                    *ctx.match_info.team_scores.entry(zone.owner_team_id).or_insert(0) += 1;
+                }
+            }
+        }
+    }
+
+    fn check_win(&self, ctx: &ModeContext, time_up: bool) -> Option<MatchOutcome> {
+        team_limit_outcome(ctx.match_info).or_else(|| time_up.then(|| team_score_outcome(ctx.match_info)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn neutral_zone() -> ServerZoneState {
+        initial_zone_states().remove(0)
+    }
+
+    #[test]
+    fn test_capture_speed_scales_with_players_and_freezes_when_contested() {
+        let mut zone = neutral_zone();
+        assert_eq!(update_zone(&mut zone, [1, 0], ZONE_CAPTURE_SECS / 2.0), None);
+        assert_eq!(zone.capturing_team_id, 1);
+
+        // Both teams inside: nothing moves
+        let progress = zone.capture_progress;
+        assert_eq!(update_zone(&mut zone, [2, 1], 5.0), None);
+        assert!(zone.contested);
+        assert_eq!(zone.capture_progress, progress);
+
+        // Two players finish the remaining half in a quarter of the solo time
+        assert_eq!(update_zone(&mut zone, [2, 0], ZONE_CAPTURE_SECS / 4.0 + 0.01), Some(1));
+        assert_eq!((zone.owner_team_id, zone.capturing_team_id, zone.contested), (1, 0, false));
+    }
+
+    #[test]
+    fn test_enemy_progress_is_undone_before_capturing() {
+        let mut zone = neutral_zone();
+        zone.owner_team_id = 1;
+        update_zone(&mut zone, [0, 1], ZONE_CAPTURE_SECS / 2.0);
+        assert_eq!(zone.capturing_team_id, 2);
+
+        // A defender pushes the attackers' progress back to nothing
+        update_zone(&mut zone, [1, 0], ZONE_CAPTURE_SECS);
+        assert_eq!((zone.capturing_team_id, zone.capture_progress), (0, 0.0));
+        assert_eq!(zone.owner_team_id, 1);
+    }
+}
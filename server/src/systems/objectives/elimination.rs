@@ -0,0 +1,268 @@
+// massive_game_server/server/src/systems/objectives/elimination.rs
+// Round-based team elimination, best of N rounds.
+//
+// The match opens with a warmup with free respawns. Each round then starts with a freeze where
+// everyone is put back at their spawn with input locked, followed by a live round with no
+// respawns that goes to the last team with anyone standing. Teams swap bases at half time.
+use super::rules::{team_limit_outcome, MatchRules, SupportedRules};
+use super::{sit_out_late_joiner, team_score_outcome, GameMode, MatchOutcome, ModeContext, SpawnRules};
+use crate::core::constants::*;
+use crate::core::types::{PlayerID, FIELD_FLAG};
+use crate::flatbuffers_generated::game_protocol as fb;
+use crate::server::instance::{ServerMatchInfo, ServerRoundState};
+use tracing::info;
+
+pub struct Elimination {
+    best_of: u8,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoundResult {
+    Won(u8),
+    Draw,
+}
+
+impl Elimination {
+    pub fn new(best_of: u8) -> Self {
+        Elimination { best_of: best_of.max(1) }
+    }
+
+    fn rounds_to_win(&self) -> i32 {
+        self.best_of as i32 / 2 + 1
+    }
+}
+
+pub fn initial_round_state(total_rounds: u8) -> ServerRoundState {
+    ServerRoundState {
+        round_number: 0,
+        total_rounds,
+        phase: fb::RoundPhase::Warmup,
+        phase_time_remaining: ROUND_WARMUP_SECS,
+        sides_swapped: false,
+        respawn_all: false,
+    }
+}
+
+/// Who takes a live round given the players alive on teams 1 and 2, if it's over.
+pub fn round_result(alive: [u32; 2], time_up: bool) -> Option<RoundResult> {
+    match alive {
+        [0, 0] => Some(RoundResult::Draw),
+        [_, 0] => Some(RoundResult::Won(1)),
+        [0, _] => Some(RoundResult::Won(2)),
+        [team1, team2] if time_up => Some(match team1.cmp(&team2) {
+            std::cmp::Ordering::Greater => RoundResult::Won(1),
+            std::cmp::Ordering::Less => RoundResult::Won(2),
+            std::cmp::Ordering::Equal => RoundResult::Draw,
+        }),
+        _ => None,
+    }
+}
+
+/// A team that has won enough rounds to take the match.
+pub fn rounds_limit_outcome(match_info: &ServerMatchInfo) -> Option<MatchOutcome> {
+    let rounds_to_win = match_info.rules.rounds_to_win?;
+    match_info.team_scores.iter()
+        .find(|(_, rounds_won)| **rounds_won >= rounds_to_win)
+        .map(|(team_id, rounds_won)| MatchOutcome::TeamWin { team_id: *team_id, score: *rounds_won })
+}
+
+/// Advances the round clock. Returns the result of a live round that ended this tick.
+pub fn advance_round(round: &mut ServerRoundState, alive: [u32; 2], delta_time: f32) -> Option<RoundResult> {
+    round.phase_time_remaining -= delta_time;
+    match round.phase {
+        fb::RoundPhase::Warmup | fb::RoundPhase::RoundEnd if round.phase_time_remaining <= 0.0 => {
+            if round.round_number >= round.total_rounds {
+                return None; // Last round played; the match is over
+            }
+            round.round_number += 1;
+            // Swap bases once, going into the first round of the second half
+            if round.round_number == round.total_rounds / 2 + 1 && round.total_rounds > 1 {
+                round.sides_swapped = !round.sides_swapped;
+            }
+            round.phase = fb::RoundPhase::Freeze;
+            round.phase_time_remaining = ROUND_FREEZE_SECS;
+            round.respawn_all = true;
+            None
+        }
+        fb::RoundPhase::Freeze if round.phase_time_remaining <= 0.0 => {
+            round.phase = fb::RoundPhase::Live;
+            round.phase_time_remaining = ROUND_LIVE_SECS;
+            None
+        }
+        fb::RoundPhase::Live => {
+            let result = round_result(alive, round.phase_time_remaining <= 0.0)?;
+            round.phase = fb::RoundPhase::RoundEnd;
+            round.phase_time_remaining = ROUND_END_SECS;
+            Some(result)
+        }
+        _ => None,
+    }
+}
+
+impl GameMode for Elimination {
+    fn mode_type(&self) -> fb::GameModeType {
+        fb::GameModeType::Elimination
+    }
+
+    fn default_rules(&self) -> MatchRules {
+        MatchRules {
+            // Only a backstop: rounds decide the match long before this runs out
+            time_limit_secs: ROUND_WARMUP_SECS + self.best_of as f32 * (ROUND_FREEZE_SECS + ROUND_LIVE_SECS + ROUND_END_SECS) + 1.0,
+            rounds_to_win: Some(self.rounds_to_win()),
+            ..MatchRules::default()
+        }
+    }
+
+    /// Team score is rounds won, so the rounds limit stands in for a score limit.
+    fn supported_rules(&self) -> SupportedRules {
+        SupportedRules { score_limit: false, mercy: true, rounds: true }
+    }
+
+    fn spawn_rules(&self) -> SpawnRules {
+        SpawnRules { teams: true, use_team_spawns: true, respawns: false }
+    }
+
+    fn respawns_allowed(&self, match_info: &ServerMatchInfo) -> bool {
+        match_info.match_state != fb::MatchStateType::Active
+            || match_info.round.as_ref().is_some_and(|round| round.phase == fb::RoundPhase::Warmup)
+    }
+
+    fn input_locked(&self, match_info: &ServerMatchInfo) -> bool {
+        match_info.match_state == fb::MatchStateType::Active
+            && match_info.round.as_ref().is_some_and(|round| round.phase == fb::RoundPhase::Freeze)
+    }
+
+    fn spawn_side(&self, match_info: &ServerMatchInfo, team_id: u8) -> u8 {
+        match (match_info.round.as_ref().is_some_and(|round| round.sides_swapped), team_id) {
+            (true, 1) => 2,
+            (true, 2) => 1,
+            _ => team_id,
+        }
+    }
+
+    fn on_match_start(&self, ctx: &mut ModeContext) {
+        ctx.match_info.team_scores.clear();
+        ctx.match_info.round = Some(initial_round_state(self.best_of));
+        info!("Elimination warmup started, best of {}.", self.best_of);
+    }
+
+    fn on_tick(&self, ctx: &mut ModeContext, delta_time: f32) {
+        let mut alive = [0u32; 2];
+        ctx.player_manager.for_each_player(|_id, state| {
+            if state.alive && (1..=2).contains(&state.team_id) {
+                alive[state.team_id as usize - 1] += 1;
+            }
+        });
+
+        let Some(round) = ctx.match_info.round.as_mut() else { return };
+        let was_warmup = round.phase == fb::RoundPhase::Warmup;
+        let result = advance_round(round, alive, delta_time);
+        let round_number = round.round_number;
+
+        if was_warmup && round.phase == fb::RoundPhase::Freeze {
+            // Warmup frags don't count
+            ctx.player_manager.for_each_player_mut(|_id, p_state| {
+                p_state.reset_match_stats();
+                p_state.mark_field_changed(FIELD_FLAG);
+            });
+        }
+
+        match result {
+            Some(RoundResult::Won(team_id)) => {
+                *ctx.match_info.team_scores.entry(team_id).or_insert(0) += 1;
+                info!("Team {} wins round {}.", team_id, round_number);
+            }
+            Some(RoundResult::Draw) => info!("Round {} is a draw.", round_number),
+            None => {}
+        }
+    }
+
+    /// Joining after warmup means watching until the next round puts everyone back at spawn.
+    fn on_player_join(&self, ctx: &mut ModeContext, player_id: &PlayerID) {
+        sit_out_late_joiner(self, ctx, player_id);
+    }
+
+    fn check_win(&self, ctx: &ModeContext, time_up: bool) -> Option<MatchOutcome> {
+        if let Some(outcome) = rounds_limit_outcome(ctx.match_info).or_else(|| team_limit_outcome(ctx.match_info)) {
+            return Some(outcome);
+        }
+        // Drawn rounds can leave nobody with a majority after the last one
+        let all_played = ctx.match_info.round.as_ref().is_some_and(|round| {
+            round.round_number >= round.total_rounds && round.phase == fb::RoundPhase::RoundEnd && round.phase_time_remaining <= 0.0
+        });
+        (all_played || time_up).then(|| team_score_outcome(ctx.match_info))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::concurrent::event_queue::PriorityEventQueue;
+    use crate::concurrent::spatial_index::ImprovedSpatialIndex;
+    use crate::entities::player::ImprovedPlayerManager;
+    use crate::systems::objectives::rules::parse_match_rules;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_score_override_keeps_rounds_limit() {
+        let mode = Elimination::new(3);
+        let mut match_info = ServerMatchInfo::new(fb::GameModeType::Elimination);
+        match_info.rules = parse_match_rules("score=0", &mode.default_rules()).unwrap();
+        match_info.team_scores.insert(1, 1);
+        match_info.team_scores.insert(2, 0);
+        assert_eq!(rounds_limit_outcome(&match_info), None);
+        match_info.team_scores.insert(1, 2);
+        assert_eq!(rounds_limit_outcome(&match_info), Some(MatchOutcome::TeamWin { team_id: 1, score: 2 }));
+    }
+
+    #[test]
+    fn test_round_result() {
+        assert_eq!(round_result([3, 1], false), None);
+        assert_eq!(round_result([2, 0], false), Some(RoundResult::Won(1)));
+        assert_eq!(round_result([1, 2], true), Some(RoundResult::Won(2)));
+        assert_eq!(round_result([1, 1], true), Some(RoundResult::Draw));
+    }
+
+    #[test]
+    fn test_phases_cycle_and_sides_swap_at_half_time() {
+        let mut round = initial_round_state(4);
+        assert_eq!(advance_round(&mut round, [2, 2], ROUND_WARMUP_SECS), None);
+        assert_eq!((round.round_number, round.phase, round.respawn_all), (1, fb::RoundPhase::Freeze, true));
+
+        for round_number in 1..=4u8 {
+            assert_eq!(round.round_number, round_number);
+            assert_eq!(round.sides_swapped, round_number > 2);
+            advance_round(&mut round, [2, 2], ROUND_FREEZE_SECS);
+            assert_eq!(round.phase, fb::RoundPhase::Live);
+            assert_eq!(advance_round(&mut round, [2, 0], 1.0), Some(RoundResult::Won(1)));
+            advance_round(&mut round, [2, 0], ROUND_END_SECS);
+        }
+        // Nothing starts after the last round
+        assert_eq!((round.round_number, round.phase), (4, fb::RoundPhase::RoundEnd));
+    }
+
+    #[test]
+    fn test_joining_after_warmup_sits_out_the_round() {
+        let spatial_index = Arc::new(ImprovedSpatialIndex::new(
+            WORLD_MAX_X - WORLD_MIN_X, WORLD_MAX_Y - WORLD_MIN_Y, WORLD_MIN_X, WORLD_MIN_Y, SPATIAL_INDEX_CELL_SIZE,
+        ));
+        let player_manager = ImprovedPlayerManager::new(4, spatial_index);
+        let events = PriorityEventQueue::new();
+        let mode = Elimination::new(3);
+        let mut match_info = ServerMatchInfo::new(fb::GameModeType::Elimination);
+        let mut ctx = ModeContext { match_info: &mut match_info, player_manager: &player_manager, events: &events };
+        ctx.match_info.match_state = fb::MatchStateType::Active;
+        mode.on_match_start(&mut ctx);
+
+        let warmup_joiner = player_manager.add_player("warmup".to_string(), "warmup".to_string(), 0.0, 0.0).unwrap();
+        mode.on_player_join(&mut ctx, &warmup_joiner);
+        assert!(player_manager.get_player_state(&warmup_joiner).unwrap().alive);
+
+        for phase in [fb::RoundPhase::Freeze, fb::RoundPhase::Live, fb::RoundPhase::RoundEnd] {
+            ctx.match_info.round.as_mut().unwrap().phase = phase;
+            let late = player_manager.add_player(format!("late {:?}", phase), "late".to_string(), 0.0, 0.0).unwrap();
+            mode.on_player_join(&mut ctx, &late);
+            assert!(!player_manager.get_player_state(&late).unwrap().alive, "joined during {:?}", phase);
+        }
+    }
+}
@@ -0,0 +1,239 @@
+// massive_game_server/server/src/systems/objectives/infection.rs
+// Infection: a few infected hunt everyone else with melee only, but move faster. A survivor
+// killed by the infected turns on the spot and gets straight back up as one of them. The
+// infected win once nobody is left to infect; the survivors win if anyone holds out until time.
+use super::rules::{MatchRules, SupportedRules};
+use super::{GameMode, KillInfo, MatchOutcome, ModeContext, SpawnRules};
+use crate::core::constants::*;
+use crate::core::types::{GameEvent, EventPriority, PlayerID, PlayerState, ServerWeaponType, Vec2, FIELD_SCORE_STATS, FIELD_WEAPON_AMMO};
+use crate::flatbuffers_generated::game_protocol as fb;
+use crate::server::instance::ServerMatchInfo;
+use rand::seq::SliceRandom;
+use tracing::info;
+
+pub const SURVIVOR_TEAM: u8 = 1;
+pub const INFECTED_TEAM: u8 = 2;
+
+pub struct Infection;
+
+/// How many players start infected: a fraction of the lobby, at least one, never everyone.
+pub fn initial_infected_count(player_count: usize) -> usize {
+    let count = ((player_count as f32 * INFECTION_INITIAL_FRACTION).round() as usize).max(1);
+    count.min(player_count.saturating_sub(1)).max(1)
+}
+
+pub fn infection_outcome(survivors: usize, infected: usize, time_up: bool) -> Option<MatchOutcome> {
+    if survivors == 0 && infected > 0 {
+        Some(MatchOutcome::TeamWin { team_id: INFECTED_TEAM, score: infected as i32 })
+    } else if time_up && survivors > 0 {
+        Some(MatchOutcome::TeamWin { team_id: SURVIVOR_TEAM, score: survivors as i32 })
+    } else if time_up {
+        Some(MatchOutcome::NoWinner)
+    } else {
+        None
+    }
+}
+
+/// Moves a player to the infected team with their only allowed weapon.
+fn infect(state: &mut PlayerState) {
+    state.team_id = INFECTED_TEAM;
+    state.equip_weapon(ServerWeaponType::Melee);
+    state.mark_field_changed(FIELD_WEAPON_AMMO | FIELD_SCORE_STATS);
+}
+
+/// (survivors, infected) currently in the match, skipping `except` if given.
+fn team_counts(ctx: &ModeContext, except: Option<&PlayerID>) -> (usize, usize) {
+    let (mut survivors, mut infected) = (0, 0);
+    ctx.player_manager.for_each_player(|player_id, state| {
+        if except == Some(player_id) {
+            return;
+        }
+        match state.team_id {
+            SURVIVOR_TEAM => survivors += 1,
+            INFECTED_TEAM => infected += 1,
+            _ => {}
+        }
+    });
+    (survivors, infected)
+}
+
+impl GameMode for Infection {
+    fn mode_type(&self) -> fb::GameModeType {
+        fb::GameModeType::Infection
+    }
+
+    fn default_rules(&self) -> MatchRules {
+        MatchRules { time_limit_secs: INFECTION_MATCH_DURATION_SECS, ..MatchRules::default() }
+    }
+
+    /// Decided by who is left uninfected; there is no score to reach.
+    fn supported_rules(&self) -> SupportedRules {
+        SupportedRules { score_limit: false, mercy: false, rounds: false }
+    }
+
+    fn spawn_rules(&self) -> SpawnRules {
+        SpawnRules { teams: true, use_team_spawns: true, respawns: true }
+    }
+
+    fn allows_weapon(&self, team_id: u8, weapon: ServerWeaponType) -> bool {
+        team_id != INFECTED_TEAM || weapon == ServerWeaponType::Melee
+    }
+
+    fn speed_boosted(&self, team_id: u8) -> bool {
+        team_id == INFECTED_TEAM
+    }
+
+    fn spawn_weapon(&self, team_id: u8) -> Option<ServerWeaponType> {
+        (team_id == INFECTED_TEAM).then_some(ServerWeaponType::Melee)
+    }
+
+    fn team_name(&self, team_id: u8) -> String {
+        match team_id {
+            SURVIVOR_TEAM => "Survivors".to_string(),
+            INFECTED_TEAM => "Infected".to_string(),
+            _ => format!("Team {}", team_id),
+        }
+    }
+
+    fn team_for_new_player(&self, match_info: &ServerMatchInfo) -> Option<u8> {
+        // Late joiners start on the winning side rather than as free survivors
+        (match_info.match_state == fb::MatchStateType::Active).then_some(INFECTED_TEAM)
+    }
+
+    fn on_match_start(&self, ctx: &mut ModeContext) {
+        ctx.match_info.team_scores.clear();
+        let mut player_ids = Vec::new();
+        ctx.player_manager.for_each_player(|player_id, _| player_ids.push(player_id.clone()));
+        player_ids.shuffle(&mut rand::thread_rng());
+
+        let infected_count = initial_infected_count(player_ids.len());
+        for (idx, player_id) in player_ids.iter().enumerate() {
+            if let Some(mut p_state_mut) = ctx.player_manager.get_player_state_mut(player_id) {
+                if idx < infected_count {
+                    infect(&mut p_state_mut);
+                } else {
+                    p_state_mut.team_id = SURVIVOR_TEAM;
+                    if p_state_mut.weapon == ServerWeaponType::Melee {
+                        if let Some(gun) = p_state_mut.inventory.next_gun_with_ammo(ServerWeaponType::Melee) {
+                            p_state_mut.equip_weapon(gun);
+                        }
+                    }
+                    p_state_mut.mark_field_changed(FIELD_WEAPON_AMMO | FIELD_SCORE_STATS);
+                }
+            }
+        }
+        info!("Infection started with {} infected out of {} players.", infected_count, player_ids.len());
+    }
+
+    fn on_tick(&self, ctx: &mut ModeContext, _delta_time: f32) {
+        // The scoreboard shows how many are left on each side
+        let (survivors, infected) = team_counts(ctx, None);
+        ctx.match_info.team_scores.insert(SURVIVOR_TEAM, survivors as i32);
+        ctx.match_info.team_scores.insert(INFECTED_TEAM, infected as i32);
+    }
+
+    fn on_kill(&self, ctx: &mut ModeContext, kill: &KillInfo) {
+        if kill.killer_team != INFECTED_TEAM || kill.victim_team != SURVIVOR_TEAM {
+            return;
+        }
+        if let Some(mut victim_state) = ctx.player_manager.get_player_state_mut(&kill.victim_id) {
+            // Rise where they fell instead of waiting out the respawn timer
+            victim_state.respawn(kill.victim_position.x, kill.victim_position.y, &[]);
+            infect(&mut victim_state);
+            info!("{} has been infected.", victim_state.username);
+        }
+        ctx.events.push(GameEvent::PlayerInfected {
+            player_id: kill.victim_id.clone(),
+            infected_by: kill.killer_id.clone(),
+            position: kill.victim_position,
+        }, EventPriority::High);
+    }
+
+    fn on_player_join(&self, ctx: &mut ModeContext, player_id: &PlayerID) {
+        if let Some(mut p_state_mut) = ctx.player_manager.get_player_state_mut(player_id) {
+            if p_state_mut.team_id == INFECTED_TEAM {
+                infect(&mut p_state_mut);
+            }
+        }
+    }
+
+    fn on_player_leave(&self, ctx: &mut ModeContext, player_id: &PlayerID, _last_position: Vec2) {
+        if ctx.match_info.match_state != fb::MatchStateType::Active {
+            return;
+        }
+        let (survivors, infected) = team_counts(ctx, Some(player_id));
+        if infected > 0 || survivors < 2 {
+            return;
+        }
+        // The last infected left: someone else has to take over
+        let mut candidates = Vec::new();
+        ctx.player_manager.for_each_player(|id, state| {
+            if id != player_id && state.team_id == SURVIVOR_TEAM {
+                candidates.push(id.clone());
+            }
+        });
+        if let Some(new_infected) = candidates.choose(&mut rand::thread_rng()) {
+            if let Some(mut p_state_mut) = ctx.player_manager.get_player_state_mut(new_infected) {
+                infect(&mut p_state_mut);
+                info!("{} was infected to replace a player who left.", p_state_mut.username);
+            }
+        }
+    }
+
+    fn check_win(&self, ctx: &ModeContext, time_up: bool) -> Option<MatchOutcome> {
+        let (survivors, infected) = team_counts(ctx, None);
+        infection_outcome(survivors, infected, time_up)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::concurrent::event_queue::PriorityEventQueue;
+    use crate::concurrent::spatial_index::ImprovedSpatialIndex;
+    use crate::entities::player::ImprovedPlayerManager;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_initial_infected_count() {
+        assert_eq!(initial_infected_count(2), 1);
+        assert_eq!(initial_infected_count(10), 2);
+        assert_eq!(initial_infected_count(40), 6);
+    }
+
+    #[test]
+    fn test_infection_outcome() {
+        assert_eq!(infection_outcome(3, 2, false), None);
+        assert_eq!(infection_outcome(0, 5, false), Some(MatchOutcome::TeamWin { team_id: INFECTED_TEAM, score: 5 }));
+        assert_eq!(infection_outcome(1, 4, true), Some(MatchOutcome::TeamWin { team_id: SURVIVOR_TEAM, score: 1 }));
+        assert_eq!(Infection.team_name(INFECTED_TEAM), "Infected");
+    }
+
+    #[test]
+    fn test_infected_kill_turns_the_victim() {
+        let spatial_index = Arc::new(ImprovedSpatialIndex::new(
+            WORLD_MAX_X - WORLD_MIN_X, WORLD_MAX_Y - WORLD_MIN_Y, WORLD_MIN_X, WORLD_MIN_Y, SPATIAL_INDEX_CELL_SIZE,
+        ));
+        let player_manager = ImprovedPlayerManager::new(4, spatial_index);
+        let events = PriorityEventQueue::new();
+        let mut match_info = ServerMatchInfo::new(fb::GameModeType::Infection);
+        let mut ctx = ModeContext { match_info: &mut match_info, player_manager: &player_manager, events: &events };
+        let zombie = player_manager.add_player("zombie".to_string(), "zombie".to_string(), 0.0, 0.0).unwrap();
+        let victim = player_manager.add_player("victim".to_string(), "victim".to_string(), 0.0, 0.0).unwrap();
+
+        Infection.on_kill(&mut ctx, &KillInfo {
+            killer_id: zombie.clone(),
+            victim_id: victim.clone(),
+            killer_team: INFECTED_TEAM,
+            victim_team: SURVIVOR_TEAM,
+            weapon: ServerWeaponType::Melee,
+            victim_position: Vec2::new(5.0, 5.0),
+            victim_flag_team_id: 0,
+        });
+        assert_eq!(player_manager.get_player_state(&victim).unwrap().team_id, INFECTED_TEAM);
+        match events.pop() {
+            Some(GameEvent::PlayerInfected { player_id, infected_by, .. }) => assert_eq!((player_id, infected_by), (victim, zombie)),
+            other => panic!("expected an infection event, got {:?}", other),
+        }
+    }
+}
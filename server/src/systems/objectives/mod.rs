@@ -0,0 +1,220 @@
+// massive_game_server/server/src/systems/objectives/mod.rs
+// Game modes.
+//
+// The match flow (waiting -> active -> ended -> reset) lives in the server; everything a mode
+// decides (objectives, what a kill is worth to a team, when the match is won, how players are
+// split and spawned) goes through the `GameMode` hooks below. The mode is picked from
+// `ServerConfig::game_mode` at startup. Limits and tie breaks are `rules::MatchRules`.
+pub mod battle_royale;
+pub mod ctf;
+pub mod deathmatch;
+pub mod domination;
+pub mod elimination;
+pub mod infection;
+pub mod rules;
+pub mod scoring;
+
+use crate::concurrent::event_queue::PriorityEventQueue;
+use crate::core::config::ServerConfig;
+use crate::core::types::{PlayerID, ServerWeaponType, Vec2};
+use crate::entities::player::ImprovedPlayerManager;
+use crate::flatbuffers_generated::game_protocol as fb;
+use crate::server::instance::ServerMatchInfo;
+use rules::{MatchRules, SupportedRules};
+use scoring::ScoringPolicy;
+use std::sync::Arc;
+
+/// What a mode hook may read and change. Built by the server with `match_info` write-locked.
+pub struct ModeContext<'a> {
+    pub match_info: &'a mut ServerMatchInfo,
+    pub player_manager: &'a ImprovedPlayerManager,
+    pub events: &'a PriorityEventQueue,
+}
+
+#[derive(Clone, Debug)]
+pub struct KillInfo {
+    pub killer_id: PlayerID,
+    pub victim_id: PlayerID,
+    pub killer_team: u8,
+    pub victim_team: u8,
+    pub weapon: ServerWeaponType,
+    pub victim_position: Vec2,
+    pub victim_flag_team_id: u8, // Flag the victim was carrying, 0 if none
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MatchOutcome {
+    TeamWin { team_id: u8, score: i32 },
+    PlayerWin { player_id: PlayerID, score: i32 },
+    Draw { score: i32 },
+    NoWinner,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpawnRules {
+    pub teams: bool,           // Players are split into teams 1 and 2; otherwise everyone is team 0
+    pub use_team_spawns: bool, // Respawns prefer the player's team base
+    pub respawns: bool,        // Dead players come back during an active match
+}
+
+pub trait GameMode: Send + Sync {
+    fn mode_type(&self) -> fb::GameModeType;
+
+    /// Rules a match of this mode is played under unless the config overrides them.
+    fn default_rules(&self) -> MatchRules {
+        MatchRules::default()
+    }
+
+    /// The optional limits this mode's `check_win` honours.
+    fn supported_rules(&self) -> SupportedRules {
+        SupportedRules::default()
+    }
+
+    fn spawn_rules(&self) -> SpawnRules;
+
+    /// Points for kills, assists and objective play in this mode.
+    fn scoring_policy(&self) -> ScoringPolicy {
+        ScoringPolicy::default()
+    }
+
+    /// Whether a player on `team_id` may hold or fire `weapon`.
+    fn allows_weapon(&self, _team_id: u8, _weapon: ServerWeaponType) -> bool {
+        true
+    }
+
+    /// Whether players on `team_id` always move at boosted speed.
+    fn speed_boosted(&self, _team_id: u8) -> bool {
+        false
+    }
+
+    /// Weapon a player on `team_id` respawns with, if not the default.
+    fn spawn_weapon(&self, _team_id: u8) -> Option<ServerWeaponType> {
+        None
+    }
+
+    /// Whether dead players may respawn right now.
+    fn respawns_allowed(&self, match_info: &ServerMatchInfo) -> bool {
+        match_info.match_state != fb::MatchStateType::Active || self.spawn_rules().respawns
+    }
+
+    /// Whether movement and firing are currently locked for everyone.
+    fn input_locked(&self, _match_info: &ServerMatchInfo) -> bool {
+        false
+    }
+
+    /// The team base `team_id` spawns at; modes that swap sides map teams to the other base.
+    fn spawn_side(&self, _match_info: &ServerMatchInfo, team_id: u8) -> u8 {
+        team_id
+    }
+
+    /// What `team_id` is called in match results.
+    fn team_name(&self, team_id: u8) -> String {
+        match team_id {
+            1 => "Red Team".to_string(),
+            2 => "Blue Team".to_string(),
+            _ => format!("Team {}", team_id),
+        }
+    }
+
+    /// Team for a player joining now, if the mode picks it rather than the server's balancing.
+    fn team_for_new_player(&self, _match_info: &ServerMatchInfo) -> Option<u8> {
+        None
+    }
+
+    fn on_match_start(&self, _ctx: &mut ModeContext) {}
+
+    /// Runs every tick while the match is active.
+    fn on_tick(&self, _ctx: &mut ModeContext, _delta_time: f32) {}
+
+    /// Runs after the server has applied a kill's individual stats.
+    fn on_kill(&self, _ctx: &mut ModeContext, _kill: &KillInfo) {}
+
+    fn on_player_join(&self, _ctx: &mut ModeContext, _player_id: &PlayerID) {}
+
+    /// Runs just before a player is removed from the match.
+    fn on_player_leave(&self, _ctx: &mut ModeContext, _player_id: &PlayerID, _last_position: Vec2) {}
+
+    /// Checked every active tick; `time_up` is set once the clock runs out and throughout sudden
+    /// death, when a mode must return an outcome. Limits are in `ctx.match_info.rules`.
+    fn check_win(&self, ctx: &ModeContext, time_up: bool) -> Option<MatchOutcome>;
+}
+
+/// Keeps a player who joins while the dead stay dead out of play until respawns are back on,
+/// so a late joiner or a reconnect under a new session can't walk into a live round.
+pub fn sit_out_late_joiner(mode: &dyn GameMode, ctx: &mut ModeContext, player_id: &PlayerID) {
+    if mode.respawns_allowed(ctx.match_info) {
+        return;
+    }
+    if let Some(mut p_state_mut) = ctx.player_manager.get_player_state_mut(player_id) {
+        p_state_mut.sit_out();
+    }
+}
+
+pub fn create_game_mode(config: &ServerConfig) -> Arc<dyn GameMode> {
+    match config.game_mode {
+        fb::GameModeType::FreeForAll => Arc::new(deathmatch::FreeForAll),
+        fb::GameModeType::TeamDeathmatch => Arc::new(deathmatch::TeamDeathmatch),
+        fb::GameModeType::Domination => Arc::new(domination::Domination),
+        fb::GameModeType::Infection => Arc::new(infection::Infection),
+        fb::GameModeType::Elimination => Arc::new(elimination::Elimination::new(config.rounds_best_of)),
+        fb::GameModeType::BattleRoyale => Arc::new(battle_royale::BattleRoyale::new(
+            config.safe_zone_phases.clone(),
+            config.battle_royale_teams,
+        )),
+        _ => Arc::new(ctf::CaptureTheFlag),
+    }
+}
+
+/// Parses a mode name as used in `GAME_MODE` (ffa, tdm, ctf, dom, br, infection, elim).
+pub fn parse_game_mode(name: &str) -> Option<fb::GameModeType> {
+    match name.trim().to_ascii_lowercase().as_str() {
+        "ffa" | "freeforall" => Some(fb::GameModeType::FreeForAll),
+        "tdm" | "teamdeathmatch" => Some(fb::GameModeType::TeamDeathmatch),
+        "ctf" | "capturetheflag" => Some(fb::GameModeType::CaptureTheFlag),
+        "dom" | "domination" | "koth" => Some(fb::GameModeType::Domination),
+        "br" | "battleroyale" => Some(fb::GameModeType::BattleRoyale),
+        "infection" | "infected" | "zombies" => Some(fb::GameModeType::Infection),
+        "elim" | "elimination" | "rounds" => Some(fb::GameModeType::Elimination),
+        _ => None,
+    }
+}
+
+/// The outcome of a two-team match decided on score.
+pub fn team_score_outcome(match_info: &ServerMatchInfo) -> MatchOutcome {
+    let team1_score = match_info.team_scores.get(&1).copied().unwrap_or(0);
+    let team2_score = match_info.team_scores.get(&2).copied().unwrap_or(0);
+    if team1_score > team2_score {
+        MatchOutcome::TeamWin { team_id: 1, score: team1_score }
+    } else if team2_score > team1_score {
+        MatchOutcome::TeamWin { team_id: 2, score: team2_score }
+    } else if team1_score > 0 {
+        MatchOutcome::Draw { score: team1_score }
+    } else {
+        MatchOutcome::NoWinner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_team_score_outcome() {
+        let mut match_info = ServerMatchInfo::new(fb::GameModeType::TeamDeathmatch);
+        assert_eq!(team_score_outcome(&match_info), MatchOutcome::NoWinner);
+        match_info.team_scores.insert(1, 4);
+        match_info.team_scores.insert(2, 4);
+        assert_eq!(team_score_outcome(&match_info), MatchOutcome::Draw { score: 4 });
+        match_info.team_scores.insert(2, 5);
+        assert_eq!(team_score_outcome(&match_info), MatchOutcome::TeamWin { team_id: 2, score: 5 });
+    }
+
+    #[test]
+    fn test_modes_are_selected_by_name() {
+        assert_eq!(parse_game_mode("TDM"), Some(fb::GameModeType::TeamDeathmatch));
+        assert_eq!(parse_game_mode("bogus"), None);
+        let mode = create_game_mode(&ServerConfig { game_mode: fb::GameModeType::FreeForAll, ..Default::default() });
+        assert_eq!(mode.mode_type(), fb::GameModeType::FreeForAll);
+        assert!(!mode.spawn_rules().teams);
+    }
+}
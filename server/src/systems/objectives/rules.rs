@@ -0,0 +1,243 @@
+// massive_game_server/server/src/systems/objectives/rules.rs
+// Match rules: how a match starts, when it's over, and how a tie is broken.
+//
+// Every mode has its own defaults (`GameMode::default_rules`). `MATCH_RULES` in the server config
+// or the admin endpoint can replace them; new rules are picked up when the next match starts.
+// Overrides of limits the mode never checks (`GameMode::supported_rules`) are rejected.
+// A tie when the clock runs out goes to overtime if the rules have any, then to sudden death,
+// where the first side to pull ahead wins.
+use super::MatchOutcome;
+use crate::core::constants::*;
+use crate::flatbuffers_generated::game_protocol as fb;
+use crate::server::instance::ServerMatchInfo;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MatchRules {
+    pub time_limit_secs: f32,     // 0 plays without a clock
+    pub score_limit: Option<i32>, // Team score, or player score in modes without teams
+    pub rounds_to_win: Option<i32>, // Round-based modes: rounds a team must take
+    pub min_players: usize,       // Counting bots
+    pub start_countdown_secs: f32,
+    pub overtime_secs: f32,       // Played once if tied at the time limit; 0 for none
+    pub sudden_death: bool,       // Still tied: play on until someone leads
+    pub mercy_lead: Option<i32>,  // A team this far ahead wins on the spot
+    pub post_match_secs: f32,     // Results are shown this long before the next match
+}
+
+impl Default for MatchRules {
+    fn default() -> Self {
+        MatchRules {
+            time_limit_secs: DEFAULT_MATCH_DURATION_SECS,
+            score_limit: None,
+            rounds_to_win: None,
+            min_players: MIN_PLAYERS_TO_START,
+            start_countdown_secs: MATCH_START_COUNTDOWN_SECS,
+            overtime_secs: 0.0,
+            sudden_death: false,
+            mercy_lead: None,
+            post_match_secs: MATCH_RESET_DELAY_SECS,
+        }
+    }
+}
+
+/// The optional limits a mode's `check_win` looks at.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SupportedRules {
+    pub score_limit: bool,
+    pub mercy: bool,
+    pub rounds: bool,
+}
+
+impl Default for SupportedRules {
+    fn default() -> Self {
+        SupportedRules { score_limit: true, mercy: true, rounds: false }
+    }
+}
+
+/// Parses `key=value` pairs, comma separated, as used in `MATCH_RULES`, over `base`. Keys are
+/// time, score, rounds, min_players, countdown, overtime, sudden_death, mercy and post_match; a
+/// score, rounds or mercy of 0 turns that rule off. Returns None if any pair is malformed.
+pub fn parse_match_rules(spec: &str, base: &MatchRules) -> Option<MatchRules> {
+    let mut rules = base.clone();
+    for pair in spec.split(',').map(str::trim).filter(|pair| !pair.is_empty()) {
+        let (key, value) = pair.split_once('=')?;
+        let value = value.trim();
+        match key.trim().to_ascii_lowercase().as_str() {
+            "time" => rules.time_limit_secs = value.parse::<f32>().ok().filter(|secs| *secs >= 0.0)?,
+            "score" => rules.score_limit = Some(value.parse::<i32>().ok()?).filter(|score| *score > 0),
+            "rounds" => rules.rounds_to_win = Some(value.parse::<i32>().ok()?).filter(|rounds| *rounds > 0),
+            "min_players" => rules.min_players = value.parse().ok()?,
+            "countdown" => rules.start_countdown_secs = value.parse::<f32>().ok().filter(|secs| *secs >= 0.0)?,
+            "overtime" => rules.overtime_secs = value.parse::<f32>().ok().filter(|secs| *secs >= 0.0)?,
+            "sudden_death" => rules.sudden_death = matches!(value, "1" | "true" | "on"),
+            "mercy" => rules.mercy_lead = Some(value.parse::<i32>().ok()?).filter(|lead| *lead > 0),
+            "post_match" => rules.post_match_secs = value.parse::<f32>().ok().filter(|secs| *secs >= 0.0)?,
+            _ => return None,
+        }
+    }
+    Some(rules)
+}
+
+/// Keys of the limits `rules` changes from `base` that the mode doesn't support.
+pub fn unsupported_overrides(rules: &MatchRules, base: &MatchRules, supported: SupportedRules) -> Vec<&'static str> {
+    let mut keys = Vec::new();
+    if !supported.score_limit && rules.score_limit != base.score_limit {
+        keys.push("score");
+    }
+    if !supported.rounds && rules.rounds_to_win != base.rounds_to_win {
+        keys.push("rounds");
+    }
+    if !supported.mercy && rules.mercy_lead != base.mercy_lead {
+        keys.push("mercy");
+    }
+    keys
+}
+
+/// Whether the current period's clock has run out. Regulation without a time limit never does.
+pub fn clock_expired(match_info: &ServerMatchInfo) -> bool {
+    let untimed = match_info.period == fb::MatchPeriod::Regulation && match_info.rules.time_limit_secs <= 0.0;
+    !untimed && match_info.time_remaining <= 0.0
+}
+
+/// A team that has reached the score limit or, under the mercy rule, leads by enough to end it.
+/// Both teams reaching the limit on the same tick level is a draw, left to `apply_tie_rules`.
+pub fn team_limit_outcome(match_info: &ServerMatchInfo) -> Option<MatchOutcome> {
+    let rules = &match_info.rules;
+    let team1_score = match_info.team_scores.get(&1).copied().unwrap_or(0);
+    let team2_score = match_info.team_scores.get(&2).copied().unwrap_or(0);
+    if rules.score_limit.is_some_and(|limit| team1_score.max(team2_score) >= limit) {
+        return Some(match team1_score.cmp(&team2_score) {
+            std::cmp::Ordering::Greater => MatchOutcome::TeamWin { team_id: 1, score: team1_score },
+            std::cmp::Ordering::Less => MatchOutcome::TeamWin { team_id: 2, score: team2_score },
+            std::cmp::Ordering::Equal => MatchOutcome::Draw { score: team1_score },
+        });
+    }
+    let lead = rules.mercy_lead?;
+    if team1_score - team2_score >= lead {
+        Some(MatchOutcome::TeamWin { team_id: 1, score: team1_score })
+    } else if team2_score - team1_score >= lead {
+        Some(MatchOutcome::TeamWin { team_id: 2, score: team2_score })
+    } else {
+        None
+    }
+}
+
+/// Takes the mode's outcome for this tick and returns it if the match ends now. A tie when the
+/// clock runs out, or level at the score limit, moves the match into overtime or sudden death
+/// instead, if the rules allow.
+pub fn apply_tie_rules(match_info: &mut ServerMatchInfo, outcome: Option<MatchOutcome>) -> Option<MatchOutcome> {
+    let outcome = outcome?;
+    let tied = matches!(outcome, MatchOutcome::Draw { .. } | MatchOutcome::NoWinner);
+    let expired = clock_expired(match_info);
+    let level_at_limit = match (&outcome, match_info.rules.score_limit) {
+        (MatchOutcome::Draw { score }, Some(limit)) => *score >= limit,
+        _ => false,
+    };
+    // A tie the mode calls early (everyone dead, say) stands
+    if !tied || (!expired && !level_at_limit && match_info.period != fb::MatchPeriod::SuddenDeath) {
+        return Some(outcome);
+    }
+    match match_info.period {
+        fb::MatchPeriod::Regulation if match_info.rules.overtime_secs > 0.0 => {
+            match_info.period = fb::MatchPeriod::Overtime;
+            match_info.time_remaining = match_info.rules.overtime_secs;
+            None
+        }
+        fb::MatchPeriod::Regulation | fb::MatchPeriod::Overtime if match_info.rules.sudden_death => {
+            match_info.period = fb::MatchPeriod::SuddenDeath;
+            match_info.time_remaining = SUDDEN_DEATH_MAX_SECS;
+            None
+        }
+        // Still level at the limit: the next score settles it
+        fb::MatchPeriod::Overtime | fb::MatchPeriod::SuddenDeath if !expired => None,
+        _ => Some(outcome),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_match_rules() {
+        let base = MatchRules { score_limit: Some(3), ..MatchRules::default() };
+        let rules = parse_match_rules("time=600, score=0, overtime=60, sudden_death=true, mercy=5", &base).unwrap();
+        assert_eq!(rules.time_limit_secs, 600.0);
+        assert_eq!(rules.score_limit, None);
+        assert_eq!((rules.overtime_secs, rules.sudden_death, rules.mercy_lead), (60.0, true, Some(5)));
+        assert_eq!(rules.min_players, base.min_players);
+        assert_eq!(parse_match_rules("time=soon", &base), None);
+        assert_eq!(parse_match_rules("bogus=1", &base), None);
+    }
+
+    #[test]
+    fn test_unsupported_overrides() {
+        let base = MatchRules { rounds_to_win: Some(3), ..MatchRules::default() };
+        let supported = SupportedRules { score_limit: false, mercy: true, rounds: true };
+        let rules = parse_match_rules("score=0, rounds=4, mercy=2", &base).unwrap();
+        assert!(unsupported_overrides(&rules, &base, supported).is_empty());
+
+        let rules = parse_match_rules("score=10, rounds=0", &base).unwrap();
+        assert_eq!(unsupported_overrides(&rules, &base, supported), vec!["score"]);
+        assert_eq!(unsupported_overrides(&rules, &base, SupportedRules::default()), vec!["rounds"]);
+    }
+
+    #[test]
+    fn test_score_limit_and_mercy() {
+        let mut match_info = ServerMatchInfo::new(fb::GameModeType::TeamDeathmatch);
+        match_info.rules = MatchRules { score_limit: Some(10), mercy_lead: Some(5), ..MatchRules::default() };
+        match_info.team_scores.insert(1, 4);
+        match_info.team_scores.insert(2, 0);
+        assert_eq!(team_limit_outcome(&match_info), None);
+        match_info.team_scores.insert(1, 5);
+        assert_eq!(team_limit_outcome(&match_info), Some(MatchOutcome::TeamWin { team_id: 1, score: 5 }));
+        match_info.team_scores.insert(2, 10);
+        assert_eq!(team_limit_outcome(&match_info), Some(MatchOutcome::TeamWin { team_id: 2, score: 10 }));
+    }
+
+    #[test]
+    fn test_tie_goes_to_overtime_then_sudden_death() {
+        let mut match_info = ServerMatchInfo::new(fb::GameModeType::TeamDeathmatch);
+        match_info.rules = MatchRules { overtime_secs: 60.0, sudden_death: true, ..MatchRules::default() };
+        match_info.time_remaining = 0.0;
+        let draw = MatchOutcome::Draw { score: 2 };
+
+        assert_eq!(apply_tie_rules(&mut match_info, Some(draw.clone())), None);
+        assert_eq!((match_info.period, match_info.time_remaining), (fb::MatchPeriod::Overtime, 60.0));
+
+        match_info.time_remaining = 0.0;
+        assert_eq!(apply_tie_rules(&mut match_info, Some(draw.clone())), None);
+        assert_eq!(match_info.period, fb::MatchPeriod::SuddenDeath);
+        assert_eq!(apply_tie_rules(&mut match_info, Some(draw.clone())), None);
+
+        let win = MatchOutcome::TeamWin { team_id: 2, score: 3 };
+        assert_eq!(apply_tie_rules(&mut match_info, Some(win.clone())), Some(win));
+        match_info.time_remaining = 0.0;
+        assert_eq!(apply_tie_rules(&mut match_info, Some(draw.clone())), Some(draw));
+    }
+
+    #[test]
+    fn test_teams_level_at_the_score_limit_play_on() {
+        let mut match_info = ServerMatchInfo::new(fb::GameModeType::TeamDeathmatch);
+        match_info.rules = MatchRules { score_limit: Some(10), overtime_secs: 60.0, ..MatchRules::default() };
+        match_info.team_scores.insert(1, 10);
+        match_info.team_scores.insert(2, 10);
+        let outcome = team_limit_outcome(&match_info);
+        assert_eq!(outcome, Some(MatchOutcome::Draw { score: 10 }));
+        assert_eq!(apply_tie_rules(&mut match_info, outcome), None);
+        assert_eq!((match_info.period, match_info.time_remaining), (fb::MatchPeriod::Overtime, 60.0));
+        let outcome = team_limit_outcome(&match_info);
+        assert_eq!(apply_tie_rules(&mut match_info, outcome), None);
+
+        match_info.team_scores.insert(2, 11);
+        let outcome = team_limit_outcome(&match_info);
+        assert_eq!(apply_tie_rules(&mut match_info, outcome), Some(MatchOutcome::TeamWin { team_id: 2, score: 11 }));
+
+        // Without overtime or sudden death the draw stands
+        match_info.rules = MatchRules { score_limit: Some(10), ..MatchRules::default() };
+        match_info.period = fb::MatchPeriod::Regulation;
+        match_info.team_scores.insert(2, 10);
+        let outcome = team_limit_outcome(&match_info);
+        assert_eq!(apply_tie_rules(&mut match_info, outcome), Some(MatchOutcome::Draw { score: 10 }));
+    }
+}
@@ -0,0 +1,384 @@
+// massive_game_server/server/src/systems/objectives/scoring.rs
+// Scoring: what each action is worth, per-player match stats, and the end-of-match summary.
+//
+// Points go straight onto PlayerState::score as things happen, at the values in the mode's
+// ScoringPolicy. Everything else a scoreboard wants (assists, accuracy, streaks, objective play)
+// is kept in each player's PlayerMatchStats, which resets along with the score. When a match
+// ends the server gathers them into a MatchSummary with awards and an MVP for every client. Each
+// client is sent the top of the scoreboard, the award winners and its own row, with their ranks.
+use crate::core::constants::*;
+use crate::core::types::{PlayerID, PlayerState, FIELD_SCORE_STATS};
+use crate::flatbuffers_generated::game_protocol as fb;
+use crate::server::instance::ServerMatchInfo;
+use crate::systems::combat::damage::DamageLog;
+use bytes::Bytes;
+use flatbuffers::FlatBufferBuilder;
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScoringPolicy {
+    pub kill: i32,
+    pub team_kill: i32,
+    pub assist: i32,
+    pub streak_bonus: i32,             // Every KILL_STREAK_MILESTONE kills without dying
+    pub multi_kill_bonus: i32,         // Each kill after the first in a multi-kill
+    pub objective_capture: i32,        // Flag or zone capture, per capturer
+    pub objective_return: i32,         // Returning your own dropped flag
+    pub carrier_kill: i32,             // Killing a flag carrier
+    pub objective_defense: i32,        // Killing an enemy near your own flag
+    pub objective_points_per_sec: i32, // Carrying a flag or standing in a zone
+}
+
+impl Default for ScoringPolicy {
+    fn default() -> Self {
+        ScoringPolicy {
+            kill: 100,
+            team_kill: -200,
+            assist: 50,
+            streak_bonus: 50,
+            multi_kill_bonus: 25,
+            objective_capture: 0,
+            objective_return: 0,
+            carrier_kill: 0,
+            objective_defense: 0,
+            objective_points_per_sec: 0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PlayerMatchStats {
+    pub assists: i32,
+    pub damage_dealt: i32,
+    pub shots_fired: u32, // Each shotgun pellet counts
+    pub shots_hit: u32,
+    pub streak: u32,      // Kills since the last death
+    pub best_streak: u32,
+    pub multi_kill: u32,  // Kills in the current chain
+    pub best_multi_kill: u32,
+    pub last_kill_ms: u64,
+    pub captures: u32,    // Flags or zones
+    pub flag_returns: u32,
+    pub carrier_kills: u32,
+    pub defenses: u32,
+    pub objective_secs: f32,
+}
+
+impl PlayerMatchStats {
+    pub fn accuracy(&self) -> f32 {
+        if self.shots_fired == 0 {
+            return 0.0;
+        }
+        (self.shots_hit as f32 / self.shots_fired as f32).min(1.0)
+    }
+
+    /// Counts a kill toward the streak and multi-kill chain. Returns the bonus points it earned.
+    pub fn record_kill(&mut self, timestamp_ms: u64, policy: &ScoringPolicy) -> i32 {
+        self.streak += 1;
+        self.best_streak = self.best_streak.max(self.streak);
+        let chained = self.multi_kill > 0 && timestamp_ms.saturating_sub(self.last_kill_ms) <= MULTI_KILL_WINDOW_MS;
+        self.multi_kill = if chained { self.multi_kill + 1 } else { 1 };
+        self.best_multi_kill = self.best_multi_kill.max(self.multi_kill);
+        self.last_kill_ms = timestamp_ms;
+
+        let mut bonus = 0;
+        if self.streak.is_multiple_of(KILL_STREAK_MILESTONE) {
+            bonus += policy.streak_bonus;
+        }
+        if self.multi_kill > 1 {
+            bonus += policy.multi_kill_bonus;
+        }
+        bonus
+    }
+
+    pub fn end_streak(&mut self) {
+        self.streak = 0;
+        self.multi_kill = 0;
+    }
+
+    /// Adds time on the objective. Returns the points for each whole second it completes.
+    pub fn add_objective_time(&mut self, secs: f32, policy: &ScoringPolicy) -> i32 {
+        let whole_secs_before = self.objective_secs.floor();
+        self.objective_secs += secs;
+        (self.objective_secs.floor() - whole_secs_before) as i32 * policy.objective_points_per_sec
+    }
+}
+
+/// Credits a player with time on the objective, and any points it earns them.
+pub fn credit_objective_time(state: &mut PlayerState, secs: f32, policy: &ScoringPolicy) {
+    let points = state.match_stats.add_objective_time(secs, policy);
+    if points != 0 {
+        state.score += points;
+        state.mark_field_changed(FIELD_SCORE_STATS);
+    }
+}
+
+/// Everyone but the killer who damaged the victim within ASSIST_WINDOW_MS of the death.
+pub fn assist_ids(log: &DamageLog, killer_id: &PlayerID, death_ms: u64) -> Vec<PlayerID> {
+    let mut assists: Vec<PlayerID> = Vec::new();
+    for hit in log.hits() {
+        let Some(attacker_id) = &hit.attacker_id else { continue };
+        if attacker_id != killer_id
+            && death_ms.saturating_sub(hit.timestamp_ms) <= ASSIST_WINDOW_MS
+            && !assists.contains(attacker_id)
+        {
+            assists.push(attacker_id.clone());
+        }
+    }
+    assists
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlayerSummary {
+    pub player_id: PlayerID,
+    pub username: String,
+    pub team_id: u8,
+    pub score: i32,
+    pub kills: i32,
+    pub deaths: i32,
+    pub stats: PlayerMatchStats,
+}
+
+impl PlayerSummary {
+    pub fn from_state(player_id: &PlayerID, state: &PlayerState) -> Self {
+        PlayerSummary {
+            player_id: player_id.clone(),
+            username: state.username.clone(),
+            team_id: state.team_id,
+            score: state.score,
+            kills: state.kills,
+            deaths: state.deaths,
+            stats: state.match_stats.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Award {
+    pub award: fb::AwardType,
+    pub player_id: PlayerID,
+    pub value: f32,
+}
+
+/// The player ranked first on `value` among those with anything to show for it.
+fn best_by(players: &[PlayerSummary], value: impl Fn(&PlayerSummary) -> f32) -> Option<(&PlayerSummary, f32)> {
+    players.iter()
+        .map(|player| (player, value(player)))
+        .filter(|(_, value)| *value > 0.0)
+        // Ties go to the player higher on the scoreboard
+        .fold(None, |best: Option<(&PlayerSummary, f32)>, (player, value)| match best {
+            Some((_, best_value)) if best_value >= value => best,
+            _ => Some((player, value)),
+        })
+}
+
+/// The stat an award is decided on; 0 doesn't qualify.
+type AwardStat = fn(&PlayerSummary) -> f32;
+
+/// Hands out the awards for `players`, who must already be in scoreboard order.
+pub fn pick_awards(players: &[PlayerSummary]) -> Vec<Award> {
+    let candidates: [(fb::AwardType, AwardStat); 8] = [
+        (fb::AwardType::Mvp, |p| p.score as f32),
+        (fb::AwardType::Sharpshooter, |p| if p.stats.shots_fired >= SHARPSHOOTER_MIN_SHOTS { p.stats.accuracy() } else { 0.0 }),
+        (fb::AwardType::Unstoppable, |p| p.stats.best_streak as f32),
+        (fb::AwardType::TeamPlayer, |p| p.stats.assists as f32),
+        (fb::AwardType::Capturer, |p| p.stats.captures as f32),
+        (fb::AwardType::Defender, |p| (p.stats.defenses + p.stats.carrier_kills) as f32),
+        (fb::AwardType::ObjectiveHolder, |p| p.stats.objective_secs.floor()),
+        (fb::AwardType::DamageDealer, |p| p.stats.damage_dealt as f32),
+    ];
+    candidates.iter().filter_map(|(award, value)| {
+        best_by(players, value).map(|(player, value)| Award { award: *award, player_id: player.player_id.clone(), value })
+    }).collect()
+}
+
+#[derive(Clone, Debug)]
+pub struct MatchSummary {
+    pub game_mode: fb::GameModeType,
+    pub winner_id: Option<String>,
+    pub winner_name: Option<String>,
+    pub team_scores: HashMap<u8, i32>,
+    pub players: Vec<PlayerSummary>, // Best score first
+    pub awards: Vec<Award>,
+}
+
+impl MatchSummary {
+    pub fn new(match_info: &ServerMatchInfo, mut players: Vec<PlayerSummary>) -> Self {
+        players.sort_by(|a, b| b.score.cmp(&a.score).then(b.kills.cmp(&a.kills)).then(a.deaths.cmp(&b.deaths)));
+        let awards = pick_awards(&players);
+        MatchSummary {
+            game_mode: match_info.game_mode,
+            winner_id: match_info.winner_id.clone(),
+            winner_name: match_info.winner_name.clone(),
+            team_scores: match_info.team_scores.clone(),
+            players,
+            awards,
+        }
+    }
+
+    pub fn mvp_id(&self) -> Option<&PlayerID> {
+        self.awards.iter().find(|award| award.award == fb::AwardType::Mvp).map(|award| &award.player_id)
+    }
+
+    /// Scoreboard positions `recipient_id` is sent: the top `MATCH_SUMMARY_TOP_PLAYERS`, the
+    /// award winners and the recipient's own.
+    pub fn rows_for(&self, recipient_id: &str) -> Vec<usize> {
+        (0..self.players.len()).filter(|&index| {
+            let player_id = &self.players[index].player_id;
+            index < MATCH_SUMMARY_TOP_PLAYERS
+                || player_id.as_str() == recipient_id
+                || self.awards.iter().any(|award| award.player_id == *player_id)
+        }).collect()
+    }
+
+    pub fn to_message(&self, recipient_id: &str) -> Bytes {
+        let mut builder = FlatBufferBuilder::with_capacity(4096);
+        let players_fb: Vec<_> = self.rows_for(recipient_id).into_iter().map(|index| {
+            let player = &self.players[index];
+            let player_id_fb = builder.create_string(player.player_id.as_str());
+            let username_fb = builder.create_string(&player.username);
+            let stats = &player.stats;
+            fb::PlayerMatchSummary::create(&mut builder, &fb::PlayerMatchSummaryArgs {
+                player_id: Some(player_id_fb),
+                username: Some(username_fb),
+                team_id: player.team_id as i8,
+                score: player.score,
+                kills: player.kills,
+                deaths: player.deaths,
+                assists: stats.assists,
+                damage_dealt: stats.damage_dealt,
+                shots_fired: stats.shots_fired,
+                shots_hit: stats.shots_hit,
+                accuracy: stats.accuracy(),
+                best_streak: stats.best_streak,
+                best_multi_kill: stats.best_multi_kill,
+                captures: stats.captures,
+                flag_returns: stats.flag_returns,
+                carrier_kills: stats.carrier_kills,
+                defenses: stats.defenses,
+                objective_seconds: stats.objective_secs,
+                rank: index as u32 + 1,
+            })
+        }).collect();
+        let players_vec = builder.create_vector(&players_fb);
+        let awards_fb: Vec<_> = self.awards.iter().map(|award| {
+            let player_id_fb = builder.create_string(award.player_id.as_str());
+            fb::MatchAward::create(&mut builder, &fb::MatchAwardArgs {
+                award: award.award,
+                player_id: Some(player_id_fb),
+                value: award.value,
+            })
+        }).collect();
+        let awards_vec = builder.create_vector(&awards_fb);
+        let team_scores_fb: Vec<_> = self.team_scores.iter().map(|(team_id, score)| {
+            fb::TeamScoreEntry::create(&mut builder, &fb::TeamScoreEntryArgs { team_id: *team_id as i8, score: *score })
+        }).collect();
+        let team_scores_vec = builder.create_vector(&team_scores_fb);
+        let winner_id_fb = self.winner_id.as_deref().map(|id| builder.create_string(id));
+        let winner_name_fb = self.winner_name.as_deref().map(|name| builder.create_string(name));
+        let mvp_id_fb = self.mvp_id().map(|id| builder.create_string(id.as_str()));
+
+        let summary_fb = fb::MatchSummary::create(&mut builder, &fb::MatchSummaryArgs {
+            game_mode: self.game_mode,
+            winner_id: winner_id_fb,
+            winner_name: winner_name_fb,
+            team_scores: Some(team_scores_vec),
+            players: Some(players_vec),
+            awards: Some(awards_vec),
+            mvp_id: mvp_id_fb,
+            total_players: self.players.len() as u32,
+        });
+        let game_msg = fb::GameMessage::create(&mut builder, &fb::GameMessageArgs {
+            msg_type: fb::MessageType::MatchSummary,
+            actual_message_type: fb::MessagePayload::MatchSummary,
+            actual_message: Some(summary_fb.as_union_value()),
+        });
+        builder.finish(game_msg, None);
+        Bytes::from(builder.finished_data().to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::types::ServerWeaponType;
+    use crate::systems::combat::damage::{DamageCause, DamageTaken};
+    use std::sync::Arc;
+
+    fn player(name: &str, score: i32, stats: PlayerMatchStats) -> PlayerSummary {
+        PlayerSummary { player_id: Arc::new(name.to_string()), username: name.to_string(), team_id: 0, score, kills: 0, deaths: 0, stats }
+    }
+
+    #[test]
+    fn test_streaks_and_multi_kills_earn_bonuses() {
+        let policy = ScoringPolicy::default();
+        let mut stats = PlayerMatchStats::default();
+        assert_eq!(stats.record_kill(10_000, &policy), 0);
+        assert_eq!(stats.record_kill(10_000 + MULTI_KILL_WINDOW_MS, &policy), policy.multi_kill_bonus);
+        for kill in 3..KILL_STREAK_MILESTONE {
+            stats.record_kill(100_000 * kill as u64, &policy);
+        }
+        assert_eq!(stats.record_kill(1_000_000, &policy), policy.streak_bonus);
+        assert_eq!((stats.best_streak, stats.best_multi_kill), (KILL_STREAK_MILESTONE, 2));
+
+        stats.end_streak();
+        assert_eq!(stats.record_kill(1_000_001, &policy), 0);
+        assert_eq!(stats.best_streak, KILL_STREAK_MILESTONE);
+    }
+
+    #[test]
+    fn test_assists_need_recent_damage_from_someone_else() {
+        let killer: PlayerID = Arc::new("killer".to_string());
+        let helper: PlayerID = Arc::new("helper".to_string());
+        let early: PlayerID = Arc::new("early".to_string());
+        let mut log = DamageLog::default();
+        for (attacker, timestamp_ms) in [(&early, 1_000), (&helper, 9_000), (&helper, 9_500), (&killer, 10_000)] {
+            log.record(DamageTaken {
+                attacker_id: Some(attacker.clone()),
+                cause: DamageCause::Weapon(ServerWeaponType::Rifle),
+                shield_damage: 0,
+                health_damage: 30,
+                is_melee: false,
+                distance: 100.0,
+                timestamp_ms,
+            });
+        }
+        assert_eq!(assist_ids(&log, &killer, 10_000), vec![helper]);
+    }
+
+    #[test]
+    fn test_awards_go_to_the_best_qualifying_player() {
+        let sharp = PlayerMatchStats { shots_fired: SHARPSHOOTER_MIN_SHOTS, shots_hit: SHARPSHOOTER_MIN_SHOTS / 2, assists: 3, ..Default::default() };
+        let lucky = PlayerMatchStats { shots_fired: 1, shots_hit: 1, assists: 3, ..Default::default() };
+        let players = vec![player("top", 500, sharp), player("second", 300, lucky)];
+        let awards = pick_awards(&players);
+        let winner = |award: fb::AwardType| awards.iter().find(|a| a.award == award).map(|a| a.player_id.as_str());
+
+        assert_eq!(winner(fb::AwardType::Mvp), Some("top"));
+        // One lucky shot doesn't make a sharpshooter
+        assert_eq!(winner(fb::AwardType::Sharpshooter), Some("top"));
+        // Tied assists go to the higher-ranked player
+        assert_eq!(winner(fb::AwardType::TeamPlayer), Some("top"));
+        assert_eq!(winner(fb::AwardType::Capturer), None);
+    }
+
+    #[test]
+    fn test_summary_sends_top_rows_award_winners_and_recipient() {
+        let count = MATCH_SUMMARY_TOP_PLAYERS as i32 + 20;
+        let mut players: Vec<_> = (0..count).map(|i| player(&format!("p{}", i), 1000 - i, PlayerMatchStats::default())).collect();
+        players[count as usize - 10].stats.damage_dealt = 500;
+        let summary = MatchSummary::new(&ServerMatchInfo::new(fb::GameModeType::FreeForAll), players);
+
+        let last = format!("p{}", count - 1);
+        let rows = summary.rows_for(&last);
+        assert_eq!(rows.len(), MATCH_SUMMARY_TOP_PLAYERS + 2);
+        assert_eq!(rows[MATCH_SUMMARY_TOP_PLAYERS], count as usize - 10);
+
+        let bytes = summary.to_message(&last);
+        let msg = flatbuffers::root::<fb::GameMessage>(&bytes).unwrap();
+        let summary_fb = msg.actual_message_as_match_summary().unwrap();
+        assert_eq!(summary_fb.total_players(), count as u32);
+        let own_row = summary_fb.players().unwrap().iter().last().unwrap();
+        assert_eq!((own_row.username(), own_row.rank()), (Some(last.as_str()), count as u32));
+    }
+}
@@ -0,0 +1,328 @@
+// massive_game_server/server/src/systems/physics/ballistics.rs
+// Swept projectile collision: where along a tick's path a shot first meets a player or a wall,
+// and what it does there.
+//
+// The path a shot covers in a tick is a segment, tested exactly against player circles and wall
+// rectangles, so nothing is skipped however fast the shot is or wherever partitions split the
+// map. At a wall a shot can glance off (ricochet), punch through a destructible wall at a loss
+// of damage (penetration), or stop. Contacts at the same distance are taken in id order, so the
+// same input always traces the same way.
+use crate::core::constants::PLAYER_RADIUS;
+use crate::core::types::{EntityId, PlayerID, Projectile, ServerWeaponType, Vec2, Wall};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BallisticSpec {
+    pub penetrations: u8,             // Destructible walls a shot can pass through
+    pub penetration_damage_kept: f32, // Share of the damage left after each one
+    pub ricochets: u8,                // Times a shot can glance off a wall
+    pub ricochet_max_angle: f32,      // Steepest hit that still glances, in radians off the wall's face
+    pub ricochet_damage_kept: f32,
+}
+
+// How far outside a wall a shot still counts as in it, so a shot sitting on the face it just
+// went through or glanced off doesn't hit that face again
+const WALL_CONTACT_TOLERANCE: f32 = 0.01;
+
+const PLAIN_SHOT: BallisticSpec = BallisticSpec {
+    penetrations: 0,
+    penetration_damage_kept: 1.0,
+    ricochets: 0,
+    ricochet_max_angle: 0.0,
+    ricochet_damage_kept: 1.0,
+};
+
+pub fn ballistic_spec(weapon: ServerWeaponType) -> BallisticSpec {
+    match weapon {
+        ServerWeaponType::Sniper => BallisticSpec { penetrations: 2, penetration_damage_kept: 0.6, ..PLAIN_SHOT },
+        ServerWeaponType::Pistol | ServerWeaponType::Rifle => BallisticSpec {
+            ricochets: 1,
+            ricochet_max_angle: std::f32::consts::PI / 9.0, // 20 degrees
+            ricochet_damage_kept: 0.5,
+            ..PLAIN_SHOT
+        },
+        _ => PLAIN_SHOT,
+    }
+}
+
+/// The part of a projectile a trace moves and spends.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Shot {
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub damage: i32,
+    pub penetrations_left: u8,
+    pub ricochets_left: u8,
+    pub inside_wall: Option<EntityId>, // The wall it last went into or glanced off, until it's clear of it
+}
+
+pub fn shot_of(proj: &Projectile) -> Shot {
+    Shot {
+        position: Vec2::new(proj.x, proj.y),
+        velocity: Vec2::new(proj.velocity_x, proj.velocity_y),
+        damage: proj.damage,
+        penetrations_left: proj.penetrations_left,
+        ricochets_left: proj.ricochets_left,
+        inside_wall: proj.inside_wall,
+    }
+}
+
+pub fn apply_shot(proj: &mut Projectile, shot: &Shot) {
+    proj.x = shot.position.x;
+    proj.y = shot.position.y;
+    proj.velocity_x = shot.velocity.x;
+    proj.velocity_y = shot.velocity.y;
+    proj.damage = shot.damage;
+    proj.penetrations_left = shot.penetrations_left;
+    proj.ricochets_left = shot.ricochets_left;
+    proj.inside_wall = shot.inside_wall;
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ShotContact {
+    Player { target_id: PlayerID, point: Vec2, damage: i32 },
+    Wall { wall_id: EntityId, point: Vec2, damage: i32, is_destructible: bool }, // Stopped the shot
+    Penetrated { wall_id: EntityId, point: Vec2, damage: i32 },                 // Damage dealt to the wall
+    Ricochet { wall_id: EntityId, point: Vec2 },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShotTrace {
+    pub contacts: Vec<ShotContact>, // In the order the shot met them
+    pub stopped: bool,              // Ended at its last contact
+}
+
+/// Where the segment from `from` to `to` enters the wall, as a fraction along it, and the normal
+/// of the face it enters through. A segment that starts inside enters at 0 with no normal; one
+/// that only grazes an edge or face doesn't enter at all.
+pub fn segment_wall_entry(from: Vec2, to: Vec2, wall: &Wall) -> Option<(f32, Vec2)> {
+    let (mut t_enter, mut t_exit) = (f32::NEG_INFINITY, f32::INFINITY);
+    let mut normal = Vec2::zero();
+    let axes = [
+        (from.x, to.x - from.x, wall.x, wall.x + wall.width, Vec2::new(1.0, 0.0)),
+        (from.y, to.y - from.y, wall.y, wall.y + wall.height, Vec2::new(0.0, 1.0)),
+    ];
+    for (start, delta, low, high, axis) in axes {
+        if delta.abs() < f32::EPSILON {
+            if start <= low || start >= high {
+                return None;
+            }
+            continue;
+        }
+        let (t1, t2) = ((low - start) / delta, (high - start) / delta);
+        if t1.min(t2) > t_enter {
+            t_enter = t1.min(t2);
+            let sign = if delta > 0.0 { -1.0 } else { 1.0 };
+            normal = Vec2::new(axis.x * sign, axis.y * sign);
+        }
+        t_exit = t_exit.min(t1.max(t2));
+    }
+    if t_enter >= t_exit || t_exit <= 0.0 || t_enter > 1.0 {
+        return None;
+    }
+    if t_enter < 0.0 {
+        return Some((0.0, Vec2::zero()));
+    }
+    Some((t_enter, normal))
+}
+
+/// Where the segment from `from` to `to` first touches the circle, as a fraction along it; 0 if
+/// it starts inside.
+pub fn segment_circle_entry(from: Vec2, to: Vec2, center: Vec2, radius: f32) -> Option<f32> {
+    let (dx, dy) = (to.x - from.x, to.y - from.y);
+    let (fx, fy) = (from.x - center.x, from.y - center.y);
+    let c = fx * fx + fy * fy - radius * radius;
+    if c <= 0.0 {
+        return Some(0.0);
+    }
+    let a = dx * dx + dy * dy;
+    if a < f32::EPSILON {
+        return None;
+    }
+    let b = 2.0 * (fx * dx + fy * dy);
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let t = (-b - discriminant.sqrt()) / (2.0 * a);
+    (0.0..=1.0).contains(&t).then_some(t)
+}
+
+fn contains_point(wall: &Wall, point: Vec2) -> bool {
+    point.x >= wall.x - WALL_CONTACT_TOLERANCE && point.x <= wall.x + wall.width + WALL_CONTACT_TOLERANCE
+        && point.y >= wall.y - WALL_CONTACT_TOLERANCE && point.y <= wall.y + wall.height + WALL_CONTACT_TOLERANCE
+}
+
+fn lerp(from: Vec2, to: Vec2, t: f32) -> Vec2 {
+    Vec2::new(from.x + (to.x - from.x) * t, from.y + (to.y - from.y) * t)
+}
+
+fn reflect(v: Vec2, normal: Vec2) -> Vec2 {
+    let along = v.x * normal.x + v.y * normal.y;
+    Vec2::new(v.x - 2.0 * along * normal.x, v.y - 2.0 * along * normal.y)
+}
+
+/// Moves a shot along its path to `to`, through whatever it penetrates or glances off, until it
+/// stops or gets there. `walls_along` gives the walls that may lie on a segment; `targets` are
+/// the players it can hit, at the positions to test them at.
+pub fn trace_shot(
+    shot: &mut Shot,
+    spec: &BallisticSpec,
+    to: Vec2,
+    walls_along: impl Fn(Vec2, Vec2) -> Vec<Wall>,
+    targets: &[(PlayerID, Vec2)],
+) -> ShotTrace {
+    let mut contacts = Vec::new();
+    let mut end = to;
+    // Each leg either stops the shot, spends a penetration or ricochet, or reaches the end
+    let legs = 1 + shot.penetrations_left as usize + shot.ricochets_left as usize;
+    for _ in 0..legs {
+        let from = shot.position;
+        let walls = walls_along(from, end);
+        if let Some(inside_id) = shot.inside_wall {
+            if !walls.iter().any(|wall| wall.id == inside_id && contains_point(wall, from)) {
+                shot.inside_wall = None;
+            }
+        }
+        let wall_hit = walls.into_iter()
+            .filter(|wall| !(wall.is_destructible && wall.current_health <= 0) && Some(wall.id) != shot.inside_wall)
+            .filter_map(|wall| segment_wall_entry(from, end, &wall).map(|(t, normal)| (t, normal, wall)))
+            .min_by(|a, b| a.0.total_cmp(&b.0).then(a.2.id.cmp(&b.2.id)));
+        let player_hit = targets.iter()
+            .filter_map(|(target_id, center)| segment_circle_entry(from, end, *center, PLAYER_RADIUS).map(|t| (t, target_id)))
+            .min_by(|a, b| a.0.total_cmp(&b.0).then_with(|| a.1.cmp(b.1)));
+
+        match (player_hit, wall_hit) {
+            (Some((t, target_id)), wall) if wall.as_ref().is_none_or(|(wall_t, _, _)| t < *wall_t) => {
+                shot.position = lerp(from, end, t);
+                contacts.push(ShotContact::Player { target_id: target_id.clone(), point: shot.position, damage: shot.damage });
+                return ShotTrace { contacts, stopped: true };
+            }
+            (_, Some((t, normal, wall))) => {
+                let point = lerp(from, end, t);
+                let (dx, dy) = (end.x - from.x, end.y - from.y);
+                let length = dx.hypot(dy);
+                let incidence = if length > f32::EPSILON { ((dx * normal.x + dy * normal.y) / length).abs() } else { 1.0 };
+                let glances = normal != Vec2::zero() && incidence <= spec.ricochet_max_angle.sin();
+                shot.position = point;
+                if shot.ricochets_left > 0 && glances {
+                    let remaining = reflect(Vec2::new(dx * (1.0 - t), dy * (1.0 - t)), normal);
+                    end = Vec2::new(point.x + remaining.x, point.y + remaining.y);
+                    shot.velocity = reflect(shot.velocity, normal);
+                    shot.damage = (shot.damage as f32 * spec.ricochet_damage_kept).round() as i32;
+                    shot.ricochets_left -= 1;
+                    shot.inside_wall = Some(wall.id);
+                    contacts.push(ShotContact::Ricochet { wall_id: wall.id, point });
+                } else if wall.is_destructible && shot.penetrations_left > 0 {
+                    contacts.push(ShotContact::Penetrated { wall_id: wall.id, point, damage: shot.damage });
+                    shot.damage = (shot.damage as f32 * spec.penetration_damage_kept).round() as i32;
+                    shot.penetrations_left -= 1;
+                    shot.inside_wall = Some(wall.id);
+                } else {
+                    contacts.push(ShotContact::Wall { wall_id: wall.id, point, damage: shot.damage, is_destructible: wall.is_destructible });
+                    return ShotTrace { contacts, stopped: true };
+                }
+            }
+            _ => break,
+        }
+    }
+    shot.position = end;
+    ShotTrace { contacts, stopped: false }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn wall(id: EntityId, x: f32, y: f32, width: f32, height: f32, is_destructible: bool) -> Wall {
+        Wall { id, x, y, width, height, is_destructible, current_health: 100, max_health: 100 }
+    }
+
+    fn shot(position: Vec2, velocity: Vec2, spec: &BallisticSpec) -> Shot {
+        Shot {
+            position,
+            velocity,
+            damage: 100,
+            penetrations_left: spec.penetrations,
+            ricochets_left: spec.ricochets,
+            inside_wall: None,
+        }
+    }
+
+    #[test]
+    fn test_fast_shot_hits_thin_wall_and_nearest_player() {
+        let spec = ballistic_spec(ServerWeaponType::Shotgun);
+        // A 2-unit wall halfway along a 500-unit step, with a player on either side of it
+        let walls = vec![wall(1, 249.0, -50.0, 2.0, 100.0, false)];
+        let targets = vec![(Arc::new("near".to_string()), Vec2::new(100.0, 10.0)), (Arc::new("far".to_string()), Vec2::new(400.0, 0.0))];
+        let mut near_shot = shot(Vec2::zero(), Vec2::new(500.0, 0.0), &spec);
+        let trace = trace_shot(&mut near_shot, &spec, Vec2::new(500.0, 0.0), |_, _| walls.clone(), &targets);
+        assert!(trace.stopped);
+        match &trace.contacts[..] {
+            [ShotContact::Player { target_id, point, damage: 100 }] => {
+                assert_eq!(target_id.as_str(), "near");
+                assert!((point.x - (100.0 - (PLAYER_RADIUS.powi(2) - 100.0).sqrt())).abs() < 1e-3);
+            }
+            other => panic!("unexpected contacts {:?}", other),
+        }
+
+        let mut blocked_shot = shot(Vec2::zero(), Vec2::new(500.0, 0.0), &spec);
+        let trace = trace_shot(&mut blocked_shot, &spec, Vec2::new(500.0, 0.0), |_, _| walls.clone(), &targets[1..]);
+        assert_eq!(trace.contacts, vec![ShotContact::Wall { wall_id: 1, point: Vec2::new(249.0, 0.0), damage: 100, is_destructible: false }]);
+        assert_eq!(blocked_shot.position, Vec2::new(249.0, 0.0));
+
+        // Grazing along a face is a miss
+        assert_eq!(segment_wall_entry(Vec2::new(0.0, -50.0), Vec2::new(500.0, -50.0), &walls[0]), None);
+    }
+
+    #[test]
+    fn test_sniper_penetrates_destructible_walls() {
+        let spec = ballistic_spec(ServerWeaponType::Sniper);
+        let walls = vec![
+            wall(1, 100.0, -20.0, 10.0, 40.0, true),
+            wall(2, 200.0, -20.0, 10.0, 40.0, true),
+            wall(3, 300.0, -20.0, 10.0, 40.0, true),
+        ];
+        let mut sniper_shot = shot(Vec2::zero(), Vec2::new(400.0, 0.0), &spec);
+        let trace = trace_shot(&mut sniper_shot, &spec, Vec2::new(400.0, 0.0), |_, _| walls.clone(), &[]);
+        assert_eq!(trace.contacts, vec![
+            ShotContact::Penetrated { wall_id: 1, point: Vec2::new(100.0, 0.0), damage: 100 },
+            ShotContact::Penetrated { wall_id: 2, point: Vec2::new(200.0, 0.0), damage: 60 },
+            ShotContact::Wall { wall_id: 3, point: Vec2::new(300.0, 0.0), damage: 36, is_destructible: true },
+        ]);
+        assert_eq!((sniper_shot.damage, sniper_shot.penetrations_left), (36, 0));
+
+        // Solid walls still stop it
+        let solid = vec![wall(4, 100.0, -20.0, 10.0, 40.0, false)];
+        let mut blocked = shot(Vec2::zero(), Vec2::new(400.0, 0.0), &spec);
+        assert!(trace_shot(&mut blocked, &spec, Vec2::new(400.0, 0.0), |_, _| solid.clone(), &[]).stopped);
+    }
+
+    #[test]
+    fn test_glancing_shot_ricochets() {
+        let spec = ballistic_spec(ServerWeaponType::Rifle);
+        let floor = vec![wall(1, -500.0, 0.0, 1000.0, 20.0, false)];
+        // 10 degrees down onto the top face glances off; straight down doesn't
+        let (sin, cos) = (10.0f32.to_radians().sin(), 10.0f32.to_radians().cos());
+        let start = Vec2::new(-100.0 * cos, -100.0 * sin);
+        let mut glancing = shot(start, Vec2::new(cos * 500.0, sin * 500.0), &spec);
+        let trace = trace_shot(&mut glancing, &spec, Vec2::new(100.0 * cos, 100.0 * sin), |_, _| floor.clone(), &[]);
+        assert!(!trace.stopped);
+        assert!(matches!(trace.contacts[..], [ShotContact::Ricochet { wall_id: 1, .. }]));
+        assert!((glancing.position.x - 100.0 * cos).abs() < 1e-3 && (glancing.position.y + 100.0 * sin).abs() < 1e-3);
+        assert!(glancing.velocity.y < 0.0);
+        assert_eq!((glancing.damage, glancing.ricochets_left), (50, 0));
+
+        let mut steep = shot(Vec2::new(0.0, -50.0), Vec2::new(0.0, 500.0), &spec);
+        let trace = trace_shot(&mut steep, &spec, Vec2::new(0.0, 50.0), |_, _| floor.clone(), &[]);
+        assert!(trace.stopped);
+        assert_eq!(steep.position, Vec2::new(0.0, 0.0));
+
+        // Once clear of the wall it glanced off, a shot can hit that wall again
+        let mut returning = shot(Vec2::new(0.0, -50.0), Vec2::new(0.0, 500.0), &spec);
+        returning.inside_wall = Some(1);
+        let trace = trace_shot(&mut returning, &spec, Vec2::new(0.0, 50.0), |_, _| floor.clone(), &[]);
+        assert!(trace.stopped);
+        assert_eq!(returning.inside_wall, None);
+    }
+}
@@ -0,0 +1,49 @@
+// massive_game_server/server/src/systems/physics/movement.rs
+// Server adapters for the shared movement rules in `massive_game_protocol::movement`.
+// The rules themselves live in that crate so clients can compile the same code.
+use crate::core::types::{PlayerInputData, PlayerState, Wall};
+pub use massive_game_protocol::movement::{
+    apply_movement_input, integrate_movement, overlaps_wall, step_movement, MovementInput, MovementState, WallRect,
+};
+
+pub fn movement_state_of(player: &PlayerState) -> MovementState {
+    MovementState {
+        x: player.x,
+        y: player.y,
+        velocity_x: player.velocity_x,
+        velocity_y: player.velocity_y,
+        rotation: player.rotation,
+    }
+}
+
+pub fn movement_input_of(input: &PlayerInputData, player: &PlayerState) -> MovementInput {
+    MovementInput {
+        move_forward: input.move_forward,
+        move_backward: input.move_backward,
+        move_left: input.move_left,
+        move_right: input.move_right,
+        rotation: input.rotation,
+        speed_multiplier: player.effects.modifiers().move_speed,
+    }
+}
+
+/// Writes a movement result back. Returns true if position or rotation changed.
+pub fn apply_movement_state(player: &mut PlayerState, state: &MovementState) -> bool {
+    let changed = (player.x - state.x).abs() > 0.01
+        || (player.y - state.y).abs() > 0.01
+        || (player.rotation - state.rotation).abs() > 0.001;
+    player.x = state.x;
+    player.y = state.y;
+    player.velocity_x = state.velocity_x;
+    player.velocity_y = state.velocity_y;
+    player.rotation = state.rotation;
+    changed
+}
+
+/// Standing walls as collision rectangles; destroyed destructible walls are skipped.
+pub fn wall_rects<'a>(walls: impl IntoIterator<Item = &'a Wall>) -> Vec<WallRect> {
+    walls.into_iter()
+        .filter(|wall| !(wall.is_destructible && wall.current_health <= 0))
+        .map(|wall| WallRect { x: wall.x, y: wall.y, width: wall.width, height: wall.height })
+        .collect()
+}
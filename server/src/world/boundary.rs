@@ -0,0 +1,227 @@
+// massive_game_server/server/src/world/boundary.rs
+// Shrinking safe zone for battle royale.
+//
+// The zone runs through a list of phases. Each phase waits with the current circle shown and
+// the next circle announced, then shrinks the current circle onto the next one. Next circles
+// are picked at random but always lie entirely inside the current one. Players outside take
+// the current phase's damage once per SAFE_ZONE_DAMAGE_INTERVAL_SECS.
+use crate::core::constants::*;
+use crate::core::types::Vec2;
+use rand::Rng;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SafeZonePhase {
+    pub wait_secs: f32,
+    pub shrink_secs: f32,
+    pub radius_fraction: f32, // Radius of the next circle relative to the current one
+    pub damage_per_sec: i32,  // Taken outside the zone during this phase
+}
+
+impl SafeZonePhase {
+    pub fn new(wait_secs: f32, shrink_secs: f32, radius_fraction: f32, damage_per_sec: i32) -> Self {
+        SafeZonePhase { wait_secs, shrink_secs, radius_fraction, damage_per_sec }
+    }
+}
+
+pub fn default_safe_zone_phases() -> Vec<SafeZonePhase> {
+    vec![
+        SafeZonePhase::new(60.0, 45.0, 0.6, 2),
+        SafeZonePhase::new(45.0, 40.0, 0.55, 4),
+        SafeZonePhase::new(35.0, 30.0, 0.5, 8),
+        SafeZonePhase::new(25.0, 25.0, 0.45, 12),
+        SafeZonePhase::new(20.0, 20.0, 0.0, 20),
+    ]
+}
+
+/// Parses phases written as `wait:shrink:radius_fraction:damage`, comma separated, as used in
+/// `SAFE_ZONE_PHASES`. Returns None if any phase is malformed.
+pub fn parse_safe_zone_phases(spec: &str) -> Option<Vec<SafeZonePhase>> {
+    spec.split(',').map(|phase| {
+        let parts: Vec<&str> = phase.trim().split(':').map(str::trim).collect();
+        let [wait, shrink, fraction, damage] = parts.as_slice() else { return None };
+        Some(SafeZonePhase::new(wait.parse().ok()?, shrink.parse().ok()?, fraction.parse().ok()?, damage.parse().ok()?))
+    }).collect()
+}
+
+/// Seconds from the first wait until the last circle has closed.
+pub fn total_phase_secs(phases: &[SafeZonePhase]) -> f32 {
+    phases.iter().map(|phase| phase.wait_secs + phase.shrink_secs).sum()
+}
+
+/// A random center for a circle of `next_radius` that lies entirely inside the current circle.
+pub fn pick_next_center(center: Vec2, radius: f32, next_radius: f32, rng: &mut impl Rng) -> Vec2 {
+    let max_offset = (radius - next_radius).max(0.0);
+    let angle = rng.gen_range(0.0..2.0 * std::f32::consts::PI);
+    // sqrt keeps the pick uniform over the area rather than bunched at the center
+    let distance = max_offset * rng.gen_range(0.0f32..=1.0).sqrt();
+    let next = Vec2::new(center.x + distance * angle.cos(), center.y + distance * angle.sin());
+    Vec2::new(next.x.clamp(WORLD_MIN_X, WORLD_MAX_X), next.y.clamp(WORLD_MIN_Y, WORLD_MAX_Y))
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SafeZone {
+    phases: Vec<SafeZonePhase>,
+    pub phase_index: usize,    // Equal to the phase count once the last circle has closed
+    pub shrinking: bool,
+    pub phase_time_remaining: f32,
+    pub center: Vec2,
+    pub radius: f32,
+    pub next_center: Vec2,
+    pub next_radius: f32,
+    shrink_from_center: Vec2,
+    shrink_from_radius: f32,
+    damage_timer: f32,
+}
+
+impl SafeZone {
+    /// A zone covering the whole map, waiting on the first phase.
+    pub fn new(phases: Vec<SafeZonePhase>, rng: &mut impl Rng) -> Self {
+        let center = Vec2::new((WORLD_MIN_X + WORLD_MAX_X) / 2.0, (WORLD_MIN_Y + WORLD_MAX_Y) / 2.0);
+        let radius = (WORLD_MAX_X - WORLD_MIN_X).hypot(WORLD_MAX_Y - WORLD_MIN_Y) / 2.0;
+        let mut zone = SafeZone {
+            phases,
+            phase_index: 0,
+            shrinking: false,
+            phase_time_remaining: 0.0,
+            center,
+            radius,
+            next_center: center,
+            next_radius: radius,
+            shrink_from_center: center,
+            shrink_from_radius: radius,
+            damage_timer: 0.0,
+        };
+        zone.start_wait(rng);
+        zone
+    }
+
+    pub fn is_final(&self) -> bool {
+        self.phase_index >= self.phases.len()
+    }
+
+    /// Damage per second outside the zone; the last phase's damage keeps applying once it closes.
+    pub fn damage_per_sec(&self) -> i32 {
+        self.phases.get(self.phase_index.min(self.phases.len().saturating_sub(1)))
+            .map_or(0, |phase| phase.damage_per_sec)
+    }
+
+    pub fn contains(&self, position: Vec2) -> bool {
+        let dx = position.x - self.center.x;
+        let dy = position.y - self.center.y;
+        dx * dx + dy * dy <= self.radius * self.radius
+    }
+
+    fn start_wait(&mut self, rng: &mut impl Rng) {
+        let Some(phase) = self.phases.get(self.phase_index) else {
+            self.shrinking = false;
+            self.phase_time_remaining = 0.0;
+            return;
+        };
+        self.shrinking = false;
+        self.phase_time_remaining = phase.wait_secs;
+        self.next_radius = self.radius * phase.radius_fraction.clamp(0.0, 1.0);
+        self.next_center = pick_next_center(self.center, self.radius, self.next_radius, rng);
+    }
+
+    pub fn tick(&mut self, delta_time: f32, rng: &mut impl Rng) {
+        if self.is_final() {
+            return;
+        }
+        self.phase_time_remaining -= delta_time;
+
+        if !self.shrinking {
+            if self.phase_time_remaining <= 0.0 {
+                self.shrinking = true;
+                self.phase_time_remaining += self.phases[self.phase_index].shrink_secs;
+                self.shrink_from_center = self.center;
+                self.shrink_from_radius = self.radius;
+            }
+            return;
+        }
+
+        let shrink_secs = self.phases[self.phase_index].shrink_secs;
+        if self.phase_time_remaining <= 0.0 || shrink_secs <= 0.0 {
+            self.center = self.next_center;
+            self.radius = self.next_radius;
+            self.phase_index += 1;
+            self.start_wait(rng);
+            return;
+        }
+        let t = 1.0 - self.phase_time_remaining / shrink_secs;
+        self.center = Vec2::new(
+            self.shrink_from_center.x + (self.next_center.x - self.shrink_from_center.x) * t,
+            self.shrink_from_center.y + (self.next_center.y - self.shrink_from_center.y) * t,
+        );
+        self.radius = self.shrink_from_radius + (self.next_radius - self.shrink_from_radius) * t;
+    }
+
+    /// Advances the damage clock; returns the damage to deal outside the zone when a tick is due.
+    pub fn damage_due(&mut self, delta_time: f32) -> Option<i32> {
+        self.damage_timer += delta_time;
+        if self.damage_timer < SAFE_ZONE_DAMAGE_INTERVAL_SECS {
+            return None;
+        }
+        self.damage_timer -= SAFE_ZONE_DAMAGE_INTERVAL_SECS;
+        let damage = (self.damage_per_sec() as f32 * SAFE_ZONE_DAMAGE_INTERVAL_SECS).round() as i32;
+        (damage > 0).then_some(damage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_circle_stays_inside_current() {
+        let mut rng = rand::thread_rng();
+        let center = Vec2::new(100.0, -50.0);
+        for _ in 0..200 {
+            let next = pick_next_center(center, 300.0, 120.0, &mut rng);
+            let offset = (next.x - center.x).hypot(next.y - center.y);
+            assert!(offset + 120.0 <= 300.0 + 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_phases_wait_then_shrink_onto_next_circle() {
+        let mut rng = rand::thread_rng();
+        let phases = vec![SafeZonePhase::new(10.0, 20.0, 0.5, 5), SafeZonePhase::new(5.0, 5.0, 0.0, 10)];
+        let mut zone = SafeZone::new(phases, &mut rng);
+        let start_radius = zone.radius;
+
+        zone.tick(10.0, &mut rng);
+        assert!(zone.shrinking);
+        assert_eq!(zone.radius, start_radius);
+
+        zone.tick(10.0, &mut rng);
+        assert!((zone.radius - start_radius * 0.75).abs() < 1e-3);
+
+        let target = zone.next_center;
+        zone.tick(10.0, &mut rng);
+        assert_eq!((zone.phase_index, zone.shrinking), (1, false));
+        assert_eq!(zone.center, target);
+        assert_eq!(zone.damage_per_sec(), 10);
+
+        zone.tick(5.0, &mut rng);
+        zone.tick(5.0, &mut rng);
+        assert!(zone.is_final());
+        assert_eq!(zone.radius, 0.0);
+        assert_eq!(zone.damage_per_sec(), 10);
+    }
+
+    #[test]
+    fn test_parse_phases() {
+        assert_eq!(parse_safe_zone_phases("30:20:0.5:3, 10:10:0:9"),
+                   Some(vec![SafeZonePhase::new(30.0, 20.0, 0.5, 3), SafeZonePhase::new(10.0, 10.0, 0.0, 9)]));
+        assert_eq!(parse_safe_zone_phases("30:20:0.5"), None);
+    }
+
+    #[test]
+    fn test_damage_is_dealt_once_per_interval() {
+        let mut rng = rand::thread_rng();
+        let mut zone = SafeZone::new(vec![SafeZonePhase::new(10.0, 10.0, 0.5, 7)], &mut rng);
+        let tick = SAFE_ZONE_DAMAGE_INTERVAL_SECS / 4.0;
+        let dealt: Vec<_> = (0..8).filter_map(|_| zone.damage_due(tick)).collect();
+        assert_eq!(dealt, vec![7, 7]);
+    }
+}
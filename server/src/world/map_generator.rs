@@ -160,6 +160,16 @@ impl MapGenerator {
         walls
     }
     
+    /// Capture zones for Domination as (center, radius), in zone id order. They sit on the
+    /// center line between the bases: one in the arena and one in each outer lane.
+    pub fn get_capture_zones() -> Vec<(Vec2, f32)> {
+        vec![
+            (Vec2::new(0.0, WORLD_MIN_Y * 0.6), ZONE_DEFAULT_RADIUS),
+            (Vec2::new(0.0, 0.0), ZONE_DEFAULT_RADIUS),
+            (Vec2::new(0.0, WORLD_MAX_Y * 0.6), ZONE_DEFAULT_RADIUS),
+        ]
+    }
+
     pub fn get_team_spawn_areas() -> Vec<(Vec2, u8)> { 
         let mut spawns = Vec::new();
         let base_depth = 250.0;
@@ -1,6 +1,6 @@
 // massive_game_server/server/src/world/mod.rs
 pub mod partition;
-// pub mod boundary; // Could be part of partition.rs or separate
+pub mod boundary; // Battle royale safe zone
 // pub mod map_loader; // For loading map data
 // pub mod navigation; // For AI pathfinding
 pub mod map_generator;
@@ -120,6 +120,7 @@ let currentMapName = "Unknown Map";
 let inputSequence = 0;
 let pendingInputs = [];
 let lastProcessedInput = 0;
+let lastAppliedServerTick = 0n; // Acked in every input so the server can send deltas against it
 let localPlayerState = null;
 
 // Interpolation state
@@ -889,6 +890,8 @@ function processServerUpdate(messageData, isInitial = false) {
     const serverTime = Number(messageData.timestamp);
 
     if (isInitial) {
+        // Baselines acked before a fresh initial state are gone on the server
+        lastAppliedServerTick = 0n;
         walls.clear();
         if (messageData.walls) {
             messageData.walls.forEach(wallData => walls.set(wallData.id, wallData));
@@ -932,6 +935,18 @@ function processServerUpdate(messageData, isInitial = false) {
         }
     }
 
+    // Without a usable baseline the server lists everything in our AoI: forget the rest
+    if (messageData.full_snapshot) {
+        const listedPlayers = new Set((messageData.players || []).map(p => p.id));
+        for (const id of players.keys()) {
+            if (id !== myPlayerId && !listedPlayers.has(id)) players.delete(id);
+        }
+        const listedProjectiles = new Set((messageData.projectiles || []).map(p => p.id));
+        for (const id of projectiles.keys()) {
+            if (!listedProjectiles.has(id)) projectiles.delete(id);
+        }
+    }
+
     // Update players
     if (messageData.players) {
         messageData.players.forEach(pData => {
@@ -1826,7 +1841,8 @@ function sendInputIfNeeded() {
     const now = Date.now();
     if (now - lastInputSendTime < 1000 / INPUT_SEND_RATE) return;
     if (!dataChannel || dataChannel.readyState !== 'open') return;
-    if (!localPlayerState || !localPlayerState.alive) return;
+    // Keep sending while dead: the server ignores the movement but still needs our snapshot acks
+    if (!localPlayerState) return;
     
     lastInputSendTime = now;
     
@@ -1846,6 +1862,7 @@ function sendInputIfNeeded() {
     GP.PlayerInput.addMeleeAttack(builder, inputState.melee_attack);
     GP.PlayerInput.addChangeWeaponSlot(builder, inputState.change_weapon_slot);
     GP.PlayerInput.addUseAbilitySlot(builder, inputState.use_ability_slot);
+    GP.PlayerInput.addLastAppliedServerTick(builder, lastAppliedServerTick);
     const playerInputOffset = GP.PlayerInput.endPlayerInput(builder);
 
     GP.GameMessage.startGameMessage(builder);
@@ -1857,8 +1874,8 @@ function sendInputIfNeeded() {
     
     dataChannel.send(builder.asUint8Array());
     
-    // Store pending input for reconciliation
-    if (localPlayerState) {
+    // Store pending input for reconciliation; inputs sent while dead never move us
+    if (localPlayerState.alive) {
         pendingInputs.push({
             sequence: inputSequence,
             input: { ...inputState },
@@ -1901,6 +1918,9 @@ function handleDataChannelMessage(event) {
                         break;
                     case 'delta':
                         processServerUpdate(parsed.data, false);
+                        if (parsed.data.server_tick > lastAppliedServerTick) {
+                            lastAppliedServerTick = parsed.data.server_tick;
+                        }
                         break;
                     case 'chat':
                         if (parsed.data) {
@@ -2114,6 +2134,8 @@ function parseFlatBufferMessage(data) {
                     game_events: [],
                     timestamp: Number(delta.timestamp()),
                     last_processed_input_sequence: delta.lastProcessedInputSequence(),
+                    server_tick: delta.serverTick(),
+                    full_snapshot: delta.fullSnapshot(),
                     removed_player_ids: [] // Initialize for removed players
                 };
                 
@@ -45,6 +45,9 @@ export declare class DeltaStateMessage {
     removedPlayerIdsLength(): number;
     updatedWalls(index: number, obj?: Wall): Wall | null;
     updatedWallsLength(): number;
+    serverTick(): bigint;
+    baselineTick(): bigint;
+    fullSnapshot(): boolean;
     static startDeltaStateMessage(builder: flatbuffers.Builder): void;
     static addPlayers(builder: flatbuffers.Builder, playersOffset: flatbuffers.Offset): void;
     static createPlayersVector(builder: flatbuffers.Builder, data: flatbuffers.Offset[]): flatbuffers.Offset;
@@ -85,6 +88,9 @@ export declare class DeltaStateMessage {
     static addUpdatedWalls(builder: flatbuffers.Builder, updatedWallsOffset: flatbuffers.Offset): void;
     static createUpdatedWallsVector(builder: flatbuffers.Builder, data: flatbuffers.Offset[]): flatbuffers.Offset;
     static startUpdatedWallsVector(builder: flatbuffers.Builder, numElems: number): void;
+    static addServerTick(builder: flatbuffers.Builder, serverTick: bigint): void;
+    static addBaselineTick(builder: flatbuffers.Builder, baselineTick: bigint): void;
+    static addFullSnapshot(builder: flatbuffers.Builder, fullSnapshot: boolean): void;
     static endDeltaStateMessage(builder: flatbuffers.Builder): flatbuffers.Offset;
 }
 //# sourceMappingURL=delta-state-message.d.ts.map
\ No newline at end of file
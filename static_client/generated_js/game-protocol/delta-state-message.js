@@ -138,8 +138,20 @@ export class DeltaStateMessage {
         const offset = this.bb.__offset(this.bb_pos, 32);
         return offset ? this.bb.__vector_len(this.bb_pos + offset) : 0;
     }
+    serverTick() {
+        const offset = this.bb.__offset(this.bb_pos, 34);
+        return offset ? this.bb.readUint64(this.bb_pos + offset) : BigInt('0');
+    }
+    baselineTick() {
+        const offset = this.bb.__offset(this.bb_pos, 36);
+        return offset ? this.bb.readUint64(this.bb_pos + offset) : BigInt('0');
+    }
+    fullSnapshot() {
+        const offset = this.bb.__offset(this.bb_pos, 38);
+        return offset ? !!this.bb.readInt8(this.bb_pos + offset) : false;
+    }
     static startDeltaStateMessage(builder) {
-        builder.startObject(15);
+        builder.startObject(18);
     }
     static addPlayers(builder, playersOffset) {
         builder.addFieldOffset(0, playersOffset, 0);
@@ -306,6 +318,15 @@ export class DeltaStateMessage {
     static startUpdatedWallsVector(builder, numElems) {
         builder.startVector(4, numElems, 4);
     }
+    static addServerTick(builder, serverTick) {
+        builder.addFieldInt64(15, serverTick, BigInt('0'));
+    }
+    static addBaselineTick(builder, baselineTick) {
+        builder.addFieldInt64(16, baselineTick, BigInt('0'));
+    }
+    static addFullSnapshot(builder, fullSnapshot) {
+        builder.addFieldInt8(17, +fullSnapshot, +false);
+    }
     static endDeltaStateMessage(builder) {
         const offset = builder.endObject();
         return offset;
@@ -180,8 +180,23 @@ updatedWallsLength():number {
   return offset ? this.bb!.__vector_len(this.bb_pos + offset) : 0;
 }
 
+serverTick():bigint {
+  const offset = this.bb!.__offset(this.bb_pos, 34);
+  return offset ? this.bb!.readUint64(this.bb_pos + offset) : BigInt('0');
+}
+
+baselineTick():bigint {
+  const offset = this.bb!.__offset(this.bb_pos, 36);
+  return offset ? this.bb!.readUint64(this.bb_pos + offset) : BigInt('0');
+}
+
+fullSnapshot():boolean {
+  const offset = this.bb!.__offset(this.bb_pos, 38);
+  return offset ? !!this.bb!.readInt8(this.bb_pos + offset) : false;
+}
+
 static startDeltaStateMessage(builder:flatbuffers.Builder) {
-  builder.startObject(15);
+  builder.startObject(18);
 }
 
 static addPlayers(builder:flatbuffers.Builder, playersOffset:flatbuffers.Offset) {
@@ -388,6 +403,18 @@ static startUpdatedWallsVector(builder:flatbuffers.Builder, numElems:number) {
   builder.startVector(4, numElems, 4);
 }
 
+static addServerTick(builder:flatbuffers.Builder, serverTick:bigint) {
+  builder.addFieldInt64(15, serverTick, BigInt('0'));
+}
+
+static addBaselineTick(builder:flatbuffers.Builder, baselineTick:bigint) {
+  builder.addFieldInt64(16, baselineTick, BigInt('0'));
+}
+
+static addFullSnapshot(builder:flatbuffers.Builder, fullSnapshot:boolean) {
+  builder.addFieldInt8(17, +fullSnapshot, +false);
+}
+
 static endDeltaStateMessage(builder:flatbuffers.Builder):flatbuffers.Offset {
   const offset = builder.endObject();
   return offset;
@@ -17,6 +17,7 @@ export declare class PlayerInput {
     meleeAttack(): boolean;
     changeWeaponSlot(): number;
     useAbilitySlot(): number;
+    lastAppliedServerTick(): bigint;
     static startPlayerInput(builder: flatbuffers.Builder): void;
     static addTimestamp(builder: flatbuffers.Builder, timestamp: bigint): void;
     static addSequence(builder: flatbuffers.Builder, sequence: number): void;
@@ -30,7 +31,8 @@ export declare class PlayerInput {
     static addMeleeAttack(builder: flatbuffers.Builder, meleeAttack: boolean): void;
     static addChangeWeaponSlot(builder: flatbuffers.Builder, changeWeaponSlot: number): void;
     static addUseAbilitySlot(builder: flatbuffers.Builder, useAbilitySlot: number): void;
+    static addLastAppliedServerTick(builder: flatbuffers.Builder, lastAppliedServerTick: bigint): void;
     static endPlayerInput(builder: flatbuffers.Builder): flatbuffers.Offset;
-    static createPlayerInput(builder: flatbuffers.Builder, timestamp: bigint, sequence: number, moveForward: boolean, moveBackward: boolean, moveLeft: boolean, moveRight: boolean, shooting: boolean, reload: boolean, rotation: number, meleeAttack: boolean, changeWeaponSlot: number, useAbilitySlot: number): flatbuffers.Offset;
+    static createPlayerInput(builder: flatbuffers.Builder, timestamp: bigint, sequence: number, moveForward: boolean, moveBackward: boolean, moveLeft: boolean, moveRight: boolean, shooting: boolean, reload: boolean, rotation: number, meleeAttack: boolean, changeWeaponSlot: number, useAbilitySlot: number, lastAppliedServerTick: bigint): flatbuffers.Offset;
 }
 //# sourceMappingURL=player-input.d.ts.map
\ No newline at end of file
@@ -66,8 +66,12 @@ export class PlayerInput {
         const offset = this.bb.__offset(this.bb_pos, 26);
         return offset ? this.bb.readInt8(this.bb_pos + offset) : 0;
     }
+    lastAppliedServerTick() {
+        const offset = this.bb.__offset(this.bb_pos, 28);
+        return offset ? this.bb.readUint64(this.bb_pos + offset) : BigInt('0');
+    }
     static startPlayerInput(builder) {
-        builder.startObject(12);
+        builder.startObject(13);
     }
     static addTimestamp(builder, timestamp) {
         builder.addFieldInt64(0, timestamp, BigInt('0'));
@@ -105,11 +109,14 @@ export class PlayerInput {
     static addUseAbilitySlot(builder, useAbilitySlot) {
         builder.addFieldInt8(11, useAbilitySlot, 0);
     }
+    static addLastAppliedServerTick(builder, lastAppliedServerTick) {
+        builder.addFieldInt64(12, lastAppliedServerTick, BigInt('0'));
+    }
     static endPlayerInput(builder) {
         const offset = builder.endObject();
         return offset;
     }
-    static createPlayerInput(builder, timestamp, sequence, moveForward, moveBackward, moveLeft, moveRight, shooting, reload, rotation, meleeAttack, changeWeaponSlot, useAbilitySlot) {
+    static createPlayerInput(builder, timestamp, sequence, moveForward, moveBackward, moveLeft, moveRight, shooting, reload, rotation, meleeAttack, changeWeaponSlot, useAbilitySlot, lastAppliedServerTick) {
         PlayerInput.startPlayerInput(builder);
         PlayerInput.addTimestamp(builder, timestamp);
         PlayerInput.addSequence(builder, sequence);
@@ -123,6 +130,7 @@ export class PlayerInput {
         PlayerInput.addMeleeAttack(builder, meleeAttack);
         PlayerInput.addChangeWeaponSlot(builder, changeWeaponSlot);
         PlayerInput.addUseAbilitySlot(builder, useAbilitySlot);
+        PlayerInput.addLastAppliedServerTick(builder, lastAppliedServerTick);
         return PlayerInput.endPlayerInput(builder);
     }
 }
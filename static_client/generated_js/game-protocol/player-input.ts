@@ -82,8 +82,13 @@ useAbilitySlot():number {
   return offset ? this.bb!.readInt8(this.bb_pos + offset) : 0;
 }
 
+lastAppliedServerTick():bigint {
+  const offset = this.bb!.__offset(this.bb_pos, 28);
+  return offset ? this.bb!.readUint64(this.bb_pos + offset) : BigInt('0');
+}
+
 static startPlayerInput(builder:flatbuffers.Builder) {
-  builder.startObject(12);
+  builder.startObject(13);
 }
 
 static addTimestamp(builder:flatbuffers.Builder, timestamp:bigint) {
@@ -134,12 +139,16 @@ static addUseAbilitySlot(builder:flatbuffers.Builder, useAbilitySlot:number) {
   builder.addFieldInt8(11, useAbilitySlot, 0);
 }
 
+static addLastAppliedServerTick(builder:flatbuffers.Builder, lastAppliedServerTick:bigint) {
+  builder.addFieldInt64(12, lastAppliedServerTick, BigInt('0'));
+}
+
 static endPlayerInput(builder:flatbuffers.Builder):flatbuffers.Offset {
   const offset = builder.endObject();
   return offset;
 }
 
-static createPlayerInput(builder:flatbuffers.Builder, timestamp:bigint, sequence:number, moveForward:boolean, moveBackward:boolean, moveLeft:boolean, moveRight:boolean, shooting:boolean, reload:boolean, rotation:number, meleeAttack:boolean, changeWeaponSlot:number, useAbilitySlot:number):flatbuffers.Offset {
+static createPlayerInput(builder:flatbuffers.Builder, timestamp:bigint, sequence:number, moveForward:boolean, moveBackward:boolean, moveLeft:boolean, moveRight:boolean, shooting:boolean, reload:boolean, rotation:number, meleeAttack:boolean, changeWeaponSlot:number, useAbilitySlot:number, lastAppliedServerTick:bigint):flatbuffers.Offset {
   PlayerInput.startPlayerInput(builder);
   PlayerInput.addTimestamp(builder, timestamp);
   PlayerInput.addSequence(builder, sequence);
@@ -153,6 +162,7 @@ static createPlayerInput(builder:flatbuffers.Builder, timestamp:bigint, sequence
   PlayerInput.addMeleeAttack(builder, meleeAttack);
   PlayerInput.addChangeWeaponSlot(builder, changeWeaponSlot);
   PlayerInput.addUseAbilitySlot(builder, useAbilitySlot);
+  PlayerInput.addLastAppliedServerTick(builder, lastAppliedServerTick);
   return PlayerInput.endPlayerInput(builder);
 }
 }